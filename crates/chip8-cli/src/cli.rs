@@ -0,0 +1,806 @@
+use std::path::PathBuf;
+use chip8_core::{audio::{AudioSettings, Waveform}, backend::Backend, breakpoint::Breakpoint, errors::{Chip8Error, LoadError}, filter::Filter, indicator::SoundIndicator, memory::Protection, netplay::NetplayMode, palette::Palette, profile::Profile, scheduler::LagPolicy, sprites::FONT_HEIGHT, testing::Expectation, watchpoint::Watchpoint};
+use chip8_frontend_minifb::DEFAULT_BACKEND;
+
+// Subcommands, running a ROM is the default
+pub enum Command {
+    Run(Box<Args>), // Boxed, the options outweigh every other command
+    Test(TestArgs),
+    Bench(BenchArgs), // Measure how fast a ROM runs
+    Verify(VerifyArgs), // Compare execution with a reference trace
+    Info(InfoArgs), // Report facts about a ROM
+    Check(InfoArgs), // Report whether a ROM is expected to run
+    Decompile(DecompileArgs), // Turn a ROM into Octo source
+    Sprites(SpritesArgs), // Show the data in a ROM as sprites
+    Conformance, // Print which opcodes are implemented and checked
+    Tour, // Run the tutorial with hints for first-time users
+    Compare(Vec<PathBuf>), // Run ROMs side by side, each in its own window
+}
+
+impl Command {
+    // Parses the arguments, skipping the program name
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Command, Chip8Error> {
+        let mut args = args.skip(1).peekable();
+        match args.peek().map(String::as_str) {
+            Some("test") => TestArgs::parse(args.skip(1)).map(Command::Test),
+            Some("bench") => BenchArgs::parse(args.skip(1)).map(Command::Bench),
+            Some("verify") => VerifyArgs::parse(args.skip(1)).map(Command::Verify),
+            Some("info") => InfoArgs::parse(args.skip(1)).map(Command::Info),
+            Some("check") => InfoArgs::parse(args.skip(1)).map(Command::Check),
+            Some("decompile") => DecompileArgs::parse(args.skip(1)).map(Command::Decompile),
+            Some("sprites") => SpritesArgs::parse(args.skip(1)).map(Command::Sprites),
+            Some("conformance") => match args.nth(1) {
+                Some(arg) => Err(unexpected(&arg)),
+                None => Ok(Command::Conformance),
+            },
+            Some("tour") => match args.nth(1) {
+                Some(arg) => Err(unexpected(&arg)),
+                None => Ok(Command::Tour),
+            },
+            Some("compare") => roms(args.skip(1)).map(Command::Compare),
+            Some("run") => Args::parse(args.skip(1)).map(|args| Command::Run(Box::new(args))),
+            _ => Args::parse(args).map(|args| Command::Run(Box::new(args))),
+        }
+    }
+}
+
+// Options for running a ROM
+pub struct Args {
+    pub rom: Option<PathBuf>, // Program to run, the splash screen is shown without one
+    pub banks: Vec<PathBuf>, // Further programs to switch to while running
+    pub record_input: Option<PathBuf>, // File to record key presses to
+    pub replay_input: Option<PathBuf>, // Recorded key presses to play back instead of the keyboard
+    pub record_gif: Option<PathBuf>, // File to record gameplay to
+    pub heatmap: Option<PathBuf>, // Where to save the memory access heatmap on exit
+    pub events: Option<PathBuf>, // File to log structured events to, one JSON object per line
+    pub database: Option<PathBuf>, // ROM database to use instead of the bundled one
+    pub no_database: bool, // Skip looking up per-ROM settings
+    pub palette: Option<Palette>, // Colors chosen by the user, override the ROM database
+    pub fullscreen: bool,
+    pub vsync: bool, // Present frames in step with the monitor's refresh
+    pub phosphor: bool, // Fade erased pixels out to reduce flicker
+    pub stats: bool, // Show what the program does every second in the title
+    pub autofire: Vec<(u8, u32)>, // Keys pressing repeatedly while held, with presses per second
+    pub turbo: Option<u32>, // Speed multiplier while the turbo key is held
+    pub filter: Filter, // Post-processing like scanlines
+    pub sound_indicator: SoundIndicator, // Shows beeps on screen
+    pub font: Option<String>, // Named font or font file replacing the built-in digits
+    pub icon: Option<PathBuf>, // PNG image replacing the bundled window icon
+    pub backend: Backend, // Library the window is opened with
+    pub audio: AudioSettings, // Waveform, frequency and volume of the beep
+    pub no_audio: bool, // Don't open an audio device at all
+    pub watchpoints: Vec<Watchpoint>, // Memory accesses that pause the emulator
+    pub breakpoints: Vec<Breakpoint>, // Instructions the emulator pauses before
+    pub labels: Option<PathBuf>, // Names for addresses in disassembly and the call stack
+    pub warn_smc: bool, // Warn when the program modifies its own code
+    pub protection: Protection, // Warn or stop when the program writes below the program start
+    pub gdb: Option<u16>, // Port a remote debugger can attach on
+    pub http: Option<u16>, // Port the HTTP API listens on
+    pub netplay: Option<NetplayMode>, // Whether to host or join a two player session
+    pub script: Option<PathBuf>, // Rhai script hooked into the emulator
+    pub cheats: Option<PathBuf>, // Cheat file to use instead of the one next to the ROM
+    pub rom_dir: Option<PathBuf>, // Directory to pick a program from when none is given
+    pub platform: Option<Profile>, // Quirks and speed to use, override the ROM database
+    pub lag: LagPolicy, // What to do about frames missed while running behind
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, Chip8Error> {
+        let mut parsed = Args { rom: None, banks: Vec::new(), record_input: None, replay_input: None, record_gif: None, heatmap: None, events: None, database: None, no_database: false, palette: None, fullscreen: false, vsync: false, phosphor: false, stats: false, autofire: Vec::new(), turbo: None, filter: Filter::None, sound_indicator: SoundIndicator::None, font: None, icon: None, backend: DEFAULT_BACKEND, audio: AudioSettings::default(), no_audio: false, watchpoints: Vec::new(), breakpoints: Vec::new(), labels: None, warn_smc: false, protection: Protection::Off, gdb: None, http: None, netplay: None, script: None, cheats: None, rom_dir: None, platform: None, lag: LagPolicy::default() };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record-input" => parsed.record_input = Some(value(&arg, &mut args)?.into()),
+                "--replay-input" => parsed.replay_input = Some(value(&arg, &mut args)?.into()),
+                "--record" => parsed.record_gif = Some(value(&arg, &mut args)?.into()),
+                "--heatmap" => parsed.heatmap = Some(value(&arg, &mut args)?.into()),
+                "--events" => parsed.events = Some(value(&arg, &mut args)?.into()),
+                "--database" => parsed.database = Some(value(&arg, &mut args)?.into()),
+                "--no-database" => parsed.no_database = true,
+                "--fullscreen" => parsed.fullscreen = true,
+                "--vsync" => parsed.vsync = true,
+                "--phosphor" => parsed.phosphor = true,
+                "--stats" => parsed.stats = true,
+                "--autofire" => parsed.autofire.push(autofire(&value(&arg, &mut args)?)?),
+                "--turbo" => parsed.turbo = Some(number(&arg, &value(&arg, &mut args)?)?.min(u32::MAX as u64) as u32),
+                "--filter" => parsed.filter = filter(&value(&arg, &mut args)?)?,
+                "--sound-indicator" => parsed.sound_indicator = sound_indicator(&value(&arg, &mut args)?)?,
+                "--font" => parsed.font = Some(value(&arg, &mut args)?),
+                "--icon" => parsed.icon = Some(value(&arg, &mut args)?.into()),
+                "--backend" => parsed.backend = backend(&value(&arg, &mut args)?)?,
+                "--waveform" => parsed.audio.waveform = waveform(&value(&arg, &mut args)?)?,
+                "--frequency" => parsed.audio.frequency = frequency(&arg, &value(&arg, &mut args)?)?,
+                "--no-audio" => parsed.no_audio = true,
+                "--warn-smc" => parsed.warn_smc = true,
+                "--protect" => parsed.protection = protection(&value(&arg, &mut args)?)?,
+                "--break" => parsed.breakpoints.push(Breakpoint::parse(&value(&arg, &mut args)?)?),
+                "--labels" => parsed.labels = Some(value(&arg, &mut args)?.into()),
+                "--cheats" => parsed.cheats = Some(value(&arg, &mut args)?.into()),
+                "--roms" => parsed.rom_dir = Some(value(&arg, &mut args)?.into()),
+                "--platform" => parsed.platform = Some(platform(&value(&arg, &mut args)?)?),
+                "--lag" => parsed.lag = lag(&value(&arg, &mut args)?)?,
+                "--script" => parsed.script = Some(value(&arg, &mut args)?.into()),
+                "--gdb" => parsed.gdb = Some(port(&arg, &value(&arg, &mut args)?)?),
+                "--http" => parsed.http = Some(port(&arg, &value(&arg, &mut args)?)?),
+                "--netplay" => parsed.netplay = Some(match value(&arg, &mut args)?.as_str() {
+                    "host" => NetplayMode::Host,
+                    "join" => NetplayMode::Join(value("--netplay join", &mut args)?),
+                    mode => return Err(invalid(format!("--netplay expects host or join ADDRESS, got {mode}"))),
+                }),
+                "--watch" => parsed.watchpoints.push(Watchpoint::parse(&value(&arg, &mut args)?)?),
+                "--volume" => parsed.audio.volume = number(&arg, &value(&arg, &mut args)?)?.min(u8::MAX as u64) as u8,
+                "--palette" => parsed.palette = Some(palette(&value(&arg, &mut args)?)?),
+                "--colors" => parsed.palette = Some(Palette::parse(&value(&arg, &mut args)?)?),
+                flag if flag.starts_with("--") => return Err(unknown(flag)),
+                _ if parsed.rom.is_none() => parsed.rom = Some(arg.into()),
+                _ => parsed.banks.push(arg.into()),
+            }
+        }
+        parsed.audio.validate()?;
+        if parsed.netplay.is_some() && parsed.replay_input.is_some() {
+            return Err(invalid("--netplay can't replay recorded input".to_string()));
+        }
+        Ok(parsed)
+    }
+}
+
+// Options for running a ROM headlessly and checking the screen
+pub struct TestArgs {
+    pub rom: PathBuf,
+    pub cycles: u64, // Instructions to execute
+    pub expect: Option<Expectation>, // Nothing is checked without one
+    pub save: Option<PathBuf>, // Where to save the resulting screen as a reference image or text
+    pub replay_input: Option<PathBuf>, // Recorded key presses to feed the program
+    pub platform: Option<Profile>, // Quirks and speed to run with
+}
+
+impl TestArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<TestArgs, Chip8Error> {
+        let mut rom = None;
+        let mut parsed = TestArgs { rom: PathBuf::new(), cycles: 1_000_000, expect: None, save: None, replay_input: None, platform: None };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--cycles" => parsed.cycles = number(&arg, &value(&arg, &mut args)?)?,
+                "--hash" => parsed.expect = Some(Expectation::Hash(number(&arg, &value(&arg, &mut args)?)?)),
+                "--image" => parsed.expect = Some(Expectation::Image(value(&arg, &mut args)?.into())),
+                "--text" => parsed.expect = Some(Expectation::Text(value(&arg, &mut args)?.into())),
+                "--save" => parsed.save = Some(value(&arg, &mut args)?.into()),
+                "--replay-input" => parsed.replay_input = Some(value(&arg, &mut args)?.into()),
+                "--platform" => parsed.platform = Some(platform(&value(&arg, &mut args)?)?),
+                flag if flag.starts_with("--") => return Err(unknown(flag)),
+                _ if rom.is_none() => rom = Some(arg.into()),
+                _ => return Err(unexpected(&arg)),
+            }
+        }
+        parsed.rom = rom.ok_or(LoadError::MissingFilePath)?;
+        Ok(parsed)
+    }
+}
+
+// Options for benchmarking a ROM
+pub struct BenchArgs {
+    pub rom: PathBuf,
+    pub cycles: u64, // Instructions to execute
+    pub predecode: bool, // Decode the program up front
+    pub platform: Option<Profile>, // Quirks and speed to run with
+}
+
+impl BenchArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<BenchArgs, Chip8Error> {
+        let mut rom = None;
+        let mut cycles = 10_000_000;
+        let mut predecode = false;
+        let mut platform_profile = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--cycles" => cycles = number(&arg, &value(&arg, &mut args)?)?,
+                "--predecode" => predecode = true,
+                "--platform" => platform_profile = Some(platform(&value(&arg, &mut args)?)?),
+                flag if flag.starts_with("--") => return Err(unknown(flag)),
+                _ if rom.is_none() => rom = Some(arg.into()),
+                _ => return Err(unexpected(&arg)),
+            }
+        }
+        Ok(BenchArgs { rom: rom.ok_or(LoadError::MissingFilePath)?, cycles, predecode, platform: platform_profile })
+    }
+}
+
+// Options for checking a ROM's execution against a trace, or writing one
+pub struct VerifyArgs {
+    pub rom: PathBuf,
+    pub trace: Option<PathBuf>, // Reference trace to compare with
+    pub save: Option<PathBuf>, // Where to write the trace instead
+    pub cycles: u64, // Instructions to trace when writing one
+    pub labels: Option<PathBuf>, // Names for addresses in the written trace
+    pub platform: Option<Profile>, // Quirks and speed to run with
+}
+
+impl VerifyArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<VerifyArgs, Chip8Error> {
+        let mut rom = None;
+        let mut parsed = VerifyArgs { rom: PathBuf::new(), trace: None, save: None, cycles: 1_000_000, labels: None, platform: None };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--trace" => parsed.trace = Some(value(&arg, &mut args)?.into()),
+                "--save" => parsed.save = Some(value(&arg, &mut args)?.into()),
+                "--cycles" => parsed.cycles = number(&arg, &value(&arg, &mut args)?)?,
+                "--labels" => parsed.labels = Some(value(&arg, &mut args)?.into()),
+                "--platform" => parsed.platform = Some(platform(&value(&arg, &mut args)?)?),
+                flag if flag.starts_with("--") => return Err(unknown(flag)),
+                _ if rom.is_none() => rom = Some(arg.into()),
+                _ => return Err(unexpected(&arg)),
+            }
+        }
+        parsed.rom = rom.ok_or(LoadError::MissingFilePath)?;
+        if parsed.trace.is_some() == parsed.save.is_some() {
+            return Err(invalid("verify expects either --trace or --save".to_string()));
+        }
+        Ok(parsed)
+    }
+}
+
+// Options for looking at a ROM without running it
+pub struct InfoArgs {
+    pub rom: PathBuf,
+    pub platform: Option<Profile>, // Where the ROM is loaded, at 0x200 without one
+}
+
+impl InfoArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<InfoArgs, Chip8Error> {
+        let mut rom = None;
+        let mut platform_profile = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--platform" => platform_profile = Some(platform(&value(&arg, &mut args)?)?),
+                flag if flag.starts_with("--") => return Err(unknown(flag)),
+                _ if rom.is_none() => rom = Some(arg.into()),
+                _ => return Err(unexpected(&arg)),
+            }
+        }
+        Ok(InfoArgs { rom: rom.ok_or(LoadError::MissingFilePath)?, platform: platform_profile })
+    }
+}
+
+// Options for turning a ROM into Octo source
+pub struct DecompileArgs {
+    pub rom: PathBuf,
+    pub out: Option<PathBuf>, // Where to write the source, printed without one
+    pub platform: Option<Profile>, // Where the ROM is loaded, at 0x200 without one
+}
+
+impl DecompileArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<DecompileArgs, Chip8Error> {
+        let mut rom = None;
+        let mut out = None;
+        let mut platform_profile = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-o" | "--output" => out = Some(value(&arg, &mut args)?.into()),
+                "--platform" => platform_profile = Some(platform(&value(&arg, &mut args)?)?),
+                flag if flag.starts_with("--") => return Err(unknown(flag)),
+                _ if rom.is_none() => rom = Some(arg.into()),
+                _ => return Err(unexpected(&arg)),
+            }
+        }
+        Ok(DecompileArgs { rom: rom.ok_or(LoadError::MissingFilePath)?, out, platform: platform_profile })
+    }
+}
+
+// Options for the sprite sheet of a ROM
+pub struct SpritesArgs {
+    pub rom: PathBuf,
+    pub height: usize, // Rows of every sprite, 5 for font-sized ones
+    pub out: Option<PathBuf>, // Where to save the sheet as a PNG, shown in a window without one
+    pub platform: Option<Profile>, // Where the ROM is loaded, at 0x200 without one
+}
+
+impl SpritesArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<SpritesArgs, Chip8Error> {
+        let mut rom = None;
+        let mut parsed = SpritesArgs { rom: PathBuf::new(), height: FONT_HEIGHT, out: None, platform: None };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--height" => parsed.height = number(&arg, &value(&arg, &mut args)?)? as usize,
+                "-o" | "--output" => parsed.out = Some(value(&arg, &mut args)?.into()),
+                "--platform" => parsed.platform = Some(platform(&value(&arg, &mut args)?)?),
+                flag if flag.starts_with("--") => return Err(unknown(flag)),
+                _ if rom.is_none() => rom = Some(arg.into()),
+                _ => return Err(unexpected(&arg)),
+            }
+        }
+        parsed.rom = rom.ok_or(LoadError::MissingFilePath)?;
+        Ok(parsed)
+    }
+}
+
+// Takes two or more ROM paths
+fn roms(args: impl Iterator<Item = String>) -> Result<Vec<PathBuf>, Chip8Error> {
+    let mut roms = Vec::new();
+    for arg in args {
+        if arg.starts_with("--") {
+            return Err(unknown(&arg));
+        }
+        roms.push(PathBuf::from(arg));
+    }
+    match roms.len() {
+        0 => Err(LoadError::MissingFilePath.into()),
+        1 => Err(invalid("compare expects at least two ROMs".to_string())),
+        _ => Ok(roms),
+    }
+}
+
+// Takes the value following a flag
+fn value(flag: &str, args: &mut impl Iterator<Item = String>) -> Result<String, Chip8Error> {
+    args.next().ok_or_else(|| invalid(format!("missing value for {flag}")))
+}
+
+// Parses a decimal or 0x prefixed hex number, underscores are allowed as separators
+fn number(flag: &str, value: &str) -> Result<u64, Chip8Error> {
+    let value = value.replace('_', "");
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| invalid(format!("{flag} expects a number, got {value}")))
+}
+
+// Parses a TCP port number
+fn port(flag: &str, value: &str) -> Result<u16, Chip8Error> {
+    value.parse().map_err(|_| invalid(format!("{flag} expects a port number, got {value}")))
+}
+
+// Looks up a palette preset by name
+fn palette(name: &str) -> Result<Palette, Chip8Error> {
+    Palette::preset(name).ok_or_else(|| {
+        let names = Palette::preset_names().collect::<Vec<&str>>().join(", ");
+        invalid(format!("unknown palette {name}, expected one of {names}"))
+    })
+}
+
+// Looks up a platform profile by name
+fn platform(name: &str) -> Result<Profile, Chip8Error> {
+    Profile::parse(name).ok_or_else(|| {
+        let names = Profile::names().collect::<Vec<&str>>().join(", ");
+        invalid(format!("unknown platform {name}, expected one of {names}"))
+    })
+}
+
+// Parses what to do about missed frames
+fn lag(value: &str) -> Result<LagPolicy, Chip8Error> {
+    LagPolicy::parse(value).ok_or_else(|| invalid(format!("--lag expects catch-up, skip or a number of frames, got {value}")))
+}
+
+// Parses a hex chip8 key and the presses per second it autofires at, e.g. 5:15
+fn autofire(value: &str) -> Result<(u8, u32), Chip8Error> {
+    let parsed = value.split_once(':').and_then(|(key, rate)| {
+        let key = u8::from_str_radix(key, 16).ok().filter(|&key| key < 16)?;
+        Some((key, rate.parse().ok().filter(|&rate| rate > 0)?))
+    });
+    parsed.ok_or_else(|| invalid(format!("--autofire expects a key and presses per second, e.g. 5:15, got {value}")))
+}
+
+// Parses a frequency in Hz, fractions are allowed
+fn frequency(flag: &str, value: &str) -> Result<f32, Chip8Error> {
+    value.parse().map_err(|_| invalid(format!("{flag} expects a frequency in Hz, got {value}")))
+}
+
+// Looks up a waveform by name
+fn waveform(name: &str) -> Result<Waveform, Chip8Error> {
+    Waveform::parse(name).ok_or_else(|| {
+        let names = Waveform::names().collect::<Vec<&str>>().join(", ");
+        invalid(format!("unknown waveform {name}, expected one of {names}"))
+    })
+}
+
+// Looks up a post-processing filter by name
+fn filter(name: &str) -> Result<Filter, Chip8Error> {
+    Filter::parse(name).ok_or_else(|| {
+        let names = Filter::names().collect::<Vec<&str>>().join(", ");
+        invalid(format!("unknown filter {name}, expected one of {names}"))
+    })
+}
+
+// Every backend is known, opening one the frontend was built without fails with the feature it needs
+fn backend(name: &str) -> Result<Backend, Chip8Error> {
+    Backend::parse(name).ok_or_else(|| {
+        let names = Backend::names().collect::<Vec<&str>>().join(", ");
+        invalid(format!("unknown window backend {name}, expected one of {names}"))
+    })
+}
+
+fn sound_indicator(name: &str) -> Result<SoundIndicator, Chip8Error> {
+    SoundIndicator::parse(name).ok_or_else(|| {
+        let names = SoundIndicator::names().collect::<Vec<&str>>().join(", ");
+        invalid(format!("unknown sound indicator {name}, expected one of {names}"))
+    })
+}
+
+fn protection(name: &str) -> Result<Protection, Chip8Error> {
+    Protection::parse(name).ok_or_else(|| {
+        let names = Protection::names().collect::<Vec<&str>>().join(", ");
+        invalid(format!("unknown protection {name}, expected one of {names}"))
+    })
+}
+
+fn invalid(message: String) -> Chip8Error {
+    LoadError::InvalidArgument(message).into()
+}
+
+fn unknown(flag: &str) -> Chip8Error {
+    invalid(format!("unknown option {flag}"))
+}
+
+fn unexpected(arg: &str) -> Chip8Error {
+    invalid(format!("unexpected argument {arg}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, Chip8Error> {
+        Command::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn parse_run(args: &[&str]) -> Result<Args, Chip8Error> {
+        match parse(args)? {
+            Command::Run(args) => Ok(*args),
+            _ => panic!("expected the run command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_rom() {
+        let args = parse_run(&["chip8", "pong.ch8"]).unwrap();
+        assert_eq!(args.rom, Some(PathBuf::from("pong.ch8")));
+        assert_eq!(args.record_input, None);
+    }
+
+    #[test]
+    fn test_parse_record_input() {
+        let args = parse_run(&["chip8", "--record-input", "keys.txt", "pong.ch8"]).unwrap();
+        assert_eq!(args.rom, Some(PathBuf::from("pong.ch8")));
+        assert_eq!(args.record_input, Some(PathBuf::from("keys.txt")));
+
+        let args = parse_run(&["chip8", "pong.ch8", "--replay-input", "keys.txt"]).unwrap();
+        assert_eq!(args.replay_input, Some(PathBuf::from("keys.txt")));
+        let Command::Test(args) = parse(&["chip8", "test", "ibm.ch8", "--text", "ibm.txt"]).unwrap() else {
+            panic!("expected the test command");
+        };
+        assert!(matches!(args.expect, Some(Expectation::Text(path)) if path.to_str() == Some("ibm.txt")));
+    }
+
+    #[test]
+    fn test_parse_record_gif() {
+        let args = parse_run(&["chip8", "pong.ch8", "--record", "out.gif"]).unwrap();
+        assert_eq!(args.record_gif, Some(PathBuf::from("out.gif")));
+        let args = parse_run(&["chip8", "pong.ch8", "--heatmap", "heat.png"]).unwrap();
+        assert_eq!(args.heatmap, Some(PathBuf::from("heat.png")));
+        let args = parse_run(&["chip8", "pong.ch8", "--events", "out.jsonl"]).unwrap();
+        assert_eq!(args.events, Some(PathBuf::from("out.jsonl")));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse_run(&["chip8"]).unwrap().rom.is_none());
+        assert!(parse(&["chip8", "--record-input"]).is_err());
+        assert!(parse(&["chip8", "--unknown"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_banks() {
+        let args = parse_run(&["chip8", "run", "a.ch8", "--phosphor", "b.ch8", "c.ch8"]).unwrap();
+        assert_eq!(args.rom, Some(PathBuf::from("a.ch8")));
+        assert_eq!(args.banks, vec![PathBuf::from("b.ch8"), PathBuf::from("c.ch8")]);
+        assert!(parse_run(&["chip8", "a.ch8"]).unwrap().banks.is_empty());
+    }
+
+    #[test]
+    fn test_parse_database() {
+        let args = parse_run(&["chip8", "pong.ch8", "--database", "programs.json"]).unwrap();
+        assert_eq!(args.database, Some(PathBuf::from("programs.json")));
+        assert!(!args.no_database);
+        assert!(parse_run(&["chip8", "--no-database", "pong.ch8"]).unwrap().no_database);
+        assert!(parse(&["chip8", "pong.ch8", "--database"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_palette() {
+        let args = parse_run(&["chip8", "pong.ch8", "--palette", "gameboy"]).unwrap();
+        assert_eq!(args.palette, Palette::preset("gameboy"));
+        let args = parse_run(&["chip8", "pong.ch8", "--colors", "FF00FF,000000"]).unwrap();
+        assert_eq!(args.palette, Palette::parse("FF00FF,000000").ok());
+        assert!(parse(&["chip8", "--palette", "sepia"]).is_err());
+        assert!(parse(&["chip8", "--colors", "FF00FF"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_platform() {
+        assert_eq!(parse_run(&["chip8", "pong.ch8", "--platform", "schip"]).unwrap().platform, Profile::parse("schip"));
+        assert_eq!(parse_run(&["chip8"]).unwrap().platform, None);
+        assert!(parse(&["chip8", "--platform", "megachip"]).is_err());
+        match parse(&["chip8", "test", "rom.ch8", "--platform", "chip48"]).unwrap() {
+            Command::Test(args) => assert_eq!(args.platform, Profile::parse("chip48")),
+            _ => panic!("expected the test command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_lag() {
+        assert_eq!(parse_run(&["chip8", "--lag", "skip"]).unwrap().lag, LagPolicy::Skip);
+        assert_eq!(parse_run(&["chip8", "--lag", "10"]).unwrap().lag, LagPolicy::Clamp(10));
+        assert_eq!(parse_run(&["chip8"]).unwrap().lag, LagPolicy::default());
+        assert!(parse(&["chip8", "--lag", "fast"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_fullscreen() {
+        assert!(parse_run(&["chip8", "--fullscreen"]).unwrap().fullscreen);
+        assert!(!parse_run(&["chip8"]).unwrap().fullscreen);
+    }
+
+    #[test]
+    fn test_parse_vsync() {
+        assert!(parse_run(&["chip8", "--vsync"]).unwrap().vsync);
+        assert!(!parse_run(&["chip8"]).unwrap().vsync);
+    }
+
+    #[test]
+    fn test_parse_phosphor() {
+        assert!(parse_run(&["chip8", "--phosphor", "pong.ch8"]).unwrap().phosphor);
+        assert!(!parse_run(&["chip8", "pong.ch8"]).unwrap().phosphor);
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        assert!(parse_run(&["chip8", "--stats", "pong.ch8"]).unwrap().stats);
+        assert!(!parse_run(&["chip8", "pong.ch8"]).unwrap().stats);
+    }
+
+    #[test]
+    fn test_parse_netplay() {
+        assert_eq!(parse_run(&["chip8", "pong.ch8", "--netplay", "host"]).unwrap().netplay, Some(NetplayMode::Host));
+        let args = parse_run(&["chip8", "pong.ch8", "--netplay", "join", "192.168.1.5"]).unwrap();
+        assert_eq!(args.netplay, Some(NetplayMode::Join("192.168.1.5".to_string())));
+        assert!(parse(&["chip8", "pong.ch8", "--netplay", "join"]).is_err());
+        assert!(parse(&["chip8", "pong.ch8", "--netplay", "both"]).is_err());
+        assert!(parse(&["chip8", "pong.ch8", "--netplay", "host", "--replay-input", "keys.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_autofire() {
+        let args = parse_run(&["chip8", "--autofire", "5:15", "--autofire", "a:10", "--turbo", "8", "shooter.ch8"]).unwrap();
+        assert_eq!(args.autofire, vec![(0x5, 15), (0xA, 10)]);
+        assert_eq!(args.turbo, Some(8));
+        assert!(parse(&["chip8", "--autofire", "10:15"]).is_err());
+        assert!(parse(&["chip8", "--autofire", "5:0"]).is_err());
+        assert!(parse(&["chip8", "--autofire", "5"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(parse_run(&["chip8", "--filter", "scanlines"]).unwrap().filter, Filter::Scanlines);
+        assert_eq!(parse_run(&["chip8"]).unwrap().filter, Filter::None);
+        assert!(parse(&["chip8", "--filter", "blur"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_sound_indicator() {
+        assert_eq!(parse_run(&["chip8", "--sound-indicator", "border"]).unwrap().sound_indicator, SoundIndicator::Border);
+        assert_eq!(parse_run(&["chip8"]).unwrap().sound_indicator, SoundIndicator::None);
+        assert!(parse(&["chip8", "--sound-indicator", "flash"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_audio() {
+        let args = parse_run(&["chip8", "--waveform", "square", "--frequency", "523.25", "--volume", "40"]).unwrap();
+        assert_eq!(args.audio, AudioSettings { waveform: Waveform::Square, frequency: 523.25, volume: 40 });
+        assert_eq!(parse_run(&["chip8"]).unwrap().audio, AudioSettings::default());
+        assert!(parse(&["chip8", "--waveform", "sawtooth"]).is_err());
+        assert!(parse(&["chip8", "--frequency", "high"]).is_err());
+        assert!(parse(&["chip8", "--volume", "150"]).is_err());
+        assert!(parse_run(&["chip8", "--no-audio"]).unwrap().no_audio);
+        assert!(!parse_run(&["chip8"]).unwrap().no_audio);
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        let args = parse_run(&["chip8", "--watch", "0x300:w", "--watch", "0x200-0x20F", "pong.ch8"]).unwrap();
+        assert_eq!(args.watchpoints, vec![Watchpoint::parse("0x300:w").unwrap(), Watchpoint::parse("0x200-0x20F").unwrap()]);
+        assert!(parse(&["chip8", "--watch", "everything"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let args = parse_run(&["chip8", "pong.ch8", "--script", "bot.rhai"]).unwrap();
+        assert_eq!(args.script, Some(PathBuf::from("bot.rhai")));
+        assert_eq!(args.rom, Some(PathBuf::from("pong.ch8")));
+    }
+
+    #[test]
+    fn test_parse_font() {
+        assert_eq!(parse_run(&["chip8", "--font", "eti660", "pong.ch8"]).unwrap().font.as_deref(), Some("eti660"));
+        assert_eq!(parse_run(&["chip8"]).unwrap().font, None);
+        assert!(parse(&["chip8", "--font"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_backend() {
+        assert_eq!(parse_run(&["chip8", "--backend", "minifb", "pong.ch8"]).unwrap().backend, Backend::Minifb);
+        assert_eq!(parse_run(&["chip8"]).unwrap().backend, DEFAULT_BACKEND);
+        assert!(parse(&["chip8", "--backend", "vulkan"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_icon() {
+        assert_eq!(parse_run(&["chip8", "--icon", "logo.png", "pong.ch8"]).unwrap().icon, Some(PathBuf::from("logo.png")));
+        assert_eq!(parse_run(&["chip8"]).unwrap().icon, None);
+    }
+
+    #[test]
+    fn test_parse_labels() {
+        let args = parse_run(&["chip8", "--labels", "pong.sym", "pong.ch8"]).unwrap();
+        assert_eq!(args.labels, Some(PathBuf::from("pong.sym")));
+        assert!(parse(&["chip8", "--labels"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_cheats() {
+        let args = parse_run(&["chip8", "--cheats", "lives.cht", "brix.ch8"]).unwrap();
+        assert_eq!(args.cheats, Some(PathBuf::from("lives.cht")));
+        assert!(parse(&["chip8", "--cheats"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rom_dir() {
+        let args = parse_run(&["chip8", "--roms", "games"]).unwrap();
+        assert_eq!(args.rom_dir, Some(PathBuf::from("games")));
+        assert_eq!(args.rom, None);
+        assert!(parse(&["chip8", "--roms"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_gdb() {
+        let args = parse_run(&["chip8", "--gdb", "2159", "pong.ch8"]).unwrap();
+        assert_eq!(args.gdb, Some(2159));
+        assert!(parse(&["chip8", "--gdb", "70000"]).is_err());
+        assert_eq!(parse_run(&["chip8", "--http", "8080"]).unwrap().http, Some(8080));
+    }
+
+    #[test]
+    fn test_parse_break() {
+        let args = parse_run(&["chip8", "--break", "0x2F0 if V3 == 0x1F", "--break", "draw", "pong.ch8"]).unwrap();
+        assert_eq!(args.breakpoints, vec![Breakpoint::parse("0x2F0 if V3 == 0x1F").unwrap(), Breakpoint::parse("draw").unwrap()]);
+        assert!(parse(&["chip8", "--break", "0x2F0 if"]).is_err());
+        assert!(!args.warn_smc);
+        assert!(parse_run(&["chip8", "--warn-smc", "pong.ch8"]).unwrap().warn_smc);
+    }
+
+    #[test]
+    fn test_parse_protect() {
+        assert_eq!(parse_run(&["chip8", "--protect", "strict", "pong.ch8"]).unwrap().protection, Protection::Strict);
+        assert_eq!(parse_run(&["chip8"]).unwrap().protection, Protection::Off);
+        assert!(parse(&["chip8", "--protect", "always"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_test() {
+        let Command::Test(args) = parse(&["chip8", "test", "ibm.ch8", "--cycles", "10_000", "--hash", "0xff"]).unwrap() else {
+            panic!("expected the test command");
+        };
+        assert_eq!(args.rom, PathBuf::from("ibm.ch8"));
+        assert_eq!(args.cycles, 10_000);
+        assert!(matches!(args.expect, Some(Expectation::Hash(0xff))));
+        assert!(parse(&["chip8", "test"]).is_err());
+
+        let Command::Test(args) = parse(&["chip8", "test", "pong.ch8", "--replay-input", "keys.txt"]).unwrap() else {
+            panic!("expected the test command");
+        };
+        assert_eq!(args.replay_input, Some(PathBuf::from("keys.txt")));
+        assert!(parse(&["chip8", "test", "ibm.ch8", "--cycles", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_info() {
+        assert!(matches!(parse(&["chip8", "info", "pong.ch8"]), Ok(Command::Info(args)) if args.rom.to_str() == Some("pong.ch8") && args.platform.is_none()));
+        assert!(parse(&["chip8", "info"]).is_err());
+        assert!(parse(&["chip8", "info", "a.ch8", "b.ch8"]).is_err());
+        assert!(matches!(parse(&["chip8", "check", "pong.ch8"]), Ok(Command::Check(args)) if args.rom.to_str() == Some("pong.ch8")));
+        let Command::Check(args) = parse(&["chip8", "check", "--platform", "eti660", "pong.ch8"]).unwrap() else {
+            panic!("expected the check command");
+        };
+        assert_eq!(args.platform, Profile::parse("eti660"));
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        let Command::Bench(args) = parse(&["chip8", "bench", "pong.ch8", "--cycles", "1_000"]).unwrap() else {
+            panic!("expected the bench command");
+        };
+        assert_eq!(args.rom, PathBuf::from("pong.ch8"));
+        assert_eq!(args.cycles, 1000);
+        let Command::Bench(args) = parse(&["chip8", "bench", "pong.ch8"]).unwrap() else {
+            panic!("expected the bench command");
+        };
+        assert_eq!(args.cycles, 10_000_000);
+        assert!(!args.predecode);
+        let Command::Bench(args) = parse(&["chip8", "bench", "--predecode", "pong.ch8"]).unwrap() else {
+            panic!("expected the bench command");
+        };
+        assert!(args.predecode);
+        assert!(parse(&["chip8", "bench"]).is_err());
+        assert!(parse(&["chip8", "bench", "pong.ch8", "--hash", "0x1"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_conformance() {
+        assert!(matches!(parse(&["chip8", "conformance"]), Ok(Command::Conformance)));
+        assert!(parse(&["chip8", "conformance", "extra"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        let Command::Verify(args) = parse(&["chip8", "verify", "pong.ch8", "--trace", "reference.log"]).unwrap() else {
+            panic!("expected the verify command");
+        };
+        assert_eq!(args.rom, PathBuf::from("pong.ch8"));
+        assert_eq!(args.trace, Some(PathBuf::from("reference.log")));
+        let Command::Verify(args) = parse(&["chip8", "verify", "pong.ch8", "--save", "out.log", "--cycles", "500"]).unwrap() else {
+            panic!("expected the verify command");
+        };
+        assert_eq!((args.save, args.cycles), (Some(PathBuf::from("out.log")), 500));
+        let Command::Verify(args) = parse(&["chip8", "verify", "pong.ch8", "--save", "out.log", "--labels", "pong.sym"]).unwrap() else {
+            panic!("expected the verify command");
+        };
+        assert_eq!(args.labels, Some(PathBuf::from("pong.sym")));
+        assert!(parse(&["chip8", "verify", "pong.ch8"]).is_err());
+        assert!(parse(&["chip8", "verify", "pong.ch8", "--trace", "a.log", "--save", "b.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_decompile() {
+        let Command::Decompile(args) = parse(&["chip8", "decompile", "pong.ch8", "-o", "pong.8o"]).unwrap() else {
+            panic!("expected the decompile command");
+        };
+        assert_eq!((args.rom, args.out), (PathBuf::from("pong.ch8"), Some(PathBuf::from("pong.8o"))));
+        let Command::Decompile(args) = parse(&["chip8", "decompile", "pong.ch8"]).unwrap() else {
+            panic!("expected the decompile command");
+        };
+        assert_eq!((args.out, args.platform), (None, None));
+        let Command::Decompile(args) = parse(&["chip8", "decompile", "pong.ch8", "--platform", "xochip"]).unwrap() else {
+            panic!("expected the decompile command");
+        };
+        assert_eq!(args.platform, Profile::parse("xochip"));
+        assert!(parse(&["chip8", "decompile", "-o", "pong.8o"]).is_err());
+        assert!(parse(&["chip8", "decompile", "pong.ch8", "-o"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_sprites() {
+        let Command::Sprites(args) = parse(&["chip8", "sprites", "pong.ch8", "--height", "15", "-o", "sheet.png"]).unwrap() else {
+            panic!("expected the sprites command");
+        };
+        assert_eq!((args.rom, args.height, args.out), (PathBuf::from("pong.ch8"), 15, Some(PathBuf::from("sheet.png"))));
+        let Command::Sprites(args) = parse(&["chip8", "sprites", "pong.ch8"]).unwrap() else {
+            panic!("expected the sprites command");
+        };
+        assert_eq!((args.height, args.out), (FONT_HEIGHT, None));
+        assert!(parse(&["chip8", "sprites", "--height", "5"]).is_err());
+        assert!(parse(&["chip8", "sprites", "pong.ch8", "--height", "tall"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_compare() {
+        let Command::Compare(roms) = parse(&["chip8", "compare", "a.ch8", "b.ch8"]).unwrap() else {
+            panic!("expected the compare command");
+        };
+        assert_eq!(roms, vec![PathBuf::from("a.ch8"), PathBuf::from("b.ch8")]);
+        assert!(parse(&["chip8", "compare", "a.ch8"]).is_err());
+        assert!(parse(&["chip8", "compare", "a.ch8", "b.ch8", "--phosphor"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_tour() {
+        assert!(matches!(parse(&["chip8", "tour"]), Ok(Command::Tour)));
+        assert!(parse(&["chip8", "tour", "extra"]).is_err());
+    }
+}
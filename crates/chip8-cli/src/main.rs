@@ -0,0 +1,557 @@
+mod cli;
+
+use chip8_core::{Chip8, Chip8Builder, Key, Memory};
+use chip8_core::{bench, capabilities::Feature, compat::{CompatReport, Verdict}, conformance, database::Database, decompile, errors::{report, Chip8Error, IoError}, events::Event, font, icon::WindowIcon, labels::Labels, memory::Layout, netplay::{NetplayMode, NETPLAY_PORT}, octo, palette::Palette, patches::Patches, profile::Profile, rom::{self, RomBank, RomInfo}, splash::{SPLASH, TUTORIAL}, sprites::SpriteSheet, testing, trace};
+use chip8_core::frontend::Scale;
+use chip8_frontend_minifb::MinifbFrontend;
+use cli::{Args, BenchArgs, Command, DecompileArgs, InfoArgs, SpritesArgs, TestArgs, VerifyArgs};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::{env, fs, io, thread};
+
+// Directory browsed for ROMs when neither a ROM nor --roms is given
+const DEFAULT_ROM_DIR: &str = "roms";
+
+// Windows of compared ROMs are 512 pixels wide at this scale, placed left to right with a gap
+const COMPARE_SCALE: Scale = Scale::X8;
+const COMPARE_SPACING: isize = 512 + 16;
+
+fn main() {
+    let command = Command::parse(env::args()).unwrap_or_else(|err| {
+        eprintln!("{}", report(&err));
+        process::exit(1);
+    });
+
+    match command {
+        Command::Run(args) => run(*args),
+        Command::Test(args) => test(args),
+        Command::Bench(args) => bench(args),
+        Command::Verify(args) => verify(args),
+        Command::Info(args) => info(args),
+        Command::Check(args) => check(args),
+        Command::Decompile(args) => decompile(args),
+        Command::Sprites(args) => sprites(args),
+        Command::Conformance => print!("{}", conformance::report()),
+        Command::Tour => tour(),
+        Command::Compare(roms) => compare(roms),
+    }
+}
+
+// Runs a ROM in a window
+fn run(args: Args) {
+    let mut builder = Chip8::builder().frontend(MinifbFrontend).palette(Palette::default()).phosphor(args.phosphor).filter(args.filter).sound_indicator(args.sound_indicator).audio(args.audio).protection(args.protection).backend(args.backend);
+    if let Some(profile) = args.platform {
+        // The rest of the profile is applied after the ROM database
+        builder = builder.memory_size(profile.memory_size).program_start(profile.program_start);
+    }
+    if let Some(name) = &args.font {
+        builder = builder.font(font::load(name).unwrap_or_else(|e| {
+            eprintln!("{}", report(&e));
+            process::exit(1);
+        }));
+    }
+    if let Some(path) = &args.icon {
+        builder = builder.icon(WindowIcon::from_file(path).unwrap_or_else(|e| {
+            eprintln!("{}", report(&e));
+            process::exit(1);
+        }));
+    }
+
+    // Show the built-in splash screen when no ROM is given
+    let (mut chip8, mut mem) = match &args.rom {
+        Some(path) => build(builder.rom_file(path)),
+        None => build(builder.rom(SPLASH)),
+    };
+
+    let mut features = Vec::new();
+    if !args.no_audio {
+        features.push(Feature::Audio);
+    }
+    if args.fullscreen {
+        features.push(Feature::Fullscreen);
+    }
+    if args.vsync {
+        features.push(Feature::Vsync);
+    }
+    enable(&mut chip8, &features);
+    chip8.set_lag_policy(args.lag);
+
+    chip8.insert_binding(0x2, Key::W);
+    chip8.insert_binding(0x4, Key::A);
+    chip8.insert_binding(0x6, Key::D);
+    chip8.insert_binding(0x8, Key::S);
+
+    for &watchpoint in &args.watchpoints {
+        mem.add_watchpoint(watchpoint);
+    }
+    chip8.on_event(report_event);
+    for breakpoint in &args.breakpoints {
+        chip8.add_breakpoint(breakpoint.clone());
+    }
+    label(&mut chip8, args.labels.as_deref(), args.rom.as_deref(), mem.layout());
+    chip8.set_self_modify_warnings(args.warn_smc);
+    chip8.set_stats_shown(args.stats);
+    for &(key, rate) in &args.autofire {
+        chip8.set_autofire(key, Some(rate));
+    }
+    if let Some(factor) = args.turbo {
+        chip8.set_turbo_factor(factor);
+    }
+    cheat(&mut chip8, &args, mem.layout());
+    if args.rom.is_none() {
+        browse(&mut chip8, &args);
+    }
+    if !args.banks.is_empty() {
+        add_banks(&mut chip8, &args, mem.layout());
+    }
+    if let Some(path) = &args.script {
+        load_script(&mut chip8, path);
+    }
+    if let Some(port) = args.gdb {
+        match chip8.listen_remote(("127.0.0.1", port)) {
+            Ok(addr) => println!("Waiting for a debugger on {addr}"),
+            Err(e) => {
+                eprintln!("{}", report(&e));
+                process::exit(1);
+            }
+        }
+    }
+    if let Some(port) = args.http {
+        listen_http(&mut chip8, port);
+    }
+
+    if !args.no_database {
+        let rom = match &args.rom {
+            Some(path) => rom::read(path, mem.layout()).unwrap_or_default(),
+            None => SPLASH.to_vec(),
+        };
+        configure(&mut chip8, args.database.as_deref(), &rom);
+    }
+
+    if let Some(palette) = args.palette {
+        if let Some(warning) = palette.contrast_warning() {
+            eprintln!("Warning: {warning}, try --palette high-contrast");
+        }
+        chip8.set_palette(palette);
+    }
+
+    if let Some(profile) = args.platform {
+        warn_unsupported(&profile);
+        chip8.set_quirks(profile.quirks);
+        chip8.set_tickrate(profile.tickrate);
+    }
+
+    if let Some(path) = &args.replay_input {
+        if let Err(e) = chip8.replay_input(path) {
+            eprintln!("Error while loading input recording: {}", report(&e));
+            process::exit(1);
+        }
+    }
+
+    if let Some(path) = &args.record_input {
+        if let Err(e) = chip8.record_input(path) {
+            eprintln!("Error while starting input recording: {}", report(&e));
+            process::exit(1);
+        }
+    }
+
+    if let Some(path) = &args.events {
+        if let Err(e) = chip8.record_events(path) {
+            eprintln!("Error while starting event log: {}", report(&e));
+            process::exit(1);
+        }
+    }
+
+    if let Some(path) = &args.record_gif {
+        if let Err(e) = chip8.start_gif_recording(path) {
+            eprintln!("Error while starting GIF recording: {}", report(&e));
+            process::exit(1);
+        }
+    }
+
+    if let Some(mode) = &args.netplay {
+        if *mode == NetplayMode::Host {
+            println!("Waiting for the other player on port {NETPLAY_PORT}");
+        }
+        if let Err(e) = chip8.start_netplay(mode, &mem) {
+            eprintln!("Error while connecting to the other player: {}", report(&e));
+            process::exit(1);
+        }
+    }
+
+    mem.set_heatmap(args.heatmap.is_some());
+    let result = chip8.run(&mut mem);
+    if let (Some(path), Some(heatmap)) = (&args.heatmap, mem.heatmap()) {
+        if let Err(e) = heatmap.save(path) {
+            eprintln!("Error while saving the heatmap: {}", report(&e));
+        }
+    }
+    if let Err(e) = result {
+        eprintln!("Error while running chip8: {}", report(&e));
+        process::exit(1);
+    }
+}
+
+// Runs the tutorial with hints for first-time users
+fn tour() {
+    let (mut chip8, mut mem) = build(Chip8::builder().frontend(MinifbFrontend).palette(Palette::default()).rom(TUTORIAL));
+    enable(&mut chip8, &[Feature::Audio]);
+    chip8.start_tour();
+
+    if let Err(e) = chip8.run(&mut mem) {
+        eprintln!("Error while running chip8: {}", report(&e));
+        process::exit(1);
+    }
+}
+
+// Runs the ROMs side by side, each in its own window on its own thread.
+// The emulator owns its window and audio stream, which can't move between threads,
+// so every instance is created by the thread running it. Only the first one plays sound.
+fn compare(roms: Vec<PathBuf>) {
+    let instances = roms.into_iter().enumerate().map(|(i, path)| {
+        thread::spawn(move || -> Result<(), Chip8Error> {
+            let (mut chip8, mut mem) = Chip8::builder().frontend(MinifbFrontend).palette(Palette::default()).rom_file(&path).build()?;
+            if i == 0 {
+                enable(&mut chip8, &[Feature::Audio]);
+            }
+            chip8.set_scale(COMPARE_SCALE);
+            chip8.set_window_position(i as isize * COMPARE_SPACING, 0);
+            configure(&mut chip8, None, &rom::read(&path, mem.layout()).unwrap_or_default());
+            chip8.run(&mut mem)
+        })
+    }).collect::<Vec<_>>();
+
+    let mut failed = false;
+    for instance in instances {
+        match instance.join() {
+            Ok(Ok(())) => (),
+            Ok(Err(e)) => {
+                eprintln!("Error while running chip8: {}", report(&e));
+                failed = true;
+            }
+            Err(_) => failed = true, // The panic message has been printed already
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+// Runs a ROM headlessly and compares the screen with the expectation
+fn test(args: TestArgs) {
+    let (mut chip8, mut mem) = build(with_profile(Chip8::builder().rom_file(&args.rom).headless(), args.platform));
+
+    if let Some(path) = &args.replay_input {
+        if let Err(e) = chip8.replay_input(path) {
+            eprintln!("Error while loading input recording: {}", report(&e));
+            process::exit(1);
+        }
+    }
+
+    if let Err(e) = testing::run_headless(&mut chip8, &mut mem, args.cycles) {
+        eprintln!("Error while running chip8: {}", report(&e));
+        process::exit(1);
+    }
+    println!("Framebuffer hash: {:#018x}", testing::framebuffer_hash(&chip8));
+
+    if let Some(path) = &args.save {
+        if let Err(e) = testing::save_screen(&chip8, path) {
+            eprintln!("{}", report(&e));
+            process::exit(1);
+        }
+    }
+
+    if let Some(expectation) = &args.expect {
+        match testing::matches(&chip8, expectation) {
+            Ok(true) => println!("PASS"),
+            Ok(false) => {
+                println!("FAIL");
+                process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("{}", report(&e));
+                process::exit(1);
+            }
+        }
+    }
+}
+
+// Runs a ROM headlessly as fast as possible and prints how long its instructions took
+fn bench(args: BenchArgs) {
+    let (mut chip8, mut mem) = build(with_profile(Chip8::builder().rom_file(&args.rom).headless().predecode(args.predecode), args.platform));
+    match bench::run(&mut chip8, &mut mem, args.cycles) {
+        Ok(report) => println!("{report}"),
+        Err(e) => {
+            eprintln!("Error while running chip8: {}", report(&e));
+            process::exit(1);
+        }
+    }
+}
+
+// Runs a ROM headlessly and compares the state after every instruction with a reference trace,
+// or writes the trace of the run to compare with later
+fn verify(args: VerifyArgs) {
+    let (mut chip8, mut mem) = build(with_profile(Chip8::builder().rom_file(&args.rom).headless(), args.platform));
+    label(&mut chip8, args.labels.as_deref(), Some(&args.rom), mem.layout());
+    if let Some(path) = &args.save {
+        let written: Result<(), Chip8Error> = fs::File::create(path)
+            .map_err(|source| IoError::Trace(source).into())
+            .and_then(|file| trace::record(&mut chip8, &mut mem, args.cycles, io::BufWriter::new(file)));
+        if let Err(e) = written {
+            eprintln!("Error while writing trace: {}", report(&e));
+            process::exit(1);
+        }
+    }
+
+    if let Some(path) = &args.trace {
+        let reference = trace::read(path).unwrap_or_else(|e| {
+            eprintln!("{}", report(&e));
+            process::exit(1);
+        });
+        match trace::verify(&mut chip8, &mut mem, &reference) {
+            Ok(None) => println!("Matched all {} instructions", reference.len()),
+            Ok(Some(divergence)) => {
+                println!("{divergence}");
+                process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Error while running chip8: {}", report(&e));
+                process::exit(1);
+            }
+        }
+    }
+}
+
+// Prints facts about a ROM
+fn info(args: InfoArgs) {
+    let layout = layout(args.platform);
+    match rom::read(&args.rom, layout) {
+        Ok(rom) => println!("{}", RomInfo::analyze(&rom, layout.program_start)),
+        Err(e) => {
+            eprintln!("{}", report(&e));
+            process::exit(1);
+        }
+    }
+}
+
+// Writes the ROM as Octo source, or prints it
+fn decompile(args: DecompileArgs) {
+    let layout = layout(args.platform);
+    let source = rom::read(&args.rom, layout).map(|rom| decompile::decompile(&rom, layout.program_start)).unwrap_or_else(|e| {
+        eprintln!("{}", report(&e));
+        process::exit(1);
+    });
+    match &args.out {
+        Some(path) => {
+            if let Err(e) = fs::write(path, source) {
+                eprintln!("Failed to write {}: {e}", path.display());
+                process::exit(1);
+            }
+        }
+        None => print!("{source}"),
+    }
+}
+
+// Saves or shows every candidate sprite in a ROM
+fn sprites(args: SpritesArgs) {
+    let layout = layout(args.platform);
+    let shown = rom::read(&args.rom, layout).and_then(|rom| SpriteSheet::scan(&rom, args.height, layout.program_start)).and_then(|sheet| match &args.out {
+        Some(path) => sheet.save(path),
+        None => sheet.show(&MinifbFrontend, &format!("Sprites - {}", args.rom.display())),
+    });
+    if let Err(e) = shown {
+        eprintln!("{}", report(&e));
+        process::exit(1);
+    }
+}
+
+// Prints whether a ROM is expected to run, failing if it isn't
+fn check(args: InfoArgs) {
+    let layout = layout(args.platform);
+    match rom::read(&args.rom, layout) {
+        Ok(rom) => {
+            let report = CompatReport::check(&rom, layout.program_start);
+            println!("{report}");
+            if report.verdict() == Verdict::Incompatible {
+                process::exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", report(&e));
+            process::exit(1);
+        }
+    }
+}
+
+// Turns on the features, warning about the ones the emulator has to do without
+fn enable(chip8: &mut Chip8, features: &[Feature]) {
+    for &feature in features {
+        if let Some(fallback) = chip8.enable(feature) {
+            eprintln!("Warning: {fallback}");
+        }
+    }
+}
+
+// Prints why the debugger paused and what it warns about, the emulator only reports them as events
+fn report_event(event: &Event) {
+    match event {
+        Event::Watchpoint { addr, access, pc, opcode } => println!("Watchpoint: {access} of {addr:#05X} by {opcode:04X} at {pc:#05X}"),
+        Event::ReservedWrite { addr, pc, opcode } => println!("Reserved memory: write to {addr:#05X} by {opcode:04X} at {pc:#05X}"),
+        Event::SelfModify { addr, pc, opcode } => println!("Self-modifying code: write to {addr:#05X} by {opcode:04X} at {pc:#05X}"),
+        Event::Breakpoint { breakpoint, pc, instruction, write: Some(addr) } => println!("Breakpoint: {breakpoint}, write to {addr:#05X} by {instruction} at {pc:#05X}"),
+        Event::Breakpoint { breakpoint, pc, instruction, write: None } => println!("Breakpoint: {breakpoint} at {pc:#05X}: {instruction}"),
+        _ => (),
+    }
+}
+
+// Names addresses with the labels in the file given, or those of the ROM if it's Octo source,
+// exiting if they can't be read
+fn label(chip8: &mut Chip8, labels: Option<&Path>, rom: Option<&Path>, layout: Layout) {
+    let labels = match (labels, rom) {
+        (Some(path), _) => Labels::from_file(path, layout.size),
+        (None, Some(rom)) if octo::is_source(rom) => octo::assemble_file(rom, layout).map(|assembled| assembled.labels),
+        _ => return,
+    };
+    match labels {
+        Ok(labels) => chip8.set_labels(labels),
+        Err(e) => {
+            eprintln!("{}", report(&e));
+            process::exit(1);
+        }
+    }
+}
+
+// Applies the cheats given or found next to the ROM, exiting if they can't be read
+fn cheat(chip8: &mut Chip8, args: &Args, layout: Layout) {
+    let patches = match (&args.cheats, &args.rom) {
+        (Some(path), _) => Patches::from_file(path, layout.size).map(Some),
+        (None, Some(rom)) => Patches::for_rom(rom, layout.size),
+        (None, None) => Ok(None),
+    };
+    match patches {
+        Ok(Some(patches)) => {
+            println!("Applying {} cheats", patches.patches().len());
+            chip8.set_patches(patches);
+        }
+        Ok(None) => (),
+        Err(e) => {
+            eprintln!("{}", report(&e));
+            process::exit(1);
+        }
+    }
+}
+
+// Lists the ROMs in the given directory or ./roms to pick one from, the splash screen stays without any
+fn browse(chip8: &mut Chip8, args: &Args) {
+    let dir = args.rom_dir.as_deref().unwrap_or(Path::new(DEFAULT_ROM_DIR));
+    match chip8.browse(dir) {
+        Ok(0) if args.rom_dir.is_some() => println!("No ROMs found in {}", dir.display()),
+        Ok(_) => (),
+        Err(_) if args.rom_dir.is_none() => (), // No ROM directory to browse
+        Err(e) => {
+            eprintln!("{}", report(&e));
+            process::exit(1);
+        }
+    }
+}
+
+// Adds the given ROM and the further ones to switch between, exiting if one can't be read
+fn add_banks(chip8: &mut Chip8, args: &Args, layout: Layout) {
+    let first = args.rom.iter().map(|path| {
+        let mut bank = RomBank::from_file(path, layout)?;
+        if let Some(cheats) = &args.cheats {
+            bank.patches = Patches::from_file(cheats, layout.size)?;
+        }
+        Ok(bank)
+    });
+    for bank in first.chain(args.banks.iter().map(|path| RomBank::from_file(path, layout))) {
+        match bank {
+            Ok(bank) => chip8.add_bank(bank),
+            Err(e) => {
+                eprintln!("{}", report(&e));
+                process::exit(1);
+            }
+        }
+    }
+}
+
+// Loads a script, exiting on failure
+#[cfg(feature = "scripting")]
+fn load_script(chip8: &mut Chip8, path: &Path) {
+    if let Err(e) = chip8.load_script(path) {
+        eprintln!("Error while loading script: {}", report(&e));
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+fn load_script(_: &mut Chip8, _: &Path) {
+    eprintln!("Scripting is not available in this build, rebuild with --features scripting");
+    process::exit(1);
+}
+
+#[cfg(feature = "remote")]
+fn listen_http(chip8: &mut Chip8, port: u16) {
+    match chip8.listen_http(("127.0.0.1", port)) {
+        Ok(addr) => println!("HTTP API listening on http://{addr}"),
+        Err(e) => {
+            eprintln!("{}", report(&e));
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(not(feature = "remote"))]
+fn listen_http(_: &mut Chip8, _: u16) {
+    eprintln!("The HTTP API is not available in this build, rebuild with --features remote");
+    process::exit(1);
+}
+
+// Applies the ROM database settings for the program being run
+fn configure(chip8: &mut Chip8, database: Option<&Path>, rom: &[u8]) {
+    let database = match database {
+        Some(path) => Database::from_file(path).unwrap_or_else(|err| {
+            eprintln!("{}", report(&err));
+            process::exit(1);
+        }),
+        None => Database::bundled(),
+    };
+
+    if let Some((title, settings)) = database.lookup(rom) {
+        println!("Found {title} in the ROM database");
+        for warning in settings.apply(chip8) {
+            eprintln!("Warning: {warning}");
+        }
+    }
+}
+
+// Configures the builder for the platform, if one was given
+fn with_profile(builder: Chip8Builder, profile: Option<Profile>) -> Chip8Builder {
+    match profile {
+        Some(profile) => {
+            warn_unsupported(&profile);
+            builder.profile(profile)
+        }
+        None => builder,
+    }
+}
+
+// Memory of the platform, if one was given, for looking at a ROM without running it
+fn layout(profile: Option<Profile>) -> Layout {
+    profile.map_or_else(Layout::default, |profile| profile.layout())
+}
+
+// Warns about the parts of the platform the emulator runs without
+fn warn_unsupported(profile: &Profile) {
+    for missing in profile.unsupported() {
+        eprintln!("Warning: {missing}");
+    }
+}
+
+// Creates the emulator and its memory, exiting on failure
+fn build(builder: Chip8Builder) -> (Chip8, Memory) {
+    builder.build().unwrap_or_else(|err| {
+        eprintln!("Error while creating the emulator: {}", report(&err));
+        process::exit(1);
+    })
+}
@@ -0,0 +1,64 @@
+// Benchmarks of the emulator's hot paths, run with cargo bench --features bench
+use std::hint::black_box;
+use chip8_core::{Chip8, Memory};
+use chip8_core::internals;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+// Instruction mixes executed in a loop, each leaves the stack as it found it
+const MIXES: &[(&str, &[u16])] = &[
+    ("alu", &[0x6105, 0x6203, 0x8124, 0x8125, 0x8126, 0x7101, 0x8212, 0x8133]),
+    ("control_flow", &[0x2300, 0x00EE, 0x1200, 0x3105, 0x4105, 0x5120, 0x9120, 0xB200]),
+    ("memory", &[0xA300, 0xF133, 0xF355, 0xF365, 0xF11E, 0xF229]),
+    ("draw", &[0xA000, 0x6108, 0xD125, 0xD125, 0x00E0]),
+];
+
+fn decode(c: &mut Criterion) {
+    c.bench_function("decode", |b| b.iter(|| {
+        for code in (0..=u16::MAX).step_by(257) {
+            black_box(internals::decode(black_box(code)));
+        }
+    }));
+}
+
+fn fetch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fetch");
+    for predecode in [false, true] {
+        let mut mem = Memory::splash();
+        mem.set_predecoding(predecode);
+        group.bench_function(if predecode { "predecoded" } else { "decoded" }, |b| b.iter(|| {
+            for addr in (0x200..0x300).step_by(2) {
+                black_box(internals::fetch(&mut mem, black_box(addr)));
+            }
+        }));
+    }
+    group.finish();
+}
+
+fn execute(c: &mut Criterion) {
+    let mut group = c.benchmark_group("execute");
+    for (name, mix) in MIXES {
+        let (mut chip8, mut mem) = (Chip8::new(), Memory::new());
+        group.throughput(Throughput::Elements(mix.len() as u64));
+        group.bench_function(*name, |b| b.iter(|| {
+            for &op_code in *mix {
+                internals::execute(&mut chip8, black_box(op_code), &mut mem).unwrap();
+            }
+        }));
+    }
+    group.finish();
+}
+
+fn display(c: &mut Criterion) {
+    let mut chip8 = Chip8::new();
+    let sprite = [0xF0, 0x90, 0xF0, 0x90, 0x90, 0xFF, 0x81, 0xFF];
+    c.bench_function("display_draw", |b| b.iter(|| {
+        // Wraps around the right and bottom edges for part of the positions
+        for (x, y) in [(0, 0), (30, 12), (60, 28)] {
+            black_box(internals::draw(&mut chip8, x, y, black_box(&sprite)));
+        }
+    }));
+    c.bench_function("display_update_buffer", |b| b.iter(|| internals::update_buffer(&mut chip8)));
+}
+
+criterion_group!(benches, decode, fetch, execute, display);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+// Drives a 128x64 monochrome display the way an SSD1306 driver would, then prints
+// its memory to the terminal. On a microcontroller the display would be the
+// driver's, e.g. ssd1306::Ssd1306 in buffered graphics mode, flushed every frame.
+// Run with cargo run --example embedded_display --features embedded-graphics
+
+use std::{convert::Infallible, sync::{Arc, Mutex}};
+use chip8_core::{Chip8Builder, embedded::EmbeddedScreen, splash::SPLASH, testing};
+use embedded_graphics_core::{Pixel, draw_target::DrawTarget, geometry::{OriginDimensions, Size}, pixelcolor::BinaryColor};
+
+const WIDTH: usize = 128;
+const HEIGHT: usize = 64;
+
+// Display memory laid out like the SSD1306's: pages of 8 rows, a byte per column
+struct Ssd1306 {
+    buffer: [u8; WIDTH * HEIGHT / 8],
+}
+
+impl OriginDimensions for Ssd1306 {
+    fn size(&self) -> Size {
+        Size::new(WIDTH as u32, HEIGHT as u32)
+    }
+}
+
+impl DrawTarget for Ssd1306 {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I: IntoIterator<Item = Pixel<BinaryColor>>>(&mut self, pixels: I) -> Result<(), Infallible> {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) else { continue };
+            if x < WIDTH && y < HEIGHT {
+                let byte = &mut self.buffer[y / 8 * WIDTH + x];
+                match color {
+                    BinaryColor::On => *byte |= 1 << (y % 8),
+                    BinaryColor::Off => *byte &= !(1 << (y % 8)),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let (mut chip8, mut mem) = Chip8Builder::new().rom(SPLASH).headless().build().expect("splash screen builds");
+    let display = Arc::new(Mutex::new(Ssd1306 { buffer: [0; WIDTH * HEIGHT / 8] }));
+    let screen = EmbeddedScreen::new(BinaryColor::On, BinaryColor::Off).scale(2);
+    let target = Arc::clone(&display);
+    chip8.on_frame(move |frame| {
+        let Ok(()) = screen.draw(frame, &mut *target.lock().unwrap());
+    });
+    testing::run_headless(&mut chip8, &mut mem, 1000).expect("splash screen runs");
+
+    // Two display rows per line of text
+    let display = display.lock().unwrap();
+    for y in (0..HEIGHT).step_by(2) {
+        let line: String = (0..WIDTH).map(|x| {
+            let lit = |y: usize| display.buffer[y / 8 * WIDTH + x] & (1 << (y % 8)) != 0;
+            match (lit(y), lit(y + 1)) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            }
+        }).collect();
+        println!("{}", line.trim_end());
+    }
+}
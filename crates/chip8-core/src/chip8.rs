@@ -0,0 +1,2356 @@
+#[cfg(feature = "remote")]
+mod api;
+#[cfg(feature = "std")]
+pub mod audio;
+#[cfg(feature = "std")]
+pub mod backend;
+pub mod battery;
+#[cfg(feature = "std")]
+pub mod bench;
+#[cfg(feature = "std")]
+pub mod breakpoint;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod capabilities;
+#[cfg(feature = "std")]
+pub mod compat;
+#[cfg(feature = "std")]
+pub mod conformance;
+#[cfg(feature = "std")]
+pub mod database;
+#[cfg(feature = "std")]
+pub mod decompile;
+#[cfg(feature = "std")]
+mod debugger;
+mod display;
+pub mod draw;
+#[cfg(feature = "embedded-graphics")]
+pub mod embedded;
+pub mod heatmap;
+#[cfg(feature = "std")]
+pub mod icon;
+#[cfg(feature = "std")]
+pub mod indicator;
+mod input;
+#[cfg(feature = "std")]
+mod keys;
+#[cfg(feature = "std")]
+pub mod labels;
+#[cfg(feature = "std")]
+mod menu;
+#[cfg(feature = "std")]
+mod browser;
+pub mod megachip;
+#[cfg(feature = "std")]
+pub mod netplay;
+#[cfg(feature = "std")]
+mod notify;
+mod number;
+pub mod memory;
+#[cfg(feature = "std")]
+pub mod octo;
+pub mod opcode;
+pub mod palette;
+pub mod patches;
+pub mod profile;
+pub mod program;
+pub mod errors;
+pub mod events;
+#[cfg(feature = "std")]
+pub mod filter;
+mod flags;
+pub mod font;
+pub mod frame;
+pub mod frontend;
+#[cfg(feature = "bench")]
+pub mod internals;
+pub mod quirks;
+mod random;
+pub mod splash;
+pub mod state;
+#[cfg(feature = "std")]
+pub mod recording;
+#[cfg(feature = "std")]
+mod remote;
+pub mod rom;
+#[cfg(feature = "scripting")]
+mod script;
+#[cfg(feature = "std")]
+pub mod scheduler;
+#[cfg(feature = "std")]
+mod speed;
+#[cfg(feature = "std")]
+pub mod sprites;
+pub mod stats;
+pub mod storage;
+#[cfg(feature = "std")]
+pub mod testing;
+#[cfg(feature = "std")]
+mod text;
+#[cfg(feature = "std")]
+mod tour;
+#[cfg(feature = "std")]
+pub mod trace;
+pub mod watchpoint;
+
+#[cfg(test)]
+mod tests;
+
+pub use memory::Memory;
+use memory::Protection;
+use errors::{Chip8Error, ExecError, report};
+use events::{Event, Timer};
+#[cfg(feature = "std")]
+use events::EventLog;
+use flags::UserFlags;
+#[cfg(feature = "remote")]
+use api::{ApiRequest, ApiResponse, ApiServer};
+use battery::SaveRam;
+use display::Display;
+use draw::{Clip, DrawStrategy, Wrap};
+use input::InputQueue;
+use megachip::MegaScreen;
+use opcode::OpCode;
+use palette::Palette;
+use patches::Patches;
+use frame::FrameBuffer;
+use quirks::Quirks;
+use random::Random;
+#[cfg(feature = "scripting")]
+use script::{Hook, Script};
+use stats::{Stats, StatsCounter};
+use state::MachineState;
+use storage::Storage;
+
+use alloc::{boxed::Box, format, string::{String, ToString}, vec::Vec};
+
+// Windows, audio, files, sockets and the run loop
+#[cfg(feature = "std")]
+use {
+    audio::{Audio, AudioSettings},
+    backend::Backend,
+    breakpoint::Breakpoint,
+    builder::Chip8Builder,
+    display::Screen,
+    capabilities::{Capabilities, Feature},
+    debugger::{Debugger, Registers},
+    errors::{IoError, LoadError},
+    keys::Keys,
+    labels::Labels,
+    netplay::{Netplay, NetplayMode, NETPLAY_PORT},
+    menu::{MenuItem, PauseMenu},
+    browser::RomBrowser,
+    filter::Filter,
+    icon::WindowIcon,
+    indicator::SoundIndicator,
+    recording::{GifRecorder, InputRecorder, InputRecording},
+    rom::RomBank,
+    state::SaveState,
+    scheduler::{Due, FramePacer, LagPolicy, Scheduler},
+    remote::{RemoteServer, Request, STOPPED},
+    speed::SpeedCounter,
+    storage::FileStorage,
+    notify::{Notifier, Status},
+    std::{collections::HashMap, net::{SocketAddr, ToSocketAddrs}, path::Path, sync::Arc, time::{Instant, SystemTime, UNIX_EPOCH}},
+};
+#[cfg(not(feature = "std"))]
+use storage::MemoryStorage;
+
+#[cfg(feature = "std")]
+use frontend::{Frontend, Key, Scale, Window}; // Windows and audio, opened by the frontend
+
+// Display
+pub const DISPLAY_WIDTH: usize = 64;
+pub const DISPLAY_HEIGHT: usize = 32;
+#[cfg(feature = "std")]
+pub const DISPLAY_SCALE: Scale = Scale::X16;
+const WINDOW_NAME: &str = "Chip8 Emulator";
+// Application the windows belong to, which desktops group them under and match with a chip8.desktop entry
+#[cfg(feature = "std")]
+const APP_NAME: &str = "chip8";
+
+// Memory
+pub const MEMORY_SIZE: usize = 1024 * 4;
+pub const MAX_MEMORY_SIZE: usize = 1024 * 64; // The most 16-bit addresses reach, see Memory::with_layout
+pub const PROGRAM_START: u16 = 0x200;
+const SPRITE_SIZE: u16 = 5;
+
+// Chip8 specifications
+const NUM_REGISTERS: usize = 16;
+const FLAG_REGISTER: usize = 15;
+const STACK_DEPTH: usize = 16;
+
+// Sound
+#[cfg(feature = "std")]
+const BEEP_FREQUENCY: f32 = 440.0; // A4, used unless set otherwise
+#[cfg(feature = "std")]
+pub const SAMPLE_RATE: u32 = 44100;
+#[cfg(feature = "std")]
+pub const AUDIO_BUFFER_SIZE: u64 = 512; // Samples, beeps start and stop at multiples of it
+
+// Instructions executed per 60hz frame by default
+pub const DEFAULT_TICKRATE: u32 = 16;
+// Speed multiplier while the turbo key is held, unless set otherwise
+#[cfg(feature = "std")]
+const TURBO_FACTOR: u32 = 4;
+// Seed of Cxkk's random numbers without std, which seeds them from the system
+#[cfg(not(feature = "std"))]
+const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+// Display and timers update frequency
+
+// Hotkeys
+#[cfg(feature = "std")]
+const SCREENSHOT_KEY: Key = Key::F12;
+#[cfg(feature = "std")]
+const GIF_RECORDING_KEY: Key = Key::F10;
+#[cfg(feature = "std")]
+const RESET_KEY: Key = Key::F5;
+#[cfg(feature = "std")]
+const POWER_CYCLE_KEY: Key = Key::F6;
+#[cfg(feature = "std")]
+const STEP_KEY: Key = Key::F8;
+#[cfg(feature = "std")]
+const FRAME_STEP_KEY: Key = Key::F9;
+#[cfg(feature = "std")]
+const PAUSE_KEY: Key = Key::Escape;
+#[cfg(feature = "std")]
+const MENU_UP_KEY: Key = Key::Up;
+#[cfg(feature = "std")]
+const MENU_DOWN_KEY: Key = Key::Down;
+#[cfg(feature = "std")]
+const MENU_SELECT_KEY: Key = Key::Enter;
+#[cfg(feature = "std")]
+const SPEED_KEY: Key = Key::F3;
+#[cfg(feature = "std")]
+const STATS_KEY: Key = Key::F4;
+#[cfg(feature = "std")]
+const TURBO_KEY: Key = Key::Tab;
+#[cfg(feature = "std")]
+const FULLSCREEN_KEY: Key = Key::F11;
+#[cfg(feature = "std")]
+const SCALE_DOWN_KEY: Key = Key::Minus;
+#[cfg(feature = "std")]
+const SCALE_UP_KEY: Key = Key::Equal;
+#[cfg(feature = "std")]
+const MUTE_KEY: Key = Key::M;
+#[cfg(feature = "std")]
+const MEMORY_VIEWER_KEY: Key = Key::F7;
+#[cfg(feature = "std")]
+const PREVIOUS_BANK_KEY: Key = Key::PageUp;
+#[cfg(feature = "std")]
+const NEXT_BANK_KEY: Key = Key::PageDown;
+#[cfg(feature = "std")]
+const SAVE_STATE_KEY: Key = Key::F1;
+#[cfg(feature = "std")]
+const LOAD_STATE_KEY: Key = Key::F2;
+
+// Callback handed the screen after every frame
+type FrameObserver = Box<dyn FnMut(&FrameBuffer) + Send>;
+
+// Callback handed everything that happens, as it happens
+type EventObserver = Box<dyn FnMut(&Event) + Send>;
+
+// Windows open during a run, kept out of Chip8 as windows can't move to other threads
+#[cfg(feature = "std")]
+struct Windows {
+    screen: Screen,
+    debugger: Option<Box<dyn Window>>, // Memory viewer, if open
+}
+
+// Whether instructions run, or Fx0A holds execution until a key is pressed and released
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CpuState {
+    Running,
+    WaitingForKey(usize), // Register the key goes to
+    WaitingForRelease(usize, u8), // Register, and the key pressed
+}
+
+pub struct Chip8 {
+    // Registers
+    v: [u8; NUM_REGISTERS], // 16 general purpose 8-bit registers
+    idx: u16, // 16-bit address register
+
+    // Timers - counts down at 60hz to 0
+    dt: u8, // delay timer
+    st: u8, // sound timer
+
+    pc: u16, // Program counter
+    sp: u8, // Stack pointer, the next free slot and so the call depth
+    stack: [u16; STACK_DEPTH], // 16 16-bit stack fields
+    calls: [u16; STACK_DEPTH], // Subroutine entered by the call that pushed each return address
+
+    display: Display, // Display struct 
+
+    #[cfg(feature = "std")]
+    keyboard: Keys, // Key bindings
+
+    #[cfg(feature = "std")]
+    audio: Result<Audio, String>, // Beeper, or why there is no sound
+    #[cfg(feature = "std")]
+    audio_settings: AudioSettings, // Applied to the beeper whenever it is opened
+    muted: bool, // Sound timer is ignored while muted
+    frame: u64, // Emulated 60hz frames, the clock for audio
+
+    keys: u16, // Key state latched for the current frame, bit n set if key n is down
+    input: InputQueue, // Key presses and releases from the window and press_key since the last frame
+    #[cfg(feature = "std")]
+    recorder: Option<InputRecorder>, // Input recording, if enabled
+    #[cfg(feature = "std")]
+    playback: Option<InputRecording>, // Recorded input replayed instead of the keyboard
+    #[cfg(feature = "std")]
+    gif: Option<GifRecorder>, // Gameplay recording, if enabled
+    #[cfg(feature = "std")]
+    events: Option<EventLog>, // Machine-readable log of what happened, if enabled
+    #[cfg(feature = "std")]
+    saved: Option<Box<SaveState>>, // Machine kept with the save state hotkey, if saved
+    #[cfg(feature = "std")]
+    notifier: Option<Notifier>, // Notifications shown over the screen, e.g. the tour's hints
+    paused: bool, // Execution is stopped, instructions run only when stepped
+    cpu_state: CpuState, // Waiting for a key with Fx0A, checked every step
+    instructions: u64, // Instructions executed so far
+    #[cfg(feature = "std")]
+    speed: Option<SpeedCounter>, // Frames and instructions per second shown in the title, if enabled
+    stats: StatsCounter, // What the program did in the current and the last emulated second
+    show_stats: bool, // Show the last second's stats in the title
+    storage: Box<dyn Storage>, // Where persistent data is kept
+    user_flags: UserFlags, // Registers saved by Fx75, kept in the storage
+    save_ram: Option<SaveRam>, // Memory kept in the storage between sessions, if the program has any
+    #[cfg(feature = "std")]
+    debugger: Debugger, // Inspection tools opened with hotkeys
+    #[cfg(feature = "std")]
+    breakpoints: Vec<Breakpoint>,
+    #[cfg(feature = "std")]
+    labels: Labels, // Names shown for addresses in disassembly and the call stack
+    #[cfg(feature = "std")]
+    warn_self_modify: bool, // Print every instruction writing to the code executed so far
+    patches: Patches, // Cheats written to memory
+    #[cfg(feature = "std")]
+    banks: Vec<RomBank>, // Programs to switch between with hotkeys
+    #[cfg(feature = "std")]
+    bank: usize, // Index of the running bank
+    #[cfg(feature = "std")]
+    resumed_at: Option<u16>, // Breakpoint address execution continues from, not hit again right away
+    #[cfg(feature = "std")]
+    remote: Option<RemoteServer>, // Server external debuggers attach to, if listening
+    #[cfg(feature = "remote")]
+    api: Option<ApiServer>, // HTTP API to control the emulator, if listening
+    #[cfg(feature = "std")]
+    netplay: Option<Netplay>, // Link to another player's emulator, if connected
+    rng: Random, // Source of Cxkk's random numbers
+    #[cfg(feature = "scripting")]
+    script: Option<Script>, // Hooks run during emulation, if loaded
+    rom_name: Option<String>, // Shown in the window title
+    title: Option<String>, // Window title replacing the generated one
+    #[cfg(feature = "std")]
+    headless: bool, // Never opens a window or an audio device
+    frame_observers: Vec<FrameObserver>, // Called with the screen after every frame
+    sound_observers: Vec<Box<dyn FnMut(bool) + Send>>, // Called when the beep starts or stops
+    event_observers: Vec<EventObserver>, // Called with everything logged
+    sounding: bool, // Whether the beep sounded in the last frame
+
+    quirks: Quirks, // Interpreter behaviors the program expects
+    tickrate: u32, // Instructions per 60hz frame
+    #[cfg(feature = "std")]
+    turbo: u32, // Speed multiplier while the turbo key is held
+    #[cfg(feature = "std")]
+    turbo_base: Option<u32>, // Tick rate to return to once the turbo key is released, while held
+    #[cfg(feature = "std")]
+    lag_policy: LagPolicy, // What to do about frames missed while running behind
+    #[cfg(feature = "std")]
+    vsync: bool, // Pace the loop by the monitor's refresh rather than the 60hz clock
+}
+
+
+impl Chip8 {
+    // Creates a new Chip8 instance with the given key bindings
+    pub fn new() -> Self {
+        // Display setup
+        let display = Display::new();
+
+        Chip8 {
+            v: [0x00; NUM_REGISTERS],
+            idx: 0x0000,
+            dt: 0,
+            st: 0,
+            pc: PROGRAM_START,
+            sp: 0x00,
+            stack: [0x0000; STACK_DEPTH],
+            calls: [0x0000; STACK_DEPTH],
+            display,
+            #[cfg(feature = "std")]
+            keyboard: Keys::get_default(),
+            #[cfg(feature = "std")]
+            audio: Err("audio is not enabled".to_string()), // Opened with enable or set_audio_enabled
+            #[cfg(feature = "std")]
+            audio_settings: AudioSettings::default(),
+            muted: false,
+            frame: 0,
+            keys: 0,
+            input: InputQueue::default(),
+            #[cfg(feature = "std")]
+            recorder: None,
+            #[cfg(feature = "std")]
+            playback: None,
+            #[cfg(feature = "std")]
+            gif: None,
+            #[cfg(feature = "std")]
+            events: None,
+            #[cfg(feature = "std")]
+            saved: None,
+            #[cfg(feature = "std")]
+            notifier: None,
+            paused: false,
+            cpu_state: CpuState::Running,
+            instructions: 0,
+            #[cfg(feature = "std")]
+            speed: None,
+            stats: StatsCounter::default(),
+            show_stats: false,
+            #[cfg(feature = "std")]
+            storage: Box::new(FileStorage::default_location()),
+            #[cfg(not(feature = "std"))]
+            storage: Box::new(MemoryStorage::new()), // Set one with set_storage to keep high scores
+            user_flags: UserFlags::default(),
+            save_ram: None,
+            #[cfg(feature = "std")]
+            debugger: Debugger::new(),
+            #[cfg(feature = "std")]
+            breakpoints: Vec::new(),
+            #[cfg(feature = "std")]
+            labels: Labels::new(),
+            #[cfg(feature = "std")]
+            warn_self_modify: false,
+            patches: Patches::new(),
+            #[cfg(feature = "std")]
+            banks: Vec::new(),
+            #[cfg(feature = "std")]
+            bank: 0,
+            #[cfg(feature = "std")]
+            resumed_at: None,
+            #[cfg(feature = "std")]
+            remote: None,
+            #[cfg(feature = "remote")]
+            api: None,
+            #[cfg(feature = "std")]
+            netplay: None,
+            #[cfg(feature = "std")]
+            rng: Random::seeded(rand::random()),
+            #[cfg(not(feature = "std"))]
+            rng: Random::seeded(DEFAULT_SEED), // Seed it from the hardware with set_seed
+            #[cfg(feature = "scripting")]
+            script: None,
+            rom_name: None,
+            title: None,
+            #[cfg(feature = "std")]
+            headless: false,
+            frame_observers: Vec::new(),
+            sound_observers: Vec::new(),
+            event_observers: Vec::new(),
+            sounding: false,
+            quirks: Quirks::default(),
+            tickrate: DEFAULT_TICKRATE,
+            #[cfg(feature = "std")]
+            turbo: TURBO_FACTOR,
+            #[cfg(feature = "std")]
+            turbo_base: None,
+            #[cfg(feature = "std")]
+            lag_policy: LagPolicy::default(),
+            #[cfg(feature = "std")]
+            vsync: false,
+        }
+    }
+
+    // Configures a new emulator and its memory in one go, see Chip8Builder
+    #[cfg(feature = "std")]
+    pub fn builder() -> Chip8Builder {
+        Chip8Builder::new()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn run( &mut self, mem: &mut Memory ) -> Result<(), Chip8Error> {
+        if self.headless {
+            return Err(LoadError::InvalidArgument("a headless emulator has no window to run in, use testing::run_headless".to_string()).into());
+        }
+
+        // Open window
+        let mut windows = Windows { screen: self.display.init()?, debugger: None };
+        self.rom_name = mem.name().map(String::from);
+        self.update_title();
+        self.restore_save_ram(mem);
+        self.patches.apply(mem);
+
+        let mut scheduler = Scheduler::new(Instant::now(), self.lag_policy);
+        let mut pacer = None;
+        let mut executed = 0;
+        self.latch_input()?;
+
+        while self.display.is_open() {
+            // Vsync can be turned on or off while running and is left off while paused, so whichever
+            // paces the frames starts from now rather than catching up on the time in between
+            let vsync = !self.paused && self.vsync && self.capabilities().supports(Feature::Vsync);
+            if vsync && pacer.is_none() {
+                pacer = Some(FramePacer::new(Instant::now(), self.lag_policy));
+            } else if !vsync && pacer.take().is_some() {
+                scheduler = Scheduler::new(Instant::now(), self.lag_policy);
+            }
+            self.display.set_latched(vsync);
+
+            // Only the screen and hotkeys are serviced while paused, with the next sprite highlighted
+            if self.paused {
+                if self.display.is_key_pressed(STEP_KEY) {
+                    self.display.set_menu(None); // Stepping shows the screen
+                    if !self.is_latched() {
+                        self.poll_keys()?; // So a key wait can be stepped through
+                    }
+                    self.step(mem)?;
+                    self.resumed_at = None;
+                }
+                if self.display.is_key_pressed(FRAME_STEP_KEY) {
+                    self.display.set_menu(None);
+                    executed = self.step_frame(mem, executed)?;
+                }
+                self.display.set_highlight(self.next_draw(mem));
+                scheduler.wait();
+                self.display.update(&mut windows.screen)?;
+                self.debugger.update(mem, &self.registers(), &self.labels, &mut windows.debugger)?;
+                self.poll_remote(mem)?;
+                #[cfg(feature = "remote")]
+                self.poll_api(mem)?;
+                self.update_speed();
+                self.handle_hotkeys(mem, &mut windows);
+                self.update_notices();
+                continue;
+            }
+
+            if let Some(pacer) = pacer.as_mut() {
+                executed = self.run_paced(mem, &mut windows, pacer, executed)?;
+                continue;
+            }
+
+            // Run the frame's instructions in one go, stopping before an instruction a breakpoint is set on
+            while executed < self.tickrate && !self.paused && !self.check_breakpoints(mem) {
+                self.step(mem)?;
+                executed += 1;
+            }
+
+            // Update timers and display once the frame's instructions have run,
+            // so every frame executes the same number of instructions when replayed
+            if executed >= self.tickrate {
+                executed = 0;
+                scheduler.wait();
+                self.display.update(&mut windows.screen)?;
+                self.debugger.update(mem, &self.registers(), &self.labels, &mut windows.debugger)?;
+                self.poll_remote(mem)?;
+                #[cfg(feature = "remote")]
+                self.poll_api(mem)?;
+                self.update_speed();
+                self.end_frame(mem)?;
+                self.handle_hotkeys(mem, &mut windows);
+                self.update_notices();
+            }
+        }
+
+        self.store_save_ram(mem);
+        // Mark the recording as complete, an unfinished one is kept as is
+        if let Some(recorder) = self.recorder.take() {
+            recorder.finish()?;
+        }
+        if let Some(mut events) = self.events.take() {
+            events.flush()?;
+        }
+        self.stop_gif_recording()
+    }
+
+    // Runs a 60hz frame of the program and ticks the timers, for hosts keeping time themselves,
+    // e.g. from a timer interrupt on a microcontroller. Keys pressed since count from the next frame.
+    pub fn run_frame(&mut self, mem: &mut Memory) -> Result<(), Chip8Error> {
+        for _ in 0..self.tickrate {
+            self.step(mem)?;
+        }
+        self.end_frame(mem)
+    }
+
+    // Runs the rest of the frame while paused, the instructions executed of it so far given,
+    // then ticks the timers. A breakpoint stops it early, returning how far the frame got.
+    #[cfg(feature = "std")]
+    fn step_frame(&mut self, mem: &mut Memory, mut executed: u32) -> Result<u32, Chip8Error> {
+        self.resumed_at = None; // Past the instruction paused on, even with a breakpoint on it
+        while executed < self.tickrate {
+            self.step(mem)?;
+            executed += 1;
+            if executed < self.tickrate && self.check_breakpoints(mem) {
+                return Ok(executed);
+            }
+        }
+        self.end_frame(mem)?;
+        Ok(0)
+    }
+
+    // Ticks the timers and moves on to the next 60hz frame
+    fn end_frame(&mut self, mem: &mut Memory) -> Result<(), Chip8Error> {
+        self.update_timers();
+        #[cfg(feature = "std")]
+        self.display.latch_frame();
+        #[cfg(feature = "std")]
+        if let Some(gif) = self.gif.as_mut() {
+            gif.capture(self.display.get_grid())?;
+        }
+        #[cfg(feature = "std")]
+        if let Some(events) = self.events.as_mut() {
+            events.flush()?;
+        }
+        self.patches.apply_frozen(mem);
+        if self.stats.frame(self.call_depth()) && self.show_stats {
+            self.update_title();
+        }
+        #[cfg(feature = "scripting")]
+        self.run_hook(Hook::Frame, mem)?;
+        let (filled, empty) = self.display.get_colors();
+        let frame = FrameBuffer::new(self.display.get_grid(), filled, empty);
+        for observer in self.frame_observers.iter_mut() {
+            observer(&frame);
+        }
+        self.latch_input()
+    }
+
+    // Runs a hook of the loaded script, if any
+    #[cfg(feature = "scripting")]
+    fn run_hook(&mut self, hook: Hook, mem: &mut Memory) -> Result<(), Chip8Error> {
+        let Some(mut script) = self.script.take() else { return Ok(()) };
+        let result = script.run(hook, self, mem);
+        self.script = Some(script);
+        result
+    }
+
+    // Takes the key state and speed of the current frame from the replay or the keyboard,
+    // and writes them to the input recording
+    #[cfg(feature = "std")]
+    fn latch_input(&mut self) -> Result<(), Chip8Error> {
+        match self.playback.as_ref() {
+            Some(playback) => {
+                let keys = playback.keys_at(self.frame);
+                let tickrate = playback.tickrate_at(self.frame);
+                if tickrate != self.tickrate {
+                    self.tickrate = tickrate;
+                    self.update_title();
+                }
+                self.set_keys(keys);
+            }
+            None => self.poll_keys()?,
+        }
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(self.frame, self.keys, self.tickrate)?;
+        }
+        Ok(())
+    }
+
+    // Without std the keys come only from press_key and release_key
+    #[cfg(not(feature = "std"))]
+    fn latch_input(&mut self) -> Result<(), Chip8Error> {
+        let keys = self.input.latch();
+        self.set_keys(keys);
+        Ok(())
+    }
+
+    // Changes the key state the program sees, logging every key that went down or up
+    fn set_keys(&mut self, keys: u16) {
+        let changed = self.keys ^ keys;
+        for key in (0..16).filter(|key| changed & (1 << key) != 0) {
+            self.log_event(Event::Key { key, pressed: keys & (1 << key) != 0 });
+        }
+        self.keys = keys;
+    }
+
+    // Takes the key presses and releases since the last frame into the key state, together
+    // with the other player's during netplay
+    #[cfg(feature = "std")]
+    fn poll_keys(&mut self) -> Result<(), Chip8Error> {
+        for (key, pressed) in self.display.take_key_events(&self.keyboard) {
+            self.input.push(key, pressed);
+        }
+        let mut keys = self.input.latch();
+        let state = self.state_hash();
+        if let Some(netplay) = self.netplay.as_mut() {
+            keys |= netplay.exchange(self.frame, keys, state)?;
+        }
+        self.set_keys(keys);
+        Ok(())
+    }
+
+    // Recorded, replayed and netplay sessions have to see the keys change only between frames
+    #[cfg(feature = "std")]
+    fn is_latched(&self) -> bool {
+        self.recorder.is_some() || self.playback.is_some() || self.netplay.is_some()
+    }
+
+    // Hash of the registers and timers, machines in lockstep have the same one every frame
+    #[cfg(feature = "std")]
+    fn state_hash(&self) -> u64 {
+        let words = [self.pc, self.idx].into_iter().flat_map(u16::to_le_bytes);
+        netplay::hash(self.v.into_iter().chain(words).chain([self.sp, self.dt, self.st]))
+    }
+
+    // Checks if the chip8 key is held, the keys only change between frames
+    fn is_key_down(&self, key: u8) -> bool {
+        key < 16 && self.keys & (1 << key) != 0
+    }
+
+    // Fetches and executes a single instruction
+    pub fn step(&mut self, mem: &mut Memory) -> Result<(), Chip8Error> {
+        if self.wait_for_key() {
+            return Ok(());
+        }
+
+        // Scripts see the instruction first and may change the state it runs with
+        #[cfg(feature = "scripting")]
+        self.run_hook(Hook::Instruction(self.pc, mem.get_instruction(self.pc)), mem)?;
+
+        // Fetch instruction, already decoded if memory predecodes
+        let op_code = mem.fetch(self.pc);
+        let instruction = op_code.code;
+        let pc = self.pc;
+
+        // Increment program counter
+        self.pc = self.pc.wrapping_add(2);
+        self.instructions += 1;
+        self.stats.current.instructions += 1;
+        self.log_event(Event::Instruction { pc, opcode: instruction });
+
+        // Execute instruction, accesses made before it, e.g. by the memory viewer, don't count
+        mem.take_watch_hit();
+        #[cfg(feature = "scripting")]
+        mem.set_access_logging(self.script.as_ref().is_some_and(Script::watches_memory));
+        mem.take_code_write();
+        mem.take_reserved_write();
+        mem.mark_executed(pc);
+        let result = {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::trace_span!("execute", pc, opcode = instruction).entered();
+            self.execute_decoded(op_code, mem)
+        };
+        if let Err(e) = result {
+            self.log_event(Event::Error { message: report(&e) });
+            return Err(e);
+        }
+        #[cfg(feature = "scripting")]
+        for (addr, access) in mem.take_accesses() {
+            self.run_hook(Hook::Access(addr, access), mem)?;
+        }
+        #[cfg(feature = "std")]
+        if let Some(hit) = mem.take_watch_hit() {
+            self.log_event(Event::Watchpoint { addr: hit.addr, access: hit.access, pc, opcode: instruction });
+            self.paused = true;
+            self.update_title();
+        }
+        #[cfg(feature = "std")]
+        if let Some(addr) = mem.take_code_write() {
+            self.self_modified(addr, instruction, pc);
+        }
+        if let Some(addr) = mem.take_reserved_write() {
+            if mem.protection() == Protection::Strict {
+                let e = ExecError::ReservedWrite(addr, pc).into();
+                self.log_event(Event::Error { message: report(&e) });
+                return Err(e);
+            }
+            self.log_event(Event::ReservedWrite { addr, pc, opcode: instruction });
+        }
+        Ok(())
+    }
+
+    // Reports the program writing to its own code, and pauses on a breakpoint for it
+    #[cfg(feature = "std")]
+    fn self_modified(&mut self, addr: u16, instruction: u16, pc: u16) {
+        if self.warn_self_modify {
+            self.log_event(Event::SelfModify { addr, pc, opcode: instruction });
+        }
+        let registers = self.registers();
+        if let Some(hit) = self.breakpoints.iter().position(|b| b.hits_self_modify(&registers)) {
+            let breakpoint = self.take_hit(hit).to_string();
+            let instruction = self.labels.disassemble(OpCode::decode(instruction));
+            self.log_event(Event::Breakpoint { breakpoint, pc, instruction, write: Some(addr) });
+            self.paused = true;
+            self.update_title();
+        }
+    }
+
+    // Executes the opcode as if it had been fetched from PC, without reading memory for it
+    // or running hooks, e.g. to fuzz the opcode handlers with arbitrary instructions
+    pub fn execute_raw(&mut self, op_code: u16, mem: &mut Memory) -> Result<(), Chip8Error> {
+        self.pc = self.pc.wrapping_add(2);
+        self.instructions += 1;
+        self.execute_decoded(OpCode::decode(op_code), mem)
+    }
+
+    // Moves a pending Fx0A along, true while it still holds execution.
+    // The key is stored once it is released, as on the original interpreter.
+    fn wait_for_key(&mut self) -> bool {
+        match self.cpu_state {
+            CpuState::Running => return false,
+            CpuState::WaitingForKey(vx) => if let Some(key) = (0..16).find(|&key| self.is_key_down(key)) {
+                self.cpu_state = CpuState::WaitingForRelease(vx, key);
+            },
+            CpuState::WaitingForRelease(vx, key) => if !self.is_key_down(key) {
+                self.v[vx] = key;
+                self.pc = self.pc.wrapping_add(2);
+                self.cpu_state = CpuState::Running;
+            },
+        }
+        true
+    }
+
+    // Handles emulator hotkeys, checked once per display update
+    #[cfg(feature = "std")]
+    fn handle_hotkeys(&mut self, mem: &mut Memory, windows: &mut Windows) {
+        if self.display.is_key_pressed(SPEED_KEY) {
+            self.speed = match self.speed {
+                Some(_) => None,
+                None => Some(SpeedCounter::new(Instant::now(), self.instructions)),
+            };
+            self.update_title();
+        }
+
+        if self.display.is_key_pressed(STATS_KEY) {
+            self.set_stats_shown(!self.show_stats);
+        }
+
+        // Replays keep the recorded speed, and netplay the speed both sides agreed on
+        let turbo = self.display.is_key_down(TURBO_KEY) && self.playback.is_none() && self.netplay.is_none();
+        match self.turbo_base {
+            None if turbo => {
+                self.turbo_base = Some(self.tickrate);
+                self.tickrate = self.tickrate.saturating_mul(self.turbo);
+                self.update_title();
+            }
+            Some(tickrate) if !turbo => {
+                self.turbo_base = None;
+                self.tickrate = tickrate;
+                self.update_title();
+            }
+            _ => {}
+        }
+
+        if self.display.get_browser().is_some() {
+            self.handle_browser(mem);
+        } else if self.display.get_menu().is_some() {
+            self.handle_menu(mem);
+        } else if self.display.is_key_pressed(PAUSE_KEY) {
+            // Pausing, or returning to the menu after stepping
+            self.paused = true;
+            self.display.set_menu(Some(PauseMenu::new(self.saved.is_some())));
+            self.update_title();
+        }
+
+        if self.display.is_key_pressed(RESET_KEY) {
+            self.reset(mem);
+        }
+
+        if self.banks.len() > 1 && (self.display.is_key_pressed(PREVIOUS_BANK_KEY) || self.display.is_key_pressed(NEXT_BANK_KEY)) {
+            let step = if self.display.is_key_pressed(NEXT_BANK_KEY) { 1 } else { self.banks.len() - 1 };
+            if let Err(e) = self.switch_bank(mem, (self.bank + step) % self.banks.len()) {
+                eprintln!("Failed to switch ROMs: {}", report(&e));
+            }
+        }
+
+        if self.display.is_key_pressed(POWER_CYCLE_KEY) {
+            self.power_cycle(mem);
+        }
+
+        if self.display.is_key_pressed(SAVE_STATE_KEY) {
+            self.quick_save(mem);
+        }
+        if self.display.is_key_pressed(LOAD_STATE_KEY) {
+            self.quick_load(mem);
+        }
+
+        if let Some(frontend) = self.display.frontend().filter(|_| self.display.is_key_pressed(MEMORY_VIEWER_KEY)) {
+            if let Err(e) = self.debugger.toggle_memory_viewer(frontend.as_ref(), self.pc, mem, &mut windows.debugger) {
+                eprintln!("{}", report(&e));
+            }
+        }
+
+        if self.display.is_key_pressed(MUTE_KEY) {
+            self.set_muted(!self.muted);
+        }
+
+        if self.display.is_key_pressed(FULLSCREEN_KEY) {
+            self.set_fullscreen(!self.display.is_fullscreen());
+        }
+
+        // Resizing applies to the normal window, also while fullscreen
+        if self.display.is_key_pressed(SCALE_DOWN_KEY) {
+            self.set_scale(display::next_scale(self.display.get_scale(), false));
+        }
+        if self.display.is_key_pressed(SCALE_UP_KEY) {
+            self.set_scale(display::next_scale(self.display.get_scale(), true));
+        }
+
+        if self.display.is_key_pressed(SCREENSHOT_KEY) {
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+            let path = format!("screenshot-{timestamp}.png");
+            match self.screenshot(&path) {
+                Ok(()) => println!("Saved screenshot to {path}"),
+                Err(e) => eprintln!("{}", report(&e)),
+            }
+        }
+
+        if self.display.is_key_pressed(GIF_RECORDING_KEY) {
+            let result = if self.gif.is_some() {
+                self.stop_gif_recording().map(|()| println!("Stopped GIF recording"))
+            } else {
+                let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+                let path = format!("recording-{timestamp}.gif");
+                self.start_gif_recording(&path).map(|()| println!("Recording GIF to {path}"))
+            };
+            if let Err(e) = result {
+                eprintln!("{}", report(&e));
+            }
+        }
+    }
+
+    // Navigates the pause menu and carries out the selected entry
+    #[cfg(feature = "std")]
+    fn handle_menu(&mut self, mem: &mut Memory) {
+        let up = self.display.is_key_pressed(MENU_UP_KEY);
+        let down = self.display.is_key_pressed(MENU_DOWN_KEY);
+        let select = self.display.is_key_pressed(MENU_SELECT_KEY);
+        let back = self.display.is_key_pressed(PAUSE_KEY);
+        let Some(menu) = self.display.get_menu() else { return };
+
+        if up {
+            menu.navigate(false);
+        }
+        if down {
+            menu.navigate(true);
+        }
+
+        let chosen = if back {
+            Some(MenuItem::Resume)
+        } else if select {
+            Some(menu.selected())
+        } else {
+            None
+        };
+        match chosen {
+            Some(MenuItem::Resume) => self.resume(),
+            Some(MenuItem::Reset) => {
+                self.reset(mem);
+                self.resume();
+            }
+            Some(MenuItem::LoadState) if self.quick_load(mem) => self.resume(),
+            Some(MenuItem::Quit) => self.display.close(),
+            Some(MenuItem::LoadState) | None => (),
+        }
+    }
+
+    // Navigates the ROM browser and starts the picked program, going back leaves the current one running
+    #[cfg(feature = "std")]
+    fn handle_browser(&mut self, mem: &mut Memory) {
+        let up = self.display.is_key_pressed(MENU_UP_KEY);
+        let down = self.display.is_key_pressed(MENU_DOWN_KEY);
+        let select = self.display.is_key_pressed(MENU_SELECT_KEY);
+        let back = self.display.is_key_pressed(PAUSE_KEY);
+        let Some(browser) = self.display.get_browser() else { return };
+
+        if up {
+            browser.navigate(false);
+        }
+        if down {
+            browser.navigate(true);
+        }
+
+        let picked = if select { browser.selected().map(Path::to_path_buf) } else { None };
+        if let Some(path) = picked {
+            if let Err(e) = RomBank::from_file(&path, mem.layout()).and_then(|bank| self.start_bank(mem, bank)) {
+                eprintln!("Failed to load {}: {}", path.display(), report(&e));
+                return;
+            }
+        } else if !back {
+            return;
+        }
+        self.display.set_browser(None);
+        self.resume();
+    }
+
+    #[cfg(feature = "std")]
+    fn resume(&mut self) {
+        self.paused = false;
+        self.display.set_menu(None);
+        self.display.set_highlight(None);
+        self.update_title();
+    }
+
+    // Pauses if a breakpoint hits the next instruction, unless execution was just resumed from it
+    #[cfg(feature = "std")]
+    fn check_breakpoints(&mut self, mem: &Memory) -> bool {
+        if self.cpu_state != CpuState::Running {
+            return false; // Still on the Fx0A instruction that was already checked
+        }
+        if self.resumed_at.take() == Some(self.pc) {
+            return false;
+        }
+        let opcode = mem.get_instruction(self.pc);
+        let registers = self.registers();
+        let Some(hit) = self.breakpoints.iter().position(|b| b.hits(opcode, &registers)) else { return false };
+        let breakpoint = self.take_hit(hit).to_string();
+        let instruction = self.labels.disassemble(OpCode::decode(opcode));
+        self.log_event(Event::Breakpoint { breakpoint, pc: self.pc, instruction, write: None });
+        self.paused = true;
+        self.resumed_at = Some(self.pc);
+        self.update_title();
+        true
+    }
+
+    // The breakpoint that was hit, removed if it only stops once
+    #[cfg(feature = "std")]
+    fn take_hit(&mut self, index: usize) -> Breakpoint {
+        match self.breakpoints[index].is_once() {
+            true => self.breakpoints.remove(index),
+            false => self.breakpoints[index].clone(),
+        }
+    }
+
+    // Serves the requests of an attached debugger, checked once per display update
+    #[cfg(feature = "std")]
+    fn poll_remote(&mut self, mem: &mut Memory) -> Result<(), Chip8Error> {
+        let Some(remote) = self.remote.as_mut() else { return Ok(()) };
+        let (attached, requests) = remote.poll();
+        if attached {
+            // Debuggers expect the program to be stopped when they attach
+            self.paused = true;
+            self.update_title();
+        }
+
+        for request in requests {
+            let detach = request == Request::Detach;
+            let reply = self.serve_remote(request, mem)?;
+            let Some(remote) = self.remote.as_mut() else { break };
+            match reply {
+                Some(reply) => remote.reply(&reply),
+                None => remote.set_running(true),
+            }
+            if detach {
+                remote.disconnect();
+            }
+        }
+
+        // Report the stop once a continued program pauses, e.g. at a breakpoint
+        if let Some(remote) = self.remote.as_mut() {
+            if remote.is_running() && self.paused {
+                remote.set_running(false);
+                remote.reply(STOPPED);
+            }
+        }
+        Ok(())
+    }
+
+    // Answers the requests to the HTTP API, checked once per display update
+    #[cfg(feature = "remote")]
+    fn poll_api(&mut self, mem: &mut Memory) -> Result<(), Chip8Error> {
+        let Some(api) = self.api.as_mut() else { return Ok(()) };
+        for exchange in api.poll() {
+            let response = self.serve_api(&exchange.request, mem)?;
+            exchange.respond(response);
+        }
+        Ok(())
+    }
+
+    // Carries out an HTTP API request, errors of the request itself are sent back instead of returned
+    #[cfg(feature = "remote")]
+    fn serve_api(&mut self, request: &ApiRequest, mem: &mut Memory) -> Result<ApiResponse, Chip8Error> {
+        let response = match request {
+            ApiRequest::State => {
+                let state = serde_json::json!({ "paused": self.paused, "frame": self.frame, "machine": self.state() });
+                ApiResponse::Json(state.to_string())
+            }
+            ApiRequest::Screen => match self.display.screenshot_png() {
+                Ok(png) => ApiResponse::Png(png),
+                Err(e) => ApiResponse::Error(500, report(&e)),
+            },
+            ApiRequest::Pause => {
+                self.paused = true;
+                self.update_title();
+                ApiResponse::Done
+            }
+            ApiRequest::Resume => {
+                self.resume();
+                ApiResponse::Done
+            }
+            ApiRequest::Step if !self.paused => ApiResponse::Error(409, "the emulator has to be paused to step".to_string()),
+            ApiRequest::Step => {
+                self.display.set_menu(None);
+                self.step(mem)?;
+                self.resumed_at = None;
+                ApiResponse::Done
+            }
+            ApiRequest::Key(key, true) => {
+                self.press_key(*key);
+                ApiResponse::Done
+            }
+            ApiRequest::Key(key, false) => {
+                self.release_key(*key);
+                ApiResponse::Done
+            }
+            ApiRequest::Load(rom) => {
+                let bank = RomBank { name: "Uploaded ROM".to_string(), rom: rom.clone(), patches: Patches::default() };
+                match self.start_bank(mem, bank) {
+                    Ok(()) => ApiResponse::Done,
+                    Err(e) => ApiResponse::Error(400, report(&e)),
+                }
+            }
+            ApiRequest::NotFound => ApiResponse::Error(404, "unknown endpoint".to_string()),
+            ApiRequest::BadRequest => ApiResponse::Error(400, "malformed request".to_string()),
+        };
+        Ok(response)
+    }
+
+    // Carries out a debugger request, returning the reply or None if it is sent when the program stops
+    #[cfg(feature = "std")]
+    fn serve_remote(&mut self, request: Request, mem: &mut Memory) -> Result<Option<String>, Chip8Error> {
+        let reply = match request {
+            Request::Interrupt => {
+                self.paused = true;
+                self.update_title();
+                return Ok(None);
+            }
+            Request::HaltReason => STOPPED.to_string(),
+            // V0-VF, I and PC big-endian, then SP, DT and ST
+            Request::ReadRegisters => {
+                let mut registers = self.v.to_vec();
+                registers.extend(self.idx.to_be_bytes());
+                registers.extend(self.pc.to_be_bytes());
+                registers.extend([self.sp, self.dt, self.st]);
+                remote::encode_hex(&registers)
+            }
+            Request::ReadMemory(addr, length) => {
+                let end = (addr as usize + length as usize).min(mem.size());
+                let bytes: Vec<u8> = (addr as usize..end).map(|addr| mem.read_byte(addr as u16)).collect();
+                if bytes.is_empty() { "E01".to_string() } else { remote::encode_hex(&bytes) }
+            }
+            Request::WriteMemory(addr, bytes) => {
+                if addr as usize + bytes.len() > mem.size() {
+                    "E01".to_string()
+                } else {
+                    for (addr, byte) in (addr..).zip(bytes) {
+                        mem.write_byte(addr, byte);
+                    }
+                    "OK".to_string()
+                }
+            }
+            Request::Step => {
+                self.display.set_menu(None);
+                self.step(mem)?;
+                self.resumed_at = None;
+                STOPPED.to_string()
+            }
+            Request::Continue => {
+                self.resume();
+                self.resumed_at = Some(self.pc); // A breakpoint here was already reported
+                return Ok(None);
+            }
+            Request::AddBreakpoint(addr) => {
+                self.add_breakpoint(Breakpoint::at(addr));
+                "OK".to_string()
+            }
+            Request::RemoveBreakpoint(addr) => {
+                self.breakpoints.retain(|breakpoint| *breakpoint != Breakpoint::at(addr));
+                "OK".to_string()
+            }
+            Request::AddWatchpoint(watchpoint) => {
+                mem.add_watchpoint(watchpoint);
+                "OK".to_string()
+            }
+            Request::RemoveWatchpoint(watchpoint) => {
+                mem.remove_watchpoint(watchpoint);
+                "OK".to_string()
+            }
+            Request::Supported => "PacketSize=1000".to_string(),
+            Request::Detach => {
+                if self.paused {
+                    self.resume();
+                }
+                "OK".to_string()
+            }
+            Request::Unsupported => String::new(),
+        };
+        Ok(Some(reply))
+    }
+
+    // Snapshot of the registers, timers, stack and screen
+    pub fn state(&self) -> MachineState {
+        MachineState {
+            v: self.v,
+            i: self.idx,
+            pc: self.pc,
+            sp: self.sp,
+            dt: self.dt,
+            st: self.st,
+            stack: self.stack,
+            display: self.display.get_grid().to_vec(),
+        }
+    }
+
+    // Takes the machine and its memory, to go back to with restore_state
+    #[cfg(feature = "std")]
+    pub fn save_state(&self, mem: &Memory) -> SaveState {
+        SaveState { machine: self.state(), calls: self.calls, memory: mem.bytes().to_vec() }
+    }
+
+    // Goes back to a saved machine, returns false if its memory is of another size
+    #[cfg(feature = "std")]
+    pub fn restore_state(&mut self, mem: &mut Memory, saved: &SaveState) -> bool {
+        if saved.memory.len() != mem.size() {
+            return false;
+        }
+        let machine = &saved.machine;
+        self.v = machine.v;
+        self.idx = machine.i;
+        self.pc = machine.pc;
+        self.sp = machine.sp;
+        self.dt = machine.dt;
+        self.st = machine.st;
+        self.stack = machine.stack;
+        self.calls = saved.calls;
+        if let Ok(grid) = machine.display.as_slice().try_into() {
+            self.display.set_grid(grid);
+        }
+        mem.restore(&saved.memory);
+        self.cpu_state = CpuState::Running;
+        true
+    }
+
+    // Keeps the machine to go back to with the load state hotkey, replacing the last save
+    #[cfg(feature = "std")]
+    fn quick_save(&mut self, mem: &Memory) {
+        self.saved = Some(Box::new(self.save_state(mem)));
+    }
+
+    // Goes back to the last save, returns false if there is none or the input comes from elsewhere
+    #[cfg(feature = "std")]
+    fn quick_load(&mut self, mem: &mut Memory) -> bool {
+        let Some(saved) = self.saved.take() else { return false };
+        let loaded = self.playback.is_none() && self.netplay.is_none() && self.restore_state(mem, &saved);
+        self.saved = Some(saved);
+        loaded
+    }
+
+    // Snapshot of the registers for the debugger
+    #[cfg(feature = "std")]
+    fn registers(&self) -> Registers {
+        Registers { v: self.v, idx: self.idx, pc: self.pc, sp: self.sp, dt: self.dt, st: self.st, calls: self.calls }
+    }
+
+    // Area the next instruction draws to if it's a sprite draw, as x, y and height
+    #[cfg(feature = "std")]
+    fn next_draw(&self, mem: &Memory) -> Option<(usize, usize, usize)> {
+        let op_code = OpCode::decode(mem.get_instruction(self.pc));
+        if op_code.code >> 12 != 0xD {
+            return None;
+        }
+        let x = self.v[op_code.vx()] as usize % DISPLAY_WIDTH;
+        let y = self.v[op_code.vy()] as usize % DISPLAY_HEIGHT;
+        Some((x, y, op_code.nibble() as usize))
+    }
+
+    // Shows the next notification over the screen once the current one is dismissed
+    #[cfg(feature = "std")]
+    fn update_notices(&mut self) {
+        let Some(notifier) = self.notifier.as_mut() else { return };
+        let display = &self.display;
+        let status = Status {
+            frame: self.frame,
+            keys: display.pressed_keys(&self.keyboard),
+            paused: self.paused,
+            hotkey_pressed: |key| display.is_key_pressed(key),
+        };
+        if notifier.update(&status) {
+            let notice = notifier.notice();
+            self.display.set_notice(notice);
+        }
+    }
+
+    // Counts a displayed frame, refreshing the speed in the title about once a second
+    #[cfg(feature = "std")]
+    fn update_speed(&mut self) {
+        let Some(speed) = self.speed.as_mut() else { return };
+        if speed.frame(Instant::now(), self.instructions) {
+            self.update_title();
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn update_title(&mut self) {
+        let title = self.title();
+        self.display.set_title(&title);
+    }
+
+    // Without a window hosts show the title themselves, if at all
+    #[cfg(not(feature = "std"))]
+    fn update_title(&mut self) {}
+
+    // Window title with the ROM name and the status, unless replaced with set_title
+    pub fn title(&self) -> String {
+        if let Some(title) = self.title.as_ref() {
+            return title.clone();
+        }
+        let mut title = match self.rom_name.as_ref() {
+            Some(name) => format!("{name} - {WINDOW_NAME}"),
+            None => WINDOW_NAME.to_string(),
+        };
+        if self.paused {
+            title += " - Paused";
+        }
+        if self.muted {
+            title += " - Muted";
+        }
+        if self.tickrate != DEFAULT_TICKRATE {
+            title += &format!(" - {}x", self.tickrate as f64 / DEFAULT_TICKRATE as f64);
+        }
+        #[cfg(feature = "std")]
+        if let Some(speed) = self.speed.as_ref() {
+            title += &format!(" - {speed}");
+        }
+        if self.show_stats {
+            title += &format!(" - {}", self.stats.last());
+        }
+        title
+    }
+
+    fn update_timers(&mut self) {
+        // Schedule the beep for as long as the sound timer runs, muting cuts off a running beep
+        #[cfg(feature = "std")]
+        if let Ok(audio) = self.audio.as_mut() {
+            audio.update(self.frame, if self.muted { 0 } else { self.st });
+        }
+        self.frame += 1;
+
+        #[cfg(feature = "std")]
+        self.display.set_beeping(self.st > 0);
+        let sounding = self.st > 0 && !self.muted;
+        if sounding != self.sounding {
+            self.sounding = sounding;
+            for observer in self.sound_observers.iter_mut() {
+                observer(sounding);
+            }
+        }
+
+        if self.st > 0 { // Decrement sound timer at 60hz
+            self.st -= 1;
+        }
+
+        if self.dt > 0 { // Decrement delay timer at 60hz
+            self.dt -= 1;
+        }
+    }
+
+    // Executes given opcode, the run loop fetches decoded ones instead
+    #[cfg(any(test, feature = "bench"))]
+    fn execute( &mut self, op_code: u16, mem: &mut Memory) -> Result<(), Chip8Error> {
+        self.execute_decoded(OpCode::decode(op_code), mem)
+    }
+
+    // Executes a decoded opcode dividing them by their first nibble
+    fn execute_decoded( &mut self, op_code: OpCode, mem: &mut Memory) -> Result<(), Chip8Error> {
+        match op_code.code >> 12 {
+            0x0 => self.execute_0nnn(op_code, mem)?,
+            0x1 => self.execute_1nnn(op_code),
+            0x2 => self.execute_2nnn(op_code)?,
+            0x3 => self.execute_3xkk(op_code),
+            0x4 => self.execute_4xkk(op_code),
+            0x5 => self.execute_5xy0(op_code)?,
+            0x6 => self.execute_6xkk(op_code),
+            0x7 => self.execute_7xkk(op_code),
+            0x8 => self.execute_8nnn(op_code)?,
+            0x9 => self.execute_9xy0(op_code)?,
+            0xA => self.execute_annn(op_code),
+            0xB => self.execute_bnnn(op_code),
+            0xC => self.execute_cxkk(op_code),
+            0xD => self.execute_dxyn(op_code, &mem),
+            0xE => self.execute_ennn(op_code)?,
+            0xF => self.execute_fnnn(op_code, mem)?,
+            _ => return Err(ExecError::UnrecognizedOpcode(op_code.code, self.pc.wrapping_sub(2)).into()), // Impossible to reach
+        }
+        Ok(())
+    }
+
+    // 0x0nnn - System calls
+    fn execute_0nnn( &mut self, op_code: OpCode, mem: &Memory) -> Result<(), Chip8Error>{
+        match op_code.code {
+            // 0nnn - SYS addr - ignored by modern interpreters
+
+            // 00EE - RET
+            0x00ee => { // Return from a subroutine
+                if self.sp == 0 {
+                    return Err(ExecError::StackUnderflow(self.pc.wrapping_sub(2)).into());
+                }
+                self.sp -= 1;
+                self.pc = self.stack[self.sp as usize];
+            }
+            
+            // 00E0 - CLS
+            0x00e0 => { // Clear the display
+                self.display.clear();
+            }
+            
+            // NOP
+            0x0000 => (), // Do nothing
+
+            // 0011 - MEGAON
+            0x0011 => { // Switch to the Megachip screen
+                self.display.set_megachip(true);
+            }
+            _ if self.display.is_megachip() => self.execute_megachip(op_code, mem)?,
+            _ => return Err(ExecError::UnrecognizedOpcode(op_code.code, self.pc.wrapping_sub(2)).into()),
+        }
+        Ok(())
+    }
+
+    // Megachip extensions of 0nnn, only known in the Megachip mode
+    fn execute_megachip(&mut self, op_code: OpCode, mem: &Memory) -> Result<(), Chip8Error> {
+        let nn = op_code.byte();
+        match op_code.code >> 8 {
+            // 0010 - MEGAOFF
+            0x0 if op_code.code == 0x0010 => { // Switch back to the 64x32 screen
+                self.display.set_megachip(false);
+            }
+
+            // 01nn nnnn - LDHI I, nnnnnn
+            0x1 => { // Set I = 24-bit address, the low 16 bits follow the instruction
+                let addr = (nn as u32) << 16 | mem.get_instruction(self.pc) as u32;
+                if addr as usize >= mem.size() {
+                    return Err(ExecError::AddressOutOfRange(addr, self.pc.wrapping_sub(2)).into());
+                }
+                self.idx = addr as u16;
+                self.pc = self.pc.wrapping_add(2);
+            }
+
+            // 02nn - LDPAL nn
+            0x2 => { // Load nn colors from memory starting at location I
+                let colors = read_bytes(mem, self.idx, nn as usize * 4);
+                self.megachip().load_palette(&colors);
+            }
+
+            // 03nn - SPRW nn
+            0x3 => self.megachip().set_sprite_width(nn),
+
+            // 04nn - SPRH nn
+            0x4 => self.megachip().set_sprite_height(nn),
+
+            // 05nn - ALPHA nn, 060n - DIGISND n, 0700 - STOPSND, 080n - BMODE n
+            // Screen transparency, sampled sound and blend modes aren't emulated, the screen stays opaque and silent
+            0x5..=0x8 => (),
+
+            // 09nn - CCOL nn
+            0x9 => self.megachip().set_collision_color(nn),
+            _ => return Err(ExecError::UnrecognizedOpcode(op_code.code, self.pc.wrapping_sub(2)).into()),
+        }
+        Ok(())
+    }
+
+    // Screen of the Megachip mode, only called while it is on
+    fn megachip(&mut self) -> &mut MegaScreen {
+        self.display.get_megachip().expect("Megachip mode is on")
+    }
+
+    // 1nnn - JP addr
+    fn execute_1nnn( &mut self, op_code: OpCode) { // Jump to location nnn
+        let addr = op_code.addr();
+        self.pc = addr;
+    }
+
+    // 2nnn - CALL addr
+    fn execute_2nnn( &mut self, op_code: OpCode) -> Result<(), Chip8Error> { // Call subroutine at nnn
+        if self.sp as usize >= STACK_DEPTH {
+            return Err(ExecError::StackOverflow(self.pc.wrapping_sub(2)).into());
+        }
+        let addr = op_code.addr();
+        self.stack[self.sp as usize] = self.pc;
+        self.calls[self.sp as usize] = addr;
+        self.sp += 1;
+        self.stats.current.max_stack_depth = self.stats.current.max_stack_depth.max(self.call_depth());
+        self.pc = addr;
+        Ok(())
+    }
+
+    // 3xkk - SE Vx, byte
+    fn execute_3xkk( &mut self, op_code: OpCode) { // Skip next instruction if Vx = kk
+        let vx = op_code.vx();
+        let data = op_code.byte();
+        if self.v[vx] == data {
+            self.pc = self.pc.wrapping_add(2);
+        }
+    }
+
+    // 4xkk - SNE Vx, byte
+    fn execute_4xkk( &mut self, op_code: OpCode) { // Skip next instruction if Vx != kk
+        let vx = op_code.vx();
+        let data = op_code.byte();
+        if self.v[vx] != data {
+            self.pc = self.pc.wrapping_add(2);
+        }
+    }
+
+    // 5xy0 - SE Vx, Vy
+    fn execute_5xy0( &mut self, op_code: OpCode) -> Result<(), Chip8Error>{ // Skip next instruction if Vx = Vy
+        // Check if last nibble is 0, if not, it's an invalid opcode
+        if op_code.nibble() != 0x0 { 
+            return Err(ExecError::UnrecognizedOpcode(op_code.code, self.pc.wrapping_sub(2)).into());
+        }
+
+        let vx = op_code.vx(); 
+        let vy = op_code.vy();
+        if self.v[vx] == self.v[vy] {
+            self.pc = self.pc.wrapping_add(2);
+        }
+        Ok(())
+    }
+
+    // 6xkk - LD Vx, byte
+    fn execute_6xkk( &mut self, op_code: OpCode) { // Set Vx = kk
+        let vx = op_code.vx();
+        let data = op_code.byte();
+        self.v[vx] = data;
+    }
+
+    // 7xkk - ADD Vx, byte
+    fn execute_7xkk( &mut self, op_code: OpCode) { // Set Vx = Vx + kk
+        let vx = op_code.vx();
+        let data = op_code.byte();
+        self.v[vx] = self.v[vx].wrapping_add(data);
+    }
+
+    // Starts with 8 - Arithmetic operations
+    fn execute_8nnn( &mut self, op_code: OpCode) -> Result<(), Chip8Error> {
+        let vx = op_code.vx();
+        let vy = op_code.vy();
+        match op_code.nibble() {
+            
+            // 8xy0 - LD Vx, Vy
+            0x0 => { // Set Vx = Vy
+                self.v[vx] = self.v[vy];
+            }
+            
+            // 8xy1 - OR Vx, Vy
+            0x1 => { // Set Vx = Vx OR Vy
+                self.v[vx] |= self.v[vy];
+            }
+            
+            // 8xy2 - AND Vx, Vy
+            0x2 => { // Set Vx = Vx AND Vy
+                self.v[vx] &= self.v[vy];
+            } 
+            
+            // 8xy3 - XOR Vx, Vy
+            0x3 => { // Set Vx = Vx XOR Vy
+                self.v[vx] ^= self.v[vy];
+            }
+            
+            // The flag is set after the result, so it wins when Vx is VF
+
+            // 8xy4 - ADD Vx, Vy
+            0x4 => { // Set Vx = Vx + Vy, set VF = carry
+                let (sum, carry) = self.v[vx].overflowing_add(self.v[vy]);
+                self.v[vx] = sum;
+                self.v[FLAG_REGISTER] = carry as u8;
+            }
+
+            // 8xy5 - SUB Vx, Vy
+            0x5 => { // Set Vx = Vx - Vy, set VF = NOT borrow
+                let (diff, borrow) = self.v[vx].overflowing_sub(self.v[vy]);
+                self.v[vx] = diff;
+                self.v[FLAG_REGISTER] = (!borrow) as u8;
+            }
+
+            // 8xy6 - SHR Vx {, Vy}
+            0x6 => { // Set Vx = Vx SHR 1, or Vy SHR 1 without the shift quirk, set VF = LSb of the shifted value
+                let value = if self.quirks.shift { self.v[vx] } else { self.v[vy] };
+                self.v[vx] = value >> 1;
+                self.v[FLAG_REGISTER] = value & 1;
+            }
+            
+            // 8xy7 - SUBN Vx, Vy
+            0x7 => { // Set Vx = Vy - Vx, set VF = NOT borrow
+                let (diff, borrow) = self.v[vy].overflowing_sub(self.v[vx]);
+                self.v[vx] = diff;
+                self.v[FLAG_REGISTER] = (!borrow) as u8;
+            }
+
+            // 8xyE - SHL Vx {, Vy}
+            0xe => { // Set Vx = Vx SHL 1, or Vy SHL 1 without the shift quirk, set VF = MSB of the shifted value
+                let value = if self.quirks.shift { self.v[vx] } else { self.v[vy] };
+                self.v[vx] = value << 1;
+                self.v[FLAG_REGISTER] = value >> 7;
+            }
+            _ => return Err(ExecError::UnrecognizedOpcode(op_code.code, self.pc.wrapping_sub(2)).into()),
+        }
+        Ok(())
+    }
+
+    // 9xy0 SNE Vx, Vy
+    fn execute_9xy0( &mut self, op_code: OpCode) -> Result<(), Chip8Error> { // Skip next instruction if Vx != Vy
+        // Check if last nibble is 0, if not, it's an invalid opcode
+        if op_code.nibble() != 0x0 { 
+            return Err(ExecError::UnrecognizedOpcode(op_code.code, self.pc.wrapping_sub(2)).into());
+        }
+ 
+        let vx = op_code.vx();
+        let vy = op_code.vy();
+        if self.v[vx] != self.v[vy] {
+            self.pc = self.pc.wrapping_add(2);
+        }
+        Ok(())
+    }
+
+     // Annn - LD I, addr
+    fn execute_annn( &mut self, op_code: OpCode) { // Set I = nnn
+        let addr = op_code.addr();
+        self.idx = addr;
+    }
+
+    // Bnnn - JP V0, addr
+    fn execute_bnnn( &mut self, op_code: OpCode) { // Jump to location nnn + V0, or xnn + Vx with the jump quirk
+        let addr = op_code.addr();
+        let offset = if self.quirks.jump { self.v[op_code.vx()] } else { self.v[0] };
+        self.pc = addr + offset as u16;
+    }
+
+    // Cxkk - RND Vx, byte
+    fn execute_cxkk( &mut self, op_code: OpCode) { // Set Vx = random byte AND kk
+        let vx = op_code.vx();
+        let data = op_code.byte();
+        let rnd = self.rng.next_byte();
+        self.v[vx] = data & rnd;
+    }
+
+    // Dxyn - DRW Vx, Vy, nibble
+    fn execute_dxyn(&mut self, op_code: OpCode, mem: &Memory) { // Display n-byte sprite starting at memory location I at (Vx, Vy), set VF = collision
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("draw", rows = op_code.nibble()).entered();
+        let vx = op_code.vx();
+        let vy = op_code.vy();
+        let height = op_code.nibble() as usize;
+
+        // In the Megachip mode the sprite has the size set with 03nn and 04nn and a palette index per pixel
+        if self.display.is_megachip() {
+            let (x, y, idx) = (self.v[vx], self.v[vy], self.idx);
+            let screen = self.megachip();
+            let sprite = read_bytes(mem, idx, screen.sprite_len());
+            let height = screen.sprite_height() as u16;
+            self.v[FLAG_REGISTER] = screen.draw(x as usize, y as usize, &sprite) as u8;
+            self.drew(x, y, height);
+            return;
+        }
+        
+        // Read sprite from memory
+        let sprite = (0..height)
+            .map(|offset| mem.read_byte(self.idx.wrapping_add(offset as u16)));
+    
+        let x = self.v[vx] as usize;
+        let y = self.v[vy] as usize;
+        
+        // Draw sprite and set collision flag
+        self.v[FLAG_REGISTER] = self.display.draw(x, y, sprite) as u8; 
+        self.drew(x as u8, y as u8, height as u16);
+    }
+
+    // Counts a sprite draw and logs it, the flag register tells if it collided
+    fn drew(&mut self, x: u8, y: u8, height: u16) {
+        let collision = self.v[FLAG_REGISTER] == 1;
+        self.stats.current.draws += 1;
+        self.stats.current.collisions += collision as u64;
+        self.log_event(Event::Draw { x, y, height, collision });
+    }
+
+    // Ennn - Keyboard operations
+    fn execute_ennn( &mut self, op_code: OpCode) -> Result<(), Chip8Error> { 
+        let vx = op_code.vx();
+        match op_code.byte() {
+
+            // Ex9E - SKP Vx
+            0x9e => { // Skip next instruction if key with the value of Vx is pressed
+                if self.is_key_down(self.v[vx]) {
+                    self.pc = self.pc.wrapping_add(2);
+                }
+            },
+
+            // ExA1 - SKNP Vx
+            0xa1 => { // Skip next instruction if key with the value of Vx is not pressed
+                if !self.is_key_down(self.v[vx]) {
+                    self.pc = self.pc.wrapping_add(2);
+                }
+            },
+            _ => return Err(ExecError::UnrecognizedOpcode(op_code.code, self.pc.wrapping_sub(2)).into()),
+        }
+        Ok(())
+    }
+
+    // Fnnn - Miscellaneous operations
+    fn execute_fnnn( &mut self, op_code: OpCode, mem: &mut Memory) -> Result<(), Chip8Error> { // Starts with F
+        let vx = op_code.vx();
+        match op_code.byte() {
+
+            // Fx07 - LD Vx, DT
+            0x07 => { // Set Vx = delay timer value
+                self.v[vx] = self.dt;
+            }
+            
+            // Fx0A - LD Vx, K
+            0x0a => {  // Wait for a key press and release, store the value of the key in Vx
+                // Execution stays on this instruction, the main loop keeps running frames
+                // and step checks the keys until then
+                self.cpu_state = CpuState::WaitingForKey(vx);
+                self.stats.current.key_waits += 1;
+                self.pc = self.pc.wrapping_sub(2);
+            }
+
+            // Fx15 - LD DT, Vx
+            0x15 => { // Set delay timer = Vx
+                self.dt = self.v[vx];
+                self.log_event(Event::Timer { timer: Timer::Delay, value: self.dt });
+            }
+            
+            // Fx18 - LD ST, Vx
+            0x18 => { // Set sound timer = Vx
+                self.st = self.v[vx];
+                self.log_event(Event::Timer { timer: Timer::Sound, value: self.st });
+            }
+
+            // Fx1E - ADD I, Vx
+            0x1e => { // Set I = I + Vx
+                self.idx = self.idx.wrapping_add(self.v[vx] as u16);
+            }
+
+            // Fx29 - LD F, Vx
+            0x29 => { // Set I = location of sprite for digit Vx
+                self.idx = mem.font_address(self.v[vx]); // Follows fonts loaded with Memory::load_font
+            }
+
+            // Fx30 - LD HF, Vx
+            0x30 => { // Set I = location of the large SUPER-CHIP sprite for digit Vx
+                self.idx = mem.big_font_address(self.v[vx]);
+            }
+
+            // Fx33 - LD B, Vx
+            0x33 => { // Store BCD representation of Vx in memory locations I, I+1, I+2
+                mem.write_byte(self.idx, self.v[vx] / 100);
+                mem.write_byte(self.idx.wrapping_add(1), (self.v[vx] % 100) / 10);
+                mem.write_byte(self.idx.wrapping_add(2), self.v[vx] % 10);
+            }
+
+            // Fx55 - LD [I], Vx
+            0x55 => { // Store registers V0 through Vx in memory starting at location I
+                for i in 0..=vx {
+                    mem.write_byte(self.idx.wrapping_add(i as u16), self.v[i]);
+                }
+                self.idx = self.idx.wrapping_add(self.quirks.memory.amount(vx));
+            }
+
+            // Fx65 - LD Vx, [I]
+            0x65 => { // Read registers V0 through Vx from memory starting at location I
+                for i in 0..=vx {
+                    self.v[i] = mem.read_byte(self.idx.wrapping_add(i as u16));
+                }
+                self.idx = self.idx.wrapping_add(self.quirks.memory.amount(vx));
+            }
+
+            // Fx75 - LD R, Vx
+            0x75 => { // Store registers V0 through Vx in the user flags, saved for the next session
+                self.user_flags.set(mem.rom(), &self.v[..=vx], self.storage.as_mut());
+            }
+
+            // Fx85 - LD Vx, R
+            0x85 => { // Read registers V0 through Vx from the user flags
+                let flags = self.user_flags.get(mem.rom(), self.storage.as_ref());
+                self.v[..=vx].copy_from_slice(&flags[..=vx]);
+            }
+            _ => return Err(ExecError::UnrecognizedOpcode(op_code.code, self.pc.wrapping_sub(2)).into()),
+        }
+        Ok(())
+    }
+
+    pub fn set_colors(&mut self, filled: u32, empty: u32) {
+        self.display.set_colors(filled, empty);
+    }
+
+    // Features the window and audio backends support
+    #[cfg(feature = "std")]
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            audio: self.audio.as_ref().map(|_| ()).map_err(String::clone),
+            fullscreen: self.display.backend() != Backend::Minifb, // minifb has no fullscreen mode
+            gamepad: false,
+            clipboard: false,
+            vsync: self.display.backend() != Backend::Minifb,
+        }
+    }
+
+    // Turns on an optional feature, falling back when the backends don't support it.
+    // Returns a message explaining the fallback, if there was one.
+    #[cfg(feature = "std")]
+    pub fn enable(&mut self, feature: Feature) -> Option<String> {
+        if self.headless {
+            return Some(format!("{feature} is not available when running headless"));
+        }
+        if feature == Feature::Audio {
+            let _ = self.set_audio_enabled(true); // The reason is kept for the fallback message
+        }
+        let fallback = self.capabilities().fallback(feature);
+        if fallback.is_some() && feature == Feature::Fullscreen {
+            self.set_fullscreen(true);
+        }
+        if fallback.is_none() && feature == Feature::Vsync {
+            self.set_vsync(true);
+        }
+        fallback
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.set_colors(palette.filled.0, palette.empty.0);
+    }
+
+    #[cfg(feature = "std")]
+    pub fn with_bindings(&mut self, bindings: HashMap<u8, Key>) {
+        self.keyboard = Keys::from(bindings);
+    }
+
+    #[cfg(feature = "std")]
+    pub fn insert_binding(&mut self, key: u8, value: Key) {
+        self.keyboard.insert(key, value);
+    }
+
+    // Lets erased pixels fade out over a few frames instead of vanishing at once
+    #[cfg(feature = "std")]
+    pub fn set_phosphor(&mut self, enabled: bool) {
+        self.display.set_phosphor(enabled);
+    }
+
+    // Shows on screen whenever the sound timer runs, also while muted
+    #[cfg(feature = "std")]
+    pub fn set_sound_indicator(&mut self, indicator: SoundIndicator) {
+        self.display.set_sound_indicator(indicator);
+    }
+
+    // Replaces the generated window title, which shows the ROM name and status
+    pub fn set_title(&mut self, title: &str) {
+        self.title = Some(title.to_string());
+        self.update_title();
+    }
+
+    // Pauses the emulator before instructions the breakpoint matches
+    #[cfg(feature = "std")]
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    #[cfg(feature = "std")]
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    // Prints a warning whenever the program writes to the code it has executed so far,
+    // which breaks emulators caching decoded instructions
+    #[cfg(feature = "std")]
+    pub fn set_self_modify_warnings(&mut self, enabled: bool) {
+        self.warn_self_modify = enabled;
+    }
+
+    // Calls the function with the screen after every frame, e.g. to render it in another window
+    pub fn on_frame(&mut self, observer: impl FnMut(&FrameBuffer) + Send + 'static) {
+        self.frame_observers.push(Box::new(observer));
+    }
+
+    // Calls the function with true when the beep starts and false when it stops
+    pub fn on_sound(&mut self, observer: impl FnMut(bool) + Send + 'static) {
+        self.sound_observers.push(Box::new(observer));
+    }
+
+    // Calls the function with every event as it happens, the ones record_events logs, e.g. to
+    // report why the debugger paused. Nothing is printed, so this is how the reasons are seen.
+    pub fn on_event(&mut self, observer: impl FnMut(&Event) + Send + 'static) {
+        self.event_observers.push(Box::new(observer));
+    }
+
+    // Runs the frames due since the last refresh, then shows the last one completed, which waits
+    // for the next refresh. Frames end, ticking the timers, as the 60hz clock reaches them, with
+    // the rest of their instructions run first, and the next frame runs as far as the clock is
+    // into it, so frames fall between refreshes rather than being shown twice or skipped.
+    #[cfg(feature = "std")]
+    fn run_paced(&mut self, mem: &mut Memory, windows: &mut Windows, pacer: &mut FramePacer, mut executed: u32) -> Result<u32, Chip8Error> {
+        let Due { mut ticks, instructions } = pacer.due(Instant::now(), self.tickrate);
+        'frames: loop {
+            let target = if ticks > 0 { self.tickrate } else { instructions };
+            while executed < target {
+                if self.paused || self.check_breakpoints(mem) {
+                    break 'frames;
+                }
+                self.step(mem)?;
+                executed += 1;
+            }
+            if ticks == 0 {
+                break;
+            }
+            ticks -= 1;
+            executed = 0;
+            self.end_frame(mem)?;
+        }
+        self.display.update(&mut windows.screen)?;
+        self.debugger.update(mem, &self.registers(), &self.labels, &mut windows.debugger)?;
+        self.poll_remote(mem)?;
+        #[cfg(feature = "remote")]
+        self.poll_api(mem)?;
+        self.update_speed();
+        self.handle_hotkeys(mem, windows);
+        self.update_notices();
+        Ok(executed)
+    }
+
+    // Adds a program to switch to with Page Up and Page Down, the first one added is taken to be running
+    #[cfg(feature = "std")]
+    pub fn add_bank(&mut self, bank: RomBank) {
+        self.banks.push(bank);
+    }
+
+    // Loads the program of a bank with its cheats and restarts the machine as if powered on with it
+    #[cfg(feature = "std")]
+    pub fn switch_bank(&mut self, mem: &mut Memory, index: usize) -> Result<(), Chip8Error> {
+        let bank = self.banks.get(index).cloned().ok_or_else(|| LoadError::InvalidArgument(format!("there is no ROM bank {index}")))?;
+        self.start_bank(mem, bank)?;
+        self.bank = index;
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn start_bank(&mut self, mem: &mut Memory, bank: RomBank) -> Result<(), Chip8Error> {
+        self.store_save_ram(mem);
+        mem.load_from_bytes(&bank.rom)?;
+        self.patches = bank.patches;
+        self.rom_name = Some(bank.name);
+        self.power_on(mem);
+        self.update_title();
+        Ok(())
+    }
+
+    // Lists the programs in a directory to pick one from with the arrow keys and Enter once running,
+    // returns how many there are, the list isn't shown if there are none
+    #[cfg(feature = "std")]
+    pub fn browse(&mut self, dir: impl AsRef<Path>) -> Result<usize, Chip8Error> {
+        let browser = RomBrowser::scan(dir)?;
+        let found = browser.roms().len();
+        if !browser.is_empty() {
+            self.paused = true;
+            self.display.set_browser(Some(browser));
+        }
+        Ok(found)
+    }
+
+    // Sets the cheats, applied whenever the program starts with frozen values kept every frame
+    pub fn set_patches(&mut self, patches: Patches) {
+        self.patches = patches;
+    }
+
+    // Sets the memory saved when the emulator exits and restored when the program starts
+    pub fn set_save_ram(&mut self, save_ram: Option<SaveRam>) {
+        self.save_ram = save_ram;
+    }
+
+    pub fn save_ram(&self) -> Option<SaveRam> {
+        self.save_ram
+    }
+
+    // A failure only loses the saved memory, so it's reported and the program runs on
+    fn restore_save_ram(&mut self, mem: &mut Memory) {
+        if let Some(save_ram) = self.save_ram {
+            if let Err(e) = save_ram.restore(mem, self.storage.as_ref()) {
+                #[cfg(feature = "std")]
+                eprintln!("Warning: save RAM not restored, {}", report(&e));
+                #[cfg(not(feature = "std"))]
+                let _ = e;
+            }
+        }
+    }
+
+    fn store_save_ram(&mut self, mem: &Memory) {
+        if let Some(save_ram) = self.save_ram {
+            if let Err(e) = save_ram.save(mem, self.storage.as_mut()) {
+                #[cfg(feature = "std")]
+                eprintln!("Warning: save RAM not saved, {}", report(&e));
+                #[cfg(not(feature = "std"))]
+                let _ = e;
+            }
+        }
+    }
+
+    // Loads a Rhai script whose hooks run on every frame, instruction or memory access
+    #[cfg(feature = "scripting")]
+    pub fn load_script(&mut self, path: impl AsRef<Path>) -> Result<(), Chip8Error> {
+        self.script = Some(Script::load(path)?);
+        Ok(())
+    }
+
+    // Lets a debugger speaking the GDB remote serial protocol attach over TCP while running,
+    // returns the address it listens on
+    #[cfg(feature = "std")]
+    pub fn listen_remote(&mut self, addr: impl ToSocketAddrs) -> Result<SocketAddr, Chip8Error> {
+        let remote = RemoteServer::bind(addr)?;
+        let addr = remote.local_addr()?;
+        self.remote = Some(remote);
+        Ok(addr)
+    }
+
+    // Connects to another player's emulator, waiting for them when hosting, after which both
+    // exchange their keys every frame and run in lockstep. Both have to run the same program
+    // at the same speed, and the host's random seed is used on both sides.
+    #[cfg(feature = "std")]
+    pub fn start_netplay(&mut self, mode: &NetplayMode, mem: &Memory) -> Result<(), Chip8Error> {
+        let fingerprint = netplay::hash(mem.bytes().iter().copied().chain(self.tickrate.to_le_bytes()));
+        let netplay = match mode {
+            NetplayMode::Host => Netplay::host(NETPLAY_PORT, fingerprint, rand::random())?,
+            NetplayMode::Join(addr) => Netplay::join(addr, fingerprint)?,
+        };
+        self.set_seed(netplay.seed());
+        self.netplay = Some(netplay);
+        Ok(())
+    }
+
+    // Makes Cxkk produce the same numbers every time the program runs from the seed
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Random::seeded(seed);
+    }
+
+    // Serves the HTTP API on the address while running, e.g. 127.0.0.1:8080, returning the address
+    // it listens on. Clients load ROMs, pause, step, read the state, press keys and fetch the screen.
+    #[cfg(feature = "remote")]
+    pub fn listen_http(&mut self, addr: impl ToSocketAddrs) -> Result<SocketAddr, Chip8Error> {
+        let api = ApiServer::bind(addr)?;
+        let addr = api.local_addr()?;
+        self.api = Some(api);
+        Ok(addr)
+    }
+
+    // Sets the waveform, frequency and volume of the beep
+    #[cfg(feature = "std")]
+    pub fn set_audio(&mut self, settings: AudioSettings) {
+        self.audio_settings = settings;
+        if let Ok(audio) = self.audio.as_mut() {
+            audio.set(settings);
+        }
+    }
+
+    // Opens the default output device or closes it, the emulator is silent without one
+    #[cfg(feature = "std")]
+    pub fn set_audio_enabled(&mut self, enabled: bool) -> Result<(), Chip8Error> {
+        if !enabled {
+            self.audio = Err("audio is disabled".to_string());
+            return Ok(());
+        }
+        if self.audio.is_err() {
+            self.audio = match self.display.frontend() {
+                Some(frontend) => Audio::new(Arc::clone(frontend), self.display.backend()),
+                None => Err("there is no frontend to play it through".to_string()),
+            };
+        }
+        match self.audio.as_mut() {
+            Ok(audio) => {
+                audio.set(self.audio_settings);
+                Ok(())
+            }
+            Err(reason) => Err(IoError::AudioInit(reason.clone()).into()),
+        }
+    }
+
+    // Silences the beep without closing the output device
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        self.update_title();
+    }
+
+    // Sets the post-processing filter, before the window is created
+    #[cfg(feature = "std")]
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.display.set_filter(filter);
+    }
+
+    // Sets the window scale, an open window is recreated with the new size
+    #[cfg(feature = "std")]
+    pub fn set_scale(&mut self, scale: Scale) {
+        self.display.set_scale(scale);
+    }
+
+    // Switches to a borderless window covering the screen, or back to the scaled window
+    #[cfg(feature = "std")]
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.display.set_fullscreen(fullscreen);
+    }
+
+    // Presents frames in step with the monitor's refresh, emulating at the tick rate in between.
+    // Backends without vsync keep pacing by the 60hz clock, see capabilities.
+    #[cfg(feature = "std")]
+    pub fn set_vsync(&mut self, enabled: bool) {
+        self.vsync = enabled;
+        self.display.set_vsync(enabled);
+    }
+
+    // Opens the window, the debugger's and the audio device, e.g. chip8_frontend_minifb::MinifbFrontend.
+    // Without one the emulator only runs headless.
+    #[cfg(feature = "std")]
+    pub fn set_frontend(&mut self, frontend: impl Frontend + 'static) {
+        self.display.set_frontend(Arc::new(frontend));
+    }
+
+    // Library the window is opened with, a window already open is reopened with it, and so is
+    // the audio device, which the frontend may open through the same library
+    #[cfg(feature = "std")]
+    pub fn set_backend(&mut self, backend: Backend) {
+        let reopen = self.audio.is_ok() && backend != self.display.backend();
+        self.display.set_backend(backend);
+        if reopen {
+            self.audio = Err("audio is reopened".to_string());
+            let _ = self.set_audio_enabled(true); // The reason is kept, as in enable
+        }
+    }
+
+    // Replaces the bundled window icon, in the open window too
+    #[cfg(feature = "std")]
+    pub fn set_window_icon(&mut self, icon: WindowIcon) {
+        self.display.set_icon(icon);
+    }
+
+    // Names the application the window belongs to, WM_CLASS on X11 and the app id on Wayland,
+    // applied when the window opens
+    #[cfg(feature = "std")]
+    pub fn set_app_name(&mut self, name: &str) {
+        self.display.set_app_name(name);
+    }
+
+    // Opens the window with its top left corner at the given screen position
+    #[cfg(feature = "std")]
+    pub fn set_window_position(&mut self, x: isize, y: isize) {
+        self.display.set_position(x, y);
+    }
+
+    // Restarts the program: clears registers, stack, timers and display
+    // and restores the program from the copy kept when it was loaded
+    pub fn reset(&mut self, mem: &mut Memory) {
+        self.store_save_ram(mem); // Before the reload overwrites a region inside the program
+        self.restart(mem);
+    }
+
+    fn restart(&mut self, mem: &mut Memory) {
+        self.clear_state(mem);
+        mem.reload();
+        self.restore_save_ram(mem);
+        self.patches.apply(mem);
+    }
+
+    // Clears registers, stack, timers and display
+    fn clear_state(&mut self, mem: &Memory) {
+        self.v = [0x00; NUM_REGISTERS];
+        self.idx = 0x0000;
+        self.dt = 0;
+        self.st = 0;
+        self.pc = mem.program_start();
+        self.sp = 0x00;
+        self.stack = [0x0000; STACK_DEPTH];
+        self.calls = [0x0000; STACK_DEPTH];
+        self.cpu_state = CpuState::Running;
+        self.display.set_megachip(false);
+        self.display.clear();
+    }
+
+    // Same as reset, but also clears all memory the program may have written
+    pub fn power_cycle(&mut self, mem: &mut Memory) {
+        self.store_save_ram(mem);
+        self.power_on(mem);
+    }
+
+    // Power cycles without saving, for a program just loaded over the last one
+    fn power_on(&mut self, mem: &mut Memory) {
+        self.clear_state(mem);
+        mem.clear();
+        self.restore_save_ram(mem);
+        self.patches.apply(mem);
+    }
+
+    // Loads a program into memory and power cycles to start it afresh, nothing of the last one
+    // is kept. The memory is passed to run separately, so it is taken here as well.
+    pub fn load_rom_bytes(&mut self, mem: &mut Memory, rom: &[u8]) -> Result<(), Chip8Error> {
+        self.store_save_ram(mem);
+        mem.load_from_bytes(rom)?;
+        self.power_on(mem);
+        Ok(())
+    }
+
+    // Logs what happens while running to the given file as JSON lines, for analysis tools
+    #[cfg(feature = "std")]
+    pub fn record_events(&mut self, path: impl AsRef<Path>) -> Result<(), Chip8Error> {
+        self.events = Some(EventLog::create(path)?);
+        Ok(())
+    }
+
+    // Hands the event to the observers and the event log, if recording
+    fn log_event(&mut self, event: Event) {
+        for observer in self.event_observers.iter_mut() {
+            observer(&event);
+        }
+        #[cfg(feature = "std")]
+        if let Some(events) = self.events.as_mut() {
+            events.log(self.frame, &event);
+        }
+    }
+
+    // Records key presses to the given file while running
+    #[cfg(feature = "std")]
+    pub fn record_input(&mut self, path: impl AsRef<Path>) -> Result<(), Chip8Error> {
+        self.recorder = Some(InputRecorder::create(path.as_ref(), self.tickrate)?);
+        Ok(())
+    }
+
+    // Guides first-time users through the emulator with hints shown over the screen
+    #[cfg(feature = "std")]
+    pub fn start_tour(&mut self) {
+        self.notifier = Some(Notifier::new(tour::RULES));
+    }
+
+    // Replays a recorded session instead of reading the keyboard, at the recorded speed
+    #[cfg(feature = "std")]
+    pub fn replay_input(&mut self, path: impl AsRef<Path>) -> Result<(), Chip8Error> {
+        let playback = InputRecording::open(path.as_ref())?;
+        self.tickrate = playback.tickrate_at(self.frame);
+        self.playback = Some(playback);
+        Ok(())
+    }
+
+    // Holds the chip8 key down as if pressed on the keyboard until released, so bots and tests
+    // can drive the program, also headless. Like keyboard input it takes effect with the next
+    // frame, and a replayed recording still takes precedence. Keys above F are ignored.
+    pub fn press_key(&mut self, key: u8) {
+        self.input.push(key, true);
+    }
+
+    pub fn release_key(&mut self, key: u8) {
+        self.input.push(key, false);
+    }
+
+    // Makes the chip8 key press and release repeatedly while held, rate times a second,
+    // e.g. to keep firing in shooters, or stops it with None
+    pub fn set_autofire(&mut self, key: u8, rate: Option<u32>) {
+        self.input.set_autofire(key, rate);
+    }
+
+    // Sets how many times faster the program runs while the turbo key is held
+    #[cfg(feature = "std")]
+    pub fn set_turbo_factor(&mut self, factor: u32) {
+        self.turbo = factor.max(1);
+    }
+
+    // Starts recording the screen into an animated GIF
+    #[cfg(feature = "std")]
+    pub fn start_gif_recording(&mut self, path: impl AsRef<Path>) -> Result<(), Chip8Error> {
+        let (filled, empty) = self.display.get_colors();
+        let factor = self.display.get_scale().factor();
+        self.gif = Some(GifRecorder::create(path.as_ref(), factor, filled, empty)?);
+        Ok(())
+    }
+
+    // Stops the GIF recording, if there is one, and finishes the file
+    #[cfg(feature = "std")]
+    pub fn stop_gif_recording(&mut self) -> Result<(), Chip8Error> {
+        match self.gif.take() {
+            Some(gif) => gif.finish(),
+            None => Ok(()),
+        }
+    }
+
+    // Sets the instructions executed per 60hz frame, also ending the turbo
+    pub fn set_tickrate(&mut self, tickrate: u32) {
+        self.tickrate = tickrate.max(1);
+        #[cfg(feature = "std")]
+        {
+            self.turbo_base = None;
+        }
+        self.update_title();
+    }
+
+    // Takes effect the next time the emulator is run
+    #[cfg(feature = "std")]
+    pub fn set_lag_policy(&mut self, policy: LagPolicy) {
+        self.lag_policy = policy;
+    }
+
+    // Subroutines the program is in, the number of return addresses on the stack
+    pub fn call_depth(&self) -> usize {
+        self.sp as usize
+    }
+
+    // Subroutines the program is in, outermost first, by the address each was called at
+    pub fn call_stack(&self) -> &[u16] {
+        &self.calls[..self.sp as usize]
+    }
+
+    // Names for addresses, shown in disassembly, traces and the debugger's call stack
+    #[cfg(feature = "std")]
+    pub fn set_labels(&mut self, labels: Labels) {
+        self.labels = labels;
+    }
+
+    #[cfg(feature = "std")]
+    pub fn labels(&self) -> &Labels {
+        &self.labels
+    }
+
+    // What the program did during the last complete emulated second
+    pub fn stats(&self) -> Stats {
+        self.stats.last()
+    }
+
+    // Shows the last second's stats in the window title, toggled with F4
+    pub fn set_stats_shown(&mut self, shown: bool) {
+        self.show_stats = shown;
+        self.update_title();
+    }
+
+    pub fn get_tickrate(&self) -> u32 {
+        self.tickrate
+    }
+
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+        if quirks.wrap {
+            self.set_draw_strategy(Wrap);
+        } else {
+            self.set_draw_strategy(Clip);
+        }
+    }
+
+    pub fn get_quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    // Sets where persistent data is kept, the user's data directory by default
+    pub fn set_storage(&mut self, storage: impl Storage + 'static) {
+        self.storage = Box::new(storage);
+    }
+
+    pub fn storage(&mut self) -> &mut dyn Storage {
+        self.storage.as_mut()
+    }
+
+    // Sets how sprites are drawn, clipped at the screen edges by default
+    pub fn set_draw_strategy(&mut self, strategy: impl DrawStrategy + 'static) {
+        self.display.set_draw_strategy(Box::new(strategy));
+    }
+
+    // Saves the current screen as a PNG image
+    #[cfg(feature = "std")]
+    pub fn screenshot(&self, path: impl AsRef<Path>) -> Result<(), Chip8Error> {
+        self.display.screenshot(path.as_ref())
+    }
+}
+
+// Reads len bytes starting at addr, those past the end of memory read as 0
+fn read_bytes(mem: &Memory, addr: u16, len: usize) -> Vec<u8> {
+    (addr as usize..addr as usize + len)
+        .map(|addr| if addr < mem.size() { mem.read_byte(addr as u16) } else { 0 })
+        .collect()
+}
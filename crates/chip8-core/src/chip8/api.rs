@@ -0,0 +1,233 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use super::errors::{Chip8Error, IoError};
+
+// Largest request accepted, enough for headers and any ROM that fits in memory
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+// Connections still sending their request after this long are dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+// Longest the emulator waits on a client that doesn't read its response
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Request to the HTTP API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ApiRequest {
+    State, // GET /state
+    Screen, // GET /screen.png
+    Pause, // POST /pause
+    Resume, // POST /resume
+    Step, // POST /step, executes one instruction while paused
+    Key(u8, bool), // POST /keys/{key}/press or /keys/{key}/release, the key in hex
+    Load(Vec<u8>), // POST /load with the ROM as the body
+    NotFound,
+    BadRequest, // Incomplete or malformed, e.g. too large
+}
+
+impl ApiRequest {
+    // Parses the request line, the body is only used by /load
+    fn parse(method: &str, path: &str, body: &[u8]) -> ApiRequest {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("GET", ["state"]) => ApiRequest::State,
+            ("GET", ["screen.png"]) => ApiRequest::Screen,
+            ("POST", ["pause"]) => ApiRequest::Pause,
+            ("POST", ["resume"]) => ApiRequest::Resume,
+            ("POST", ["step"]) => ApiRequest::Step,
+            ("POST", ["keys", key, action]) => {
+                let key = u8::from_str_radix(key, 16).ok().filter(|&key| key < 16);
+                match (key, *action) {
+                    (Some(key), "press") => ApiRequest::Key(key, true),
+                    (Some(key), "release") => ApiRequest::Key(key, false),
+                    _ => ApiRequest::BadRequest,
+                }
+            }
+            ("POST", ["load"]) => ApiRequest::Load(body.to_vec()),
+            _ => ApiRequest::NotFound,
+        }
+    }
+}
+
+// Reply to a request
+pub(super) enum ApiResponse {
+    Json(String),
+    Png(Vec<u8>),
+    Done, // 204 without a body
+    Error(u16, String), // Status and message, sent as JSON
+}
+
+// Connection a request arrived on, answered once and closed
+pub(super) struct Exchange {
+    stream: TcpStream,
+    pub(super) request: ApiRequest,
+}
+
+impl Exchange {
+    pub(super) fn respond(mut self, response: ApiResponse) {
+        let (status, content_type, body) = match response {
+            ApiResponse::Json(json) => (200, "application/json", json.into_bytes()),
+            ApiResponse::Png(png) => (200, "image/png", png),
+            ApiResponse::Done => (204, "text/plain", Vec::new()),
+            ApiResponse::Error(status, message) => (status, "application/json", serde_json::json!({ "error": message }).to_string().into_bytes()),
+        };
+        let head = format!("HTTP/1.1 {status} {}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", reason(status), body.len());
+        // Written blocking, as a response larger than the socket buffer would stop at WouldBlock.
+        // A client that went away doesn't need its answer.
+        let _ = self.stream.set_nonblocking(false)
+            .and_then(|_| self.stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+            .and_then(|_| self.stream.write_all(head.as_bytes()))
+            .and_then(|_| self.stream.write_all(&body));
+    }
+}
+
+// Connection still sending its request
+struct Pending {
+    stream: TcpStream,
+    input: Vec<u8>,
+    accepted: Instant,
+}
+
+// Serves the HTTP API, polled by the emulator without blocking
+pub(super) struct ApiServer {
+    listener: TcpListener,
+    pending: Vec<Pending>,
+}
+
+impl ApiServer {
+    pub(super) fn bind(addr: impl ToSocketAddrs) -> Result<ApiServer, Chip8Error> {
+        let listener = TcpListener::bind(addr).map_err(IoError::Api)?;
+        listener.set_nonblocking(true).map_err(IoError::Api)?;
+        Ok(ApiServer { listener, pending: Vec::new() })
+    }
+
+    pub(super) fn local_addr(&self) -> Result<SocketAddr, Chip8Error> {
+        Ok(self.listener.local_addr().map_err(IoError::Api)?)
+    }
+
+    // Accepts waiting connections and returns the requests that arrived completely since the last poll.
+    // Connections that fail, close early or take too long to send their request are dropped.
+    pub(super) fn poll(&mut self) -> Vec<Exchange> {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.pending.push(Pending { stream, input: Vec::new(), accepted: Instant::now() });
+            }
+        }
+
+        let mut exchanges = Vec::new();
+        let mut waiting = Vec::new();
+        for mut pending in self.pending.drain(..) {
+            let mut buffer = [0; 4096];
+            let open = loop {
+                match pending.stream.read(&mut buffer) {
+                    Ok(0) => break false,
+                    Ok(read) => pending.input.extend_from_slice(&buffer[..read]),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break true,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(_) => break false,
+                }
+            };
+            match take_request(&pending.input) {
+                Some(request) => exchanges.push(Exchange { stream: pending.stream, request }),
+                None if open && pending.accepted.elapsed() < REQUEST_TIMEOUT => waiting.push(pending),
+                None => {}
+            }
+        }
+        self.pending = waiting;
+        exchanges
+    }
+}
+
+// Parses a complete request, None while more of it is expected
+fn take_request(input: &[u8]) -> Option<ApiRequest> {
+    if input.len() > MAX_REQUEST_SIZE {
+        return Some(ApiRequest::BadRequest);
+    }
+    let end = input.windows(4).position(|window| window == b"\r\n\r\n")?;
+    let Ok(head) = std::str::from_utf8(&input[..end]) else { return Some(ApiRequest::BadRequest) };
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(path)) = (request_line.next(), request_line.next()) else {
+        return Some(ApiRequest::BadRequest);
+    };
+    let length = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .map_or(Some(0), |(_, value)| value.trim().parse().ok());
+    let Some(length) = length.filter(|&length| length <= MAX_REQUEST_SIZE) else { return Some(ApiRequest::BadRequest) };
+    let body = input.get(end + 4..end + 4 + length)?;
+    Some(ApiRequest::parse(method, path.split('?').next().unwrap_or(path), body))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn test_take_request() {
+        assert_eq!(take_request(b"GET /state HTTP/1.1\r\nHost: localhost\r\n\r\n"), Some(ApiRequest::State));
+        assert_eq!(take_request(b"POST /keys/a/press HTTP/1.1\r\n\r\n"), Some(ApiRequest::Key(0xA, true)));
+        assert_eq!(take_request(b"POST /keys/10/press HTTP/1.1\r\n\r\n"), Some(ApiRequest::BadRequest));
+        assert_eq!(take_request(b"GET /pause HTTP/1.1\r\n\r\n"), Some(ApiRequest::NotFound));
+        assert_eq!(take_request(b"GET /state HTTP/1.1\r\n"), None); // Headers not finished
+
+        let load = b"POST /load HTTP/1.1\r\nContent-Length: 4\r\n\r\n\x00\xe0\x12";
+        assert_eq!(take_request(load), None); // Body not finished
+        assert_eq!(take_request(&[&load[..], b"\x00"].concat()), Some(ApiRequest::Load(vec![0x00, 0xe0, 0x12, 0x00])));
+        let huge = b"POST /load HTTP/1.1\r\nContent-Length: 18446744073709551615\r\n\r\n";
+        assert_eq!(take_request(huge), Some(ApiRequest::BadRequest)); // Would overflow the body's end
+    }
+
+    // Sends the request from another thread, answers it and returns what the client received
+    fn exchange(request: &'static [u8], respond: impl FnOnce(&Exchange) -> ApiResponse) -> Vec<u8> {
+        let mut server = ApiServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(request).unwrap();
+            let mut response = Vec::new();
+            stream.read_to_end(&mut response).unwrap();
+            response
+        });
+
+        let mut exchanges = Vec::new();
+        for _ in 0..100 {
+            exchanges.extend(server.poll());
+            if !exchanges.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let exchange = exchanges.pop().unwrap();
+        let response = respond(&exchange);
+        exchange.respond(response);
+        client.join().unwrap()
+    }
+
+    #[test]
+    fn test_serve() {
+        let response = exchange(b"POST /pause HTTP/1.1\r\n\r\n", |exchange| {
+            assert_eq!(exchange.request, ApiRequest::Pause);
+            ApiResponse::Done
+        });
+        assert!(response.starts_with(b"HTTP/1.1 204 No Content\r\n"));
+    }
+
+    #[test]
+    fn test_serve_large() {
+        // Larger than the socket buffer, so it can't be written in one go
+        let response = exchange(b"GET /screen.png HTTP/1.1\r\n\r\n", |_| ApiResponse::Png(vec![0xff; 8 << 20]));
+        assert_eq!(response.iter().filter(|&&byte| byte == 0xff).count(), 8 << 20);
+    }
+}
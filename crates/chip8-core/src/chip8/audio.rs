@@ -0,0 +1,380 @@
+use std::f32::consts::PI;
+use std::sync::{mpsc, Arc};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::thread::{self, JoinHandle};
+use super::BEEP_FREQUENCY;
+use super::backend::Backend;
+use super::errors::{Chip8Error, LoadError};
+use super::frontend::Frontend;
+
+pub use super::{AUDIO_BUFFER_SIZE, SAMPLE_RATE};
+
+// Samples played during a single 60hz frame
+const SAMPLES_PER_FRAME: u64 = SAMPLE_RATE as u64 / 60;
+
+// How far emulation may run ahead of the audio before the schedule is re-anchored, in frames
+const MAX_LEAD: u64 = 4;
+
+// Shortest beep, so a sound timer of 1 is heard as a tone rather than a click
+const MIN_BEEP: u64 = 2 * SAMPLES_PER_FRAME;
+
+// Samples over which the beep fades in and out, so it doesn't click when it starts or stops
+const FADE: u64 = SAMPLE_RATE as u64 / 200; // 5ms
+
+// Shape of the beep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Square, // Like the buzzer of the original machines
+    Triangle,
+    Noise,
+}
+
+const WAVEFORMS: &[(&str, Waveform)] = &[
+    ("sine", Waveform::Sine),
+    ("square", Waveform::Square),
+    ("triangle", Waveform::Triangle),
+    ("noise", Waveform::Noise),
+];
+
+impl Waveform {
+    pub fn parse(name: &str) -> Option<Waveform> {
+        WAVEFORMS.iter().find(|(waveform, _)| *waveform == name).map(|(_, waveform)| *waveform)
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        WAVEFORMS.iter().map(|(name, _)| *name)
+    }
+
+    // Sample at the given position within a period, from 0.0 to 1.0
+    fn sample(&self, phase: f32) -> f32 {
+        match self {
+            Waveform::Sine => (2.0 * PI * phase).sin(),
+            Waveform::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Noise => rand::random::<f32>() * 2.0 - 1.0,
+        }
+    }
+}
+
+// How the beep sounds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioSettings {
+    pub waveform: Waveform,
+    pub frequency: f32, // Hz
+    pub volume: u8, // 0 to 100
+}
+
+impl AudioSettings {
+    // Checks that the beep can be played, the frequency has to be below half the sample rate
+    pub fn validate(&self) -> Result<(), Chip8Error> {
+        if !(self.frequency > 0.0 && self.frequency < SAMPLE_RATE as f32 / 2.0) {
+            return Err(LoadError::InvalidArgument(format!("frequency must be between 0 and {} Hz, got {}", SAMPLE_RATE / 2, self.frequency)).into());
+        }
+        if self.volume > 100 {
+            return Err(LoadError::InvalidArgument(format!("volume must be between 0 and 100, got {}", self.volume)).into());
+        }
+        Ok(())
+    }
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings { waveform: Waveform::default(), frequency: BEEP_FREQUENCY, volume: 100 }
+    }
+}
+
+// Keeps the output device open on a thread of its own until dropped. Devices can't move
+// between threads, so this way the emulator holding it still can.
+struct Device {
+    stop: Option<mpsc::Sender<()>>, // Dropped to close the device
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Device {
+    fn open(frontend: Arc<dyn Frontend>, beep: Beep, backend: Backend) -> Result<Self, String> {
+        let (opened_tx, opened) = mpsc::channel();
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::Builder::new()
+            .name("chip8-audio".to_string())
+            .spawn(move || match frontend.open_audio(beep, backend) {
+                Ok(_output) => {
+                    let _ = opened_tx.send(Ok(()));
+                    let _ = stopped.recv(); // Plays until the sender is dropped
+                }
+                Err(reason) => {
+                    let _ = opened_tx.send(Err(reason));
+                }
+            })
+            .map_err(|e| e.to_string())?;
+        opened.recv().map_err(|_| "the audio thread stopped".to_string())??;
+        Ok(Device { stop: Some(stop), thread: Some(thread) })
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        self.stop = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+pub(super) struct Audio {
+    _device: Device, // Must be kept alive to play
+    schedule: BeepSchedule,
+}
+
+impl Audio {
+    // Opens the frontend's default output device for windows of the backend, fails with the
+    // reason if there is none
+    pub fn new(frontend: Arc<dyn Frontend>, backend: Backend) -> Result<Self, String> {
+        let schedule = BeepSchedule::new();
+        let device = Device::open(frontend, Beep::new(schedule.shared()), backend)?;
+        Ok(Audio { _device: device, schedule })
+    }
+
+    // Updates the beep with the sound timer value at the given emulated frame
+    pub fn update(&mut self, frame: u64, st: u8) {
+        self.schedule.update(frame, st);
+    }
+
+    // Changes how the beep sounds, also while it plays
+    pub fn set(&mut self, settings: AudioSettings) {
+        self.schedule.set(settings);
+    }
+}
+
+// Sample positions shared between the emulator and the audio thread
+struct Shared {
+    played: AtomicU64, // Start of the buffer being played
+    start: AtomicU64, // First sample of the beep
+    end: AtomicU64, // Sample after the last one of the beep
+    waveform: AtomicU8, // Index into WAVEFORMS
+    frequency: AtomicU32, // Bits of the f32 frequency
+    volume: AtomicU8,
+}
+
+// Turns sound timer values at emulated frames into sample positions of the beep.
+// Both ends of a beep are aligned to audio buffer boundaries, so beeps of the same
+// length always sound the same no matter when the audio thread picks them up.
+struct BeepSchedule {
+    shared: Arc<Shared>,
+    anchor: Option<u64>, // Sample at which emulated frame 0 plays
+}
+
+impl BeepSchedule {
+    fn new() -> Self {
+        let shared = Shared {
+            played: AtomicU64::new(0),
+            start: AtomicU64::new(0),
+            end: AtomicU64::new(0),
+            waveform: AtomicU8::new(0),
+            frequency: AtomicU32::new(BEEP_FREQUENCY.to_bits()),
+            volume: AtomicU8::new(100),
+        };
+        BeepSchedule { shared: Arc::new(shared), anchor: None }
+    }
+
+    fn set(&self, settings: AudioSettings) {
+        let waveform = WAVEFORMS.iter().position(|(_, waveform)| *waveform == settings.waveform).unwrap_or(0);
+        self.shared.waveform.store(waveform as u8, Ordering::Release);
+        self.shared.frequency.store(settings.frequency.to_bits(), Ordering::Release);
+        self.shared.volume.store(settings.volume.min(100), Ordering::Release);
+    }
+
+    fn shared(&self) -> Arc<Shared> {
+        Arc::clone(&self.shared)
+    }
+
+    fn update(&mut self, frame: u64, st: u8) {
+        let now = self.sample_at(frame);
+        let start = self.shared.start.load(Ordering::Acquire);
+        let end = self.shared.end.load(Ordering::Acquire);
+
+        if st == 0 {
+            // Sound timer was cleared early, stop at the next boundary once the beep was heard
+            if end > now {
+                self.shared.end.store(align(now).max(align(start + MIN_BEEP)), Ordering::Release);
+            }
+            return;
+        }
+
+        let until = align(now + st as u64 * SAMPLES_PER_FRAME);
+        if end < now {
+            // New beep
+            let start = align(now);
+            self.shared.end.store(until.max(align(start + MIN_BEEP)), Ordering::Release);
+            self.shared.start.store(start, Ordering::Release);
+        } else if until > end {
+            // Sound timer was raised while beeping
+            self.shared.end.store(until, Ordering::Release);
+        }
+    }
+
+    // Maps an emulated frame to a sample position, following the audio thread if they drift apart
+    fn sample_at(&mut self, frame: u64) -> u64 {
+        let played = self.shared.played.load(Ordering::Acquire) + AUDIO_BUFFER_SIZE;
+        let sample = self.anchor.map(|anchor| anchor + frame * SAMPLES_PER_FRAME);
+        match sample {
+            Some(sample) if sample >= played && sample <= played + MAX_LEAD * SAMPLES_PER_FRAME => sample,
+            _ => {
+                self.anchor = Some(played.saturating_sub(frame * SAMPLES_PER_FRAME));
+                played
+            }
+        }
+    }
+}
+
+// Rounds a sample position up to the next buffer boundary
+fn align(sample: u64) -> u64 {
+    sample.div_ceil(AUDIO_BUFFER_SIZE) * AUDIO_BUFFER_SIZE
+}
+
+// Loudness of the beep at the given sample, fading in after the start and out after the end
+fn envelope(sample: u64, start: u64, end: u64) -> f32 {
+    if end <= start || sample < start || sample >= end + FADE {
+        return 0.0;
+    }
+    let fade_in = (sample - start) as f32 / FADE as f32;
+    let fade_out = (end + FADE - sample) as f32 / FADE as f32;
+    fade_in.min(fade_out).min(1.0)
+}
+
+// Endless tone source that is only audible between the scheduled samples, played by the
+// frontend's output device one channel at SAMPLE_RATE
+pub struct Beep {
+    shared: Arc<Shared>,
+    sample: u64,
+    phase: f32, // Position within the current period, kept so frequency changes don't click
+}
+
+impl Beep {
+    fn new(shared: Arc<Shared>) -> Self {
+        Beep { shared, sample: 0, phase: 0.0 }
+    }
+}
+
+impl Iterator for Beep {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.sample;
+        self.sample += 1;
+        if sample.is_multiple_of(AUDIO_BUFFER_SIZE) {
+            self.shared.played.store(sample, Ordering::Release);
+        }
+
+        let frequency = f32::from_bits(self.shared.frequency.load(Ordering::Acquire));
+        self.phase = (self.phase + frequency / SAMPLE_RATE as f32).fract();
+
+        let start = self.shared.start.load(Ordering::Acquire);
+        let end = self.shared.end.load(Ordering::Acquire);
+        let level = envelope(sample, start, end);
+        if level == 0.0 {
+            return Some(0.0);
+        }
+        let waveform = WAVEFORMS[self.shared.waveform.load(Ordering::Acquire) as usize].1;
+        let volume = self.shared.volume.load(Ordering::Acquire) as f32 / 100.0;
+        Some(waveform.sample(self.phase) * volume * level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plays the given number of samples and counts the audible ones
+    fn audible(beep: &mut Beep, samples: u64) -> u64 {
+        (0..samples).filter(|_| beep.next() != Some(0.0)).count() as u64
+    }
+
+    #[test]
+    fn test_short_beep_is_aligned() {
+        let mut schedule = BeepSchedule::new();
+        let mut beep = Beep::new(schedule.shared());
+        audible(&mut beep, 100); // Audio thread is mid-buffer
+        schedule.update(0, 1);
+        schedule.update(1, 0);
+        let start = schedule.shared.start.load(Ordering::Acquire);
+        let end = schedule.shared.end.load(Ordering::Acquire);
+        assert_eq!(start % AUDIO_BUFFER_SIZE, 0);
+        assert_eq!(end % AUDIO_BUFFER_SIZE, 0);
+        assert!(end - start >= SAMPLES_PER_FRAME);
+        assert!(audible(&mut beep, 10 * SAMPLES_PER_FRAME) > 0);
+    }
+
+    #[test]
+    fn test_minimum_length() {
+        let mut schedule = BeepSchedule::new();
+        schedule.update(0, 1);
+        schedule.update(1, 0);
+        let start = schedule.shared.start.load(Ordering::Acquire);
+        let end = schedule.shared.end.load(Ordering::Acquire);
+        assert!(end - start >= MIN_BEEP);
+    }
+
+    #[test]
+    fn test_envelope() {
+        assert_eq!(envelope(1000, 1000, 5000), 0.0);
+        assert_eq!(envelope(1000 + FADE / 2, 1000, 5000), 0.5);
+        assert_eq!(envelope(3000, 1000, 5000), 1.0);
+        assert_eq!(envelope(5000 + FADE / 2, 1000, 5000), 0.5); // Fades out after the end
+        assert_eq!(envelope(5000 + FADE, 1000, 5000), 0.0);
+        assert_eq!(envelope(0, 0, 0), 0.0); // No beep scheduled
+    }
+
+    #[test]
+    fn test_beep_is_extended() {
+        let mut schedule = BeepSchedule::new();
+        schedule.update(0, 2);
+        let end = schedule.shared.end.load(Ordering::Acquire);
+        schedule.update(1, 5);
+        assert!(schedule.shared.end.load(Ordering::Acquire) > end);
+    }
+
+    #[test]
+    fn test_waveforms() {
+        assert_eq!(Waveform::Square.sample(0.25), 1.0);
+        assert_eq!(Waveform::Square.sample(0.75), -1.0);
+        assert_eq!(Waveform::Triangle.sample(0.5), 1.0);
+        assert_eq!(Waveform::Triangle.sample(0.0), -1.0);
+        assert!((Waveform::Sine.sample(0.25) - 1.0).abs() < 1e-6);
+        assert!(Waveform::Noise.sample(0.0).abs() <= 1.0);
+        assert_eq!(Waveform::parse("square"), Some(Waveform::Square));
+        assert_eq!(Waveform::parse("sawtooth"), None);
+    }
+
+    #[test]
+    fn test_volume() {
+        let mut schedule = BeepSchedule::new();
+        schedule.set(AudioSettings { waveform: Waveform::Square, frequency: 440.0, volume: 25 });
+        let mut beep = Beep::new(schedule.shared());
+        schedule.update(0, 10);
+        let loudest = (0..10 * SAMPLES_PER_FRAME).filter_map(|_| beep.next()).fold(0.0, |max: f32, s| max.max(s.abs()));
+        assert_eq!(loudest, 0.25);
+
+        schedule.set(AudioSettings { volume: 0, ..AudioSettings::default() });
+        assert_eq!(audible(&mut beep, SAMPLES_PER_FRAME), 0);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(AudioSettings::default().validate().is_ok());
+        assert!(AudioSettings { volume: 101, ..AudioSettings::default() }.validate().is_err());
+        assert!(AudioSettings { frequency: 0.0, ..AudioSettings::default() }.validate().is_err());
+        assert!(AudioSettings { frequency: 30000.0, ..AudioSettings::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_silent_without_sound_timer() {
+        let mut schedule = BeepSchedule::new();
+        let mut beep = Beep::new(schedule.shared());
+        for frame in 0..10 {
+            schedule.update(frame, 0);
+        }
+        assert_eq!(audible(&mut beep, 10 * SAMPLES_PER_FRAME), 0);
+    }
+}
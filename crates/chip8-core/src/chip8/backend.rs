@@ -0,0 +1,41 @@
+// Library the frontend draws and reads the window through, selectable with --backend.
+// Frontends built without a library fail to open its windows. More libraries may be added, and
+// the default stays minifb whatever a frontend is built with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Backend {
+    #[default]
+    Minifb,
+    Sdl2, // Vsync, real fullscreen and icons everywhere
+    Wgpu, // Scaled on the GPU through pixels, sharp on high-DPI screens
+}
+
+const BACKENDS: &[(&str, Backend)] = &[
+    ("minifb", Backend::Minifb),
+    ("sdl2", Backend::Sdl2),
+    ("wgpu", Backend::Wgpu),
+];
+
+impl Backend {
+    pub fn parse(name: &str) -> Option<Backend> {
+        BACKENDS.iter().find(|(backend, _)| *backend == name).map(|(_, backend)| *backend)
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        BACKENDS.iter().map(|(name, _)| *name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Backend::parse("minifb"), Some(Backend::Minifb));
+        assert_eq!(Backend::parse("opengl"), None);
+        for name in Backend::names() {
+            assert!(Backend::parse(name).is_some(), "{name}");
+        }
+    }
+}
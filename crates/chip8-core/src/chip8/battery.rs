@@ -0,0 +1,82 @@
+use alloc::{format, string::String, vec::Vec};
+use sha1::{Digest, Sha1};
+use super::{Memory, errors::{Chip8Error, LoadError}};
+use super::number::hex;
+use super::storage::Storage;
+
+// Memory a game keeps its high scores in without Fx75, saved when the emulator exits and
+// restored when the program starts, like the battery-backed RAM of cartridges. Set per ROM
+// in the ROM database as "saveRam": "0x2F0-0x2FF" and stored under ram/<rom sha1>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaveRam {
+    pub start: u16,
+    pub end: u16, // Last saved address
+}
+
+impl SaveRam {
+    // Parses ADDR or START-END, addresses in hex
+    pub fn parse(range: &str) -> Result<SaveRam, Chip8Error> {
+        let invalid = || LoadError::InvalidArgument(format!("expected a memory range like 0x2F0-0x2FF, got {range}"));
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        let (start, end) = (hex(start).ok_or_else(invalid)?, hex(end).ok_or_else(invalid)?);
+        if start > end {
+            return Err(invalid().into());
+        }
+        Ok(SaveRam { start, end })
+    }
+
+    // Writes the bytes saved for the loaded ROM back, leaving memory as is if there are none
+    pub fn restore(&self, mem: &mut Memory, storage: &dyn Storage) -> Result<(), Chip8Error> {
+        if let Some(data) = storage.load(&key(mem.rom()))? {
+            for (addr, byte) in (self.start..=self.end).zip(data) {
+                mem.write_byte(addr, byte);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, mem: &Memory, storage: &mut dyn Storage) -> Result<(), Chip8Error> {
+        let data: Vec<u8> = (self.start..=self.end).map(|addr| mem.bytes()[addr as usize % mem.size()]).collect();
+        storage.save(&key(mem.rom()), &data)
+    }
+}
+
+fn key(rom: &[u8]) -> String {
+    format!("ram/{:x}", Sha1::digest(rom))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::storage::MemoryStorage;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(SaveRam::parse("0x2F0-0x2FF").unwrap(), SaveRam { start: 0x2F0, end: 0x2FF });
+        assert_eq!(SaveRam::parse("300").unwrap(), SaveRam { start: 0x300, end: 0x300 });
+        assert!(SaveRam::parse("0x2FF-0x2F0").is_err());
+        assert!(SaveRam::parse("scores").is_err());
+    }
+
+    #[test]
+    fn test_save_and_restore() {
+        let mut storage = MemoryStorage::new();
+        let save_ram = SaveRam::parse("0x300-0x301").unwrap();
+        let mut mem = Memory::from_bytes(&[0x12, 0x00]).unwrap();
+        save_ram.restore(&mut mem, &storage).unwrap(); // Nothing saved yet
+        assert_eq!(mem.read_byte(0x300), 0);
+        mem.write_byte(0x300, 0x12);
+        mem.write_byte(0x301, 0x34);
+        mem.write_byte(0x302, 0x56);
+        save_ram.save(&mem, &mut storage).unwrap();
+
+        let mut mem = Memory::from_bytes(&[0x12, 0x00]).unwrap();
+        save_ram.restore(&mut mem, &storage).unwrap();
+        assert_eq!([mem.read_byte(0x300), mem.read_byte(0x301), mem.read_byte(0x302)], [0x12, 0x34, 0x00]);
+
+        // Another ROM has its own
+        let mut mem = Memory::from_bytes(&[0x12, 0x02]).unwrap();
+        save_ram.restore(&mut mem, &storage).unwrap();
+        assert_eq!(mem.read_byte(0x300), 0);
+    }
+}
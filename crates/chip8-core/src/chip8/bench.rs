@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+use super::{Chip8, Memory, errors::Chip8Error, conformance::MATRIX};
+
+// Executions of the opcodes matching a pattern of the conformance matrix, e.g. 8xy4
+pub struct OpcodeTiming {
+    pub opcode: &'static str, // "????" for opcodes no platform knows
+    pub count: u64,
+    pub time: Duration,
+}
+
+impl OpcodeTiming {
+    pub fn average(&self) -> Duration {
+        Duration::from_nanos((self.time.as_nanos() / self.count.max(1) as u128) as u64)
+    }
+}
+
+// Result of running a program as fast as possible
+pub struct BenchReport {
+    pub instructions: u64,
+    pub wall_time: Duration,
+    pub opcodes: Vec<OpcodeTiming>, // Slowest in total first
+}
+
+impl BenchReport {
+    // Instructions executed per second of wall time
+    pub fn ips(&self) -> f64 {
+        self.instructions as f64 / self.wall_time.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Instructions: {}", self.instructions)?;
+        writeln!(f, "Wall time: {:.3}s", self.wall_time.as_secs_f64())?;
+        writeln!(f, "Instructions per second: {:.0}", self.ips())?;
+        write!(f, "opcode\tcount\ttotal\taverage")?;
+        for timing in &self.opcodes {
+            write!(f, "\n{}\t{}\t{:.3}ms\t{}ns", timing.opcode, timing.count, timing.time.as_secs_f64() * 1000.0, timing.average().as_nanos())?;
+        }
+        Ok(())
+    }
+}
+
+// Runs the program without a window for the given number of instructions and no delays,
+// ending a frame every tickrate instructions like testing::run_headless.
+// Every instruction is timed, so the wall time includes reading the clock.
+pub fn run(chip8: &mut Chip8, mem: &mut Memory, cycles: u64) -> Result<BenchReport, Chip8Error> {
+    chip8.latch_input()?;
+    chip8.patches.apply(mem);
+    let mut timings: HashMap<u16, (u64, Duration)> = HashMap::new();
+    let mut executed = 0;
+
+    let start = Instant::now();
+    let mut last = start;
+    for _ in 0..cycles {
+        let opcode = mem.get_instruction(chip8.pc);
+        chip8.step(mem)?;
+        let now = Instant::now();
+        let timing = timings.entry(opcode).or_default();
+        timing.0 += 1;
+        timing.1 += now - last;
+        last = now;
+
+        executed += 1;
+        if executed >= chip8.get_tickrate() {
+            chip8.end_frame(mem)?;
+            executed = 0;
+            last = Instant::now(); // Frames aren't counted towards the instruction before them
+        }
+    }
+    let wall_time = start.elapsed();
+
+    let mut grouped: HashMap<&'static str, OpcodeTiming> = HashMap::new();
+    for (opcode, (count, time)) in timings {
+        let pattern = pattern(opcode).unwrap_or("????");
+        let timing = grouped.entry(pattern).or_insert(OpcodeTiming { opcode: pattern, count: 0, time: Duration::ZERO });
+        timing.count += count;
+        timing.time += time;
+    }
+    let mut opcodes: Vec<OpcodeTiming> = grouped.into_values().collect();
+    opcodes.sort_by(|a, b| b.time.cmp(&a.time).then(a.opcode.cmp(b.opcode)));
+    Ok(BenchReport { instructions: cycles, wall_time, opcodes })
+}
+
+// Most specific pattern of the conformance matrix matching the opcode, lowercase letters match any digit
+pub fn pattern(opcode: u16) -> Option<&'static str> {
+    MATRIX.iter()
+        .map(|entry| entry.opcode)
+        .filter(|pattern| pattern.chars().enumerate().all(|(i, c)| {
+            let digit = (opcode >> (12 - 4 * i)) & 0xf;
+            c.is_ascii_lowercase() || c.to_digit(16) == Some(digit as u32)
+        }))
+        .min_by_key(|pattern| pattern.chars().filter(char::is_ascii_lowercase).count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::PROGRAM_START;
+
+    #[test]
+    fn test_pattern() {
+        assert_eq!(pattern(0x00E0), Some("00E0"));
+        assert_eq!(pattern(0x0A23), Some("0nnn"));
+        assert_eq!(pattern(0x0123), Some("01nn"));
+        assert_eq!(pattern(0x8124), Some("8xy4"));
+        assert_eq!(pattern(0xF233), Some("Fx33"));
+        assert_eq!(pattern(0xD120), Some("Dxy0"));
+        assert_eq!(pattern(0xD125), Some("Dxyn"));
+        assert_eq!(pattern(0x8128), None);
+    }
+
+    #[test]
+    fn test_run() {
+        // Counts up V0 in a loop
+        let mut mem = Memory::new();
+        for (i, byte) in [0x70, 0x01, 0x12, 0x00].into_iter().enumerate() {
+            mem.write_byte(PROGRAM_START + i as u16, byte);
+        }
+        let mut chip8 = Chip8::new();
+        let report = run(&mut chip8, &mut mem, 1000).unwrap();
+        assert_eq!(report.instructions, 1000);
+        assert_eq!(chip8.v[0], 244); // 500 additions, wrapping at 256
+        let counts: Vec<_> = report.opcodes.iter().map(|timing| (timing.opcode, timing.count)).collect();
+        assert_eq!(counts.len(), 2);
+        assert!(counts.contains(&("7xkk", 500)) && counts.contains(&("1nnn", 500)));
+        assert!(report.to_string().starts_with("Instructions: 1000\n"));
+    }
+}
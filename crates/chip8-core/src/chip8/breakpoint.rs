@@ -0,0 +1,329 @@
+use std::fmt;
+use std::iter::Peekable;
+use super::errors::{Chip8Error, LoadError};
+use super::debugger::Registers;
+use super::number::number;
+use super::opcode::OpCode;
+
+// Where execution stops, before the instruction runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Location {
+    Address(u16),
+    Draw, // Any Dxyn
+    Sound, // Any instruction setting the sound timer or XO-CHIP audio
+    Key, // Any instruction reading the keyboard
+    Unknown, // Any opcode no platform has
+    SelfModify, // After any instruction writing to the code executed so far
+}
+
+// Value a condition compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    V(usize),
+    I,
+    Pc,
+    Sp,
+    Dt,
+    St,
+    Value(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Compare(Operand, Comparison, Operand),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+// Pauses the emulator at an address or on every instruction of a kind, optionally only when a
+// condition holds, e.g. "0x2F0 if V3 == 0x1F", "draw if VF != 0" or "first key"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    location: Location,
+    condition: Option<Condition>,
+    once: bool, // Removed after the first hit
+    text: String, // As given, for reporting hits
+}
+
+impl Breakpoint {
+    // Parses [first] LOCATION [if CONDITION], where the location is an address, "draw", "sound",
+    // "key", "unknown" or "smc", the latter stopping after the instruction writing to the code.
+    // Conditions compare registers (V0-VF, I, PC, SP, DT, ST) and numbers with
+    // ==, !=, <, <=, > and >=, joined with && and ||. A leading "break" is allowed.
+    // Breakpoints starting with "first" only stop once.
+    pub fn parse(spec: &str) -> Result<Breakpoint, Chip8Error> {
+        let invalid = |reason: &str| LoadError::InvalidArgument(format!("invalid breakpoint {spec}: {reason}"));
+        let tokens = tokenize(spec).ok_or_else(|| invalid("unexpected character"))?;
+        let mut tokens = tokens.iter().map(String::as_str).peekable();
+        if tokens.peek().is_some_and(|token| token.eq_ignore_ascii_case("break")) {
+            tokens.next();
+        }
+        let once = tokens.next_if(|token| token.eq_ignore_ascii_case("first")).is_some();
+
+        let expected = "expected an address, draw, sound, key, unknown or smc";
+        let location = match tokens.next().map(str::to_ascii_lowercase).as_deref() {
+            Some("draw") => Location::Draw,
+            Some("sound") => Location::Sound,
+            Some("key") => Location::Key,
+            Some("unknown") => Location::Unknown,
+            Some("smc") => Location::SelfModify,
+            Some(token) => Location::Address(value(token).ok_or_else(|| invalid(expected))?),
+            None => return Err(invalid(expected).into()),
+        };
+        let condition = match tokens.next() {
+            Some(token) if token.eq_ignore_ascii_case("if") => Some(parse_or(&mut tokens).ok_or_else(|| invalid("malformed condition"))?),
+            Some(_) => return Err(invalid("expected if").into()),
+            None => None,
+        };
+        if tokens.next().is_some() {
+            return Err(invalid("unexpected text after the condition").into());
+        }
+
+        let text = spec.trim().strip_prefix("break ").unwrap_or(spec.trim()).to_string();
+        Ok(Breakpoint { location, condition, once, text })
+    }
+
+    // Stops at the address every time
+    pub fn at(addr: u16) -> Breakpoint {
+        Breakpoint { location: Location::Address(addr), condition: None, once: false, text: format!("{addr:#05X}") }
+    }
+
+    // Whether the breakpoint goes away once hit
+    pub(super) fn is_once(&self) -> bool {
+        self.once
+    }
+
+    // Checks if execution should stop before the given instruction runs
+    pub(super) fn hits(&self, opcode: u16, registers: &Registers) -> bool {
+        let opcode = OpCode::decode(opcode);
+        let here = match self.location {
+            Location::Address(addr) => addr == registers.pc,
+            Location::Draw => opcode.is_draw(),
+            Location::Sound => opcode.is_sound(),
+            Location::Key => opcode.is_key_read(),
+            Location::Unknown => !opcode.is_known(),
+            Location::SelfModify => false,
+        };
+        here && self.condition.as_ref().is_none_or(|condition| condition.holds(registers))
+    }
+
+    // Checks if execution should stop after an instruction modified the program
+    pub(super) fn hits_self_modify(&self, registers: &Registers) -> bool {
+        self.location == Location::SelfModify && self.condition.as_ref().is_none_or(|condition| condition.holds(registers))
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl Condition {
+    fn holds(&self, registers: &Registers) -> bool {
+        match self {
+            Condition::Compare(lhs, comparison, rhs) => {
+                let (lhs, rhs) = (lhs.value(registers), rhs.value(registers));
+                match comparison {
+                    Comparison::Eq => lhs == rhs,
+                    Comparison::Ne => lhs != rhs,
+                    Comparison::Lt => lhs < rhs,
+                    Comparison::Le => lhs <= rhs,
+                    Comparison::Gt => lhs > rhs,
+                    Comparison::Ge => lhs >= rhs,
+                }
+            }
+            Condition::And(lhs, rhs) => lhs.holds(registers) && rhs.holds(registers),
+            Condition::Or(lhs, rhs) => lhs.holds(registers) || rhs.holds(registers),
+        }
+    }
+}
+
+impl Operand {
+    fn parse(token: &str) -> Option<Operand> {
+        let operand = match token.to_ascii_uppercase().as_str() {
+            "I" => Operand::I,
+            "PC" => Operand::Pc,
+            "SP" => Operand::Sp,
+            "DT" => Operand::Dt,
+            "ST" => Operand::St,
+            register if register.len() == 2 && register.starts_with('V') => {
+                Operand::V(usize::from_str_radix(&register[1..], 16).ok()?)
+            }
+            _ => Operand::Value(value(token)?),
+        };
+        Some(operand)
+    }
+
+    fn value(&self, registers: &Registers) -> u16 {
+        match *self {
+            Operand::V(n) => registers.v[n] as u16,
+            Operand::I => registers.idx,
+            Operand::Pc => registers.pc,
+            Operand::Sp => registers.sp as u16,
+            Operand::Dt => registers.dt as u16,
+            Operand::St => registers.st as u16,
+            Operand::Value(value) => value,
+        }
+    }
+}
+
+// condition || condition, binding looser than &&
+fn parse_or<'a>(tokens: &mut Peekable<impl Iterator<Item = &'a str>>) -> Option<Condition> {
+    let mut condition = parse_and(tokens)?;
+    while tokens.next_if_eq(&"||").is_some() {
+        condition = Condition::Or(Box::new(condition), Box::new(parse_and(tokens)?));
+    }
+    Some(condition)
+}
+
+fn parse_and<'a>(tokens: &mut Peekable<impl Iterator<Item = &'a str>>) -> Option<Condition> {
+    let mut condition = parse_comparison(tokens)?;
+    while tokens.next_if_eq(&"&&").is_some() {
+        condition = Condition::And(Box::new(condition), Box::new(parse_comparison(tokens)?));
+    }
+    Some(condition)
+}
+
+fn parse_comparison<'a>(tokens: &mut Peekable<impl Iterator<Item = &'a str>>) -> Option<Condition> {
+    let lhs = Operand::parse(tokens.next()?)?;
+    let comparison = match tokens.next()? {
+        "==" => Comparison::Eq,
+        "!=" => Comparison::Ne,
+        "<" => Comparison::Lt,
+        "<=" => Comparison::Le,
+        ">" => Comparison::Gt,
+        ">=" => Comparison::Ge,
+        _ => return None,
+    };
+    let rhs = Operand::parse(tokens.next()?)?;
+    Some(Condition::Compare(lhs, comparison, rhs))
+}
+
+// Splits into words and operators, so spaces around operators are optional
+fn tokenize(text: &str) -> Option<Vec<String>> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                word.push(c);
+            }
+            tokens.push(word);
+        } else if "=!<>&|".contains(c) {
+            let mut operator = String::new();
+            while let Some(c) = chars.next_if(|c| "=!<>&|".contains(*c)) {
+                operator.push(c);
+            }
+            tokens.push(operator);
+        } else {
+            return None;
+        }
+    }
+    Some(tokens)
+}
+
+// Parses a 16-bit number, underscores are allowed as separators
+fn value(token: &str) -> Option<u16> {
+    number(&token.replace('_', "")).and_then(|value| u16::try_from(value).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{NUM_REGISTERS, STACK_DEPTH};
+
+    fn registers(pc: u16) -> Registers {
+        Registers { v: [0; NUM_REGISTERS], idx: 0, pc, sp: 0, dt: 0, st: 0, calls: [0; STACK_DEPTH] }
+    }
+
+    #[test]
+    fn test_address() {
+        let breakpoint = Breakpoint::parse("0x2F0").unwrap();
+        assert!(breakpoint.hits(0x00E0, &registers(0x2F0)));
+        assert!(!breakpoint.hits(0x00E0, &registers(0x2F2)));
+    }
+
+    #[test]
+    fn test_at() {
+        let breakpoint = Breakpoint::at(0x2F0);
+        assert_eq!(breakpoint, Breakpoint::parse("0x2F0").unwrap());
+        assert!(breakpoint.hits(0x00E0, &registers(0x2F0)));
+    }
+
+    #[test]
+    fn test_condition() {
+        let breakpoint = Breakpoint::parse("break 0x2F0 if V3 == 0x1F").unwrap();
+        let mut state = registers(0x2F0);
+        assert!(!breakpoint.hits(0x00E0, &state));
+        state.v[3] = 0x1F;
+        assert!(breakpoint.hits(0x00E0, &state));
+        assert_eq!(breakpoint.to_string(), "0x2F0 if V3 == 0x1F");
+    }
+
+    #[test]
+    fn test_draw() {
+        let breakpoint = Breakpoint::parse("draw if VF!=0 || I>=0x300 && DT < 5").unwrap();
+        let mut state = registers(0x200);
+        assert!(!breakpoint.hits(0xD015, &state));
+        state.idx = 0x300;
+        assert!(breakpoint.hits(0xD015, &state)); // && binds tighter than ||
+        assert!(!breakpoint.hits(0x00E0, &state));
+        state.dt = 5;
+        assert!(!breakpoint.hits(0xD015, &state));
+        state.v[0xF] = 1;
+        assert!(breakpoint.hits(0xD015, &state));
+    }
+
+    #[test]
+    fn test_kinds() {
+        let state = registers(0x200);
+        assert!(Breakpoint::parse("sound").unwrap().hits(0xF318, &state));
+        assert!(!Breakpoint::parse("sound").unwrap().hits(0xF315, &state));
+        assert!(Breakpoint::parse("KEY").unwrap().hits(0xF00A, &state));
+        assert!(Breakpoint::parse("unknown").unwrap().hits(0x8AB8, &state));
+        assert!(!Breakpoint::parse("unknown").unwrap().hits(0x8AB4, &state));
+
+        let breakpoint = Breakpoint::parse("break first draw if V0 == 0").unwrap();
+        assert!(breakpoint.is_once() && breakpoint.hits(0xD015, &state));
+        assert_eq!(breakpoint.to_string(), "first draw if V0 == 0");
+        assert!(!Breakpoint::parse("draw").unwrap().is_once());
+        assert!(Breakpoint::parse("first").is_err());
+    }
+
+    #[test]
+    fn test_self_modify() {
+        let breakpoint = Breakpoint::parse("smc if V0 == 1").unwrap();
+        let mut state = registers(0x200);
+        assert!(!breakpoint.hits(0xF055, &state)); // Only checked after the write
+        assert!(!breakpoint.hits_self_modify(&state));
+        state.v[0] = 1;
+        assert!(breakpoint.hits_self_modify(&state));
+        assert!(!Breakpoint::parse("draw").unwrap().hits_self_modify(&state));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Breakpoint::parse("").is_err());
+        assert!(Breakpoint::parse("somewhere").is_err());
+        assert!(Breakpoint::parse("0x200 when V0 == 1").is_err());
+        assert!(Breakpoint::parse("0x200 if V0 ==").is_err());
+        assert!(Breakpoint::parse("0x200 if VG == 1").is_err());
+        assert!(Breakpoint::parse("0x200 if V0 = 1").is_err());
+        assert!(Breakpoint::parse("0x200 if V0 == 1 V1").is_err());
+        assert!(Breakpoint::parse("0x200 if V0 == $1").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use super::{DISPLAY_WIDTH, errors::{Chip8Error, LoadError}};
+use super::text::{draw_text, fill_rect, CHAR_WIDTH, LINE_HEIGHT};
+
+// Extensions of the files listed as programs, Octo source is assembled when picked
+const ROM_EXTENSIONS: [&str; 3] = ["ch8", "c8", "8o"];
+
+// Programs listed below the heading at once
+const VISIBLE_ROMS: usize = 4;
+
+// Characters of a name that fit next to the cursor
+const NAME_LENGTH: usize = (DISPLAY_WIDTH - 5) / CHAR_WIDTH;
+
+// List of the programs in a directory to pick one from, shown instead of the screen
+pub struct RomBrowser {
+    roms: Vec<PathBuf>, // Sorted by file name
+    selected: usize,
+}
+
+impl RomBrowser {
+    // Lists the programs in a directory, without descending into subdirectories
+    pub fn scan(dir: impl AsRef<Path>) -> Result<RomBrowser, Chip8Error> {
+        let dir = dir.as_ref();
+        let entries = fs::read_dir(dir).map_err(|source| LoadError::FileRead { path: dir.to_path_buf(), source })?;
+        let mut roms: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ROM_EXTENSIONS.iter().any(|rom| ext.eq_ignore_ascii_case(rom))))
+            .collect();
+        roms.sort_by_key(|path| path.file_name().map(|name| name.to_ascii_lowercase()));
+        Ok(RomBrowser { roms, selected: 0 })
+    }
+
+    pub fn roms(&self) -> &[PathBuf] {
+        &self.roms
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roms.is_empty()
+    }
+
+    pub fn selected(&self) -> Option<&Path> {
+        self.roms.get(self.selected).map(PathBuf::as_path)
+    }
+
+    // Moves the selection up or down, wrapping around
+    pub fn navigate(&mut self, down: bool) {
+        let len = self.roms.len().max(1);
+        self.selected = if down { (self.selected + 1) % len } else { (self.selected + len - 1) % len };
+    }
+
+    // Draws the heading with the position in the list and the names around the selection
+    pub fn render(&self, buffer: &mut [u32], filled: u32, empty: u32) {
+        fill_rect(buffer, DISPLAY_WIDTH, 0, 0, DISPLAY_WIDTH, buffer.len() / DISPLAY_WIDTH, empty);
+        draw_text(buffer, DISPLAY_WIDTH, 1, 1, &format!("ROMS {}/{}", self.selected + 1, self.roms.len()), filled);
+
+        let first = self.selected.saturating_sub(VISIBLE_ROMS - 1);
+        for (i, path) in self.roms.iter().enumerate().skip(first).take(VISIBLE_ROMS) {
+            let y = 1 + (i - first + 1) * LINE_HEIGHT;
+            if i == self.selected {
+                draw_text(buffer, DISPLAY_WIDTH, 1, y, ">", filled);
+            }
+            let name = path.file_stem().map(|name| name.to_string_lossy()).unwrap_or_default();
+            draw_text(buffer, DISPLAY_WIDTH, 5, y, &name.chars().take(NAME_LENGTH).collect::<String>(), filled);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::DISPLAY_HEIGHT;
+
+    #[test]
+    fn test_scan() {
+        let dir = std::env::temp_dir().join("chip8_test_browser");
+        fs::create_dir_all(dir.join("nested.ch8")).unwrap();
+        for file in ["pong.ch8", "Brix.C8", "notes.txt"] {
+            fs::write(dir.join(file), [0x00, 0xE0]).unwrap();
+        }
+        let browser = RomBrowser::scan(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let browser = browser.unwrap();
+        assert_eq!(browser.roms(), &[dir.join("Brix.C8"), dir.join("pong.ch8")]);
+        assert!(matches!(RomBrowser::scan(&dir), Err(Chip8Error::Load(LoadError::FileRead { .. }))));
+    }
+
+    #[test]
+    fn test_navigate() {
+        let mut browser = RomBrowser { roms: vec!["a.ch8".into(), "b.ch8".into()], selected: 0 };
+        browser.navigate(false);
+        assert_eq!(browser.selected(), Some(Path::new("b.ch8")));
+        browser.navigate(true);
+        assert_eq!(browser.selected(), Some(Path::new("a.ch8")));
+
+        let mut empty = RomBrowser { roms: Vec::new(), selected: 0 };
+        empty.navigate(true);
+        assert_eq!(empty.selected(), None);
+    }
+
+    #[test]
+    fn test_render_scrolls() {
+        let roms = (0..6).map(|n| PathBuf::from(format!("{n}.ch8"))).collect();
+        let mut browser = RomBrowser { roms, selected: 0 };
+        for _ in 0..5 {
+            browser.navigate(true);
+        }
+        let mut buffer = vec![0xffffff; DISPLAY_WIDTH * DISPLAY_HEIGHT];
+        browser.render(&mut buffer, 0xffffff, 0x000000);
+        assert_eq!(buffer[0], 0x000000); // Cleared screen
+        assert_eq!(buffer[1 + 25 * DISPLAY_WIDTH], 0xffffff); // Cursor on the last visible line
+        assert_eq!(buffer[5 + 7 * DISPLAY_WIDTH], 0xffffff); // Top of the "2", the first visible name
+    }
+}
@@ -0,0 +1,308 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use super::{Chip8, Memory, DEFAULT_TICKRATE, MEMORY_SIZE, PROGRAM_START, errors::{Chip8Error, LoadError}};
+use super::audio::AudioSettings;
+use super::backend::Backend;
+use super::filter::Filter;
+use super::frontend::Frontend;
+use super::icon::WindowIcon;
+use super::indicator::SoundIndicator;
+use super::memory::{FONT, Protection};
+use super::palette::Palette;
+use super::patches::Patches;
+use super::profile::Profile;
+use super::quirks::Quirks;
+use super::scheduler::LagPolicy;
+use super::storage::Storage;
+
+// Instructions per second at the default tick rate
+const DEFAULT_SPEED: u32 = DEFAULT_TICKRATE * 60;
+
+// Program to load
+enum Rom {
+    Bytes(Vec<u8>),
+    File(PathBuf),
+}
+
+// Configures an emulator and the memory it runs, checked once when built, e.g.
+// Chip8::builder().rom(bytes).palette(p).quirks(q).speed(540).headless().build()?
+pub struct Chip8Builder {
+    rom: Option<Rom>,
+    palette: Option<Palette>,
+    quirks: Quirks,
+    speed: u32, // Instructions per second
+    lag_policy: LagPolicy,
+    font: [u8; 80],
+    memory_size: usize,
+    program_start: u16,
+    protection: Protection,
+    phosphor: bool,
+    filter: Filter,
+    sound_indicator: SoundIndicator,
+    audio: AudioSettings,
+    patches: Patches,
+    storage: Option<Box<dyn Storage>>,
+    frontend: Option<Arc<dyn Frontend>>,
+    icon: Option<WindowIcon>,
+    app_name: Option<String>,
+    backend: Backend,
+    headless: bool,
+    predecode: bool,
+}
+
+impl Chip8Builder {
+    pub fn new() -> Self {
+        Chip8Builder {
+            rom: None,
+            palette: None,
+            quirks: Quirks::default(),
+            speed: DEFAULT_SPEED,
+            lag_policy: LagPolicy::default(),
+            font: FONT,
+            memory_size: MEMORY_SIZE,
+            program_start: PROGRAM_START,
+            protection: Protection::Off,
+            phosphor: false,
+            filter: Filter::None,
+            sound_indicator: SoundIndicator::None,
+            audio: AudioSettings::default(),
+            patches: Patches::new(),
+            storage: None,
+            frontend: None,
+            icon: None,
+            app_name: None,
+            backend: Backend::default(),
+            headless: false,
+            predecode: false,
+        }
+    }
+
+    // Program to run, memory is left empty without one
+    pub fn rom(mut self, rom: impl Into<Vec<u8>>) -> Self {
+        self.rom = Some(Rom::Bytes(rom.into()));
+        self
+    }
+
+    // Program to run, read from a file when built
+    pub fn rom_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.rom = Some(Rom::File(path.into()));
+        self
+    }
+
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    pub fn quirks(mut self, quirks: Quirks) -> Self {
+        self.quirks = quirks;
+        self
+    }
+
+    // Quirks, speed and memory layout of the platform the program was written for
+    pub fn profile(self, profile: Profile) -> Self {
+        self.quirks(profile.quirks).speed(profile.tickrate * 60).memory_size(profile.memory_size).program_start(profile.program_start)
+    }
+
+    // Instructions executed per second, a multiple of the 60hz frame rate
+    pub fn speed(mut self, speed: u32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    // What to do about frames missed while running behind real time
+    pub fn lag_policy(mut self, policy: LagPolicy) -> Self {
+        self.lag_policy = policy;
+        self
+    }
+
+    pub fn font(mut self, font: [u8; 80]) -> Self {
+        self.font = font;
+        self
+    }
+
+    // Bytes of memory, see Memory::with_layout
+    pub fn memory_size(mut self, size: usize) -> Self {
+        self.memory_size = size;
+        self
+    }
+
+    // Where the program is loaded and starts running
+    pub fn program_start(mut self, addr: u16) -> Self {
+        self.program_start = addr;
+        self
+    }
+
+    // What to do about writes below the program start, see Memory::set_protection
+    pub fn protection(mut self, protection: Protection) -> Self {
+        self.protection = protection;
+        self
+    }
+
+    pub fn phosphor(mut self, enabled: bool) -> Self {
+        self.phosphor = enabled;
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn sound_indicator(mut self, indicator: SoundIndicator) -> Self {
+        self.sound_indicator = indicator;
+        self
+    }
+
+    // Sound of the beep, the device is opened with Chip8::enable
+    pub fn audio(mut self, settings: AudioSettings) -> Self {
+        self.audio = settings;
+        self
+    }
+
+    pub fn patches(mut self, patches: Patches) -> Self {
+        self.patches = patches;
+        self
+    }
+
+    pub fn storage(mut self, storage: impl Storage + 'static) -> Self {
+        self.storage = Some(Box::new(storage));
+        self
+    }
+
+    // Opens the windows and the audio device, see Chip8::set_frontend
+    pub fn frontend(mut self, frontend: impl Frontend + 'static) -> Self {
+        self.frontend = Some(Arc::new(frontend));
+        self
+    }
+
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    // Window icon replacing the bundled one
+    pub fn icon(mut self, icon: WindowIcon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    // Application the window belongs to, chip8 unless set, see Chip8::set_app_name
+    pub fn app_name(mut self, name: impl Into<String>) -> Self {
+        self.app_name = Some(name.into());
+        self
+    }
+
+    // Runs without a window or sound, through testing::run_headless
+    pub fn headless(mut self) -> Self {
+        self.headless = true;
+        self
+    }
+
+    // Decodes the program up front instead of on every fetch, see Memory::set_predecoding
+    pub fn predecode(mut self, enabled: bool) -> Self {
+        self.predecode = enabled;
+        self
+    }
+
+    // Checks the configuration and creates the emulator along with the memory holding the program
+    pub fn build(self) -> Result<(Chip8, Memory), Chip8Error> {
+        if self.speed == 0 || !self.speed.is_multiple_of(60) {
+            return Err(LoadError::InvalidArgument(format!("speed must be a positive multiple of 60 instructions per second, got {}", self.speed)).into());
+        }
+        if self.headless && (self.phosphor || self.filter != Filter::None) {
+            return Err(LoadError::InvalidArgument("phosphor and filters need a window, but the emulator is headless".to_string()).into());
+        }
+        self.audio.validate()?;
+
+        let mut mem = Memory::with_layout(self.memory_size, self.program_start)?;
+        match self.rom {
+            Some(Rom::Bytes(rom)) => mem.load_from_bytes(&rom)?,
+            Some(Rom::File(path)) => mem.load_from_file(path)?,
+            None => (),
+        }
+        mem.load_font(&self.font)?;
+        mem.set_predecoding(self.predecode);
+        mem.set_protection(self.protection);
+
+        let mut chip8 = Chip8::new();
+        chip8.pc = mem.program_start();
+        chip8.headless = self.headless;
+        if let Some(palette) = self.palette {
+            chip8.set_palette(palette);
+        }
+        chip8.set_quirks(self.quirks);
+        chip8.set_tickrate(self.speed / 60);
+        chip8.set_lag_policy(self.lag_policy);
+        chip8.set_phosphor(self.phosphor);
+        chip8.set_filter(self.filter);
+        chip8.set_sound_indicator(self.sound_indicator);
+        chip8.set_audio(self.audio);
+        chip8.set_patches(self.patches);
+        if let Some(storage) = self.storage {
+            chip8.storage = storage;
+        }
+        if let Some(frontend) = self.frontend {
+            chip8.display.set_frontend(frontend);
+        }
+        chip8.set_backend(self.backend);
+        if let Some(icon) = self.icon {
+            chip8.set_window_icon(icon);
+        }
+        if let Some(name) = self.app_name {
+            chip8.set_app_name(&name);
+        }
+        Ok((chip8, mem))
+    }
+}
+
+impl Default for Chip8Builder {
+    fn default() -> Self {
+        Chip8Builder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{PROGRAM_START, testing};
+
+    #[test]
+    fn test_build() {
+        let quirks = Quirks { wrap: false, ..Quirks::default() };
+        let (mut chip8, mut mem) = Chip8Builder::new().rom([0x60, 0x2A]).quirks(quirks).speed(540).headless().predecode(true).build().unwrap();
+        assert_eq!(chip8.get_tickrate(), 9);
+        assert!(mem.is_predecoding());
+        assert_eq!(chip8.get_quirks(), quirks);
+        assert_eq!(mem.get_instruction(PROGRAM_START), 0x602A);
+
+        testing::run_headless(&mut chip8, &mut mem, 1).unwrap();
+        assert_eq!(chip8.v[0], 0x2A);
+        assert!(chip8.run(&mut mem).is_err()); // No window to run in
+    }
+
+    #[test]
+    fn test_build_profile() {
+        let profile = Profile::parse("chip48").unwrap();
+        let (chip8, _) = Chip8Builder::new().profile(profile).headless().build().unwrap();
+        assert_eq!(chip8.get_quirks(), profile.quirks);
+        assert_eq!(chip8.get_tickrate(), profile.tickrate);
+
+        let profile = Profile::parse("eti660").unwrap();
+        let (mut chip8, mut mem) = Chip8Builder::new().rom([0x60, 0x2A]).profile(profile).headless().build().unwrap();
+        assert_eq!(chip8.pc, 0x600);
+        testing::run_headless(&mut chip8, &mut mem, 1).unwrap();
+        assert_eq!(chip8.v[0], 0x2A);
+    }
+
+    #[test]
+    fn test_build_invalid() {
+        assert!(Chip8Builder::new().speed(0).build().is_err());
+        assert!(Chip8Builder::new().speed(100).build().is_err());
+        assert!(Chip8Builder::new().filter(Filter::Crt).headless().build().is_err());
+        assert!(Chip8Builder::new().rom(vec![0; 4096]).build().is_err());
+        assert!(Chip8Builder::new().memory_size(1000).build().is_err());
+        assert!(Chip8Builder::new().font([0; 80]).build().is_err());
+        assert!(Chip8Builder::new().audio(AudioSettings { volume: 101, ..AudioSettings::default() }).build().is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+// Optional features a frontend may or may not be able to provide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    Audio,
+    Fullscreen,
+    Gamepad,
+    Clipboard,
+    Vsync,
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Feature::Audio => write!(f, "audio"),
+            Feature::Fullscreen => write!(f, "fullscreen"),
+            Feature::Gamepad => write!(f, "gamepad input"),
+            Feature::Clipboard => write!(f, "clipboard"),
+            Feature::Vsync => write!(f, "vsync"),
+        }
+    }
+}
+
+// Features supported by the backends the emulator is running with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    pub audio: Result<(), String>, // Why audio is unavailable, if it is
+    pub fullscreen: bool,
+    pub gamepad: bool,
+    pub clipboard: bool,
+    pub vsync: bool, // Presenting a frame waits for the monitor's refresh
+}
+
+impl Capabilities {
+    pub fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Audio => self.audio.is_ok(),
+            Feature::Fullscreen => self.fullscreen,
+            Feature::Gamepad => self.gamepad,
+            Feature::Clipboard => self.clipboard,
+            Feature::Vsync => self.vsync,
+        }
+    }
+
+    // Explains how the emulator carries on without the feature, None if it is supported
+    pub fn fallback(&self, feature: Feature) -> Option<String> {
+        if self.supports(feature) {
+            return None;
+        }
+        let fallback = match feature {
+            Feature::Audio => format!("running without sound ({})", self.audio.as_ref().unwrap_err()),
+            Feature::Fullscreen => "using a borderless window covering the screen instead".to_string(),
+            Feature::Gamepad => "use the keyboard instead".to_string(),
+            Feature::Clipboard => "ignoring clipboard actions".to_string(),
+            Feature::Vsync => "pacing frames with the 60hz clock instead".to_string(),
+        };
+        Some(format!("{feature} is not available, {fallback}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback() {
+        let capabilities = Capabilities { audio: Err("no device".to_string()), fullscreen: false, gamepad: false, clipboard: true, vsync: false };
+        assert_eq!(capabilities.fallback(Feature::Audio).unwrap(), "audio is not available, running without sound (no device)");
+        assert!(capabilities.fallback(Feature::Fullscreen).is_some());
+        assert_eq!(capabilities.fallback(Feature::Clipboard), None);
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+use super::{bench::pattern, conformance::MATRIX, profile::Profile, rom::{Platform, RomInfo}};
+
+// Instructions after an Fx55 or Fx65 looked at for uses of I
+const MEMORY_QUIRK_WINDOW: usize = 8;
+
+// Whether a ROM is expected to run, worked out from its opcodes without running it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Compatible,
+    DependsOnQuirks, // Runs, but only behaves as intended with the right quirks
+    Incompatible, // Uses opcodes the emulator doesn't know or implement
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Compatible => write!(f, "compatible"),
+            Verdict::DependsOnQuirks => write!(f, "compatible with the right quirks"),
+            Verdict::Incompatible => write!(f, "incompatible"),
+        }
+    }
+}
+
+// Quirk the program behaves differently with, and the first instruction showing it
+pub struct QuirkHint {
+    pub quirk: &'static str, // As used by the ROM database
+    pub addr: u16,
+    pub opcode: u16,
+}
+
+// Compatibility of a ROM with this emulator
+pub struct CompatReport {
+    pub platform: Platform,
+    pub unsupported: Vec<(u16, u16)>, // Reachable opcodes of extensions the emulator lacks, as address and opcode
+    pub unknown: Vec<(u16, u16)>, // Reachable opcodes no platform knows
+    pub quirks: Vec<QuirkHint>,
+    pub profile: Option<Profile>, // Platform to run it with, if not plain CHIP-8
+}
+
+impl CompatReport {
+    // Checks the ROM as loaded at the program start, which the platform decides
+    pub fn check(rom: &[u8], program_start: u16) -> Self {
+        let info = RomInfo::analyze(rom, program_start);
+        let unsupported = info.opcodes.iter()
+            .filter(|&&(_, op)| pattern(op).is_some_and(|pattern| !is_supported(pattern)))
+            .copied()
+            .collect();
+        let profile = match info.platform {
+            Platform::SuperChip => Profile::parse("schip"),
+            Platform::XoChip => Profile::parse("xochip"),
+            Platform::Chip8 | Platform::MegaChip => None,
+        };
+
+        CompatReport {
+            platform: info.platform,
+            unsupported,
+            quirks: quirk_hints(&info.opcodes),
+            unknown: info.unknown,
+            profile,
+        }
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        if !self.unsupported.is_empty() || !self.unknown.is_empty() {
+            Verdict::Incompatible
+        } else if !self.quirks.is_empty() {
+            Verdict::DependsOnQuirks
+        } else {
+            Verdict::Compatible
+        }
+    }
+}
+
+impl fmt::Display for CompatReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Platform: {}", self.platform)?;
+        for (addr, op) in &self.unsupported {
+            writeln!(f, "Unsupported: {op:04X} at {addr:#05X} ({} extension)", pattern(*op).and_then(platform).unwrap_or(self.platform))?;
+        }
+        for (addr, op) in &self.unknown {
+            writeln!(f, "Unknown: {op:04X} at {addr:#05X}")?;
+        }
+        for hint in &self.quirks {
+            writeln!(f, "Depends on the {} quirk: {:04X} at {:#05X}", hint.quirk, hint.opcode, hint.addr)?;
+        }
+        if let Some(profile) = self.profile {
+            writeln!(f, "Suggested: --platform {}", profile.name)?;
+        }
+        write!(f, "Verdict: {}", self.verdict())
+    }
+}
+
+// Whether any entry of the conformance matrix for the pattern is implemented
+fn is_supported(pattern: &str) -> bool {
+    MATRIX.iter().filter(|entry| entry.opcode == pattern).any(|entry| entry.is_supported())
+}
+
+// Platform of the extension an unsupported pattern belongs to
+fn platform(pattern: &str) -> Option<Platform> {
+    MATRIX.iter().find(|entry| entry.opcode == pattern && !entry.is_supported()).map(|entry| entry.platform)
+}
+
+// First reachable instruction behaving differently with each quirk
+fn quirk_hints(opcodes: &[(u16, u16)]) -> Vec<QuirkHint> {
+    let x = |op: u16| (op >> 8) & 0xf;
+    let y = |op: u16| (op >> 4) & 0xf;
+    let shifts = |op: u16| op >> 12 == 0x8 && matches!(op & 0xf, 0x6 | 0xe) && x(op) != y(op);
+    let jumps = |op: u16| op >> 12 == 0xb && x(op) != 0;
+    let uses_i = |op: u16| op >> 12 == 0xd || (op >> 12 == 0xf && matches!(op & 0xff, 0x1e | 0x33 | 0x55 | 0x65));
+    let sets_i = |op: u16| op >> 12 == 0xa || (op >> 12 == 0xf && matches!(op & 0xff, 0x29 | 0x30));
+    // I left by Fx55 or Fx65 is used before it is set again
+    let moves_i = |i: usize| {
+        let op = opcodes[i].1;
+        op >> 12 == 0xf && matches!(op & 0xff, 0x55 | 0x65) && opcodes[i + 1..].iter()
+            .take(MEMORY_QUIRK_WINDOW)
+            .map(|&(_, op)| op)
+            .find(|&op| uses_i(op) || sets_i(op))
+            .is_some_and(uses_i)
+    };
+
+    let mut hints = Vec::new();
+    let mut hint = |quirk, found: Option<usize>| if let Some(i) = found {
+        hints.push(QuirkHint { quirk, addr: opcodes[i].0, opcode: opcodes[i].1 });
+    };
+    hint("shift", opcodes.iter().position(|&(_, op)| shifts(op)));
+    hint("jump", opcodes.iter().position(|&(_, op)| jumps(op)));
+    hint("memoryLeaveIUnchanged", (0..opcodes.len()).find(|&i| moves_i(i)));
+    hints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{PROGRAM_START, splash::SPLASH};
+
+    #[test]
+    fn test_compatible() {
+        let report = CompatReport::check(&SPLASH, PROGRAM_START);
+        assert_eq!(report.verdict(), Verdict::Compatible);
+        assert!(report.profile.is_none());
+        assert!(report.to_string().ends_with("Verdict: compatible"));
+    }
+
+    #[test]
+    fn test_unsupported() {
+        // 00FF switches to the SUPER-CHIP high resolution
+        let report = CompatReport::check(&[0x00, 0xff, 0x12, 0x02], PROGRAM_START);
+        assert_eq!(report.unsupported, vec![(0x200, 0x00ff)]);
+        assert_eq!(report.verdict(), Verdict::Incompatible);
+        assert_eq!(report.profile.map(|profile| profile.name), Some("schip"));
+        assert!(report.to_string().contains("Unsupported: 00FF at 0x200 (SUPER-CHIP extension)"));
+
+        let report = CompatReport::check(&[0x81, 0x28, 0x12, 0x02], PROGRAM_START);
+        assert_eq!(report.unknown, vec![(0x200, 0x8128)]);
+        assert_eq!(report.verdict(), Verdict::Incompatible);
+    }
+
+    #[test]
+    fn test_quirks() {
+        // SHR V1, V2, then Fx65 followed by a draw from the I it left
+        let report = CompatReport::check(&[0x81, 0x26, 0xf1, 0x65, 0xd0, 0x15, 0x12, 0x06], PROGRAM_START);
+        let quirks: Vec<_> = report.quirks.iter().map(|hint| (hint.quirk, hint.addr)).collect();
+        assert_eq!(quirks, vec![("shift", 0x200), ("memoryLeaveIUnchanged", 0x202)]);
+        assert_eq!(report.verdict(), Verdict::DependsOnQuirks);
+
+        // Shifting in place and setting I again are the same under any quirks
+        let report = CompatReport::check(&[0x81, 0x16, 0xf1, 0x65, 0xa3, 0x00, 0xd0, 0x15, 0xb0, 0x00], PROGRAM_START);
+        assert!(report.quirks.is_empty());
+    }
+}
@@ -0,0 +1,336 @@
+use std::fmt;
+use super::{Chip8, Memory, FLAG_REGISTER, PROGRAM_START, SPRITE_SIZE, draw::is_lit, quirks::Quirks, rom::Platform, storage::MemoryStorage};
+
+// Result of an entry of the conformance matrix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail,
+    Untested, // Implemented, but there is no check for it
+    Unsupported, // Not implemented by this emulator
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Fail => write!(f, "fail"),
+            Status::Untested => write!(f, "untested"),
+            Status::Unsupported => write!(f, "unsupported"),
+        }
+    }
+}
+
+// Opcode behavior under a platform and quirk setting, with the check proving it
+pub struct Entry {
+    pub opcode: &'static str,
+    pub platform: Platform,
+    pub quirk: &'static str, // "-" when no quirk is involved
+    check: Option<fn() -> bool>,
+}
+
+impl Entry {
+    const fn checked(opcode: &'static str, check: fn() -> bool) -> Self {
+        Entry { opcode, platform: Platform::Chip8, quirk: "-", check: Some(check) }
+    }
+
+    const fn quirk(opcode: &'static str, quirk: &'static str, check: fn() -> bool) -> Self {
+        Entry { opcode, platform: Platform::Chip8, quirk, check: Some(check) }
+    }
+
+    const fn extension(opcode: &'static str, platform: Platform, check: fn() -> bool) -> Self {
+        Entry { opcode, platform, quirk: "-", check: Some(check) }
+    }
+
+    const fn untested(opcode: &'static str) -> Self {
+        Entry { opcode, platform: Platform::Chip8, quirk: "-", check: None }
+    }
+
+    const fn unsupported(opcode: &'static str, platform: Platform) -> Self {
+        Entry { opcode, platform, quirk: "-", check: None }
+    }
+
+    // Whether the emulator implements it, without running the check
+    pub fn is_supported(&self) -> bool {
+        self.check.is_some() || self.platform == Platform::Chip8
+    }
+
+    // Runs the check of the entry
+    pub fn status(&self) -> Status {
+        match self.check {
+            Some(check) if check() => Status::Pass,
+            Some(_) => Status::Fail,
+            None if self.platform == Platform::Chip8 => Status::Untested,
+            None => Status::Unsupported,
+        }
+    }
+}
+
+// Every opcode this emulator knows of
+pub const MATRIX: &[Entry] = &[
+    Entry::untested("0nnn"),
+    Entry::checked("00E0", || {
+        let (mut chip8, mut mem) = machine(&[0x00e0]);
+        chip8.display.draw(0, 0, [0xff].into_iter());
+        step(&mut chip8, &mut mem, 1) && chip8.display.get_grid().iter().all(|&row| row == 0)
+    }),
+    Entry::checked("00EE", || {
+        let (mut chip8, mut mem) = machine(&[0x2204, 0x0000, 0x00ee]);
+        step(&mut chip8, &mut mem, 2) && chip8.pc == 0x202
+    }),
+    Entry::checked("1nnn", || {
+        let (mut chip8, mut mem) = machine(&[0x1208]);
+        step(&mut chip8, &mut mem, 1) && chip8.pc == 0x208
+    }),
+    Entry::checked("2nnn", || {
+        let (mut chip8, mut mem) = machine(&[0x2208]);
+        step(&mut chip8, &mut mem, 1) && chip8.pc == 0x208
+    }),
+    Entry::checked("3xkk", || {
+        let (mut chip8, mut mem) = machine(&[0x6105, 0x3105, 0x0000, 0x3106]);
+        step(&mut chip8, &mut mem, 3) && chip8.pc == 0x208
+    }),
+    Entry::checked("4xkk", || {
+        let (mut chip8, mut mem) = machine(&[0x6105, 0x4106, 0x0000, 0x4105]);
+        step(&mut chip8, &mut mem, 3) && chip8.pc == 0x208
+    }),
+    Entry::checked("5xy0", || {
+        let (mut chip8, mut mem) = machine(&[0x6105, 0x6205, 0x5120, 0x0000, 0x5130]);
+        step(&mut chip8, &mut mem, 4) && chip8.pc == 0x20a
+    }),
+    Entry::checked("6xkk", || {
+        let (mut chip8, mut mem) = machine(&[0x6a42]);
+        step(&mut chip8, &mut mem, 1) && chip8.v[0xa] == 0x42
+    }),
+    Entry::checked("7xkk", || {
+        let (mut chip8, mut mem) = machine(&[0x61ff, 0x7102]);
+        step(&mut chip8, &mut mem, 2) && chip8.v[1] == 0x01 && chip8.v[FLAG_REGISTER] == 0
+    }),
+    Entry::checked("8xy0", || alu(0x0, 0x12, 0x34, 0x34, None)),
+    Entry::checked("8xy1", || alu(0x1, 0x12, 0x34, 0x36, None)),
+    Entry::checked("8xy2", || alu(0x2, 0x12, 0x34, 0x10, None)),
+    Entry::checked("8xy3", || alu(0x3, 0x12, 0x34, 0x26, None)),
+    Entry::checked("8xy4", || alu(0x4, 0xff, 0x02, 0x01, Some(1)) && alu(0x4, 0x01, 0x02, 0x03, Some(0))),
+    Entry::checked("8xy5", || alu(0x5, 0x05, 0x02, 0x03, Some(1)) && alu(0x5, 0x02, 0x05, 0xfd, Some(0))),
+    Entry::checked("8xy6", || alu(0x6, 0x05, 0x00, 0x02, Some(1)) && alu(0x6, 0x04, 0x00, 0x02, Some(0))),
+    Entry::checked("8xy7", || alu(0x7, 0x02, 0x05, 0x03, Some(1)) && alu(0x7, 0x05, 0x02, 0xfd, Some(0))),
+    Entry::checked("8xyE", || alu(0xe, 0x81, 0x00, 0x02, Some(1)) && alu(0xe, 0x41, 0x00, 0x82, Some(0))),
+    Entry::checked("9xy0", || {
+        let (mut chip8, mut mem) = machine(&[0x6105, 0x6206, 0x9120, 0x0000, 0x9110]);
+        step(&mut chip8, &mut mem, 4) && chip8.pc == 0x20a
+    }),
+    Entry::checked("Annn", || {
+        let (mut chip8, mut mem) = machine(&[0xa123]);
+        step(&mut chip8, &mut mem, 1) && chip8.idx == 0x123
+    }),
+    Entry::checked("Bnnn", || {
+        let (mut chip8, mut mem) = machine(&[0x6004, 0xb300]);
+        step(&mut chip8, &mut mem, 2) && chip8.pc == 0x304
+    }),
+    Entry::quirk("Bxnn", "jump", || {
+        let (mut chip8, mut mem) = machine(&[0x6004, 0x6302, 0xb300]);
+        chip8.set_quirks(Quirks { jump: true, ..Quirks::default() });
+        step(&mut chip8, &mut mem, 3) && chip8.pc == 0x302
+    }),
+    Entry::checked("Cxkk", || {
+        let (mut chip8, mut mem) = machine(&[0xc10f]);
+        step(&mut chip8, &mut mem, 1) && chip8.v[1] & 0xf0 == 0
+    }),
+    Entry::checked("Dxyn", || {
+        let (mut chip8, mut mem) = machine(&[0xa000, 0xd005, 0xd005]);
+        let drawn = step(&mut chip8, &mut mem, 2) && is_lit(chip8.display.get_grid(), 0, 0) && chip8.v[FLAG_REGISTER] == 0;
+        drawn && step(&mut chip8, &mut mem, 1) && !is_lit(chip8.display.get_grid(), 0, 0) && chip8.v[FLAG_REGISTER] == 1
+    }),
+    Entry::quirk("Dxyn", "clip", || {
+        let (mut chip8, mut mem) = machine(&[0xa000, 0x603e, 0xd011]); // Top row of 0 at the right edge
+        step(&mut chip8, &mut mem, 3) && is_lit(chip8.display.get_grid(), 63, 0) && !is_lit(chip8.display.get_grid(), 0, 0)
+    }),
+    Entry::quirk("Dxyn", "wrap", || {
+        let (mut chip8, mut mem) = machine(&[0xa000, 0x603e, 0xd011]);
+        chip8.set_quirks(Quirks { wrap: true, ..Quirks::default() });
+        step(&mut chip8, &mut mem, 3) && is_lit(chip8.display.get_grid(), 63, 0) && is_lit(chip8.display.get_grid(), 1, 0)
+    }),
+    Entry::checked("Ex9E", || {
+        let (mut chip8, mut mem) = machine(&[0xe09e]); // No key is held headless
+        step(&mut chip8, &mut mem, 1) && chip8.pc == 0x202
+    }),
+    Entry::checked("ExA1", || {
+        let (mut chip8, mut mem) = machine(&[0xe0a1]);
+        step(&mut chip8, &mut mem, 1) && chip8.pc == 0x204
+    }),
+    Entry::checked("Fx07", || {
+        let (mut chip8, mut mem) = machine(&[0x6109, 0xf115, 0xf207]);
+        step(&mut chip8, &mut mem, 3) && chip8.v[2] == 9
+    }),
+    Entry::checked("Fx0A", || {
+        let (mut chip8, mut mem) = machine(&[0xf10a]); // Waits while no key is pressed
+        step(&mut chip8, &mut mem, 3) && chip8.pc == PROGRAM_START
+    }),
+    Entry::checked("Fx15", || {
+        let (mut chip8, mut mem) = machine(&[0x6109, 0xf115]);
+        step(&mut chip8, &mut mem, 2) && chip8.dt == 9
+    }),
+    Entry::checked("Fx18", || {
+        let (mut chip8, mut mem) = machine(&[0x6109, 0xf118]);
+        step(&mut chip8, &mut mem, 2) && chip8.st == 9
+    }),
+    Entry::checked("Fx1E", || {
+        let (mut chip8, mut mem) = machine(&[0xa100, 0x6109, 0xf11e]);
+        step(&mut chip8, &mut mem, 3) && chip8.idx == 0x109
+    }),
+    Entry::checked("Fx29", || {
+        let (mut chip8, mut mem) = machine(&[0x610a, 0xf129]);
+        step(&mut chip8, &mut mem, 2) && chip8.idx == 0xa * SPRITE_SIZE
+    }),
+    Entry::checked("Fx33", || {
+        let (mut chip8, mut mem) = machine(&[0xa300, 0x61fe, 0xf133]);
+        step(&mut chip8, &mut mem, 3) && [mem.read_byte(0x300), mem.read_byte(0x301), mem.read_byte(0x302)] == [2, 5, 4]
+    }),
+    Entry::checked("Fx55", || {
+        let (mut chip8, mut mem) = machine(&[0xa300, 0x6001, 0x6102, 0xf155]);
+        step(&mut chip8, &mut mem, 4) && mem.read_byte(0x300) == 1 && mem.read_byte(0x301) == 2
+    }),
+    Entry::checked("Fx65", || {
+        let (mut chip8, mut mem) = machine(&[0xa000, 0xf165]); // First font row
+        step(&mut chip8, &mut mem, 2) && chip8.v[0] == 0xf0 && chip8.v[1] == 0x90
+    }),
+    Entry::unsupported("00Cn", Platform::SuperChip),
+    Entry::unsupported("00FB", Platform::SuperChip),
+    Entry::unsupported("00FC", Platform::SuperChip),
+    Entry::unsupported("00FD", Platform::SuperChip),
+    Entry::unsupported("00FE", Platform::SuperChip),
+    Entry::unsupported("00FF", Platform::SuperChip),
+    Entry::unsupported("Dxy0", Platform::SuperChip),
+    Entry::extension("Fx30", Platform::SuperChip, || {
+        let (mut chip8, mut mem) = machine(&[0x6009, 0xf030, 0xf065]); // Top row of the large 9
+        step(&mut chip8, &mut mem, 3) && chip8.v[0] == 0xff
+    }),
+    Entry::extension("Fx75", Platform::SuperChip, || {
+        let (mut chip8, mut mem) = machine(&[0x6007, 0x6108, 0xf175, 0x6000, 0xf085]);
+        chip8.set_storage(MemoryStorage::new());
+        step(&mut chip8, &mut mem, 5) && chip8.v[0] == 7
+    }),
+    Entry::extension("Fx85", Platform::SuperChip, || {
+        let (mut chip8, mut mem) = machine(&[0x6007, 0x6108, 0xf175, 0x6000, 0x6100, 0xf185]);
+        chip8.set_storage(MemoryStorage::new());
+        step(&mut chip8, &mut mem, 6) && chip8.v[0] == 7 && chip8.v[1] == 8
+    }),
+    Entry::extension("0011", Platform::MegaChip, || {
+        let (mut chip8, mut mem) = machine(&[0x0011]);
+        step(&mut chip8, &mut mem, 1) && chip8.display.is_megachip()
+    }),
+    Entry::extension("0010", Platform::MegaChip, || {
+        let (mut chip8, mut mem) = machine(&[0x0011, 0x0010]);
+        step(&mut chip8, &mut mem, 2) && !chip8.display.is_megachip()
+    }),
+    Entry::extension("01nn", Platform::MegaChip, || {
+        let (mut chip8, mut mem) = machine(&[0x0011, 0x0100, 0x0345]);
+        step(&mut chip8, &mut mem, 2) && chip8.idx == 0x345 && chip8.pc == 0x206
+    }),
+    Entry::extension("02nn", Platform::MegaChip, || {
+        let (mut chip8, mut mem) = mega_sprite(&[0x00, 0x12, 0x34, 0x56, 0x01]);
+        step(&mut chip8, &mut mem, 8) && chip8.display.get_megachip().is_some_and(|screen| screen.render()[0] == 0x123456)
+    }),
+    Entry::extension("03nn", Platform::MegaChip, || {
+        let (mut chip8, mut mem) = machine(&[0x0011, 0x0310, 0x0402]);
+        step(&mut chip8, &mut mem, 3) && chip8.display.get_megachip().is_some_and(|screen| screen.sprite_len() == 32)
+    }),
+    Entry::extension("04nn", Platform::MegaChip, || {
+        let (mut chip8, mut mem) = machine(&[0x0011, 0x0302, 0x0400]);
+        step(&mut chip8, &mut mem, 3) && chip8.display.get_megachip().is_some_and(|screen| screen.sprite_len() == 512)
+    }),
+    Entry::unsupported("05nn", Platform::MegaChip),
+    Entry::unsupported("060n", Platform::MegaChip),
+    Entry::unsupported("0700", Platform::MegaChip),
+    Entry::unsupported("080n", Platform::MegaChip),
+    Entry::extension("09nn", Platform::MegaChip, || {
+        let (mut chip8, mut mem) = mega_sprite(&[0x00, 0x12, 0x34, 0x56, 0x01]);
+        let drawn = step(&mut chip8, &mut mem, 8) && chip8.v[FLAG_REGISTER] == 0;
+        drawn && step(&mut chip8, &mut mem, 1) && chip8.v[FLAG_REGISTER] == 1
+    }),
+    Entry::extension("Dxyn", Platform::MegaChip, || {
+        let (mut chip8, mut mem) = mega_sprite(&[0x00, 0x12, 0x34, 0x56, 0x01]);
+        step(&mut chip8, &mut mem, 8) && chip8.display.get_megachip().is_some_and(|screen| screen.pixel(0, 0) == 1)
+    }),
+    Entry::unsupported("00Dn", Platform::XoChip),
+    Entry::unsupported("5xy2", Platform::XoChip),
+    Entry::unsupported("5xy3", Platform::XoChip),
+    Entry::unsupported("F000", Platform::XoChip),
+    Entry::unsupported("Fn01", Platform::XoChip),
+    Entry::unsupported("F002", Platform::XoChip),
+    Entry::unsupported("Fx3A", Platform::XoChip),
+];
+
+// Runs every check and prints the matrix as tab separated values
+pub fn report() -> String {
+    let mut report = String::from("opcode\tplatform\tquirk\tstatus\n");
+    for entry in MATRIX {
+        report += &format!("{}\t{}\t{}\t{}\n", entry.opcode, entry.platform, entry.quirk, entry.status());
+    }
+    report
+}
+
+// Machine with the given instructions loaded at the program start
+fn machine(program: &[u16]) -> (Chip8, Memory) {
+    let mut mem = Memory::new();
+    for (i, op) in program.iter().enumerate() {
+        let addr = PROGRAM_START + 2 * i as u16;
+        mem.write_byte(addr, (op >> 8) as u8);
+        mem.write_byte(addr + 1, *op as u8);
+    }
+    (Chip8::new(), mem)
+}
+
+// Megachip program drawing the 1x1 sprite at 0x305 with collision color 1 at 0, 0,
+// after loading the palette at 0x301, then drawing the sprite again
+fn mega_sprite(data: &[u8]) -> (Chip8, Memory) {
+    let (chip8, mut mem) = machine(&[0x0011, 0xa301, 0x0201, 0x0301, 0x0401, 0x0901, 0xa305, 0xd000, 0xd000]);
+    for (i, &byte) in data.iter().enumerate() {
+        mem.write_byte(0x301 + i as u16, byte);
+    }
+    (chip8, mem)
+}
+
+// Executes the given number of instructions, false if any fails
+fn step(chip8: &mut Chip8, mem: &mut Memory, steps: usize) -> bool {
+    (0..steps).all(|_| chip8.step(mem).is_ok())
+}
+
+// Runs 8xyN on V1 and V2, checking V1 and optionally VF
+fn alu(n: u16, x: u8, y: u8, result: u8, flag: Option<u8>) -> bool {
+    let (mut chip8, mut mem) = machine(&[0x6100 | x as u16, 0x6200 | y as u16, 0x8120 | n]);
+    step(&mut chip8, &mut mem, 3) && chip8.v[1] == result && flag.is_none_or(|flag| chip8.v[FLAG_REGISTER] == flag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::rom::classify;
+
+    #[test]
+    fn test_matrix_passes() {
+        let failing: Vec<_> = MATRIX.iter()
+            .filter(|entry| entry.status() == Status::Fail)
+            .map(|entry| entry.opcode)
+            .collect();
+        assert!(failing.is_empty(), "failing: {failing:?}");
+    }
+
+    #[test]
+    fn test_matrix_platforms() {
+        // Spot check entries against the ROM analyzer's view of each opcode
+        for (opcode, op) in [("00E0", 0x00e0), ("8xyE", 0x812e), ("Fx65", 0xf165), ("02nn", 0x0201), ("00FF", 0x00ff), ("5xy2", 0x5122)] {
+            let entry = MATRIX.iter().find(|entry| entry.opcode == opcode).unwrap();
+            assert_eq!(Some(entry.platform), classify(op));
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let report = report();
+        assert!(report.starts_with("opcode\tplatform\tquirk\tstatus\n"));
+        assert!(report.contains("00E0\tCHIP-8\t-\tpass\n"));
+        assert!(report.contains("Dxyn\tCHIP-8\twrap\tpass\n"));
+        assert!(report.contains("00FF\tSUPER-CHIP\t-\tunsupported\n"));
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use super::{Chip8, battery::SaveRam, errors::{Chip8Error, LoadError}, palette::{Color, Palette}};
+
+// Database shipped with the emulator
+const BUNDLED: &str = include_str!("../../assets/roms.json");
+
+// Per-ROM settings database, laid out like the CHIP-8 community database's programs.json:
+// a list of programs, each with its ROMs keyed by SHA-1
+pub struct Database {
+    programs: Vec<Program>,
+}
+
+#[derive(Deserialize)]
+struct Program {
+    title: String,
+    roms: HashMap<String, RomSettings>,
+}
+
+// Settings for a single ROM, missing ones are left as they are
+#[derive(Debug, Default, Deserialize)]
+pub struct RomSettings {
+    pub tickrate: Option<u32>, // Instructions per 60hz frame
+    pub colors: Option<RomColors>,
+    #[serde(default)]
+    pub quirks: HashMap<String, bool>,
+    #[serde(rename = "saveRam")]
+    pub save_ram: Option<String>, // Memory range kept between sessions, like 0x2F0-0x2FF
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RomColors {
+    pub pixels: Vec<String>, // Empty and filled pixel colors as #RRGGBB
+}
+
+impl Database {
+    pub fn bundled() -> Self {
+        Database::parse(BUNDLED).expect("bundled ROM database is valid")
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, Chip8Error> {
+        let json = fs::read_to_string(path).map_err(|source| LoadError::FileRead { path: path.to_path_buf(), source })?;
+        Database::parse(&json)
+    }
+
+    pub fn parse(json: &str) -> Result<Self, Chip8Error> {
+        let programs = serde_json::from_str(json).map_err(LoadError::Database)?;
+        Ok(Database { programs })
+    }
+
+    // Finds the title and settings of a ROM by its contents
+    pub fn lookup(&self, rom: &[u8]) -> Option<(&str, &RomSettings)> {
+        let sha1 = format!("{:x}", Sha1::digest(rom));
+        self.programs.iter().find_map(|program| {
+            program.roms.get(&sha1).map(|settings| (program.title.as_str(), settings))
+        })
+    }
+}
+
+impl RomSettings {
+    // Configures the emulator, returns warnings about settings that couldn't be applied
+    pub fn apply(&self, chip8: &mut Chip8) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if let Some(tickrate) = self.tickrate {
+            chip8.set_tickrate(tickrate);
+        }
+
+        if let Some(colors) = &self.colors {
+            match colors.pixels.iter().map(|color| Color::parse(color)).collect::<Option<Vec<Color>>>().as_deref() {
+                Some(&[empty, filled, ..]) => {
+                    let palette = Palette { filled, empty };
+                    warnings.extend(palette.contrast_warning());
+                    chip8.set_palette(palette);
+                }
+                _ => warnings.push(format!("invalid colors {:?}", colors.pixels)),
+            }
+        }
+
+        let mut quirks = chip8.get_quirks();
+        for (name, &enabled) in &self.quirks {
+            if !quirks.set(name, enabled) {
+                warnings.push(format!("quirk {name} is not supported"));
+            }
+        }
+        chip8.set_quirks(quirks);
+
+        if let Some(range) = &self.save_ram {
+            match SaveRam::parse(range) {
+                Ok(save_ram) => chip8.set_save_ram(Some(save_ram)),
+                Err(_) => warnings.push(format!("invalid save RAM {range}")),
+            }
+        }
+
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::splash::SPLASH;
+    use super::super::quirks::MemoryIncrement;
+
+    #[test]
+    fn test_bundled_lookup() {
+        let database = Database::bundled();
+        let (title, settings) = database.lookup(&SPLASH).unwrap();
+        assert_eq!(title, "Chip8 splash screen");
+        assert_eq!(settings.tickrate, Some(16));
+        assert!(database.lookup(&[0x12, 0x00]).is_none());
+    }
+
+    #[test]
+    fn test_apply() {
+        let database = Database::parse(r##"[{
+            "title": "Test",
+            "roms": { "92a5652d382a18e89c4881ec57041fc7d885ca80": {
+                "tickrate": 30,
+                "colors": { "pixels": ["#102030", "#405060"] },
+                "quirks": { "wrap": false, "vblank": true, "memoryIncrementByX": true },
+                "saveRam": "0x2F0-0x2FF"
+            }}
+        }]"##).unwrap();
+        let (_, settings) = database.lookup(&[0x12, 0x00]).unwrap();
+
+        let mut chip8 = Chip8::new();
+        let warnings = settings.apply(&mut chip8);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("colors #405060 and #102030 have a contrast of 2.0:1")); // Dark blue on darker blue
+        assert_eq!(warnings[1], "quirk vblank is not supported");
+        assert_eq!(chip8.tickrate, 30);
+        assert_eq!(chip8.display.get_colors(), (0x405060, 0x102030));
+        assert!(!chip8.get_quirks().wrap);
+        assert_eq!(chip8.get_quirks().memory, MemoryIncrement::X);
+        assert_eq!(chip8.save_ram(), Some(SaveRam { start: 0x2F0, end: 0x2FF }));
+    }
+}
@@ -0,0 +1,305 @@
+use super::{NUM_REGISTERS, STACK_DEPTH, errors::Chip8Error, labels::Labels, memory::Memory};
+use super::frontend::{Frontend, Key, KeyRepeat, Scale, Window, WindowSettings};
+use super::text::{draw_text, fill_rect, CHAR_WIDTH, LINE_HEIGHT};
+
+// Memory viewer layout, every row is an address followed by its bytes,
+// with the registers and the call stack in a panel to the right
+const BYTES_PER_ROW: usize = 8;
+const ROWS: usize = 16;
+const DUMP_WIDTH: usize = 2 + (6 + BYTES_PER_ROW * 3) * CHAR_WIDTH;
+const CALL_NAME_LENGTH: usize = 10; // Longer labels are cut short in the call stack
+const PANEL_WIDTH: usize = (11 + 2 + 2 + CALL_NAME_LENGTH) * CHAR_WIDTH;
+const VIEWER_WIDTH: usize = DUMP_WIDTH + PANEL_WIDTH;
+const VIEWER_HEIGHT: usize = 1 + (ROWS + 1) * LINE_HEIGHT;
+
+// Memory viewer colors
+const TEXT_COLOR: u32 = 0xc0c0c0;
+const PC_COLOR: u32 = 0xffff00; // Bytes of the next instruction
+const I_COLOR: u32 = 0x00c0ff; // Byte the index register points to
+const CURSOR_COLOR: u32 = 0x404080;
+const UNUSED_COLOR: u32 = 0x505050; // Stack slots above the stack pointer
+
+// Machine state shown next to the memory
+pub(super) struct Registers {
+    pub v: [u8; NUM_REGISTERS],
+    pub idx: u16,
+    pub pc: u16,
+    pub sp: u8,
+    pub dt: u8,
+    pub st: u8,
+    pub calls: [u16; STACK_DEPTH], // Subroutine entered by each call on the stack
+}
+
+// Tools for inspecting a running program, shown in windows of their own
+pub(super) struct Debugger {
+    memory_viewer: Option<MemoryViewer>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Debugger { memory_viewer: None }
+    }
+
+    // Opens the memory viewer into window with the cursor at the given address, or closes it.
+    // The caller keeps the window, as windows can't move to other threads.
+    pub fn toggle_memory_viewer(&mut self, frontend: &dyn Frontend, cursor: u16, mem: &Memory, window: &mut Option<Box<dyn Window>>) -> Result<(), Chip8Error> {
+        *window = None;
+        if self.memory_viewer.take().is_none() {
+            *window = Some(MemoryViewer::open(frontend)?);
+            self.memory_viewer = Some(MemoryViewer::new(cursor, mem.size()));
+        }
+        Ok(())
+    }
+
+    // Refreshes the open tools and applies their edits, once per displayed frame
+    pub fn update(&mut self, mem: &mut Memory, registers: &Registers, labels: &Labels, window: &mut Option<Box<dyn Window>>) -> Result<(), Chip8Error> {
+        if let (Some(viewer), Some(viewer_window)) = (self.memory_viewer.as_mut(), window.as_mut()) {
+            viewer.update(viewer_window.as_mut(), mem, registers, labels)?;
+            if viewer.closed || !viewer_window.is_open() {
+                self.memory_viewer = None;
+                *window = None;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Hex dump of the memory around a cursor, which can be moved and typed over, next to the registers
+struct MemoryViewer {
+    closed: bool, // Closed with Escape
+    buffer: Vec<u32>,
+    cursor: u16, // Selected byte
+    nibble: Option<u8>, // High nibble typed at the cursor, waiting for the low one
+    size: usize, // Bytes of memory, the cursor wraps around past them
+}
+
+impl MemoryViewer {
+    fn new(cursor: u16, size: usize) -> Self {
+        MemoryViewer { closed: false, buffer: vec![0; VIEWER_WIDTH * VIEWER_HEIGHT], cursor: (cursor as usize % size) as u16, nibble: None, size }
+    }
+
+    fn open(frontend: &dyn Frontend) -> Result<Box<dyn Window>, Chip8Error> {
+        frontend.open_window(&WindowSettings::new("Chip8 Debugger", VIEWER_WIDTH, VIEWER_HEIGHT, Scale::X4))
+    }
+
+    fn update(&mut self, window: &mut dyn Window, mem: &mut Memory, registers: &Registers, labels: &Labels) -> Result<(), Chip8Error> {
+        for key in window.get_keys_pressed(KeyRepeat::Yes) {
+            self.handle_key(key, mem, registers.pc, registers.idx);
+        }
+
+        self.render(mem, registers.pc, registers.idx);
+        render_registers(&mut self.buffer, registers, labels);
+        window.update(&self.buffer, VIEWER_WIDTH, VIEWER_HEIGHT)
+    }
+
+    // Arrows and page keys move the cursor, P and I jump to the registers, hex digits overwrite bytes
+    fn handle_key(&mut self, key: Key, mem: &mut Memory, pc: u16, idx: u16) {
+        match key {
+            Key::Left => self.move_cursor(-1),
+            Key::Right => self.move_cursor(1),
+            Key::Up => self.move_cursor(-(BYTES_PER_ROW as i32)),
+            Key::Down => self.move_cursor(BYTES_PER_ROW as i32),
+            Key::PageUp => self.move_cursor(-((BYTES_PER_ROW * ROWS) as i32)),
+            Key::PageDown => self.move_cursor((BYTES_PER_ROW * ROWS) as i32),
+            Key::P => self.jump(pc),
+            Key::I => self.jump(idx),
+            Key::Escape => self.closed = true,
+            key => {
+                if let Some(digit) = hex_digit(key) {
+                    self.type_digit(mem, digit);
+                }
+            }
+        }
+    }
+
+    fn move_cursor(&mut self, delta: i32) {
+        self.jump((self.cursor as i32 + delta).rem_euclid(self.size as i32) as u16);
+    }
+
+    fn jump(&mut self, addr: u16) {
+        self.cursor = (addr as usize % self.size) as u16;
+        self.nibble = None;
+    }
+
+    // The first digit typed is kept until the second one completes the byte
+    fn type_digit(&mut self, mem: &mut Memory, digit: u8) {
+        match self.nibble.take() {
+            Some(high) => {
+                mem.write_byte(self.cursor, high << 4 | digit);
+                self.move_cursor(1);
+            }
+            None => self.nibble = Some(digit),
+        }
+    }
+
+    // Address of the first row shown, keeping the cursor in the middle where possible
+    fn first_row(&self) -> usize {
+        let row = self.cursor as usize / BYTES_PER_ROW;
+        row.saturating_sub(ROWS / 2).min(self.size / BYTES_PER_ROW - ROWS) * BYTES_PER_ROW
+    }
+
+    fn render(&mut self, mem: &Memory, pc: u16, idx: u16) {
+        let start = self.first_row();
+        let buffer = &mut self.buffer;
+        buffer.fill(0x000000);
+        draw_text(buffer, VIEWER_WIDTH, 1, 1, &format!("PC {pc:04X}"), PC_COLOR);
+        draw_text(buffer, VIEWER_WIDTH, 1 + 9 * CHAR_WIDTH, 1, &format!("I {idx:04X}"), I_COLOR);
+
+        for row in 0..ROWS {
+            let y = 1 + (row + 1) * LINE_HEIGHT;
+            let row_addr = start + row * BYTES_PER_ROW;
+            draw_text(buffer, VIEWER_WIDTH, 1, y, &format!("{row_addr:04X}"), TEXT_COLOR);
+            for column in 0..BYTES_PER_ROW {
+                let addr = (row_addr + column) as u16;
+                let x = 1 + (6 + column * 3) * CHAR_WIDTH;
+                let mut text = format!("{:02X}", mem.read_byte(addr));
+                if addr == self.cursor {
+                    fill_rect(buffer, VIEWER_WIDTH, x - 1, y - 1, 2 * CHAR_WIDTH + 1, LINE_HEIGHT + 1, CURSOR_COLOR);
+                    if let Some(high) = self.nibble {
+                        text = format!("{high:X}_");
+                    }
+                }
+                let color = if addr == pc || addr == pc.wrapping_add(1) {
+                    PC_COLOR
+                } else if addr == idx {
+                    I_COLOR
+                } else {
+                    TEXT_COLOR
+                };
+                draw_text(buffer, VIEWER_WIDTH, x, y, &text, color);
+            }
+        }
+    }
+}
+
+// Draws the registers and the call stack into the panel right of the memory
+fn render_registers(buffer: &mut [u32], registers: &Registers, labels: &Labels) {
+    let x = DUMP_WIDTH;
+    let line = |n: usize| 1 + n * LINE_HEIGHT;
+    draw_text(buffer, VIEWER_WIDTH, x, line(0), &format!("PC {:04X}", registers.pc), PC_COLOR);
+    draw_text(buffer, VIEWER_WIDTH, x, line(1), &format!("I  {:04X}", registers.idx), I_COLOR);
+    draw_text(buffer, VIEWER_WIDTH, x, line(2), &format!("SP {:02X}", registers.sp), TEXT_COLOR);
+    draw_text(buffer, VIEWER_WIDTH, x, line(3), &format!("DT {:02X}", registers.dt), TEXT_COLOR);
+    draw_text(buffer, VIEWER_WIDTH, x, line(4), &format!("ST {:02X}", registers.st), TEXT_COLOR);
+    for (n, value) in registers.v.iter().enumerate() {
+        let column = n / 8 * 6 * CHAR_WIDTH;
+        draw_text(buffer, VIEWER_WIDTH, x + column, line(5 + n % 8), &format!("V{n:X} {value:02X}"), TEXT_COLOR);
+    }
+
+    // Calls push to slot 0 and up, SP is the next free slot. Each slot shows the subroutine
+    // called, by its label if it has one
+    let x = x + 13 * CHAR_WIDTH;
+    draw_text(buffer, VIEWER_WIDTH, x, line(0), "CALLS", TEXT_COLOR);
+    for (n, &addr) in registers.calls.iter().enumerate() {
+        let color = if n < registers.sp as usize { TEXT_COLOR } else { UNUSED_COLOR };
+        let name = match labels.name(addr) {
+            Some(name) => name.chars().take(CALL_NAME_LENGTH).collect(),
+            None => format!("{addr:04X}"),
+        };
+        draw_text(buffer, VIEWER_WIDTH, x, line(n + 1), &format!("{n:X} {name}"), color);
+    }
+}
+
+fn hex_digit(key: Key) -> Option<u8> {
+    let digit = match key {
+        Key::Key0 | Key::NumPad0 => 0x0,
+        Key::Key1 | Key::NumPad1 => 0x1,
+        Key::Key2 | Key::NumPad2 => 0x2,
+        Key::Key3 | Key::NumPad3 => 0x3,
+        Key::Key4 | Key::NumPad4 => 0x4,
+        Key::Key5 | Key::NumPad5 => 0x5,
+        Key::Key6 | Key::NumPad6 => 0x6,
+        Key::Key7 | Key::NumPad7 => 0x7,
+        Key::Key8 | Key::NumPad8 => 0x8,
+        Key::Key9 | Key::NumPad9 => 0x9,
+        Key::A => 0xA,
+        Key::B => 0xB,
+        Key::C => 0xC,
+        Key::D => 0xD,
+        Key::E => 0xE,
+        Key::F => 0xF,
+        _ => return None,
+    };
+    Some(digit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{MAX_MEMORY_SIZE, MEMORY_SIZE};
+
+    #[test]
+    fn test_render_registers() {
+        let mut registers = Registers { v: [0; NUM_REGISTERS], idx: 0x300, pc: 0x200, sp: 2, dt: 0, st: 0, calls: [0; STACK_DEPTH] };
+        registers.v[0x8] = 0x7f;
+        registers.calls[0] = 0x300;
+        registers.calls[1] = 0x2a4;
+        let mut labels = Labels::new();
+        labels.add(0x2a4, "i");
+        let mut buffer = vec![0; VIEWER_WIDTH * VIEWER_HEIGHT];
+        render_registers(&mut buffer, &registers, &labels);
+        let at = |x: usize, line: usize| buffer[x + (1 + line * LINE_HEIGHT) * VIEWER_WIDTH];
+        assert_eq!(at(DUMP_WIDTH, 0), PC_COLOR); // Top of the "P"
+        assert_eq!(at(DUMP_WIDTH + 6 * CHAR_WIDTH + 3 * CHAR_WIDTH, 5), TEXT_COLOR); // "7" of V8
+        let stack = DUMP_WIDTH + 13 * CHAR_WIDTH;
+        assert_eq!(at(stack + 1, 1), TEXT_COLOR); // Live slot
+        assert_eq!(at(stack + 1 + 3 * CHAR_WIDTH, 1), TEXT_COLOR); // Top of the "3" of 0300
+        assert_eq!(at(stack + 1 + 3 * CHAR_WIDTH, 2), 0x000000); // Only "i" is drawn for the labeled call
+        assert_eq!(at(stack + 1 + 2 * CHAR_WIDTH, 3), UNUSED_COLOR); // First "0" of the unused slot's address
+    }
+
+    #[test]
+    fn test_type_digits() {
+        let mut mem = Memory::new();
+        let mut viewer = MemoryViewer::new(0x300, MEMORY_SIZE);
+        viewer.handle_key(Key::A, &mut mem, 0x200, 0);
+        assert_eq!(mem.read_byte(0x300), 0x00); // Waits for the low nibble
+        viewer.handle_key(Key::Key5, &mut mem, 0x200, 0);
+        assert_eq!(mem.read_byte(0x300), 0xA5);
+        assert_eq!(viewer.cursor, 0x301);
+
+        viewer.handle_key(Key::F, &mut mem, 0x200, 0);
+        viewer.handle_key(Key::Left, &mut mem, 0x200, 0); // Moving drops the typed nibble
+        viewer.handle_key(Key::Key1, &mut mem, 0x200, 0);
+        assert_eq!(mem.read_byte(0x301), 0x00);
+    }
+
+    #[test]
+    fn test_move_cursor() {
+        let mut mem = Memory::new();
+        let mut viewer = MemoryViewer::new(0x000, MEMORY_SIZE);
+        viewer.handle_key(Key::Up, &mut mem, 0x200, 0x300);
+        assert_eq!(viewer.cursor as usize, MEMORY_SIZE - BYTES_PER_ROW); // Wraps around
+        viewer.handle_key(Key::P, &mut mem, 0x200, 0x300);
+        assert_eq!(viewer.cursor, 0x200);
+        viewer.handle_key(Key::I, &mut mem, 0x200, 0x300);
+        assert_eq!(viewer.cursor, 0x300);
+
+        let mut viewer = MemoryViewer::new(0x000, MAX_MEMORY_SIZE);
+        viewer.handle_key(Key::Up, &mut mem, 0x200, 0x300);
+        assert_eq!(viewer.cursor as usize, MAX_MEMORY_SIZE - BYTES_PER_ROW); // Past 4KB on XO-CHIP
+    }
+
+    #[test]
+    fn test_first_row() {
+        assert_eq!(MemoryViewer::new(0x000, MEMORY_SIZE).first_row(), 0x000);
+        assert_eq!(MemoryViewer::new(0x200, MEMORY_SIZE).first_row(), 0x200 - ROWS / 2 * BYTES_PER_ROW);
+        assert_eq!(MemoryViewer::new(0xFFF, MEMORY_SIZE).first_row(), MEMORY_SIZE - ROWS * BYTES_PER_ROW);
+        // XO-CHIP memory reaches 64KB
+        assert_eq!(MemoryViewer::new(0xFFF, MAX_MEMORY_SIZE).first_row(), 0xFB8); // No longer the last rows
+        assert_eq!(MemoryViewer::new(0xFFFF, MAX_MEMORY_SIZE).first_row(), MAX_MEMORY_SIZE - ROWS * BYTES_PER_ROW);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut mem = Memory::new();
+        mem.load_from_bytes(&[0x12, 0x34]).unwrap();
+        let mut viewer = MemoryViewer::new(0x200, MEMORY_SIZE);
+        viewer.render(&mem, 0x200, 0x000);
+        let y = 1 + (ROWS / 2 + 1) * LINE_HEIGHT; // Row of the cursor
+        let x = 1 + 6 * CHAR_WIDTH;
+        assert_eq!(viewer.buffer[x + 1 + y * VIEWER_WIDTH], PC_COLOR); // Top of the "1" of 12
+        assert_eq!(viewer.buffer[x + (y - 1) * VIEWER_WIDTH], CURSOR_COLOR);
+        assert_eq!(viewer.buffer[1 + 7 * LINE_HEIGHT * VIEWER_WIDTH], 0x000000);
+    }
+}
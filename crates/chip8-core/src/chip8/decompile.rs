@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use super::rom::reachable;
+
+// Data bytes written on one line
+const BYTES_PER_LINE: usize = 8;
+
+// Instructions after an i := searched for the sprite draw using it
+const SPRITE_LOOKAHEAD: usize = 8;
+
+// What an address is used for, the first one it's found for naming it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Use {
+    Main,
+    Call,
+    Jump,
+    Sprite(usize), // Bytes drawn from it
+    Data,
+}
+
+// Part of the ROM, laid out from the program start
+enum Item {
+    Instruction(u16),
+    Long(u16), // i := long and the address following it
+    Byte(u8),
+}
+
+// Turns a ROM into Octo source assembling back into the same bytes, see octo::assemble.
+// Reachable instructions become statements, the targets of jumps, calls and i := get labels
+// and everything else is data, with a preview of the rows wherever a sprite draw reads from.
+// The source assembles back for the same program start, which the platform decides.
+pub fn decompile(rom: &[u8], program_start: u16) -> String {
+    let code: BTreeMap<u16, u16> = reachable(rom, program_start).into_iter().collect();
+    let layout = layout(rom, &code, program_start);
+    let uses = uses(&layout, program_start);
+    let label = |addr: u16| uses.get(&addr).map(|&used| name(used, addr));
+    let target = |addr: u16| label(addr).unwrap_or_else(|| format!("{addr:#05X}"));
+
+    let mut out = String::from("# Decompiled by chip8, assembles back into the same ROM\n");
+    let mut skipping = false; // The last instruction skips the next one, which is indented
+    let mut data = Vec::new(); // Bytes waiting to be written, from the given address
+    let mut data_start = program_start;
+    for (addr, item) in &layout {
+        let addr = *addr;
+        if uses.contains_key(&addr) || !matches!(item, Item::Byte(_)) {
+            write_data(&mut out, data_start, &data, &uses);
+            data.clear();
+        }
+        if let Some(name) = label(addr) {
+            let comment = match uses[&addr] {
+                Use::Sprite(32) => " # 16x16 sprite".to_string(),
+                Use::Sprite(bytes) => format!(" # 8x{bytes} sprite"),
+                _ => String::new(),
+            };
+            let _ = writeln!(out, "\n: {name}{comment}");
+        }
+
+        let indent = if skipping { "    " } else { "  " };
+        if !matches!(item, Item::Byte(_)) {
+            if let Some(name) = label(addr + 1) {
+                let _ = writeln!(out, ":next {name}");
+            }
+        }
+        match *item {
+            Item::Instruction(op) => {
+                let _ = match statement(op, &target) {
+                    Some(text) => writeln!(out, "{indent}{text}"),
+                    None => writeln!(out, "{indent}{:#04X} {:#04X} # {op:04X} has no Octo statement", op >> 8, op & 0xFF),
+                };
+                skipping = is_skip(op);
+            }
+            Item::Long(addr) => {
+                let _ = writeln!(out, "{indent}i := long {}", target(addr));
+                skipping = false;
+            }
+            Item::Byte(byte) => {
+                if data.is_empty() {
+                    data_start = addr;
+                }
+                data.push(byte);
+                skipping = false;
+            }
+        }
+    }
+    write_data(&mut out, data_start, &data, &uses);
+    out
+}
+
+// Splits the ROM into the reachable instructions and the bytes between them
+fn layout(rom: &[u8], code: &BTreeMap<u16, u16>, program_start: u16) -> Vec<(u16, Item)> {
+    let end = program_start as usize + rom.len();
+    let mut layout = Vec::new();
+    let mut addr = program_start as usize;
+    while addr < end {
+        let i = addr - program_start as usize;
+        let item = match code.get(&(addr as u16)) {
+            Some(&0xF000) if addr + 4 <= end => Item::Long(u16::from_be_bytes([rom[i + 2], rom[i + 3]])),
+            Some(&op) => Item::Instruction(op),
+            None => Item::Byte(rom[i]),
+        };
+        let width = match item {
+            Item::Instruction(_) => 2,
+            Item::Long(_) => 4,
+            Item::Byte(_) => 1,
+        };
+        layout.push((addr as u16, item));
+        addr += width;
+    }
+    layout
+}
+
+// Addresses the instructions jump to, call or point I at, that a label can be put on: the start
+// of an item, or the second byte of an instruction through :next
+fn uses(layout: &[(u16, Item)], program_start: u16) -> BTreeMap<u16, Use> {
+    let labelable = |addr: u16| layout.binary_search_by_key(&addr, |&(start, _)| start).is_ok()
+        || layout.binary_search_by_key(&addr.wrapping_sub(1), |&(start, _)| start).is_ok_and(|i| !matches!(layout[i].1, Item::Byte(_)));
+    let mut uses = BTreeMap::new();
+    let mut add = |addr: u16, used: Use| {
+        if labelable(addr) {
+            let entry = uses.entry(addr).or_insert(used);
+            *entry = (*entry).min(used);
+        }
+    };
+
+    add(program_start, Use::Main);
+    for (i, (_, item)) in layout.iter().enumerate() {
+        let data = || sprite(&layout[i + 1..]).map_or(Use::Data, Use::Sprite);
+        match *item {
+            Item::Instruction(op) if op >> 12 == 0x1 || op >> 12 == 0xB => add(op & 0x0FFF, Use::Jump),
+            Item::Instruction(op) if op >> 12 == 0x2 => add(op & 0x0FFF, Use::Call),
+            Item::Instruction(op) if op >> 12 == 0xA => add(op & 0x0FFF, data()),
+            Item::Long(addr) => add(addr, data()),
+            _ => (),
+        }
+    }
+    uses
+}
+
+// Bytes drawn by the first sprite draw following an i :=, if it comes before I changes or
+// execution goes elsewhere
+fn sprite(following: &[(u16, Item)]) -> Option<usize> {
+    for (_, item) in following.iter().take(SPRITE_LOOKAHEAD) {
+        let Item::Instruction(op) = *item else { return None };
+        match (op >> 12, op & 0xFF) {
+            (0xD, _) if op & 0xF == 0 => return Some(32),
+            (0xD, _) => return Some(op as usize & 0xF),
+            (0x0, 0xEE | 0xFD) | (0x1 | 0x2 | 0xA | 0xB, _) | (0xF, 0x1E | 0x29 | 0x30 | 0x55 | 0x65 | 0x33) => return None,
+            _ => (),
+        }
+    }
+    None
+}
+
+fn name(used: Use, addr: u16) -> String {
+    match used {
+        Use::Main => "main".to_string(),
+        Use::Call => format!("sub_{addr:03x}"),
+        Use::Jump => format!("label_{addr:03x}"),
+        Use::Sprite(_) => format!("sprite_{addr:03x}"),
+        Use::Data => format!("data_{addr:03x}"),
+    }
+}
+
+// Writes bytes that aren't code, a row per line with a preview if a sprite is drawn from them
+fn write_data(out: &mut String, start: u16, data: &[u8], uses: &BTreeMap<u16, Use>) {
+    if data.is_empty() {
+        return;
+    }
+    let (rows, row_width) = match uses.get(&start) {
+        Some(&Use::Sprite(32)) => (16.min(data.len() / 2), 2),
+        Some(&Use::Sprite(bytes)) => (bytes.min(data.len()), 1),
+        _ => (0, 1),
+    };
+    let (sprite, rest) = data.split_at(rows * row_width);
+    for row in sprite.chunks(row_width) {
+        let bytes: Vec<String> = row.iter().map(|byte| format!("{byte:#04X}")).collect();
+        let preview: String = row.iter()
+            .flat_map(|byte| (0..8).rev().map(move |bit| if byte >> bit & 1 == 1 { '#' } else { '.' }))
+            .collect();
+        let _ = writeln!(out, "  {} # {preview}", bytes.join(" "));
+    }
+    for line in rest.chunks(BYTES_PER_LINE) {
+        let bytes: Vec<String> = line.iter().map(|byte| format!("{byte:#04X}")).collect();
+        let _ = writeln!(out, "  {}", bytes.join(" "));
+    }
+}
+
+// The instruction in Octo syntax, None for those Octo has no statement for
+fn statement(op: u16, target: &impl Fn(u16) -> String) -> Option<String> {
+    let (x, y, n, kk, nnn) = ((op >> 8) & 0xF, (op >> 4) & 0xF, op & 0xF, op & 0xFF, op & 0x0FFF);
+    let text = match (op >> 12, kk, n) {
+        (0x0, _, _) => match op {
+            0x00E0 => "clear".to_string(),
+            0x00EE => "return".to_string(),
+            0x00FB => "scroll-right".to_string(),
+            0x00FC => "scroll-left".to_string(),
+            0x00FD => "exit".to_string(),
+            0x00FE => "lores".to_string(),
+            0x00FF => "hires".to_string(),
+            op if op & 0xFFF0 == 0x00C0 => format!("scroll-down {n}"),
+            op if op & 0xFFF0 == 0x00D0 => format!("scroll-up {n}"),
+            _ => return None,
+        },
+        (0x1, _, _) => format!("jump {}", target(nnn)),
+        (0x2, _, _) => match target(nnn) {
+            name if name.starts_with("0x") => format!(":call {name}"),
+            name => name,
+        },
+        (0x3, _, _) => format!("if v{x:x} != {kk:#04X} then"),
+        (0x4, _, _) => format!("if v{x:x} == {kk:#04X} then"),
+        (0x5, _, 0x0) => format!("if v{x:x} != v{y:x} then"),
+        (0x5, _, 0x2) => format!("save v{x:x} - v{y:x}"),
+        (0x5, _, 0x3) => format!("load v{x:x} - v{y:x}"),
+        (0x6, _, _) => format!("v{x:x} := {kk:#04X}"),
+        (0x7, _, _) => format!("v{x:x} += {kk:#04X}"),
+        (0x8, _, 0x0) => format!("v{x:x} := v{y:x}"),
+        (0x8, _, 0x1) => format!("v{x:x} |= v{y:x}"),
+        (0x8, _, 0x2) => format!("v{x:x} &= v{y:x}"),
+        (0x8, _, 0x3) => format!("v{x:x} ^= v{y:x}"),
+        (0x8, _, 0x4) => format!("v{x:x} += v{y:x}"),
+        (0x8, _, 0x5) => format!("v{x:x} -= v{y:x}"),
+        (0x8, _, 0x6) => format!("v{x:x} >>= v{y:x}"),
+        (0x8, _, 0x7) => format!("v{x:x} =- v{y:x}"),
+        (0x8, _, 0xE) => format!("v{x:x} <<= v{y:x}"),
+        (0x9, _, 0x0) => format!("if v{x:x} == v{y:x} then"),
+        (0xA, _, _) => format!("i := {}", target(nnn)),
+        (0xB, _, _) => format!("jump0 {}", target(nnn)),
+        (0xC, _, _) => format!("v{x:x} := random {kk:#04X}"),
+        (0xD, _, _) => format!("sprite v{x:x} v{y:x} {n}"),
+        (0xE, 0x9E, _) => format!("if v{x:x} -key then"),
+        (0xE, 0xA1, _) => format!("if v{x:x} key then"),
+        (0xF, 0x01, _) => format!("plane {x}"),
+        (0xF, 0x02, _) if x == 0 => "audio".to_string(),
+        (0xF, 0x07, _) => format!("v{x:x} := delay"),
+        (0xF, 0x0A, _) => format!("v{x:x} := key"),
+        (0xF, 0x15, _) => format!("delay := v{x:x}"),
+        (0xF, 0x18, _) => format!("buzzer := v{x:x}"),
+        (0xF, 0x1E, _) => format!("i += v{x:x}"),
+        (0xF, 0x29, _) => format!("i := hex v{x:x}"),
+        (0xF, 0x30, _) => format!("i := bighex v{x:x}"),
+        (0xF, 0x33, _) => format!("bcd v{x:x}"),
+        (0xF, 0x3A, _) => format!("pitch := v{x:x}"),
+        (0xF, 0x55, _) => format!("save v{x:x}"),
+        (0xF, 0x65, _) => format!("load v{x:x}"),
+        (0xF, 0x75, _) => format!("saveflags v{x:x}"),
+        (0xF, 0x85, _) => format!("loadflags v{x:x}"),
+        _ => return None,
+    };
+    Some(text)
+}
+
+fn is_skip(op: u16) -> bool {
+    matches!((op >> 12, op & 0xF, op & 0xFF), (0x3 | 0x4, _, _) | (0x5 | 0x9, 0x0, _) | (0xE, _, 0x9E | 0xA1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{PROGRAM_START, memory::Layout, octo, splash::{SPLASH, TUTORIAL}};
+
+    fn reassemble(rom: &[u8]) -> Vec<u8> {
+        octo::assemble(&decompile(rom, PROGRAM_START), Layout::default()).unwrap().rom
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(reassemble(&SPLASH), SPLASH);
+        assert_eq!(reassemble(&TUTORIAL), TUTORIAL);
+        let flags = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/flags.ch8")).unwrap();
+        assert_eq!(reassemble(&flags), flags);
+    }
+
+    #[test]
+    fn test_decompile() {
+        // Calls a subroutine drawing a sprite, then loops, with an unknown opcode never reached
+        let rom = [0x22, 0x06, 0x12, 0x02, 0xFF, 0xFF, 0xA2, 0x0E, 0xD0, 0x12, 0x00, 0xEE, 0x81, 0x88, 0xF0, 0x90];
+        let source = decompile(&rom, PROGRAM_START);
+        assert!(source.contains("\n: main\n  sub_206\n"));
+        assert!(source.contains("\n: label_202\n  jump label_202\n  0xFF 0xFF\n"));
+        assert!(source.contains("\n: sub_206\n  i := sprite_20e\n  sprite v0 v1 2\n  return\n  0x81 0x88\n"));
+        assert!(source.contains("\n: sprite_20e # 8x2 sprite\n  0xF0 # ####....\n  0x90 # #..#....\n"));
+        assert_eq!(reassemble(&rom), rom);
+    }
+
+    #[test]
+    fn test_self_modifying() {
+        // Overwrites the byte of v0 := 1 through a label on the second byte, skipping with a key
+        let rom = [0xA2, 0x07, 0xE0, 0x9E, 0x12, 0x00, 0x60, 0x01, 0x12, 0x00];
+        let source = decompile(&rom, PROGRAM_START);
+        assert!(source.contains("  i := data_207\n  if v0 -key then\n    jump main\n:next data_207\n  v0 := 0x01\n"));
+        assert_eq!(reassemble(&rom), rom);
+    }
+
+    #[test]
+    fn test_program_start() {
+        // An ETI-660 program jumping over a byte of data, from 0x600
+        let rom = [0x16, 0x03, 0xAA, 0x16, 0x03];
+        let source = decompile(&rom, 0x600);
+        assert!(source.contains("\n: main\n  jump label_603\n  0xAA\n"));
+        let layout = Layout { program_start: 0x600, ..Layout::default() };
+        assert_eq!(octo::assemble(&source, layout).unwrap().rom, rom);
+    }
+}
@@ -0,0 +1,950 @@
+use alloc::boxed::Box;
+use super::DISPLAY_HEIGHT;
+use super::draw::{Clip, DrawStrategy, Grid};
+use super::megachip::MegaScreen;
+
+// The window, its input and what is drawn over the screen
+#[cfg(feature = "std")]
+use {
+    std::path::Path,
+    std::sync::Arc,
+    image::{Rgb, RgbImage},
+    super::{APP_NAME, DISPLAY_WIDTH, DISPLAY_SCALE, WINDOW_NAME},
+    super::errors::{Chip8Error, IoError},
+    super::draw,
+    super::menu::PauseMenu,
+    super::notify,
+    super::browser::RomBrowser,
+    super::megachip::{MEGA_HEIGHT, MEGA_WIDTH},
+    super::filter::{Filter, CELL_SIZE},
+    super::backend::Backend,
+    super::frontend::{Frontend, Key, KeyRepeat, Scale, Window, WindowSettings},
+    super::icon::WindowIcon,
+    super::indicator::SoundIndicator,
+};
+
+// Tint of the cells covered by the highlighted sprite
+#[cfg(feature = "std")]
+const HIGHLIGHT_COLOR: u32 = 0xffff00;
+
+// Brightness an unlit pixel keeps from one frame to the next with phosphor decay
+#[cfg(feature = "std")]
+const PHOSPHOR_DECAY: f32 = 0.5;
+
+// A window opened by Display::init, the only way to get one, until it is closed
+#[cfg(feature = "std")]
+pub(super) struct Screen {
+    window: Option<Box<dyn Window>>,
+}
+
+pub struct Display {
+    grid: Grid,
+    #[cfg(feature = "std")]
+    open: bool, // A window is shown, from init until it or close closes it
+    #[cfg(feature = "std")]
+    held: Vec<Key>, // Keys held at the last update
+    #[cfg(feature = "std")]
+    pressed: Vec<Key>, // Keys pressed before the last update, repeats left out
+    #[cfg(feature = "std")]
+    title_changed: bool, // The open window still shows the previous title
+    #[cfg(feature = "std")]
+    buffer: Vec<u32>,
+    #[cfg(feature = "std")]
+    dirty: bool, // Something was drawn or changed since the buffer was last updated
+    colors: Colors,
+    #[cfg(feature = "std")]
+    scale: Scale,
+    strategy: Box<dyn DrawStrategy>, // Sprite drawing routine
+    #[cfg(feature = "std")]
+    highlight: Option<(usize, usize, usize)>, // Cells a sprite would cover, as x, y and height
+    #[cfg(feature = "std")]
+    menu: Option<PauseMenu>, // Pause menu drawn over the screen
+    #[cfg(feature = "std")]
+    notice: Option<&'static str>, // Notification drawn over the top of the screen
+    #[cfg(feature = "std")]
+    browser: Option<RomBrowser>, // List of programs to start, drawn instead of the screen
+    mega: Option<Box<MegaScreen>>, // 256x192 color screen shown instead of the grid in the Megachip mode
+    #[cfg(feature = "std")]
+    phosphor: Option<Box<[f32; DISPLAY_WIDTH * DISPLAY_HEIGHT]>>, // Pixel brightness row by row, if pixels fade out
+    #[cfg(feature = "std")]
+    filter: Filter, // Post-processing of the buffer
+    #[cfg(feature = "std")]
+    output: Vec<u32>, // Filtered buffer shown in the window, empty without a filter
+    #[cfg(feature = "std")]
+    sound_indicator: SoundIndicator, // Shown while beeping
+    #[cfg(feature = "std")]
+    beeping: bool, // Whether the sound timer runs
+    #[cfg(feature = "std")]
+    fullscreen: bool, // Borderless window covering the screen
+    #[cfg(feature = "std")]
+    position: Option<(isize, isize)>, // Where the window opens, left to the window manager without one
+    #[cfg(feature = "std")]
+    title: String,
+    #[cfg(feature = "std")]
+    icon: WindowIcon, // Shown in the title bar and taskbar where the platform allows
+    #[cfg(feature = "std")]
+    icon_changed: bool, // The open window still shows the previous icon
+    #[cfg(feature = "std")]
+    app_name: String, // Application the window belongs to
+    #[cfg(feature = "std")]
+    backend: Backend, // Library the window is opened with
+    #[cfg(feature = "std")]
+    vsync: bool, // Presenting waits for the monitor's refresh, if the backend can
+    #[cfg(feature = "std")]
+    stale: bool, // The window has to be recreated for a new size
+    #[cfg(feature = "std")]
+    latched: Option<Grid>, // Grid at the end of the last frame, shown instead of the grid while set
+    #[cfg(feature = "std")]
+    key_events: Vec<(Key, bool)>, // Key presses and releases in the window since they were last taken
+    #[cfg(feature = "std")]
+    frontend: Option<Arc<dyn Frontend>>, // Opens the window, none when headless
+}
+
+impl Display {
+    pub fn new() -> Self {
+        let colors = Colors {
+            filled: 0xffffff,
+            empty: 0x000000
+        };
+    
+        Display {
+            grid: [0; DISPLAY_HEIGHT],
+            colors,
+            strategy: Box::new(Clip),
+            mega: None,
+            #[cfg(feature = "std")]
+            buffer: vec![0; DISPLAY_WIDTH * DISPLAY_HEIGHT],
+            #[cfg(feature = "std")]
+            dirty: true,
+            #[cfg(feature = "std")]
+            open: false,
+            #[cfg(feature = "std")]
+            held: Vec::new(),
+            #[cfg(feature = "std")]
+            pressed: Vec::new(),
+            #[cfg(feature = "std")]
+            title_changed: false,
+            #[cfg(feature = "std")]
+            scale: DISPLAY_SCALE,
+            #[cfg(feature = "std")]
+            highlight: None,
+            #[cfg(feature = "std")]
+            menu: None,
+            #[cfg(feature = "std")]
+            notice: None,
+            #[cfg(feature = "std")]
+            browser: None,
+            #[cfg(feature = "std")]
+            phosphor: None,
+            #[cfg(feature = "std")]
+            filter: Filter::None,
+            #[cfg(feature = "std")]
+            output: Vec::new(),
+            #[cfg(feature = "std")]
+            sound_indicator: SoundIndicator::None,
+            #[cfg(feature = "std")]
+            beeping: false,
+            #[cfg(feature = "std")]
+            fullscreen: false,
+            #[cfg(feature = "std")]
+            position: None,
+            #[cfg(feature = "std")]
+            title: WINDOW_NAME.to_string(),
+            #[cfg(feature = "std")]
+            icon: WindowIcon::bundled(),
+            #[cfg(feature = "std")]
+            icon_changed: false,
+            #[cfg(feature = "std")]
+            app_name: APP_NAME.to_string(),
+            #[cfg(feature = "std")]
+            backend: Backend::default(),
+            #[cfg(feature = "std")]
+            vsync: false,
+            #[cfg(feature = "std")]
+            stale: false,
+            #[cfg(feature = "std")]
+            latched: None,
+            #[cfg(feature = "std")]
+            key_events: Vec::new(),
+            #[cfg(feature = "std")]
+            frontend: None,
+        }
+    }
+
+    // Opens the window, which the caller keeps as windows can't move to other threads. Updating
+    // takes the screen this returns, so the display can't be shown before its window exists.
+    #[cfg(feature = "std")]
+    pub(super) fn init(&mut self) -> Result<Screen, Chip8Error> {
+        Ok(Screen { window: Some(self.open_window()?) })
+    }
+
+    #[cfg(feature = "std")]
+    fn open_window(&mut self) -> Result<Box<dyn Window>, Chip8Error> {
+        let Some(frontend) = self.frontend.as_ref() else { return Err(IoError::NoFrontend.into()) };
+        // A filter draws every cell with several pixels, so the window scales less
+        // The window library has no fullscreen mode, a borderless window fitting the screen comes closest
+        let scale = if self.fullscreen { Scale::FitScreen } else { self.scale };
+        let (cell, scale) = match self.filter {
+            Filter::None => (1, scale),
+            _ => (CELL_SIZE, filtered_scale(scale)),
+        };
+        let mut window = frontend.open_window(&WindowSettings {
+            resize: true,
+            borderless: self.fullscreen,
+            backend: self.backend,
+            vsync: self.vsync,
+            app_name: &self.app_name,
+            ..WindowSettings::new(&self.title, DISPLAY_WIDTH * cell, DISPLAY_HEIGHT * cell, scale)
+        })?;
+        if let Some((x, y)) = self.position {
+            window.set_position(x, y);
+        }
+        window.set_icon(&self.icon);
+
+        self.open = true;
+        self.stale = false;
+        self.title_changed = false;
+        self.icon_changed = false;
+        Ok(window)
+    }
+
+    // Get the state of all chip8 keys as a bitmask, bit n set if key n is held
+    #[cfg(feature = "std")]
+    pub(super) fn pressed_keys(&self, keyboard: &super::Keys) -> u16 {
+        self.held.iter()
+            .filter_map(|k| keyboard.get_by_key(k))
+            .fold(0, |mask, &key| mask | 1 << key)
+    }
+
+    // Check if a key is held, never true without a window
+    #[cfg(feature = "std")]
+    pub(super) fn is_key_down(&self, key: Key) -> bool {
+        self.held.contains(&key)
+    }
+
+    // Presses and releases of bound keys since the last call as chip8 key and whether it was pressed,
+    // unlike polling this sees keys tapped between two updates
+    #[cfg(feature = "std")]
+    pub(super) fn take_key_events(&mut self, keyboard: &super::Keys) -> Vec<(u8, bool)> {
+        self.key_events
+            .drain(..)
+            .filter_map(|(key, pressed)| keyboard.get_by_key(&key).map(|&key| (key, pressed)))
+            .collect()
+    }
+
+    // Check if a key was pressed since the last update, ignoring repeats
+    #[cfg(feature = "std")]
+    pub(super) fn is_key_pressed(&self, key: Key) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    // Set the window title, also used for windows created later
+    #[cfg(feature = "std")]
+    pub(super) fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+        self.title_changed = self.open;
+    }
+
+    // Check if the window is open
+    #[cfg(feature = "std")]
+    pub(super) fn is_open(&self) -> bool {
+        self.open
+    }
+
+    // Set color palette for the display
+    pub(super) fn set_colors(&mut self, filled: u32, empty: u32) {
+        self.colors.filled = filled;
+        self.colors.empty = empty;
+        #[cfg(feature = "std")]
+        {
+            self.dirty = true;
+        }
+    }
+    
+    // Update the display in the window init opened
+    #[cfg(feature = "std")]
+    pub(super) fn update(&mut self, screen: &mut Screen) -> Result<(), Chip8Error>{
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("render").entered();
+
+        // Reopen the window if its size changed, the old one has to go first
+        if self.stale {
+            screen.window = None;
+            screen.window = Some(self.open_window()?);
+        }
+        if !self.open {
+            screen.window = None;
+        }
+
+        // Draw a grid, unless it looks the same as last time. Fading pixels change every frame.
+        self.decay_phosphor();
+        if self.dirty || self.phosphor.is_some() {
+            self.update_buffer();
+        }
+        
+        let Some(window) = screen.window.as_mut() else { return Err(IoError::WindowNotOpen.into()) };
+        if self.title_changed {
+            window.set_title(&self.title);
+            self.title_changed = false;
+        }
+        if self.icon_changed {
+            window.set_icon(&self.icon);
+            self.icon_changed = false;
+        }
+
+        // The Megachip screen is shown as is, unless the menu or the ROM browser is open on the grid,
+        // otherwise the buffer
+        let result = match self.mega.as_mut().filter(|_| self.menu.is_none() && self.browser.is_none()) {
+            Some(mega) => window.update(mega.render(), MEGA_WIDTH, MEGA_HEIGHT),
+            None => match self.filter {
+                Filter::None => window.update(&self.buffer, DISPLAY_WIDTH, DISPLAY_HEIGHT),
+                _ => window.update(&self.output, DISPLAY_WIDTH * CELL_SIZE, DISPLAY_HEIGHT * CELL_SIZE),
+            },
+        };
+
+        // Input is read from the window here, so the emulator can run without one
+        self.held = window.get_keys();
+        self.pressed = window.get_keys_pressed(KeyRepeat::No);
+        self.key_events.extend(window.take_key_events());
+        self.open = window.is_open();
+        result
+    }
+
+    // Replace the screen, e.g. with one from a save state
+    #[cfg(feature = "std")]
+    pub(super) fn set_grid(&mut self, grid: &Grid) {
+        self.grid = *grid;
+        #[cfg(feature = "std")]
+        {
+            self.dirty = true;
+        }
+    }
+
+    // Clear the display
+    pub(super) fn clear(&mut self) {
+        self.grid = [0; DISPLAY_HEIGHT];
+        if let Some(mega) = self.mega.as_mut() {
+            mega.clear();
+        }
+        #[cfg(feature = "std")]
+        {
+            self.dirty = true;
+        }
+    }
+
+    // Switch to the Megachip screen and back, it starts out cleared
+    pub(super) fn set_megachip(&mut self, enabled: bool) {
+        if enabled != self.mega.is_some() {
+            self.mega = enabled.then(|| Box::new(MegaScreen::new()));
+            #[cfg(feature = "std")]
+            {
+                self.dirty = true;
+            }
+        }
+    }
+
+    pub(super) fn is_megachip(&self) -> bool {
+        self.mega.is_some()
+    }
+
+    pub(super) fn get_megachip(&mut self) -> Option<&mut MegaScreen> {
+        self.mega.as_deref_mut()
+    }
+
+    // The window goes with the next update
+    #[cfg(feature = "std")]
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn get_grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    // The screen as text for tests and fixtures, see draw::to_ascii
+    #[cfg(feature = "std")]
+    pub fn to_ascii(&self) -> String {
+        draw::to_ascii(&self.grid)
+    }
+
+    // Display without a window showing the screen read from text, None if it isn't one
+    #[cfg(test)]
+    pub fn from_ascii(text: &str) -> Option<Display> {
+        let mut display = Display::new();
+        display.grid = draw::from_ascii(text)?;
+        Some(display)
+    }
+
+    // Draw a sprite of up to 15 bytes on the display
+    pub(super) fn draw(&mut self, x: usize, y: usize, sprite: impl Iterator<Item = u8>) -> bool {
+        let mut bytes = [0; 15];
+        let mut len = 0;
+        for (slot, byte) in bytes.iter_mut().zip(sprite) {
+            *slot = byte;
+            len += 1;
+        }
+        #[cfg(feature = "std")]
+        {
+            self.dirty = true;
+        }
+        self.strategy.draw(&mut self.grid, x, y, &bytes[..len])
+    }
+
+    // Set the sprite drawing routine
+    pub(super) fn set_draw_strategy(&mut self, strategy: Box<dyn DrawStrategy>) {
+        self.strategy = strategy;
+    }
+
+    // Set the window scale, an open window is recreated on the next update
+    #[cfg(feature = "std")]
+    pub fn set_scale(&mut self, scale: Scale) {
+        self.scale = scale;
+        self.stale = self.open;
+    }
+
+    // Switch between a borderless window covering the screen and a normal one
+    #[cfg(feature = "std")]
+    pub(super) fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen;
+        self.stale = self.open;
+    }
+
+    // Applied when the window is opened
+    #[cfg(feature = "std")]
+    pub(super) fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+        self.stale = self.open;
+    }
+
+    #[cfg(feature = "std")]
+    pub(super) fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    #[cfg(feature = "std")]
+    pub(super) fn set_vsync(&mut self, vsync: bool) {
+        self.vsync = vsync;
+        self.stale = self.open;
+    }
+
+    // Shows the grid as it was when the last frame ended, rather than half drawn, while latched
+    #[cfg(feature = "std")]
+    pub(super) fn set_latched(&mut self, latched: bool) {
+        if latched != self.latched.is_some() {
+            self.latched = latched.then_some(self.grid);
+            self.dirty = true;
+        }
+    }
+
+    // Takes the grid of a frame that just ended, to show until the next one ends
+    #[cfg(feature = "std")]
+    pub(super) fn latch_frame(&mut self) {
+        if let Some(latched) = self.latched.as_mut().filter(|latched| **latched != self.grid) {
+            *latched = self.grid;
+            self.dirty = true;
+        }
+    }
+
+    // Opens the window, and the debugger's
+    #[cfg(feature = "std")]
+    pub(super) fn set_frontend(&mut self, frontend: Arc<dyn Frontend>) {
+        self.frontend = Some(frontend);
+        self.stale = self.open;
+    }
+
+    #[cfg(feature = "std")]
+    pub(super) fn frontend(&self) -> Option<&Arc<dyn Frontend>> {
+        self.frontend.as_ref()
+    }
+
+    // Set the window icon, also used for windows created later
+    #[cfg(feature = "std")]
+    pub(super) fn set_icon(&mut self, icon: WindowIcon) {
+        self.icon = icon;
+        self.icon_changed = self.open;
+    }
+
+    // Applied when the window is opened
+    #[cfg(feature = "std")]
+    pub(super) fn set_app_name(&mut self, name: &str) {
+        self.app_name = name.to_string();
+    }
+
+    // Top left corner of the window on the screen, applied when it is opened
+    #[cfg(feature = "std")]
+    pub(super) fn set_position(&mut self, x: isize, y: isize) {
+        self.position = Some((x, y));
+    }
+
+    #[cfg(feature = "std")]
+    pub(super) fn is_fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    #[cfg(feature = "std")]
+    pub fn get_scale(&self) -> Scale {
+        self.scale
+    }
+
+    // Get the filled and empty colors
+    pub(super) fn get_colors(&self) -> (u32, u32) {
+        (self.colors.filled, self.colors.empty)
+    }
+
+    // Save the grid as a PNG image, scaled and colored like the window
+    #[cfg(feature = "std")]
+    pub(super) fn screenshot(&self, path: &Path) -> Result<(), Chip8Error> {
+        Ok(self.screenshot_image().save(path).map_err(IoError::Image)?)
+    }
+
+    // The screenshot as the bytes of a PNG file
+    #[cfg(feature = "remote")]
+    pub(super) fn screenshot_png(&self) -> Result<Vec<u8>, Chip8Error> {
+        let mut png = std::io::Cursor::new(Vec::new());
+        self.screenshot_image().write_to(&mut png, image::ImageFormat::Png).map_err(IoError::Image)?;
+        Ok(png.into_inner())
+    }
+
+    #[cfg(feature = "std")]
+    fn screenshot_image(&self) -> RgbImage {
+        let factor = self.scale.factor();
+        let width = (DISPLAY_WIDTH * factor) as u32;
+        let height = (DISPLAY_HEIGHT * factor) as u32;
+        RgbImage::from_fn(width, height, |x, y| {
+            let filled = draw::is_lit(&self.grid, x as usize / factor, y as usize / factor);
+            let color = if filled { self.colors.filled } else { self.colors.empty };
+            Rgb([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+        })
+    }
+
+    // Highlight the 8 pixel wide area a sprite drawn at x, y would cover, clipped or wrapped at the
+    // edges as the draw strategy would draw it
+    #[cfg(feature = "std")]
+    pub(super) fn set_highlight(&mut self, highlight: Option<(usize, usize, usize)>) {
+        self.dirty |= self.highlight != highlight;
+        self.highlight = highlight;
+    }
+
+    // Show or hide the pause menu
+    #[cfg(feature = "std")]
+    pub(super) fn set_menu(&mut self, menu: Option<PauseMenu>) {
+        self.menu = menu;
+        self.dirty = true;
+    }
+
+    #[cfg(feature = "std")]
+    pub(super) fn set_notice(&mut self, notice: Option<&'static str>) {
+        self.notice = notice;
+        self.dirty = true;
+    }
+
+    // The menu may be navigated, so it's drawn again
+    #[cfg(feature = "std")]
+    pub(super) fn get_menu(&mut self) -> Option<&mut PauseMenu> {
+        self.dirty |= self.menu.is_some();
+        self.menu.as_mut()
+    }
+
+    // Show or hide the ROM browser
+    #[cfg(feature = "std")]
+    pub(super) fn set_browser(&mut self, browser: Option<RomBrowser>) {
+        self.browser = browser;
+        self.dirty = true;
+    }
+
+    #[cfg(feature = "std")]
+    pub(super) fn get_browser(&mut self) -> Option<&mut RomBrowser> {
+        self.dirty |= self.browser.is_some();
+        self.browser.as_mut()
+    }
+
+    // Let erased pixels fade out over a few frames like on a CRT, hiding the flicker of XOR drawing
+    #[cfg(feature = "std")]
+    pub(super) fn set_phosphor(&mut self, enabled: bool) {
+        self.phosphor = enabled.then(|| Box::new([0.0; DISPLAY_WIDTH * DISPLAY_HEIGHT]));
+        self.dirty = true;
+    }
+
+    // Advance the fading by a frame, lit pixels are at full brightness
+    #[cfg(feature = "std")]
+    fn decay_phosphor(&mut self) {
+        let Some(phosphor) = self.phosphor.as_mut() else { return };
+        let grid = self.latched.as_ref().unwrap_or(&self.grid);
+        for (lit, brightness) in draw::pixels(grid).zip(phosphor.iter_mut()) {
+            *brightness = if lit { 1.0 } else { *brightness * PHOSPHOR_DECAY };
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(super) fn set_sound_indicator(&mut self, indicator: SoundIndicator) {
+        self.sound_indicator = indicator;
+        self.dirty = true;
+    }
+
+    // The indicator shows from the frame the sound timer starts until it runs out
+    #[cfg(feature = "std")]
+    pub(super) fn set_beeping(&mut self, beeping: bool) {
+        self.dirty |= self.beeping != beeping && self.sound_indicator != SoundIndicator::None;
+        self.beeping = beeping;
+    }
+
+    // Set the post-processing filter, takes effect for a window created afterwards
+    #[cfg(feature = "std")]
+    pub(super) fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+        self.dirty = true;
+        let size = match filter {
+            Filter::None => 0,
+            _ => DISPLAY_WIDTH * DISPLAY_HEIGHT * CELL_SIZE * CELL_SIZE,
+        };
+        self.output = vec![0; size];
+    }
+
+    // Update buffer with grid, both are laid out row by row
+    #[cfg(feature = "std")]
+    pub(super) fn update_buffer(&mut self) {
+        let Colors { filled, empty } = self.colors;
+        let grid = self.latched.as_ref().unwrap_or(&self.grid);
+        match self.phosphor.as_ref() {
+            Some(phosphor) => for (lit, (color, &brightness)) in draw::pixels(grid).zip(self.buffer.iter_mut().zip(phosphor.iter())) {
+                *color = if lit { filled } else { mix(empty, filled, brightness) };
+            },
+            // Eight pixels at a time, a byte of the row each
+            None => for (byte, colors) in grid.iter().flat_map(|row| row.to_be_bytes()).zip(self.buffer.chunks_exact_mut(8)) {
+                for (i, color) in colors.iter_mut().enumerate() {
+                    *color = if byte & (0x80 >> i) != 0 { filled } else { empty };
+                }
+            },
+        }
+        self.dirty = false;
+
+        if let Some((x, y, height)) = self.highlight {
+            let mut covered = [0; DISPLAY_HEIGHT];
+            self.strategy.draw(&mut covered, x, y, &[0xff; DISPLAY_HEIGHT][..height.min(DISPLAY_HEIGHT)]);
+            for (lit, color) in draw::pixels(&covered).zip(self.buffer.iter_mut()) {
+                if lit {
+                    *color = blend(*color, HIGHLIGHT_COLOR);
+                }
+            }
+        }
+
+        if let Some(menu) = self.menu.as_ref() {
+            menu.render(&mut self.buffer, self.colors.filled, self.colors.empty);
+        }
+
+        if let Some(browser) = self.browser.as_ref() {
+            browser.render(&mut self.buffer, self.colors.filled, self.colors.empty);
+        }
+
+        if let Some(notice) = self.notice {
+            notify::render(&mut self.buffer, notice, self.colors.filled, self.colors.empty);
+        }
+
+        if self.beeping {
+            self.sound_indicator.apply(&mut self.buffer, DISPLAY_WIDTH, DISPLAY_HEIGHT);
+        }
+
+        if self.filter != Filter::None {
+            self.filter.apply(&self.buffer, DISPLAY_WIDTH, &mut self.output);
+        }
+    }
+
+    
+}
+
+// Mixes two colors half and half
+#[cfg(feature = "std")]
+fn blend(a: u32, b: u32) -> u32 {
+    ((a & 0xfefefe) >> 1) + ((b & 0xfefefe) >> 1)
+}
+
+// Moves color a towards b by t, from 0.0 to 1.0
+#[cfg(feature = "std")]
+fn mix(a: u32, b: u32, t: f32) -> u32 {
+    [16, 8, 0].iter().fold(0, |color, shift| {
+        let (a, b) = (((a >> shift) & 0xff) as f32, ((b >> shift) & 0xff) as f32);
+        color | ((a + (b - a) * t).round() as u32) << shift
+    })
+}
+
+// Window scales in increasing size
+#[cfg(feature = "std")]
+const SCALES: [Scale; 6] = [Scale::X1, Scale::X2, Scale::X4, Scale::X8, Scale::X16, Scale::X32];
+
+// Next bigger or smaller window scale, staying at the ends
+#[cfg(feature = "std")]
+pub(super) fn next_scale(scale: Scale, bigger: bool) -> Scale {
+    let current = SCALES.iter().position(|&s| s.factor() == scale.factor()).unwrap_or(0);
+    let next = if bigger { (current + 1).min(SCALES.len() - 1) } else { current.saturating_sub(1) };
+    SCALES[next]
+}
+
+// Window scale that keeps the size of the given one when every cell is CELL_SIZE pixels wide
+#[cfg(feature = "std")]
+fn filtered_scale(scale: Scale) -> Scale {
+    match scale.factor() / CELL_SIZE {
+        _ if matches!(scale, Scale::FitScreen) => Scale::FitScreen,
+        0 | 1 => Scale::X1,
+        2 => Scale::X2,
+        4 => Scale::X4,
+        _ => Scale::X8,
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Colors {
+    filled: u32,
+    empty: u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::draw::Wrap;
+    use super::super::frontend::TestFrontend;
+
+    #[test]
+    fn test_highlight() {
+        let mut display = Display::new();
+        display.set_highlight(Some((62, 31, 2)));
+        display.update_buffer();
+        assert_eq!(display.buffer[62 + 31 * DISPLAY_WIDTH], 0x7f7f00);
+        assert_eq!(display.buffer[5], 0x000000); // Clipped at the edges like sprites
+        assert_eq!(display.buffer[61 + 31 * DISPLAY_WIDTH], 0x000000);
+
+        display.set_draw_strategy(Box::new(Wrap));
+        display.update_buffer();
+        assert_eq!(display.buffer[62 + 31 * DISPLAY_WIDTH], 0x7f7f00);
+        assert_eq!(display.buffer[5], 0x7f7f00); // Wraps to the top left
+        assert_eq!(display.buffer[6], 0x000000);
+        assert_eq!(display.buffer[61 + 31 * DISPLAY_WIDTH], 0x000000);
+
+        display.set_highlight(None);
+        display.update_buffer();
+        assert!(display.buffer.iter().all(|&color| color == 0x000000));
+    }
+
+    #[test]
+    fn test_phosphor() {
+        let mut display = Display::new();
+        display.set_phosphor(true);
+        display.draw(0, 0, [0x80].into_iter());
+        display.decay_phosphor();
+        display.draw(0, 0, [0x80].into_iter()); // Erased again
+        display.decay_phosphor();
+        display.update_buffer();
+        assert_eq!(display.buffer[0], 0x808080);
+        for _ in 0..10 {
+            display.decay_phosphor();
+        }
+        display.update_buffer();
+        assert_eq!(display.buffer[0], 0x000000);
+    }
+
+    #[test]
+    fn test_sound_indicator() {
+        let mut display = Display::new();
+        display.set_sound_indicator(SoundIndicator::Corner);
+        display.update_buffer();
+        display.set_beeping(true);
+        assert!(display.dirty);
+        display.update_buffer();
+        assert_eq!(display.buffer[DISPLAY_WIDTH - 1], 0xffb000);
+        assert_eq!(display.buffer[0], 0x000000);
+
+        display.set_beeping(false);
+        display.update_buffer();
+        assert!(display.buffer.iter().all(|&color| color == 0x000000));
+    }
+
+    #[test]
+    fn test_mix() {
+        assert_eq!(mix(0x000000, 0xffffff, 0.0), 0x000000);
+        assert_eq!(mix(0x000000, 0xffffff, 1.0), 0xffffff);
+        assert_eq!(mix(0x800080, 0xffc0cb, 0.5), 0xc060a6);
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut display = Display::new();
+        display.set_filter(Filter::Scanlines);
+        display.draw(0, 0, [0x80].into_iter());
+        display.update_buffer();
+        assert_eq!(display.output.len(), DISPLAY_WIDTH * DISPLAY_HEIGHT * CELL_SIZE * CELL_SIZE);
+        assert_eq!(display.output[0], 0xffffff);
+        assert_eq!(display.output[CELL_SIZE], 0x000000);
+        assert_eq!(display.output[(CELL_SIZE - 1) * DISPLAY_WIDTH * CELL_SIZE], 0x7f7f7f);
+
+        display.set_filter(Filter::None);
+        assert!(display.output.is_empty());
+        assert_eq!(filtered_scale(Scale::X16) as u32, Scale::X4 as u32);
+        assert_eq!(filtered_scale(Scale::X2) as u32, Scale::X1 as u32);
+    }
+
+    #[test]
+    fn test_next_scale() {
+        assert_eq!(next_scale(Scale::X8, true) as u32, Scale::X16 as u32);
+        assert_eq!(next_scale(Scale::X8, false) as u32, Scale::X4 as u32);
+        assert_eq!(next_scale(Scale::X32, true) as u32, Scale::X32 as u32);
+        assert_eq!(next_scale(Scale::X1, false) as u32, Scale::X1 as u32);
+        assert_eq!(next_scale(Scale::FitScreen, false) as u32, Scale::X8 as u32);
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut display = Display::new();
+        let sprite = vec![0b10000000, 0b01000000, 0b00100000, 0b00010000, 0b00001000];
+        let collision = display.draw(0, 0, sprite.iter().copied());
+        assert_eq!(collision, false);
+        for i in 0..5 {
+            assert!(draw::is_lit(&display.grid, i, i));
+        }
+        assert_eq!(display.grid[0], 1 << 63);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut display = Display::new();
+        let sprite = vec![0b10000000, 0b01000000, 0b00100000, 0b00010000, 0b00001000];
+        display.draw(0, 0, sprite.iter().copied());
+        display.clear();
+        assert_eq!(display.grid, [0; DISPLAY_HEIGHT]);
+    }
+
+    #[test]
+    fn test_update_buffer() {
+        let mut display = Display::new();
+        let sprite = vec![0b10000000, 0b01000000, 0b00100000, 0b00010000, 0b00001000];
+        display.draw(0, 0, sprite.iter().copied());
+        display.update_buffer();
+        assert_eq!(display.buffer[0], display.colors.filled);
+        assert_eq!(display.buffer[1 + DISPLAY_WIDTH], display.colors.filled);
+        assert_eq!(display.buffer[2 + 2 * DISPLAY_WIDTH], display.colors.filled);
+        assert_eq!(display.buffer[3 + 3 * DISPLAY_WIDTH], display.colors.filled);
+        assert_eq!(display.buffer[4 + 4 * DISPLAY_WIDTH], display.colors.filled);
+    }
+
+    #[test]
+    fn test_latched_frame() {
+        let mut display = Display::new();
+        display.set_latched(true);
+        display.draw(0, 0, [0b10000000].into_iter());
+
+        // A frame still being drawn isn't shown until it ends
+        display.update_buffer();
+        assert_eq!(display.buffer[0], display.colors.empty);
+        display.latch_frame();
+        display.update_buffer();
+        assert_eq!(display.buffer[0], display.colors.filled);
+
+        // Unlatched, drawing shows right away
+        display.clear();
+        display.set_latched(false);
+        display.update_buffer();
+        assert_eq!(display.buffer[0], display.colors.empty);
+    }
+
+    #[test]
+    fn test_update_skips_unchanged_buffer() {
+        let mut display = Display::new();
+        display.set_frontend(Arc::new(TestFrontend));
+        let mut screen = display.init().unwrap();
+        display.update_buffer();
+        display.buffer[0] = 0x123456;
+        display.update(&mut screen).unwrap();
+        assert_eq!(display.buffer[0], 0x123456); // Nothing changed, so it wasn't redrawn
+
+        display.draw(8, 0, [0x80].into_iter());
+        display.update(&mut screen).unwrap();
+        assert_eq!(display.buffer[0], 0x000000);
+        assert_eq!(display.buffer[8], 0xffffff);
+
+        display.set_highlight(None); // Unchanged
+        assert!(!display.dirty);
+        display.clear();
+        assert!(display.dirty);
+    }
+
+    #[test]
+    fn test_set_colors() {
+        let mut display = Display::new();
+        display.set_colors(0x123456, 0x654321);
+        assert_eq!(display.colors.filled, 0x123456);
+        assert_eq!(display.colors.empty, 0x654321);
+    }
+
+    #[test]
+    fn test_set_scale() {
+        let mut display = Display::new();
+        display.set_scale(Scale::X2);
+        assert_eq!(display.scale as u32, Scale::X2 as u32);
+    }
+
+    #[test]
+    fn test_screenshot() {
+        let mut display = Display::new();
+        display.set_colors(0x123456, 0x654321);
+        display.set_scale(Scale::X2);
+        display.draw(0, 0, [0b10000000].into_iter());
+        let path = std::env::temp_dir().join("chip8_test_screenshot.png");
+        display.screenshot(&path).unwrap();
+        let image = image::open(&path).unwrap().to_rgb8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(image.dimensions(), (DISPLAY_WIDTH as u32 * 2, DISPLAY_HEIGHT as u32 * 2));
+        assert_eq!(image.get_pixel(1, 1), &Rgb([0x12, 0x34, 0x56]));
+        assert_eq!(image.get_pixel(2, 2), &Rgb([0x65, 0x43, 0x21]));
+    }
+
+    #[test]
+    fn test_ascii() {
+        let mut display = Display::new();
+        display.draw(0, 0, [0b10100000].into_iter());
+        let text = display.to_ascii();
+        assert!(text.starts_with("#.#..."));
+        assert_eq!(Display::from_ascii(&text).unwrap().grid, display.grid);
+        assert!(Display::from_ascii("#.#").is_none());
+    }
+
+    #[test]
+    fn test_keys_without_window() {
+        let display = Display::new();
+        assert!(!display.is_open());
+        assert_eq!(display.pressed_keys(&super::super::Keys::get_default()), 0);
+    }
+
+    #[test]
+    fn test_init() {
+        let mut display = Display::new();
+        assert!(matches!(display.init(), Err(Chip8Error::Io(IoError::NoFrontend))));
+        display.set_frontend(Arc::new(TestFrontend));
+        let screen = display.init().unwrap();
+        assert!(screen.window.as_ref().is_some_and(|window| window.is_open()));
+        assert!(display.is_open());
+    }
+
+    #[test]
+    fn test_close() {
+        let mut display = Display::new();
+        display.set_frontend(Arc::new(TestFrontend));
+        let mut screen = display.init().unwrap();
+        display.close();
+        assert!(!display.is_open());
+        assert!(matches!(display.update(&mut screen), Err(Chip8Error::Io(IoError::WindowNotOpen))));
+        assert!(screen.window.is_none());
+    }
+
+    #[test]
+    fn test_set_icon() {
+        let mut display = Display::new();
+        display.set_icon(WindowIcon::bundled());
+        assert!(!display.icon_changed); // Shown once the window opens
+
+        // The open window takes the new icon at the next update
+        display.set_frontend(Arc::new(TestFrontend));
+        let mut screen = display.init().unwrap();
+        display.set_icon(WindowIcon::bundled());
+        assert!(display.icon_changed);
+        display.update(&mut screen).unwrap();
+        assert!(!display.icon_changed);
+    }
+}
\ No newline at end of file
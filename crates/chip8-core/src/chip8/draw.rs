@@ -0,0 +1,200 @@
+use alloc::string::String;
+use super::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+// Display pixels, one row per scanline from the top, the most significant bit is the leftmost pixel
+pub type Grid = [u64; DISPLAY_HEIGHT];
+
+const _: () = assert!(DISPLAY_WIDTH == 64, "a row of the grid has to fit in a u64");
+
+// Whether the pixel at x, y is lit, both have to be on the screen
+pub fn is_lit(grid: &Grid, x: usize, y: usize) -> bool {
+    grid[y] & (1 << (DISPLAY_WIDTH - 1 - x)) != 0
+}
+
+// Whether every pixel is lit, row by row from the top left
+pub fn pixels(grid: &Grid) -> impl Iterator<Item = bool> + '_ {
+    grid.iter().flat_map(|&row| (0..DISPLAY_WIDTH).map(move |x| row & (1 << (DISPLAY_WIDTH - 1 - x)) != 0))
+}
+
+// The rows as text, # for lit and . for dark pixels with every row on its own line
+pub fn to_ascii(rows: &[u64]) -> String {
+    rows.iter()
+        .map(|&row| (0..DISPLAY_WIDTH).map(|x| if row & (1 << (DISPLAY_WIDTH - 1 - x)) != 0 { '#' } else { '.' }).collect::<String>() + "\n")
+        .collect()
+}
+
+// Reads a grid written by to_ascii, None unless it has exactly a line of # and . for every row
+pub fn from_ascii(text: &str) -> Option<Grid> {
+    let mut grid = [0; DISPLAY_HEIGHT];
+    let mut lines = text.lines();
+    for row in grid.iter_mut() {
+        let line = lines.next()?;
+        if line.len() != DISPLAY_WIDTH {
+            return None;
+        }
+        for pixel in line.bytes() {
+            *row = *row << 1 | match pixel {
+                b'#' => 1,
+                b'.' => 0,
+                _ => return None,
+            };
+        }
+    }
+    lines.next().is_none().then_some(grid)
+}
+
+// Sprite drawing routine, XORs the sprite onto the grid and reports if any pixel was erased.
+// Implementations are checked against a pixel by pixel reference by the tests below.
+pub trait DrawStrategy: Send {
+    fn draw(&self, grid: &mut Grid, x: usize, y: usize, sprite: &[u8]) -> bool;
+}
+
+// Pixels past an edge reappear on the opposite side
+pub struct Wrap;
+
+impl DrawStrategy for Wrap {
+    fn draw(&self, grid: &mut Grid, x: usize, y: usize, sprite: &[u8]) -> bool {
+        let mut collision = false;
+        for (j, &byte) in sprite.iter().enumerate() {
+            let row = &mut grid[(y + j) % DISPLAY_HEIGHT];
+            let mask = ((byte as u64) << 56).rotate_right((x % DISPLAY_WIDTH) as u32);
+            collision |= *row & mask != 0;
+            *row ^= mask;
+        }
+        collision
+    }
+}
+
+// Sprite origin wraps, but pixels past an edge are not drawn
+pub struct Clip;
+
+impl DrawStrategy for Clip {
+    fn draw(&self, grid: &mut Grid, x: usize, y: usize, sprite: &[u8]) -> bool {
+        let x = x % DISPLAY_WIDTH;
+        let y = y % DISPLAY_HEIGHT;
+        let mut collision = false;
+        for (row, &byte) in grid[y..].iter_mut().zip(sprite) {
+            let mask = ((byte as u64) << 56) >> x; // Columns past the right edge are shifted out
+            collision |= *row & mask != 0;
+            *row ^= mask;
+        }
+        collision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Deterministic pseudo-random bytes for the conformance cases
+    fn bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(0x9e3779b97f4a7c15) | 1;
+        (0..len).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        }).collect()
+    }
+
+    // Grid with some pixels already set
+    fn grid(seed: u64) -> Grid {
+        let mut grid = [0; DISPLAY_HEIGHT];
+        for (row, bytes) in grid.iter_mut().zip(bytes(seed, DISPLAY_HEIGHT * 16).chunks(16)) {
+            let (a, b) = bytes.split_at(8);
+            *row = u64::from_be_bytes(a.try_into().unwrap()) & u64::from_be_bytes(b.try_into().unwrap()); // About a quarter lit
+        }
+        grid
+    }
+
+    // Draws pixel by pixel, wrapping or clipping at the edges
+    fn reference(grid: &mut Grid, x: usize, y: usize, sprite: &[u8], wrap: bool) -> bool {
+        let mut collision = false;
+        for (j, byte) in sprite.iter().enumerate() {
+            for i in 0..8 {
+                let (px, py) = (x % DISPLAY_WIDTH + i, y % DISPLAY_HEIGHT + j);
+                if byte & (0x80 >> i) == 0 || (!wrap && (px >= DISPLAY_WIDTH || py >= DISPLAY_HEIGHT)) {
+                    continue;
+                }
+                let bit = 1 << (DISPLAY_WIDTH - 1 - px % DISPLAY_WIDTH);
+                let row = &mut grid[py % DISPLAY_HEIGHT];
+                collision |= *row & bit != 0;
+                *row ^= bit;
+            }
+        }
+        collision
+    }
+
+    // Draws with the strategy and the reference from the same state and checks they agree
+    fn assert_conforms(strategy: &dyn DrawStrategy, wrap: bool, x: usize, y: usize, seed: u64) {
+        let sprite = bytes(seed, 1 + seed as usize % 15);
+        let (mut grid_a, mut grid_b) = (grid(seed), grid(seed));
+        let collision_a = strategy.draw(&mut grid_a, x, y, &sprite);
+        let collision_b = reference(&mut grid_b, x, y, &sprite, wrap);
+        assert_eq!(collision_a, collision_b, "collision at ({x}, {y}) seed {seed}");
+        assert_eq!(grid_a, grid_b, "grid at ({x}, {y}) seed {seed}");
+    }
+
+    #[test]
+    fn test_wrap_conforms() {
+        for seed in 0..200 {
+            let x = seed as usize * 7 % 256;
+            let y = seed as usize * 3 % 256;
+            assert_conforms(&Wrap, true, x, y, seed);
+        }
+    }
+
+    #[test]
+    fn test_clip_conforms() {
+        for seed in 0..200 {
+            let x = seed as usize * 7 % 256;
+            let y = seed as usize * 3 % 256;
+            assert_conforms(&Clip, false, x, y, seed);
+        }
+    }
+
+    #[test]
+    fn test_ascii() {
+        let mut grid = [0; DISPLAY_HEIGHT];
+        Wrap.draw(&mut grid, 62, 0, &[0xC0, 0x80]);
+        let text = to_ascii(&grid);
+        assert_eq!(text.lines().count(), DISPLAY_HEIGHT);
+        assert_eq!(text.lines().next(), Some(format!("{}##", ".".repeat(62)).as_str()));
+        assert_eq!(text.lines().nth(1), Some(format!("{}#.", ".".repeat(62)).as_str()));
+        assert_eq!(from_ascii(&text), Some(grid));
+
+        assert_eq!(from_ascii(text.trim_end_matches('\n').strip_suffix(&".".repeat(DISPLAY_WIDTH)).unwrap()), None); // A row short
+        assert_eq!(from_ascii(&text.replacen('#', "x", 1)), None);
+        assert_eq!(from_ascii(&format!("{text}{}", ".".repeat(DISPLAY_WIDTH))), None);
+    }
+
+    #[test]
+    fn test_erasing_sets_collision() {
+        let strategies: [&dyn DrawStrategy; 2] = [&Wrap, &Clip];
+        for strategy in strategies {
+            let mut grid = [0; DISPLAY_HEIGHT];
+            assert!(!strategy.draw(&mut grid, 60, 30, &[0xFF, 0xFF]));
+            assert!(strategy.draw(&mut grid, 60, 30, &[0x80]));
+            assert!(!is_lit(&grid, 60, 30));
+        }
+    }
+
+    #[test]
+    fn test_wrap_at_edges() {
+        let mut grid = [0; DISPLAY_HEIGHT];
+        Wrap.draw(&mut grid, 62, 31, &[0xF0, 0xF0]);
+        assert!(is_lit(&grid, 63, 31) && is_lit(&grid, 0, 31) && is_lit(&grid, 1, 0));
+    }
+
+    #[test]
+    fn test_clip_at_edges() {
+        let mut grid = [0; DISPLAY_HEIGHT];
+        Clip.draw(&mut grid, 62, 31, &[0xF0, 0xF0]);
+        assert!(is_lit(&grid, 62, 31) && is_lit(&grid, 63, 31));
+        assert!(!is_lit(&grid, 0, 31) && !is_lit(&grid, 0, 0) && !is_lit(&grid, 62, 0));
+
+        // The origin itself wraps
+        Clip.draw(&mut grid, 64 + 2, 32 + 2, &[0x80]);
+        assert!(is_lit(&grid, 2, 2));
+    }
+}
@@ -0,0 +1,102 @@
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Point, Size};
+use embedded_graphics_core::pixelcolor::PixelColor;
+use embedded_graphics_core::primitives::Rectangle;
+use super::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use super::frame::FrameBuffer;
+
+// Draws frames on an embedded-graphics target, e.g. an SSD1306 or ST7789 driver, so a
+// microcontroller can show the screen. Pass it the frames from Chip8::on_frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedScreen<C> {
+    filled: C,
+    empty: C,
+    origin: Point, // Top left corner on the target
+    scale: u32, // Target pixels per chip8 pixel in each direction
+}
+
+impl<C: PixelColor> EmbeddedScreen<C> {
+    // BinaryColor::On and Off for monochrome displays
+    pub fn new(filled: C, empty: C) -> Self {
+        EmbeddedScreen { filled, empty, origin: Point::zero(), scale: 1 }
+    }
+
+    // A 128x64 SSD1306 fits the screen at scale 2
+    pub fn scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    pub fn origin(mut self, origin: Point) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    // Area the screen covers on the target
+    pub fn bounds(&self) -> Rectangle {
+        let size = Size::new(DISPLAY_WIDTH as u32, DISPLAY_HEIGHT as u32) * self.scale;
+        Rectangle::new(self.origin, size)
+    }
+
+    // Writes every pixel of the frame in one go, drivers clip what falls outside the display
+    pub fn draw<D: DrawTarget<Color = C>>(&self, frame: &FrameBuffer, target: &mut D) -> Result<(), D::Error> {
+        let scale = self.scale as usize;
+        let (width, height) = (DISPLAY_WIDTH * scale, DISPLAY_HEIGHT * scale);
+        let colors = (0..width * height).map(|n| {
+            let (x, y) = (n % width / scale, n / width / scale);
+            if frame.is_lit(x, y) { self.filled } else { self.empty }
+        });
+        target.fill_contiguous(&self.bounds(), colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics_core::{Pixel, pixelcolor::BinaryColor};
+
+    // Monochrome target keeping the lit pixels
+    struct Target {
+        size: Size,
+        lit: Vec<Point>,
+    }
+
+    impl embedded_graphics_core::geometry::OriginDimensions for Target {
+        fn size(&self) -> Size {
+            self.size
+        }
+    }
+
+    impl DrawTarget for Target {
+        type Color = BinaryColor;
+        type Error = std::convert::Infallible;
+
+        fn draw_iter<I: IntoIterator<Item = Pixel<BinaryColor>>>(&mut self, pixels: I) -> Result<(), Self::Error> {
+            for Pixel(point, color) in pixels {
+                if color.is_on() && Rectangle::new(Point::zero(), self.size).contains(point) {
+                    self.lit.push(point);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut grid = [0; DISPLAY_HEIGHT];
+        grid[1] = 1 << 63; // Top left pixel of the second row
+        let frame = FrameBuffer::new(&grid, 0xffffff, 0x000000);
+
+        let mut target = Target { size: Size::new(128, 64), lit: Vec::new() };
+        let screen = EmbeddedScreen::new(BinaryColor::On, BinaryColor::Off).scale(2);
+        screen.draw(&frame, &mut target).unwrap();
+        assert_eq!(screen.bounds().size, target.size);
+        assert_eq!(target.lit, [Point::new(0, 2), Point::new(1, 2), Point::new(0, 3), Point::new(1, 3)]);
+
+        // Clipped past the edge of the target
+        let mut target = Target { size: Size::new(128, 64), lit: Vec::new() };
+        let screen = EmbeddedScreen::new(BinaryColor::On, BinaryColor::Off).origin(Point::new(127, 62));
+        screen.draw(&frame, &mut target).unwrap();
+        assert_eq!(target.lit, [Point::new(127, 63)]);
+    }
+}
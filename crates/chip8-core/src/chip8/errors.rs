@@ -0,0 +1,149 @@
+use alloc::{format, string::{String, ToString}};
+#[cfg(feature = "std")]
+use std::{io, path::PathBuf};
+use thiserror::Error;
+
+// Any error of the emulator, by the part of it that failed.
+// The underlying cause, if any, is kept as the source of the error.
+#[derive(Debug, Error)]
+pub enum Chip8Error {
+    #[error(transparent)]
+    Load(#[from] LoadError),
+    #[error(transparent)]
+    Exec(#[from] ExecError),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+// Programs, settings and the files they come from that can't be read or used
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[cfg(feature = "std")]
+    #[error("Failed to read {}", .path.display())]
+    FileRead { path: PathBuf, source: io::Error },
+    #[cfg(feature = "std")]
+    #[error("Failed to read program")]
+    Read(#[source] io::Error),
+    #[error("Expected a file path as the argument")]
+    MissingFilePath,
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Program is {0} bytes, but only {1} bytes of memory are available for it")]
+    ProgramTooLarge(usize, usize),
+    #[error("Invalid font: {0}")]
+    InvalidFont(String),
+    #[cfg(feature = "std")]
+    #[error("Not a valid input recording: {}", .0.display())]
+    InvalidRecording(PathBuf),
+    #[cfg(feature = "std")]
+    #[error("Invalid ROM database")]
+    Database(#[source] serde_json::Error),
+    #[error("Invalid cheat file: {0}")]
+    InvalidPatch(String),
+    #[error("Script error: {0}")]
+    Script(String),
+    #[error("Failed to assemble: {0}")]
+    Assembly(String),
+    #[error("Invalid label file: {0}")]
+    InvalidLabels(String),
+    #[error("Invalid trace at line {0}")]
+    InvalidTrace(usize),
+    #[cfg(feature = "std")]
+    #[error("{0} is not a screen of # and . characters")]
+    InvalidScreen(PathBuf),
+}
+
+// Programs that can't go on running
+#[derive(Debug, Error)]
+pub enum ExecError {
+    #[error("Unrecognized opcode: {0:#X} at {1:#X}")]
+    UnrecognizedOpcode(u16, u16),
+    #[error("Address {0:#X} set at {1:#X} is past the end of memory")]
+    AddressOutOfRange(u32, u16),
+    #[error("Write to {0:#X} below the program start by the instruction at {1:#X}, the memory is reserved for the interpreter")]
+    ReservedWrite(u16, u16),
+    #[error("Stack overflow: subroutine called at {0:#X} nests too deep")]
+    StackOverflow(u16),
+    #[error("Stack underflow: return at {0:#X} outside of a subroutine")]
+    StackUnderflow(u16),
+    #[error("Script error: {0}")]
+    Script(String),
+    #[error("Netplay desynced at frame {0}, the other player's machine is in a different state")]
+    Desync(u64),
+}
+
+// Windows, devices and files the emulator fails to use while running
+#[derive(Debug, Error)]
+pub enum IoError {
+    #[error("Window creation error: {0}")]
+    WindowCreation(String),
+    #[error("Window update error: {0}")]
+    WindowUpdate(String),
+    #[error("SDL error: {0}")]
+    Sdl(String),
+    #[error("GPU window error: {0}")]
+    Gpu(String),
+    #[error("The window is not open, it is opened by Chip8::run")]
+    WindowNotOpen,
+    #[error("No frontend to open windows with, set one with Chip8::set_frontend")]
+    NoFrontend,
+    #[cfg(feature = "std")]
+    #[error("Image error")]
+    Image(#[source] image::ImageError),
+    #[cfg(feature = "std")]
+    #[error("Input recording error")]
+    Recording(#[source] io::Error),
+    #[cfg(feature = "std")]
+    #[error("GIF recording error")]
+    Gif(#[source] gif::EncodingError),
+    #[cfg(feature = "std")]
+    #[error("Storage error: {key}")]
+    Storage { key: String, source: io::Error },
+    #[error("Storage error: invalid key {0:?}")]
+    InvalidStorageKey(String),
+    #[error("Audio initialization error: {0}")]
+    AudioInit(String),
+    #[cfg(feature = "std")]
+    #[error("Remote debugging error")]
+    Remote(#[source] io::Error),
+    #[cfg(feature = "std")]
+    #[error("Trace writing error")]
+    Trace(#[source] io::Error),
+    #[cfg(feature = "std")]
+    #[error("Screen saving error")]
+    Screen(#[source] io::Error),
+    #[cfg(feature = "std")]
+    #[error("Netplay error")]
+    Netplay(#[source] io::Error),
+    #[cfg(feature = "std")]
+    #[error("HTTP API error")]
+    Api(#[source] io::Error),
+    #[cfg(feature = "std")]
+    #[error("Event log error")]
+    Events(#[source] io::Error),
+}
+
+// The error followed by its causes, e.g. "Failed to read a.ch8: No such file or directory"
+pub fn report(err: &dyn core::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let source = io::Error::new(io::ErrorKind::NotFound, "No such file or directory");
+        let err = Chip8Error::from(LoadError::FileRead { path: PathBuf::from("a.ch8"), source });
+        assert_eq!(err.to_string(), "Failed to read a.ch8");
+        assert_eq!(report(&err), "Failed to read a.ch8: No such file or directory");
+        assert_eq!(report(&Chip8Error::from(IoError::WindowNotOpen)), "The window is not open, it is opened by Chip8::run");
+    }
+}
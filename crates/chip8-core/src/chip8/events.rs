@@ -0,0 +1,95 @@
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::{fs::File, io::{self, BufWriter, Write}, path::Path};
+use serde::Serialize;
+use super::watchpoint::Access;
+#[cfg(feature = "std")]
+use super::errors::{Chip8Error, IoError};
+
+// Something that happened while running, for tools analyzing a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Instruction { pc: u16, opcode: u16 }, // Before it executes
+    Draw { x: u8, y: u8, height: u16, collision: bool }, // Height in pixels, up to 256 for Megachip sprites
+    Key { key: u8, pressed: bool }, // The key state the program sees changed
+    Timer { timer: Timer, value: u8 }, // The program set a timer
+    Error { message: String }, // Execution stopped
+    Watchpoint { addr: u16, access: Access, pc: u16, opcode: u16 }, // Execution paused on a watched access by the instruction
+    ReservedWrite { addr: u16, pc: u16, opcode: u16 }, // The instruction wrote below the program start, with protection warning
+    SelfModify { addr: u16, pc: u16, opcode: u16 }, // The instruction wrote to the program's code, with warnings on
+    Breakpoint { breakpoint: String, pc: u16, instruction: String, write: Option<u16> }, // Execution paused at the instruction, disassembled, or after its write to code
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Timer {
+    Delay,
+    Sound,
+}
+
+// A line of the log, the event stamped with the emulated frame it happened in
+#[cfg(feature = "std")]
+#[derive(Serialize)]
+struct Line<'a> {
+    frame: u64,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+// Writes events as JSON, one object per line, e.g.
+// {"frame":12,"event":"draw","x":8,"y":4,"height":5,"collision":false}
+#[cfg(feature = "std")]
+pub struct EventLog {
+    writer: BufWriter<File>,
+    error: Option<io::Error>, // First failed write, reported when flushing
+}
+
+#[cfg(feature = "std")]
+impl EventLog {
+    pub fn create(path: impl AsRef<Path>) -> Result<EventLog, Chip8Error> {
+        let file = File::create(path).map_err(IoError::Events)?;
+        Ok(EventLog { writer: BufWriter::new(file), error: None })
+    }
+
+    // Events are logged from everywhere in the emulator, so a failed write is kept for flush
+    pub fn log(&mut self, frame: u64, event: &Event) {
+        if self.error.is_some() {
+            return;
+        }
+        let written = serde_json::to_writer(&mut self.writer, &Line { frame, event })
+            .map_err(io::Error::from)
+            .and_then(|_| self.writer.write_all(b"\n"));
+        if let Err(e) = written {
+            self.error = Some(e);
+        }
+    }
+
+    // Writes out the events so far, or reports why logging failed
+    pub fn flush(&mut self) -> Result<(), Chip8Error> {
+        if let Some(e) = self.error.take() {
+            return Err(IoError::Events(e).into());
+        }
+        Ok(self.writer.flush().map_err(IoError::Events)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log() {
+        let path = std::env::temp_dir().join("chip8_test_events.jsonl");
+        let mut log = EventLog::create(&path).unwrap();
+        log.log(0, &Event::Instruction { pc: 0x200, opcode: 0xD015 });
+        log.log(3, &Event::Timer { timer: Timer::Sound, value: 4 });
+        log.flush().unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents, concat!(
+            "{\"frame\":0,\"event\":\"instruction\",\"pc\":512,\"opcode\":53269}\n",
+            "{\"frame\":3,\"event\":\"timer\",\"timer\":\"sound\",\"value\":4}\n",
+        ));
+    }
+}
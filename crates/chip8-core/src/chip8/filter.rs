@@ -0,0 +1,121 @@
+// Subpixels per grid cell in each direction when a filter is active
+pub const CELL_SIZE: usize = 4;
+
+// Post-processing applied to the screen, selectable with --filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Filter {
+    #[default]
+    None,
+    Scanlines, // Dark gap below every row of cells
+    Grid, // Dark gap below and right of every cell
+    Crt, // Scanlines over a warm tinted, slightly glowing screen
+}
+
+const FILTERS: &[(&str, Filter)] = &[
+    ("none", Filter::None),
+    ("scanlines", Filter::Scanlines),
+    ("grid", Filter::Grid),
+    ("crt", Filter::Crt),
+];
+
+// Brightness kept by the gaps between cells
+const GAP_BRIGHTNESS: u32 = 128;
+
+// Per channel brightness of the CRT tint, out of 256
+const CRT_TINT: [u32; 3] = [256, 240, 216];
+
+impl Filter {
+    pub fn parse(name: &str) -> Option<Filter> {
+        FILTERS.iter().find(|(filter, _)| *filter == name).map(|(_, filter)| *filter)
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        FILTERS.iter().map(|(name, _)| *name)
+    }
+
+    // Upscales a width by height buffer by CELL_SIZE into output, applying the filter
+    pub(super) fn apply(&self, buffer: &[u32], width: usize, output: &mut [u32]) {
+        let output_width = width * CELL_SIZE;
+        for (i, pixel) in output.iter_mut().enumerate() {
+            let (x, y) = (i % output_width, i / output_width);
+            let color = buffer[x / CELL_SIZE + y / CELL_SIZE * width];
+            let last_row = y % CELL_SIZE == CELL_SIZE - 1;
+            let last_column = x % CELL_SIZE == CELL_SIZE - 1;
+            *pixel = match self {
+                Filter::None => color,
+                Filter::Scanlines if last_row => darken(color, [GAP_BRIGHTNESS; 3]),
+                Filter::Grid if last_row || last_column => darken(color, [GAP_BRIGHTNESS; 3]),
+                Filter::Crt if last_row => darken(color, CRT_TINT.map(|c| c * GAP_BRIGHTNESS / 256)),
+                Filter::Crt => glow(darken(color, CRT_TINT)),
+                _ => color,
+            };
+        }
+    }
+}
+
+// Multiplies each channel by a factor out of 256
+fn darken(color: u32, factors: [u32; 3]) -> u32 {
+    [16, 8, 0].iter().zip(factors).fold(0, |scaled, (shift, factor)| {
+        scaled | ((color >> shift & 0xff) * factor / 256) << shift
+    })
+}
+
+// Lifts dark channels a little, like light bleeding on a CRT
+fn glow(color: u32) -> u32 {
+    [16, 8, 0].iter().fold(0, |lifted, shift| {
+        let channel = color >> shift & 0xff;
+        lifted | (channel + (0xff - channel) / 16) << shift
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Filter::parse("scanlines"), Some(Filter::Scanlines));
+        assert_eq!(Filter::parse("crt"), Some(Filter::Crt));
+        assert_eq!(Filter::parse("blur"), None);
+        assert_eq!(Filter::names().count(), FILTERS.len());
+    }
+
+    #[test]
+    fn test_scanlines() {
+        let buffer = [0xffffff, 0x000000];
+        let mut output = [0; 2 * CELL_SIZE * CELL_SIZE];
+        Filter::Scanlines.apply(&buffer, 2, &mut output);
+        assert_eq!(output[0], 0xffffff);
+        assert_eq!(output[CELL_SIZE - 1], 0xffffff);
+        assert_eq!(output[CELL_SIZE], 0x000000);
+        assert_eq!(output[(CELL_SIZE - 1) * 2 * CELL_SIZE], 0x7f7f7f); // Last row of the cell
+    }
+
+    #[test]
+    fn test_grid() {
+        let buffer = [0xffffff];
+        let mut output = [0; CELL_SIZE * CELL_SIZE];
+        Filter::Grid.apply(&buffer, 1, &mut output);
+        assert_eq!(output[0], 0xffffff);
+        assert_eq!(output[CELL_SIZE - 1], 0x7f7f7f);
+        assert_eq!(output[CELL_SIZE * CELL_SIZE - 1], 0x7f7f7f);
+    }
+
+    #[test]
+    fn test_none() {
+        let buffer = [0x123456, 0x654321];
+        let mut output = [0; 2 * CELL_SIZE * CELL_SIZE];
+        Filter::None.apply(&buffer, 2, &mut output);
+        assert!(output[..CELL_SIZE].iter().all(|&color| color == 0x123456));
+        assert!(output[CELL_SIZE..2 * CELL_SIZE].iter().all(|&color| color == 0x654321));
+    }
+
+    #[test]
+    fn test_crt() {
+        let buffer = [0x000000];
+        let mut output = [0; CELL_SIZE * CELL_SIZE];
+        Filter::Crt.apply(&buffer, 1, &mut output);
+        assert_eq!(output[0], 0x0f0f0f);
+        assert_eq!(output[CELL_SIZE * CELL_SIZE - 1], 0x000000);
+    }
+}
@@ -0,0 +1,131 @@
+use alloc::{collections::BTreeMap, format, string::String};
+use sha1::{Digest, Sha1};
+use super::errors::Chip8Error;
+#[cfg(feature = "std")]
+use super::errors::report;
+use super::storage::Storage;
+
+// Flags Fx75 and Fx85 reach, the HP48 had 8 and XO-CHIP 16
+pub const USER_FLAGS: usize = 16;
+
+// The RPL user flags of SUPER-CHIP, which games keep high scores in. They are saved per ROM
+// under flags/<rom sha1>, and kept in memory only once the storage fails.
+#[derive(Default)]
+pub(super) struct UserFlags {
+    flags: BTreeMap<String, [u8; USER_FLAGS]>, // By storage key, loaded on first use
+    in_memory: bool, // The storage failed, so flags are no longer saved
+}
+
+impl UserFlags {
+    pub(super) fn get(&mut self, rom: &[u8], storage: &dyn Storage) -> [u8; USER_FLAGS] {
+        let key = key(rom);
+        *self.flags(key, storage)
+    }
+
+    // Sets the flags from the first one on and saves them all, unless they are unchanged as
+    // when a game stores the same high score every frame
+    pub(super) fn set(&mut self, rom: &[u8], values: &[u8], storage: &mut dyn Storage) {
+        let key = key(rom);
+        let flags = self.flags(key.clone(), storage);
+        if flags[..values.len()] == *values {
+            return;
+        }
+        flags[..values.len()].copy_from_slice(values);
+        let flags = *flags;
+        if !self.in_memory {
+            if let Err(e) = storage.save(&key, &flags) {
+                self.fail(&e);
+            }
+        }
+    }
+
+    fn flags(&mut self, key: String, storage: &dyn Storage) -> &mut [u8; USER_FLAGS] {
+        if !self.flags.contains_key(&key) {
+            let mut flags = [0; USER_FLAGS];
+            match storage.load(&key) {
+                Ok(Some(data)) => {
+                    let len = data.len().min(USER_FLAGS);
+                    flags[..len].copy_from_slice(&data[..len]);
+                }
+                Ok(None) => (),
+                Err(e) => self.fail(&e),
+            }
+            self.flags.insert(key.clone(), flags);
+        }
+        self.flags.get_mut(&key).expect("flags were just loaded")
+    }
+
+    fn fail(&mut self, e: &Chip8Error) {
+        if !self.in_memory {
+            #[cfg(feature = "std")]
+            eprintln!("Warning: {}, user flags are kept in memory only", report(e));
+            #[cfg(not(feature = "std"))]
+            let _ = e;
+            self.in_memory = true;
+        }
+    }
+}
+
+fn key(rom: &[u8]) -> String {
+    format!("flags/{:x}", Sha1::digest(rom))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::errors::IoError;
+    use super::super::storage::MemoryStorage;
+
+    // Storage that fails every access, like a read-only data directory
+    struct BrokenStorage;
+
+    impl Storage for BrokenStorage {
+        fn load(&self, key: &str) -> Result<Option<Vec<u8>>, Chip8Error> {
+            Err(IoError::InvalidStorageKey(key.to_string()).into())
+        }
+
+        fn save(&mut self, key: &str, _: &[u8]) -> Result<(), Chip8Error> {
+            Err(IoError::InvalidStorageKey(key.to_string()).into())
+        }
+
+        fn remove(&mut self, _: &str) -> Result<(), Chip8Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_persisted() {
+        let mut storage = MemoryStorage::new();
+        UserFlags::default().set(&[0x12, 0x00], &[7, 8], &mut storage);
+
+        // Another session of the same ROM sees them, other ROMs don't
+        let mut flags = UserFlags::default();
+        assert_eq!(flags.get(&[0x12, 0x00], &storage)[..3], [7, 8, 0]);
+        assert_eq!(flags.get(&[0x12, 0x02], &storage), [0; USER_FLAGS]);
+        assert_eq!(storage.load(&key(&[0x12, 0x00])).unwrap().unwrap()[..2], [7, 8]);
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let mut storage = MemoryStorage::new();
+        let mut flags = UserFlags::default();
+        flags.set(&[0x12, 0x00], &[7, 8], &mut storage);
+        storage.remove(&key(&[0x12, 0x00])).unwrap();
+
+        // Storing the same values again writes nothing, a change does
+        flags.set(&[0x12, 0x00], &[7, 8], &mut storage);
+        assert_eq!(storage.load(&key(&[0x12, 0x00])).unwrap(), None);
+        flags.set(&[0x12, 0x00], &[7], &mut storage);
+        assert_eq!(storage.load(&key(&[0x12, 0x00])).unwrap(), None);
+        flags.set(&[0x12, 0x00], &[9], &mut storage);
+        assert_eq!(storage.load(&key(&[0x12, 0x00])).unwrap().unwrap()[..2], [9, 8]);
+    }
+
+    #[test]
+    fn test_in_memory() {
+        let mut flags = UserFlags::default();
+        flags.set(&[0x12, 0x00], &[7], &mut BrokenStorage);
+        assert!(flags.in_memory);
+        assert_eq!(flags.get(&[0x12, 0x00], &BrokenStorage)[0], 7);
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+#[cfg(feature = "std")]
+use super::errors::{Chip8Error, LoadError};
+use super::memory::FONT;
+
+// Bytes of a font file, 5 per hex digit
+pub const FONT_SIZE: usize = 80;
+
+// SUPER-CHIP large digits for Fx30, 8x10 pixels, with the hex letters XO-CHIP added
+pub const BIG_FONT: [u8; 160] = [
+    0xff, 0xff, 0xc3, 0xc3, 0xc3, 0xc3, 0xc3, 0xc3, 0xff, 0xff, // "0"
+    0x18, 0x78, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0xff, 0xff, // "1"
+    0xff, 0xff, 0x03, 0x03, 0xff, 0xff, 0xc0, 0xc0, 0xff, 0xff, // "2"
+    0xff, 0xff, 0x03, 0x03, 0xff, 0xff, 0x03, 0x03, 0xff, 0xff, // "3"
+    0xc3, 0xc3, 0xc3, 0xc3, 0xff, 0xff, 0x03, 0x03, 0x03, 0x03, // "4"
+    0xff, 0xff, 0xc0, 0xc0, 0xff, 0xff, 0x03, 0x03, 0xff, 0xff, // "5"
+    0xff, 0xff, 0xc0, 0xc0, 0xff, 0xff, 0xc3, 0xc3, 0xff, 0xff, // "6"
+    0xff, 0xff, 0x03, 0x03, 0x06, 0x0c, 0x18, 0x18, 0x18, 0x18, // "7"
+    0xff, 0xff, 0xc3, 0xc3, 0xff, 0xff, 0xc3, 0xc3, 0xff, 0xff, // "8"
+    0xff, 0xff, 0xc3, 0xc3, 0xff, 0xff, 0x03, 0x03, 0xff, 0xff, // "9"
+    0x7e, 0xff, 0xc3, 0xc3, 0xc3, 0xff, 0xff, 0xc3, 0xc3, 0xc3, // "A"
+    0xfc, 0xfc, 0xc3, 0xc3, 0xfc, 0xfc, 0xc3, 0xc3, 0xfc, 0xfc, // "B"
+    0x3c, 0xff, 0xc3, 0xc0, 0xc0, 0xc0, 0xc0, 0xc3, 0xff, 0x3c, // "C"
+    0xfc, 0xfe, 0xc3, 0xc3, 0xc3, 0xc3, 0xc3, 0xc3, 0xfe, 0xfc, // "D"
+    0xff, 0xff, 0xc0, 0xc0, 0xff, 0xff, 0xc0, 0xc0, 0xff, 0xff, // "E"
+    0xff, 0xff, 0xc0, 0xc0, 0xff, 0xff, 0xc0, 0xc0, 0xc0, 0xc0  // "F"
+];
+
+// Font of the COSMAC VIP interpreter, differing from the default in 1, 4, 7, B and D
+const VIP: [u8; FONT_SIZE] = [
+    0xf0, 0x90, 0x90, 0x90, 0xf0, 0x60, 0x20, 0x20, 0x20, 0x70, 0xf0, 0x10, 0xf0, 0x80, 0xf0, 0xf0,
+    0x10, 0xf0, 0x10, 0xf0, 0xa0, 0xa0, 0xf0, 0x20, 0x20, 0xf0, 0x80, 0xf0, 0x10, 0xf0, 0xf0, 0x80,
+    0xf0, 0x90, 0xf0, 0xf0, 0x10, 0x10, 0x10, 0x10, 0xf0, 0x90, 0xf0, 0x90, 0xf0, 0xf0, 0x90, 0xf0,
+    0x10, 0xf0, 0xf0, 0x90, 0xf0, 0x90, 0x90, 0xf0, 0x50, 0x70, 0x50, 0xf0, 0xf0, 0x80, 0x80, 0x80,
+    0xf0, 0xf0, 0x50, 0x50, 0x50, 0xf0, 0xf0, 0x80, 0xf0, 0x80, 0xf0, 0xf0, 0x80, 0xf0, 0x80, 0x80,
+];
+
+// Font of the ETI-660, 3 pixels wide
+const ETI660: [u8; FONT_SIZE] = [
+    0xe0, 0xa0, 0xa0, 0xa0, 0xe0, 0x20, 0x20, 0x20, 0x20, 0x20, 0xe0, 0x20, 0xe0, 0x80, 0xe0, 0xe0,
+    0x20, 0xe0, 0x20, 0xe0, 0xa0, 0xa0, 0xe0, 0x20, 0x20, 0xe0, 0x80, 0xe0, 0x20, 0xe0, 0xe0, 0x80,
+    0xe0, 0xa0, 0xe0, 0xe0, 0x20, 0x20, 0x20, 0x20, 0xe0, 0xa0, 0xe0, 0xa0, 0xe0, 0xe0, 0xa0, 0xe0,
+    0x20, 0xe0, 0xe0, 0xa0, 0xe0, 0xa0, 0xa0, 0xc0, 0xa0, 0xe0, 0xa0, 0xc0, 0xe0, 0x80, 0x80, 0x80,
+    0xe0, 0xc0, 0xa0, 0xa0, 0xa0, 0xc0, 0xe0, 0x80, 0xe0, 0x80, 0xe0, 0xe0, 0x80, 0xc0, 0x80, 0x80,
+];
+
+// Font of the DREAM 6800, 3 pixels wide with a centered 1
+const DREAM6800: [u8; FONT_SIZE] = [
+    0xe0, 0xa0, 0xa0, 0xa0, 0xe0, 0x40, 0x40, 0x40, 0x40, 0x40, 0xe0, 0x20, 0xe0, 0x80, 0xe0, 0xe0,
+    0x20, 0xe0, 0x20, 0xe0, 0x80, 0xa0, 0xa0, 0xe0, 0x20, 0xe0, 0x80, 0xe0, 0x20, 0xe0, 0xe0, 0x80,
+    0xe0, 0xa0, 0xe0, 0xe0, 0x20, 0x20, 0x20, 0x20, 0xe0, 0xa0, 0xe0, 0xa0, 0xe0, 0xe0, 0xa0, 0xe0,
+    0x20, 0xe0, 0xe0, 0xa0, 0xe0, 0xa0, 0xa0, 0xc0, 0xa0, 0xe0, 0xa0, 0xc0, 0xe0, 0x80, 0x80, 0x80,
+    0xe0, 0xc0, 0xa0, 0xa0, 0xa0, 0xc0, 0xe0, 0x80, 0xe0, 0x80, 0xe0, 0xe0, 0x80, 0xc0, 0x80, 0x80,
+];
+
+// Named fonts, selectable with --font
+const PRESETS: &[(&str, [u8; FONT_SIZE])] = &[
+    ("default", FONT),
+    ("vip", VIP),
+    ("eti660", ETI660),
+    ("dream6800", DREAM6800),
+];
+
+pub fn preset(name: &str) -> Option<[u8; FONT_SIZE]> {
+    PRESETS.iter().find(|(preset, _)| *preset == name).map(|(_, font)| *font)
+}
+
+pub fn preset_names() -> impl Iterator<Item = &'static str> {
+    PRESETS.iter().map(|(name, _)| *name)
+}
+
+// Reads a font file of 80 bytes, the glyphs of 0 to F one after another
+#[cfg(feature = "std")]
+pub fn from_file(path: impl AsRef<Path>) -> Result<[u8; FONT_SIZE], Chip8Error> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|source| LoadError::FileRead { path: path.to_path_buf(), source })?;
+    let len = bytes.len();
+    bytes.try_into().map_err(|_| LoadError::InvalidFont(format!("{} is {len} bytes, expected {FONT_SIZE}", path.display())).into())
+}
+
+// A named font, or else the font file at the path
+#[cfg(feature = "std")]
+pub fn load(font: &str) -> Result<[u8; FONT_SIZE], Chip8Error> {
+    match preset(font) {
+        Some(font) => Ok(font),
+        None => from_file(font),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Memory;
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(preset("default"), Some(FONT));
+        assert_eq!(preset("comic"), None);
+        let mut mem = Memory::new();
+        for name in preset_names() {
+            assert!(mem.load_font(&preset(name).unwrap()).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_from_file() {
+        let path = std::env::temp_dir().join("chip8_test_font.bin");
+        fs::write(&path, ETI660).unwrap();
+        assert_eq!(load(path.to_str().unwrap()).unwrap(), ETI660);
+        fs::write(&path, &ETI660[..75]).unwrap();
+        assert!(matches!(from_file(&path), Err(Chip8Error::Load(LoadError::InvalidFont(_)))));
+        fs::remove_file(&path).unwrap();
+    }
+}
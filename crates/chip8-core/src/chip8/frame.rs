@@ -0,0 +1,57 @@
+use alloc::vec::Vec;
+use super::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use super::draw::{self, Grid};
+
+// Screen contents handed to embedders after every frame, to render in their own widgets
+pub struct FrameBuffer<'a> {
+    grid: &'a Grid,
+    filled: u32, // Colors of the palette, as 0xRRGGBB
+    empty: u32,
+}
+
+impl<'a> FrameBuffer<'a> {
+    pub(super) fn new(grid: &'a Grid, filled: u32, empty: u32) -> Self {
+        FrameBuffer { grid, filled, empty }
+    }
+
+    pub fn width(&self) -> usize {
+        DISPLAY_WIDTH
+    }
+
+    pub fn height(&self) -> usize {
+        DISPLAY_HEIGHT
+    }
+
+    // Whether the pixel is lit, false outside the screen
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        x < DISPLAY_WIDTH && y < DISPLAY_HEIGHT && draw::is_lit(self.grid, x, y)
+    }
+
+    pub fn grid(&self) -> &Grid {
+        self.grid
+    }
+
+    // Pixels row by row as 0xRRGGBB in the palette colors
+    pub fn to_rgb(&self) -> Vec<u32> {
+        draw::pixels(self.grid).map(|lit| if lit { self.filled } else { self.empty }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_buffer() {
+        let mut grid = [0; DISPLAY_HEIGHT];
+        grid[0] = 1 << 62;
+        let frame = FrameBuffer::new(&grid, 0xffffff, 0x000000);
+        assert!(frame.is_lit(1, 0));
+        assert!(!frame.is_lit(0, 1));
+        assert!(!frame.is_lit(DISPLAY_WIDTH, 0));
+
+        let rgb = frame.to_rgb();
+        assert_eq!(rgb.len(), frame.width() * frame.height());
+        assert_eq!(&rgb[..3], &[0x000000, 0xffffff, 0x000000]);
+    }
+}
@@ -0,0 +1,162 @@
+#[cfg(feature = "std")]
+use {
+    alloc::{boxed::Box, string::String, vec::Vec},
+    super::audio::Beep,
+    super::backend::Backend,
+    super::errors::Chip8Error,
+    super::icon::WindowIcon,
+    super::APP_NAME,
+};
+
+// Keys of the host keyboard, named as in minifb whatever library reads them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15,
+    Down, Left, Right, Up,
+    Apostrophe, Backquote, Backslash, Comma, Equal, LeftBracket, Minus, Period, RightBracket, Semicolon, Slash,
+    Backspace, Delete, End, Enter, Escape, Home, Insert, Menu, PageDown, PageUp, Pause, Space, Tab,
+    NumLock, CapsLock, ScrollLock,
+    LeftShift, RightShift, LeftCtrl, RightCtrl, LeftAlt, RightAlt, LeftSuper, RightSuper,
+    NumPad0, NumPad1, NumPad2, NumPad3, NumPad4, NumPad5, NumPad6, NumPad7, NumPad8, NumPad9,
+    NumPadDot, NumPadSlash, NumPadAsterisk, NumPadMinus, NumPadPlus, NumPadEnter,
+}
+
+// Whether a key held down counts as pressed again every time it repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyRepeat {
+    Yes,
+    No,
+}
+
+// Size of a window as a multiple of the buffer shown in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    X1,
+    X2,
+    X4,
+    X8,
+    X16,
+    X32,
+    FitScreen, // As large as the screen allows
+}
+
+impl Scale {
+    // Window pixels per buffer pixel in each direction, FitScreen is taken as 16 where a size is needed
+    pub fn factor(self) -> usize {
+        match self {
+            Scale::X1 => 1,
+            Scale::X2 => 2,
+            Scale::X4 => 4,
+            Scale::X8 => 8,
+            Scale::X16 => 16,
+            Scale::X32 => 32,
+            Scale::FitScreen => 16,
+        }
+    }
+}
+
+// How a window is opened
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct WindowSettings<'a> {
+    pub title: &'a str,
+    pub width: usize, // Size of the buffers shown in it
+    pub height: usize,
+    pub scale: Scale,
+    pub resize: bool, // Can be resized, the buffer is stretched to fit keeping its aspect ratio
+    pub borderless: bool, // Without a frame, covering the screen with Scale::FitScreen
+    pub backend: Backend, // Library the window is drawn and read through
+    pub vsync: bool, // Presenting waits for the monitor's refresh, where the backend can
+    pub app_name: &'a str, // Application the window belongs to, WM_CLASS on X11 and the app id on Wayland
+}
+
+#[cfg(feature = "std")]
+impl<'a> WindowSettings<'a> {
+    // Fixed size window drawn through minifb, as the tool windows are
+    pub fn new(title: &'a str, width: usize, height: usize, scale: Scale) -> Self {
+        WindowSettings { title, width, height, scale, resize: false, borderless: false, backend: Backend::Minifb, vsync: false, app_name: APP_NAME }
+    }
+}
+
+// Window the emulator draws in and reads the keyboard of, used on the thread that opened it
+#[cfg(feature = "std")]
+pub trait Window {
+    // Shows the buffer, scaled to the window, and handles the window's events
+    fn update(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Chip8Error>;
+    // Held at the last update
+    fn get_keys(&self) -> Vec<Key>;
+    // Pressed before the last update
+    fn get_keys_pressed(&self, repeat: KeyRepeat) -> Vec<Key>;
+    // Presses and releases since the last call, in order, including taps between two updates
+    fn take_key_events(&mut self) -> Vec<(Key, bool)>;
+    fn set_title(&mut self, title: &str);
+    fn set_position(&mut self, x: isize, y: isize);
+    // Left as is where the platform takes the icon from elsewhere
+    fn set_icon(&mut self, icon: &WindowIcon);
+    fn is_open(&self) -> bool;
+}
+
+// Output device playing the beep until dropped
+#[cfg(feature = "std")]
+pub trait AudioOutput {}
+
+// Opens the windows and the audio device the emulator runs with, keeping the window and audio
+// libraries out of this crate, e.g. chip8_frontend_minifb::MinifbFrontend. Shared with the
+// thread playing the beep, so it has to be Sync.
+#[cfg(feature = "std")]
+pub trait Frontend: Send + Sync {
+    fn open_window(&self, settings: &WindowSettings) -> Result<Box<dyn Window>, Chip8Error>;
+    // Opens the default output device playing the samples of the beep, one channel at
+    // SAMPLE_RATE, fails with the reason if there is none. The window backend lets a frontend
+    // play it through the same library as the window.
+    fn open_audio(&self, beep: Beep, backend: Backend) -> Result<Box<dyn AudioOutput>, String>;
+}
+
+// Frontend for tests, its windows show nothing, and stay open with no keys held
+#[cfg(test)]
+pub(super) struct TestFrontend;
+
+#[cfg(test)]
+struct TestWindow;
+
+#[cfg(test)]
+impl Frontend for TestFrontend {
+    fn open_window(&self, _: &WindowSettings) -> Result<Box<dyn Window>, Chip8Error> {
+        Ok(Box::new(TestWindow))
+    }
+
+    fn open_audio(&self, _: Beep, _: Backend) -> Result<Box<dyn AudioOutput>, String> {
+        Err("tests play no sound".to_string())
+    }
+}
+
+#[cfg(test)]
+impl Window for TestWindow {
+    fn update(&mut self, _: &[u32], _: usize, _: usize) -> Result<(), Chip8Error> {
+        Ok(())
+    }
+
+    fn get_keys(&self) -> Vec<Key> {
+        Vec::new()
+    }
+
+    fn get_keys_pressed(&self, _: KeyRepeat) -> Vec<Key> {
+        Vec::new()
+    }
+
+    fn take_key_events(&mut self) -> Vec<(Key, bool)> {
+        Vec::new()
+    }
+
+    fn set_title(&mut self, _: &str) {}
+
+    fn set_position(&mut self, _: isize, _: isize) {}
+
+    fn set_icon(&mut self, _: &WindowIcon) {}
+
+    fn is_open(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,123 @@
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use {std::path::Path, image::{Rgb, RgbImage}, super::errors::{Chip8Error, IoError}};
+use super::{MEMORY_SIZE, watchpoint::Access};
+
+// Memory is laid out as a square of one cell per byte, row by row
+pub const HEATMAP_SIZE: usize = 64;
+// Pixels per cell in the saved image
+#[cfg(feature = "std")]
+const CELL_SIZE: u32 = 8;
+
+// How often the program read and wrote every address
+pub struct Heatmap {
+    reads: Vec<u32>,
+    writes: Vec<u32>,
+}
+
+impl Heatmap {
+    pub fn new() -> Self {
+        Heatmap { reads: vec![0; MEMORY_SIZE], writes: vec![0; MEMORY_SIZE] }
+    }
+
+    pub(super) fn record(&mut self, addr: u16, access: Access) {
+        let counts = match access {
+            Access::Read => &mut self.reads,
+            Access::Write => &mut self.writes,
+        };
+        // Only the first 4KB are counted, larger memory is mostly unused
+        if let Some(count) = counts.get_mut(addr as usize) {
+            *count = count.saturating_add(1);
+        }
+    }
+
+    pub fn reads(&self, addr: u16) -> u32 {
+        self.reads[addr as usize % MEMORY_SIZE]
+    }
+
+    pub fn writes(&self, addr: u16) -> u32 {
+        self.writes[addr as usize % MEMORY_SIZE]
+    }
+
+    // Color of an address: writes in red and reads in blue, brighter the more often
+    // relative to the busiest address, on a logarithmic scale so rare accesses still show.
+    // The scale needs ln_1p, which only std provides.
+    #[cfg(feature = "std")]
+    pub fn color(&self, addr: u16) -> u32 {
+        self.scaled_color(addr, self.max())
+    }
+
+    #[cfg(feature = "std")]
+    fn scaled_color(&self, addr: u16, max: u32) -> u32 {
+        let level = |count: u32| if count == 0 { 0 } else {
+            (64.0 + 191.0 * (count as f64).ln_1p() / (max as f64).ln_1p()) as u32
+        };
+        level(self.writes(addr)) << 16 | level(self.reads(addr))
+    }
+
+    // Accesses of the busiest address
+    #[cfg(feature = "std")]
+    fn max(&self) -> u32 {
+        self.reads.iter().chain(&self.writes).copied().max().unwrap_or(0)
+    }
+
+    // Saves the heatmap as a PNG with the first address in the top left corner
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Chip8Error> {
+        let size = HEATMAP_SIZE as u32 * CELL_SIZE;
+        let max = self.max();
+        let image = RgbImage::from_fn(size, size, |x, y| {
+            let addr = (y / CELL_SIZE) as usize * HEATMAP_SIZE + (x / CELL_SIZE) as usize;
+            let color = self.scaled_color(addr as u16, max);
+            Rgb([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+        });
+        Ok(image.save(path.as_ref()).map_err(IoError::Image)?)
+    }
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut heatmap = Heatmap::new();
+        heatmap.record(0x300, Access::Read);
+        heatmap.record(0x300, Access::Read);
+        heatmap.record(0x301, Access::Write);
+        assert_eq!((heatmap.reads(0x300), heatmap.writes(0x300)), (2, 0));
+        assert_eq!((heatmap.reads(0x301), heatmap.writes(0x301)), (0, 1));
+    }
+
+    #[test]
+    fn test_color() {
+        let mut heatmap = Heatmap::new();
+        for _ in 0..100 {
+            heatmap.record(0x300, Access::Read);
+        }
+        heatmap.record(0x301, Access::Write);
+        assert_eq!(heatmap.color(0x300), 0x0000ff); // The busiest address is brightest
+        assert_eq!(heatmap.color(0x301) & 0xff, 0);
+        assert!((64..128).contains(&(heatmap.color(0x301) >> 16)));
+        assert_eq!(heatmap.color(0x302), 0);
+    }
+
+    #[test]
+    fn test_save() {
+        let path = std::env::temp_dir().join("chip8_test_heatmap.png");
+        let mut heatmap = Heatmap::new();
+        heatmap.record(HEATMAP_SIZE as u16 + 1, Access::Write);
+        heatmap.save(&path).unwrap();
+        let image = image::open(&path).unwrap().to_rgb8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(image.dimensions(), (512, 512));
+        assert_eq!(*image.get_pixel(CELL_SIZE + 1, CELL_SIZE + 1), Rgb([255, 0, 0]));
+        assert_eq!(*image.get_pixel(0, 0), Rgb([0, 0, 0]));
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::Path;
+use super::errors::{Chip8Error, LoadError};
+use super::font::BIG_FONT;
+use super::palette::Palette;
+
+// Side of the bundled icon in pixels
+const SIZE: usize = 32;
+const GLYPH_SCALE: usize = 3; // Icon pixels per pixel of the large "8"
+
+// Icon of the emulator window, the bundled one unless the embedding application sets its own
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowIcon {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>, // 0xAARRGGBB row by row
+}
+
+impl WindowIcon {
+    // A large "8" in the colors of the default palette
+    pub fn bundled() -> Self {
+        let palette = Palette::default();
+        let mut pixels = vec![0xff000000 | palette.empty.0; SIZE * SIZE];
+        let glyph = &BIG_FONT[8 * 10..9 * 10];
+        let (left, top) = ((SIZE - 8 * GLYPH_SCALE) / 2, (SIZE - glyph.len() * GLYPH_SCALE) / 2);
+        for y in 0..glyph.len() * GLYPH_SCALE {
+            for x in 0..8 * GLYPH_SCALE {
+                if glyph[y / GLYPH_SCALE] & (0x80 >> (x / GLYPH_SCALE)) != 0 {
+                    pixels[left + x + (top + y) * SIZE] = 0xff000000 | palette.filled.0;
+                }
+            }
+        }
+        WindowIcon { width: SIZE, height: SIZE, pixels }
+    }
+
+    pub fn from_argb(width: usize, height: usize, pixels: Vec<u32>) -> Result<Self, Chip8Error> {
+        if width == 0 || height == 0 || pixels.len() != width * height {
+            return Err(LoadError::InvalidArgument(format!("an icon of {width}x{height} has {} pixels, not {}", pixels.len(), width * height)).into());
+        }
+        Ok(WindowIcon { width, height, pixels })
+    }
+
+    // Reads a PNG image, 32x32 or 64x64 suits most taskbars
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Chip8Error> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|e| LoadError::InvalidArgument(format!("{} is not a PNG image: {e}", path.display())))?
+            .to_rgba8();
+        let pixels = image.pixels().map(|pixel| {
+            let [r, g, b, a] = pixel.0;
+            u32::from_be_bytes([a, r, g, b])
+        }).collect();
+        WindowIcon::from_argb(image.width() as usize, image.height() as usize, pixels)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+}
+
+impl Default for WindowIcon {
+    fn default() -> Self {
+        WindowIcon::bundled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled() {
+        let icon = WindowIcon::bundled();
+        let palette = Palette::default();
+        assert_eq!((icon.width(), icon.height()), (SIZE, SIZE));
+        assert_eq!(icon.pixels()[0], 0xff000000 | palette.empty.0);
+        assert_eq!(icon.pixels()[4 + SIZE], 0xff000000 | palette.filled.0); // Top left of the "8"
+    }
+
+    #[test]
+    fn test_from_file() {
+        let path = std::env::temp_dir().join("chip8_test_icon.png");
+        image::RgbaImage::from_pixel(2, 1, image::Rgba([0x10, 0x20, 0x30, 0x80])).save(&path).unwrap();
+        assert_eq!(WindowIcon::from_file(&path).unwrap().pixels(), [0x80102030, 0x80102030]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(WindowIcon::from_argb(2, 2, vec![0; 3]).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+// Size of the flashing corner in pixels of the screen
+const CORNER_SIZE: usize = 4;
+
+// Drawn over the screen while the sound timer runs, stands out on any palette
+const INDICATOR_COLOR: u32 = 0xffb000;
+
+// Shows on screen when the program beeps, for players who can't hear it, selectable with --sound-indicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SoundIndicator {
+    #[default]
+    None,
+    Border, // The outermost pixels around the screen light up
+    Corner, // A square in the top right corner lights up
+}
+
+const INDICATORS: &[(&str, SoundIndicator)] = &[
+    ("none", SoundIndicator::None),
+    ("border", SoundIndicator::Border),
+    ("corner", SoundIndicator::Corner),
+];
+
+impl SoundIndicator {
+    pub fn parse(name: &str) -> Option<SoundIndicator> {
+        INDICATORS.iter().find(|(indicator, _)| *indicator == name).map(|(_, indicator)| *indicator)
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        INDICATORS.iter().map(|(name, _)| *name)
+    }
+
+    // Paints the indicator over a width by height buffer laid out row by row
+    pub(super) fn apply(&self, buffer: &mut [u32], width: usize, height: usize) {
+        for (i, color) in buffer.iter_mut().enumerate() {
+            let (x, y) = (i % width, i / width);
+            let covered = match self {
+                SoundIndicator::None => false,
+                SoundIndicator::Border => x == 0 || y == 0 || x == width - 1 || y == height - 1,
+                SoundIndicator::Corner => x >= width - CORNER_SIZE && y < CORNER_SIZE,
+            };
+            if covered {
+                *color = INDICATOR_COLOR;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(SoundIndicator::parse("border"), Some(SoundIndicator::Border));
+        assert_eq!(SoundIndicator::parse("flash"), None);
+        assert!(SoundIndicator::names().all(|name| SoundIndicator::parse(name).is_some()));
+    }
+
+    #[test]
+    fn test_apply() {
+        let lit = |indicator: SoundIndicator| {
+            let mut buffer = [0; 8 * 6];
+            indicator.apply(&mut buffer, 8, 6);
+            buffer.iter().filter(|&&color| color == INDICATOR_COLOR).count()
+        };
+        assert_eq!(lit(SoundIndicator::None), 0);
+        assert_eq!(lit(SoundIndicator::Border), 8 * 6 - 6 * 4);
+        assert_eq!(lit(SoundIndicator::Corner), CORNER_SIZE * CORNER_SIZE);
+    }
+}
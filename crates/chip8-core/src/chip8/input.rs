@@ -0,0 +1,114 @@
+use alloc::collections::VecDeque;
+
+// Frames per second the autofire rates are counted in
+const FRAME_RATE: u32 = 60;
+
+// Presses and releases of chip8 keys in the order they happened, turned into one key state per
+// frame so every instruction of a frame sees the same keys and taps between frames aren't lost
+#[derive(Default)]
+pub struct InputQueue {
+    events: VecDeque<(u8, bool)>, // Key and whether it was pressed, since the last latch
+    held: u16, // Keys down after the events latched so far
+    autofire: [u32; 16], // Frames per press and release of keys firing repeatedly while held, 0 for none
+    held_for: [u32; 16], // Frames every key has been latched as down in a row
+}
+
+impl InputQueue {
+    // Keys above F are ignored
+    pub fn push(&mut self, key: u8, pressed: bool) {
+        if key < 16 {
+            self.events.push_back((key, pressed));
+        }
+    }
+
+    // Makes the key press and release repeatedly while held, rate times a second, or stops it.
+    // Rates above 30 are capped, a press and a release take at least a frame each.
+    pub fn set_autofire(&mut self, key: u8, rate: Option<u32>) {
+        if let Some(period) = self.autofire.get_mut(key as usize) {
+            *period = rate.map_or(0, |rate| (FRAME_RATE / rate.max(1)).max(2));
+        }
+    }
+
+    // Key state for the next frame, bit n set if key n is down: the keys held at the last latch
+    // and those pressed since. A key released in between is still down for this frame, so a tap
+    // shorter than a frame counts as held for one. Autofire keys are up every other half period.
+    pub fn latch(&mut self) -> u16 {
+        let mut keys = self.edges();
+        for key in 0..16 {
+            if keys & (1 << key) == 0 {
+                self.held_for[key] = 0;
+                continue;
+            }
+            let period = self.autofire[key];
+            if period != 0 && self.held_for[key] % period >= period.div_ceil(2) {
+                keys &= !(1 << key);
+            }
+            self.held_for[key] += 1;
+        }
+        keys
+    }
+
+    // Applies the queued events, returning the keys down at some point since the last latch
+    fn edges(&mut self) -> u16 {
+        let mut keys = self.held;
+        for (key, pressed) in self.events.drain(..) {
+            if pressed {
+                if self.held & (1 << key) == 0 {
+                    self.held_for[key as usize] = 0; // Autofire starts over with every press
+                }
+                keys |= 1 << key;
+                self.held |= 1 << key;
+            } else {
+                self.held &= !(1 << key);
+            }
+        }
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latch() {
+        let mut queue = InputQueue::default();
+        queue.push(0x5, true);
+        queue.push(0x10, true); // Not a key
+        assert_eq!(queue.latch(), 1 << 0x5);
+        assert_eq!(queue.latch(), 1 << 0x5); // Held until released
+
+        queue.push(0x5, false);
+        assert_eq!(queue.latch(), 1 << 0x5); // Down until the end of the frame it was released in
+        assert_eq!(queue.latch(), 0);
+    }
+
+    #[test]
+    fn test_autofire() {
+        let mut queue = InputQueue::default();
+        queue.set_autofire(0x5, Some(15)); // Down for 2 frames, up for 2
+        queue.push(0x5, true);
+        queue.push(0x6, true);
+        let frames: Vec<u16> = (0..6).map(|_| queue.latch()).collect();
+        let down = 1 << 0x5 | 1 << 0x6;
+        assert_eq!(frames, [down, down, 1 << 0x6, 1 << 0x6, down, down]);
+
+        // Starts over with the next press
+        queue.push(0x5, false);
+        queue.latch();
+        queue.push(0x5, true);
+        assert_eq!(queue.latch() & 1 << 0x5, 1 << 0x5);
+
+        queue.set_autofire(0x5, None);
+        assert!((0..4).all(|_| queue.latch() & 1 << 0x5 != 0));
+    }
+
+    #[test]
+    fn test_tap_between_latches() {
+        let mut queue = InputQueue::default();
+        queue.push(0xA, true);
+        queue.push(0xA, false);
+        assert_eq!(queue.latch(), 1 << 0xA);
+        assert_eq!(queue.latch(), 0);
+    }
+}
@@ -0,0 +1,30 @@
+// Hot paths of the emulator exposed to the criterion benches in benches/, only built with the bench feature.
+// The emulators measured are created with Chip8::new, which opens neither a window nor an audio device.
+use super::{Chip8, Memory, OpCode, errors::Chip8Error};
+
+// Splits an opcode into the fields instructions use: x, y, n, kk and nnn
+pub fn decode(code: u16) -> (usize, usize, u8, u8, u16) {
+    let op_code = OpCode::decode(code);
+    (op_code.vx(), op_code.vy(), op_code.nibble(), op_code.byte(), op_code.addr())
+}
+
+// Fetches and decodes the instruction at an address, from the predecoded table if the memory has one
+pub fn fetch(mem: &mut Memory, addr: u16) -> (usize, usize, u8, u8, u16) {
+    let op_code = mem.fetch(addr);
+    (op_code.vx(), op_code.vy(), op_code.nibble(), op_code.byte(), op_code.addr())
+}
+
+// Executes an opcode without fetching it, the program counter isn't advanced beforehand
+pub fn execute(chip8: &mut Chip8, op_code: u16, mem: &mut Memory) -> Result<(), Chip8Error> {
+    chip8.execute(op_code, mem)
+}
+
+// Draws a sprite straight onto the screen, true on a collision
+pub fn draw(chip8: &mut Chip8, x: usize, y: usize, sprite: &[u8]) -> bool {
+    chip8.display.draw(x, y, sprite.iter().copied())
+}
+
+// Renders the screen into the window buffer, without showing it
+pub fn update_buffer(chip8: &mut Chip8) {
+    chip8.display.update_buffer();
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use super::frontend::Key;
+
+pub(super) struct Keys {
+    left: HashMap<u8, Key>,
+    right: HashMap<Key, u8>,
+}
+
+impl Keys {
+    pub fn from(bindings: HashMap<u8, Key>) -> Self {
+        let mut keys = Keys { left: HashMap::new(), right: HashMap::new() };
+        for (key, value) in bindings {
+            keys.insert(key, value);
+        }
+        keys
+    }
+
+    pub fn get_by_key(&self, key: &Key) -> Option<&u8> {
+        self.right.get(key)
+    }
+
+    pub fn insert(&mut self, key: u8, value: Key) {
+        if let Some(old_value) = self.left.insert(key, value) {
+            self.right.remove(&old_value);
+        }
+        self.right.insert(value, key);
+    }
+
+    pub fn get_default() -> Self {
+        Keys::from(HashMap::from([
+            (0x1, Key::Key1), (0x2, Key::Key2), (0x3, Key::Key3), (0xC, Key::Key4),
+            (0x4, Key::Q), (0x5, Key::W), (0x6, Key::E), (0xD, Key::R),
+            (0x7, Key::A), (0x8, Key::S), (0x9, Key::D), (0xE, Key::F),
+            (0xA, Key::Z), (0x0, Key::X), (0xB, Key::C), (0xF, Key::V),
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_by_key() {
+        let keys = Keys::get_default();
+        assert_eq!(keys.get_by_key(&Key::Key1), Some(&0x1));
+        assert_eq!(keys.get_by_key(&Key::V), Some(&0xF));
+        assert_eq!(keys.get_by_key(&Key::Key5), None);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut keys = Keys::from(HashMap::new());
+        keys.insert(0x1, Key::Key1);
+        assert_eq!(keys.get_by_key(&Key::Key1), Some(&0x1));
+        keys.insert(0x1, Key::Key2);
+        assert_eq!(keys.get_by_key(&Key::Key1), None);
+        assert_eq!(keys.get_by_key(&Key::Key2), Some(&0x1));
+    }
+
+    #[test]
+    fn test_from() {
+        let keys = Keys::from(HashMap::from([(0x1, Key::Key1), (0x2, Key::Key2)]));
+        assert_eq!(keys.get_by_key(&Key::Key1), Some(&0x1));
+        assert_eq!(keys.get_by_key(&Key::Key2), Some(&0x2));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use super::errors::{Chip8Error, LoadError};
+use super::number::hex;
+use super::opcode::OpCode;
+
+// Names for addresses in a program, e.g. the labels of its Octo source, kept in a file with
+// one label per line:
+//
+//   # Pong
+//   draw_paddle = 0x2A4
+//   : main 200
+//
+// Addresses are in hex, the = and Octo's leading : are optional and anything after a # is a comment.
+// Addresses must be within the memory of the program's platform.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Labels {
+    names: BTreeMap<u16, String>,
+}
+
+impl Labels {
+    pub fn new() -> Self {
+        Labels::default()
+    }
+
+    pub fn parse(text: &str, memory_size: usize) -> Result<Labels, Chip8Error> {
+        let mut labels = Labels::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || LoadError::InvalidLabels(format!("line {}: expected NAME = ADDR, got {line}", n + 1));
+            let label = line.strip_prefix(':').unwrap_or(line).replace('=', " ");
+            let mut fields = label.split_whitespace();
+            let (Some(name), Some(addr), None) = (fields.next(), fields.next(), fields.next()) else {
+                return Err(invalid().into());
+            };
+            let addr = hex(addr)
+                .filter(|&addr| (addr as usize) < memory_size)
+                .ok_or_else(invalid)?;
+            labels.add(addr, name);
+        }
+        Ok(labels)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>, memory_size: usize) -> Result<Labels, Chip8Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| LoadError::FileRead { path: path.to_path_buf(), source })?;
+        Labels::parse(&text, memory_size)
+    }
+
+    // A later label for the same address replaces the earlier one
+    pub fn add(&mut self, addr: u16, name: impl Into<String>) {
+        self.names.insert(addr, name.into());
+    }
+
+    pub fn name(&self, addr: u16) -> Option<&str> {
+        self.names.get(&addr).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    // Disassembles the instruction with the label of the address it jumps to, calls or points I at,
+    // e.g. CALL draw_paddle, falling back to the address when it has no label
+    pub fn disassemble(&self, op_code: OpCode) -> String {
+        let text = op_code.to_string();
+        let addr = op_code.addr();
+        let name = match op_code.code >> 12 {
+            0x1 | 0x2 | 0xA | 0xB => self.name(addr),
+            _ => None,
+        };
+        match name.and_then(|name| Some((text.strip_suffix(&format!("{addr:#05X}"))?, name))) {
+            Some((mnemonic, name)) => format!("{mnemonic}{name}"),
+            None => text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{MAX_MEMORY_SIZE, MEMORY_SIZE};
+
+    #[test]
+    fn test_parse() {
+        let labels = Labels::parse("# Pong\ndraw_paddle = 0x2A4\n\n: main 200 # Entry point\nscore=2f0", MEMORY_SIZE).unwrap();
+        assert_eq!(labels.name(0x2a4), Some("draw_paddle"));
+        assert_eq!(labels.name(0x200), Some("main"));
+        assert_eq!(labels.name(0x2f0), Some("score"));
+        assert_eq!(labels.name(0x202), None);
+
+        assert!(Labels::parse("draw_paddle", MEMORY_SIZE).is_err());
+        assert!(Labels::parse("draw_paddle = 0x2A4 0x2A6", MEMORY_SIZE).is_err());
+        assert!(Labels::parse("past_the_end = 0x1000", MEMORY_SIZE).is_err());
+        assert!(Labels::parse("xochip = 0x1000", MAX_MEMORY_SIZE).is_ok());
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let labels = Labels::parse("draw_paddle = 0x2A4\nsprite = 0x300", MEMORY_SIZE).unwrap();
+        let disassemble = |code| labels.disassemble(OpCode::decode(code));
+        assert_eq!(disassemble(0x22a4), "CALL draw_paddle");
+        assert_eq!(disassemble(0x12a4), "JP draw_paddle");
+        assert_eq!(disassemble(0xa300), "LD I, sprite");
+        assert_eq!(disassemble(0xb2a4), "JP V0, draw_paddle");
+        assert_eq!(disassemble(0x2208), "CALL 0x208");
+        assert_eq!(disassemble(0x6aa4), "LD VA, 0xA4");
+    }
+}
@@ -0,0 +1,147 @@
+use alloc::{vec, vec::Vec};
+
+// Screen of the Megachip mode, turned on by programs with 0011
+pub const MEGA_WIDTH: usize = 256;
+pub const MEGA_HEIGHT: usize = 192;
+
+// 256x192 screen of palette indices, index 0 is transparent and drawn black
+pub struct MegaScreen {
+    pixels: Vec<u8>, // Palette index of every pixel, row by row
+    palette: [u32; 256], // Colors as 0xRRGGBB
+    sprite_width: usize, // Set with 03nn
+    sprite_height: usize, // Set with 04nn
+    collision: Option<u8>, // Drawing over this palette index sets VF, none until set with 09nn
+    #[cfg(feature = "std")]
+    buffer: Vec<u32>, // Colored pixels shown in the window
+}
+
+impl MegaScreen {
+    pub(super) fn new() -> Self {
+        MegaScreen {
+            pixels: vec![0; MEGA_WIDTH * MEGA_HEIGHT],
+            palette: [0; 256],
+            sprite_width: 0,
+            sprite_height: 0,
+            collision: None,
+            #[cfg(feature = "std")]
+            buffer: vec![0; MEGA_WIDTH * MEGA_HEIGHT],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pixels.fill(0);
+    }
+
+    // Sets colors from index 1 on, from 4 bytes each in ARGB order, alpha is ignored
+    pub fn load_palette(&mut self, colors: &[u8]) {
+        for (slot, color) in self.palette[1..].iter_mut().zip(colors.chunks_exact(4)) {
+            *slot = u32::from_be_bytes([0, color[1], color[2], color[3]]);
+        }
+    }
+
+    // Sprite width in pixels, 0 stands for 256
+    pub fn set_sprite_width(&mut self, width: u8) {
+        self.sprite_width = sprite_size(width);
+    }
+
+    // Sprite height in pixels, 0 stands for 256
+    pub fn set_sprite_height(&mut self, height: u8) {
+        self.sprite_height = sprite_size(height);
+    }
+
+    pub fn sprite_height(&self) -> usize {
+        self.sprite_height
+    }
+
+    // Bytes a sprite of the current size takes, one per pixel
+    pub fn sprite_len(&self) -> usize {
+        self.sprite_width * self.sprite_height
+    }
+
+    pub fn set_collision_color(&mut self, index: u8) {
+        self.collision = Some(index);
+    }
+
+    // Copies the sprite's opaque pixels onto the screen, clipped at the edges.
+    // True if one of them covered the collision color.
+    pub fn draw(&mut self, x: usize, y: usize, sprite: &[u8]) -> bool {
+        let mut collision = false;
+        for (j, row) in sprite.chunks(self.sprite_width.max(1)).enumerate().take_while(|&(j, _)| y + j < MEGA_HEIGHT) {
+            for (i, &index) in row.iter().enumerate().take_while(|&(i, _)| x + i < MEGA_WIDTH) {
+                let pixel = &mut self.pixels[x + i + (y + j) * MEGA_WIDTH];
+                if index != 0 {
+                    collision |= Some(*pixel) == self.collision;
+                    *pixel = index;
+                }
+            }
+        }
+        collision
+    }
+
+    // Palette index of the pixel, 0 outside the screen
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        if x < MEGA_WIDTH && y < MEGA_HEIGHT { self.pixels[x + y * MEGA_WIDTH] } else { 0 }
+    }
+
+    // Colors the pixels with the palette, row by row
+    #[cfg(feature = "std")]
+    pub(super) fn render(&mut self) -> &[u32] {
+        for (color, &index) in self.buffer.iter_mut().zip(self.pixels.iter()) {
+            *color = self.palette[index as usize];
+        }
+        &self.buffer
+    }
+}
+
+fn sprite_size(size: u8) -> usize {
+    if size == 0 { 256 } else { size as usize }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_palette() {
+        let mut screen = MegaScreen::new();
+        screen.load_palette(&[0xff, 0x12, 0x34, 0x56, 0x00, 0xab, 0xcd, 0xef]);
+        assert_eq!(&screen.palette[..3], &[0, 0x123456, 0xabcdef]);
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut screen = MegaScreen::new();
+        screen.set_sprite_width(2);
+        screen.set_sprite_height(2);
+        assert_eq!(screen.sprite_len(), 4);
+        assert!(!screen.draw(10, 20, &[1, 1, 1, 1])); // No collision color until one is set
+        screen.clear();
+        screen.set_collision_color(2);
+        assert!(!screen.draw(10, 20, &[1, 0, 2, 3]));
+        assert_eq!([screen.pixel(10, 20), screen.pixel(11, 20), screen.pixel(10, 21), screen.pixel(11, 21)], [1, 0, 2, 3]);
+
+        // Covering the collision color is reported, transparent pixels don't count
+        assert!(!screen.draw(11, 20, &[0, 0, 0, 4]));
+        assert!(screen.draw(10, 20, &[0, 0, 5, 0]));
+        assert_eq!(screen.pixel(10, 21), 5);
+    }
+
+    #[test]
+    fn test_draw_clips() {
+        let mut screen = MegaScreen::new();
+        screen.set_sprite_width(2);
+        screen.set_sprite_height(2);
+        screen.draw(MEGA_WIDTH - 1, MEGA_HEIGHT - 1, &[1, 2, 3, 4]);
+        assert_eq!(screen.pixel(MEGA_WIDTH - 1, MEGA_HEIGHT - 1), 1);
+        assert_eq!(screen.pixel(0, MEGA_HEIGHT - 1), 0);
+        assert_eq!(screen.pixel(MEGA_WIDTH - 1, 0), 0);
+    }
+
+    #[test]
+    fn test_sprite_size_zero_is_256() {
+        let mut screen = MegaScreen::new();
+        screen.set_sprite_width(0);
+        screen.set_sprite_height(1);
+        assert_eq!(screen.sprite_len(), 256);
+    }
+}
@@ -0,0 +1,682 @@
+use alloc::{format, string::String, vec, vec::Vec};
+use core::cell::{Cell, Ref, RefCell};
+#[cfg(feature = "std")]
+use std::{fs::File, io::{BufReader, Read}, path::Path};
+use super::{OpCode, MAX_MEMORY_SIZE, MEMORY_SIZE, PROGRAM_START, SPRITE_SIZE, errors::{Chip8Error, LoadError}, splash::SPLASH};
+use super::font::BIG_FONT;
+use super::heatmap::Heatmap;
+#[cfg(feature = "std")]
+use super::rom;
+use super::watchpoint::{Access, WatchHit, Watchpoint};
+
+// Where the font sprites are stored, programs find them through Fx29 and the large ones through Fx30
+const FONT_START: u16 = 0x000;
+const BIG_FONT_START: u16 = 0x050;
+const BIG_SPRITE_SIZE: u16 = 10;
+
+// Default font sprites, 5 bytes per hex digit
+pub const FONT: [u8; 80] = [
+    0xf0, 0x90, 0x90, 0x90, 0xf0, // "0"
+    0x20, 0x60, 0x20, 0x20, 0x70, // "1"
+    0xf0, 0x10, 0xf0, 0x80, 0xf0, // "2"
+    0xf0, 0x10, 0xf0, 0x10, 0xf0, // "3"
+    0x90, 0x90, 0xf0, 0x10, 0x10, // "4"
+    0xf0, 0x80, 0xf0, 0x10, 0xf0, // "5"
+    0xf0, 0x80, 0xf0, 0x90, 0xf0, // "6"
+    0xf0, 0x10, 0x20, 0x40, 0x40, // "7"
+    0xf0, 0x90, 0xf0, 0x90, 0xf0, // "8"
+    0xf0, 0x90, 0xf0, 0x10, 0xf0, // "9"
+    0xf0, 0x90, 0xf0, 0x90, 0x90, // "A"
+    0xe0, 0x90, 0xe0, 0x90, 0xe0, // "B"
+    0xf0, 0x80, 0x80, 0x80, 0xf0, // "C"
+    0xe0, 0x90, 0x90, 0x90, 0xe0, // "D"
+    0xf0, 0x80, 0xf0, 0x80, 0xf0, // "E"
+    0xf0, 0x80, 0xf0, 0x80, 0x80  // "F"
+];
+
+// What happens when the program writes below the program start, to the fonts and the
+// memory the original interpreter kept for itself, selectable with --protect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Protection {
+    #[default]
+    Off,
+    Warn, // Prints every such write
+    Strict, // Stops the program with an error
+}
+
+const PROTECTIONS: &[(&str, Protection)] = &[
+    ("off", Protection::Off),
+    ("warn", Protection::Warn),
+    ("strict", Protection::Strict),
+];
+
+impl Protection {
+    pub fn parse(name: &str) -> Option<Protection> {
+        PROTECTIONS.iter().find(|(protection, _)| *protection == name).map(|(_, protection)| *protection)
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        PROTECTIONS.iter().map(|(name, _)| *name)
+    }
+}
+
+// Size of memory and where programs are loaded into it, see Memory::with_layout. Tools looking
+// at a ROM without running it take it from the platform profile, as addresses depend on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub size: usize,
+    pub program_start: u16,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout { size: MEMORY_SIZE, program_start: PROGRAM_START }
+    }
+}
+
+pub struct Memory {
+    memory: Vec<u8>,
+    program_start: u16, // Where programs are loaded and start running
+    font_table: [u16; 16], // Sprite address of every hex digit, used by Fx29
+    font: [u8; 80], // Loaded font, kept to restore it after clearing
+    rom: Vec<u8>, // Loaded program, kept to restart it
+    name: Option<String>, // File name of the program without the extension, if loaded from a file
+    watchpoints: Vec<Watchpoint>,
+    watch_hit: Cell<Option<WatchHit>>, // First watched access since the last check
+    accesses: Option<RefCell<Vec<(u16, Access)>>>, // Every access since the last check, if logged
+    decoded: Option<Vec<Option<OpCode>>>, // Predecoded instruction at every address, dropped when written to
+    code_end: u16, // Past the highest instruction executed, writes from the program start up to it modify code
+    code_write: Option<u16>, // First write into the code since the last check
+    protection: Protection,
+    reserved_write: Option<u16>, // First write below the program start since the last check, if protected
+    heatmap: Option<RefCell<Heatmap>>, // Accesses counted by address, if enabled
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Memory::with_layout(MEMORY_SIZE, PROGRAM_START).expect("default layout is valid")
+    }
+
+    // Creates memory of another size or loading programs elsewhere, e.g. 64KB for XO-CHIP
+    // or 0x600 for ETI-660 programs. The size is a power of two from 4KB to 64KB and
+    // the program starts past the fonts.
+    pub fn with_layout(size: usize, program_start: u16) -> Result<Memory, Chip8Error> {
+        if !size.is_power_of_two() || !(MEMORY_SIZE..=MAX_MEMORY_SIZE).contains(&size) {
+            return Err(LoadError::InvalidArgument(format!("memory size must be a power of two from {MEMORY_SIZE:#X} to {MAX_MEMORY_SIZE:#X}, got {size:#X}")).into());
+        }
+        let fonts_end = BIG_FONT_START + BIG_FONT.len() as u16;
+        if program_start < fonts_end || program_start as usize >= size {
+            return Err(LoadError::InvalidArgument(format!("programs must start from {fonts_end:#X} to the end of memory, got {program_start:#X}")).into());
+        }
+
+        let mut memory = Memory { memory: vec![0; size], program_start, font_table: [0; 16], font: FONT, rom: Vec::new(), name: None, watchpoints: Vec::new(), watch_hit: Cell::new(None), accesses: None, decoded: None, code_end: program_start, code_write: None, protection: Protection::Off, reserved_write: None, heatmap: None };
+
+        // Load font sprites into memory - 0x00 to 0x4F, then the large ones up to 0xEF
+        memory.load_font(&FONT).expect("default font is valid");
+        memory.load_big_font();
+        Ok(memory)
+    }
+
+    // Bytes of memory, addresses wrap around past it
+    pub fn size(&self) -> usize {
+        self.memory.len()
+    }
+
+    pub fn program_start(&self) -> u16 {
+        self.program_start
+    }
+
+    pub fn layout(&self) -> Layout {
+        Layout { size: self.size(), program_start: self.program_start }
+    }
+
+    // Replaces the font sprites, e.g. with thicker digits for readability.
+    // Glyphs must light at least one pixel and fit in 4 columns like the default ones,
+    // since programs space digits by their width.
+    pub fn load_font(&mut self, font: &[u8; 80]) -> Result<(), Chip8Error> {
+        for (digit, glyph) in font.chunks(SPRITE_SIZE as usize).enumerate() {
+            if glyph.iter().all(|&row| row == 0) {
+                return Err(LoadError::InvalidFont(format!("glyph {digit:X} is empty")).into());
+            }
+            if glyph.iter().any(|&row| row & 0x0f != 0) {
+                return Err(LoadError::InvalidFont(format!("glyph {digit:X} is wider than 4 pixels")).into());
+            }
+        }
+
+        let start = FONT_START as usize;
+        self.memory[start..start + font.len()].copy_from_slice(font);
+        self.predecode();
+        self.font = *font;
+        for (digit, address) in self.font_table.iter_mut().enumerate() {
+            *address = FONT_START + digit as u16 * SPRITE_SIZE;
+        }
+        Ok(())
+    }
+
+    fn load_big_font(&mut self) {
+        let start = BIG_FONT_START as usize;
+        self.memory[start..start + BIG_FONT.len()].copy_from_slice(&BIG_FONT);
+    }
+
+    // Address of the sprite for the hex digit in the low nibble
+    pub fn font_address(&self, digit: u8) -> u16 {
+        self.font_table[(digit & 0xf) as usize]
+    }
+
+    // Address of the large 8x10 sprite for the hex digit in the low nibble
+    pub fn big_font_address(&self, digit: u8) -> u16 {
+        BIG_FONT_START + (digit & 0xf) as u16 * BIG_SPRITE_SIZE
+    }
+
+    // Creates memory with the built-in splash program loaded
+    pub fn splash() -> Self {
+        Memory::from_bytes(&SPLASH).expect("splash program fits in memory")
+    }
+
+    // Addresses past the end of memory wrap around to the start, as the address bus is only as wide as memory
+    pub fn read_byte(&self, addr: u16) -> u8 {
+        let addr = self.wrap(addr);
+        self.watch(addr, Access::Read);
+        self.memory[addr as usize]
+    }
+    
+    // Same here
+    pub fn write_byte(&mut self, addr: u16, data: u8) {
+        let addr = self.wrap(addr);
+        self.watch(addr, Access::Write);
+        self.memory[addr as usize] = data;
+        if self.code_write.is_none() && (self.program_start..self.code_end).contains(&addr) {
+            self.code_write = Some(addr);
+        }
+        if self.protection != Protection::Off && self.reserved_write.is_none() && addr < self.program_start {
+            self.reserved_write = Some(addr);
+        }
+        // Self-modifying code, both instructions containing the byte have to be decoded again
+        let previous = self.wrap(addr.wrapping_sub(1));
+        if let Some(decoded) = self.decoded.as_mut() {
+            decoded[addr as usize] = None;
+            decoded[previous as usize] = None;
+        }
+    }
+
+    // Fetches an instruction from memory - 2 bytes, fetching doesn't trigger watchpoints
+    pub fn get_instruction(&self, addr: u16) -> u16 {
+        let high_byte = self.memory[self.wrap(addr) as usize];
+        let low_byte = self.memory[self.wrap(addr.wrapping_add(1)) as usize];
+    
+        ((high_byte as u16) << 8) | low_byte as u16
+    }
+
+    // Fetches a decoded instruction, from the predecoded table if enabled
+    pub(super) fn fetch(&mut self, addr: u16) -> OpCode {
+        let addr = self.wrap(addr);
+        match self.decoded.as_ref().and_then(|decoded| decoded[addr as usize]) {
+            Some(op_code) => op_code,
+            None => {
+                #[cfg(feature = "tracing")]
+                let _span = tracing::trace_span!("decode", pc = addr).entered();
+                let op_code = OpCode::decode(self.get_instruction(addr));
+                if let Some(decoded) = self.decoded.as_mut() {
+                    decoded[addr as usize] = Some(op_code);
+                }
+                op_code
+            }
+        }
+    }
+
+    // Decodes every instruction up front so fetching skips it, off by default
+    pub fn set_predecoding(&mut self, enabled: bool) {
+        self.decoded = enabled.then(Vec::new);
+        self.predecode();
+    }
+
+    pub fn is_predecoding(&self) -> bool {
+        self.decoded.is_some()
+    }
+
+    // Decodes the instruction at every address, needed after loading anything larger than a byte
+    fn predecode(&mut self) {
+        let Some(mut decoded) = self.decoded.take() else { return };
+        decoded.clear();
+        decoded.extend((0..self.size()).map(|addr| Some(OpCode::decode(self.get_instruction(addr as u16)))));
+        self.decoded = Some(decoded);
+    }
+
+    // Loads program from bytes, e.g. a ROM embedded with include_bytes!. Memory past the program
+    // start is cleared first, so nothing of a longer program loaded before is left behind it.
+    pub fn load_from_bytes(&mut self, rom: &[u8]) -> Result<(), Chip8Error> {
+        let start = self.program_start as usize;
+        if rom.len() > self.size() - start {
+            return Err(LoadError::ProgramTooLarge(rom.len(), self.size() - start).into());
+        }
+        self.memory[start..].fill(0);
+        self.rom = rom.to_vec();
+        self.reload();
+        self.name = None;
+        Ok(())
+    }
+
+    // The loaded program as it was loaded
+    pub fn rom(&self) -> &[u8] {
+        &self.rom
+    }
+
+    // Restores the loaded program, undoing any changes it made to itself
+    pub fn reload(&mut self) {
+        let start = self.program_start as usize;
+        self.memory[start..start + self.rom.len()].copy_from_slice(&self.rom);
+        self.predecode();
+        self.code_end = self.program_start;
+        self.code_write = None;
+        self.reserved_write = None;
+    }
+
+    // Clears all memory, keeping only the font and the loaded program
+    pub fn clear(&mut self) {
+        self.memory.fill(0);
+        let start = FONT_START as usize;
+        self.memory[start..start + self.font.len()].copy_from_slice(&self.font);
+        self.load_big_font();
+        self.reload();
+    }
+
+    // Loads program from anything readable
+    #[cfg(feature = "std")]
+    pub fn load_from_reader(&mut self, mut reader: impl Read) -> Result<(), Chip8Error> {
+        let mut rom = Vec::new();
+        reader.read_to_end(&mut rom).map_err(LoadError::Read)?;
+        self.load_from_bytes(&rom)
+    }
+
+    // Loads program from the file at the given path, assembling Octo source
+    #[cfg(feature = "std")]
+    pub fn load_from_file(&mut self, file_path: impl AsRef<Path>) -> Result<(), Chip8Error> {
+        let file_path = file_path.as_ref();
+        self.load_from_bytes(&rom::read(file_path, self.layout())?)?;
+        self.name = file_path.file_stem().map(|name| name.to_string_lossy().into_owned());
+        Ok(())
+    }
+
+    // Pauses the emulator when the program accesses the watched addresses
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) {
+        self.watchpoints.push(watchpoint);
+    }
+
+    pub fn remove_watchpoint(&mut self, watchpoint: Watchpoint) {
+        self.watchpoints.retain(|&w| w != watchpoint);
+    }
+
+    pub fn clear_watchpoints(&mut self) {
+        self.watchpoints.clear();
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    // Takes the first watched access made since the last call
+    pub fn take_watch_hit(&self) -> Option<WatchHit> {
+        self.watch_hit.take()
+    }
+
+    // Notes that the instruction at addr was executed, extending the code region
+    pub(super) fn mark_executed(&mut self, addr: u16) {
+        self.code_end = self.code_end.max(self.wrap(addr).saturating_add(2));
+    }
+
+    // Takes the first write into the executed code made since the last call, if the program modified itself
+    pub fn take_code_write(&mut self) -> Option<u16> {
+        self.code_write.take()
+    }
+
+    // Guards the fonts and interpreter memory against stray writes, off by default.
+    // Loading fonts isn't affected.
+    pub fn set_protection(&mut self, protection: Protection) {
+        self.protection = protection;
+        self.reserved_write = None;
+    }
+
+    pub fn protection(&self) -> Protection {
+        self.protection
+    }
+
+    // Takes the first write below the program start made since the last call, if protected
+    pub fn take_reserved_write(&mut self) -> Option<u16> {
+        self.reserved_write.take()
+    }
+
+    // Starts or stops logging every access the program makes, dropping the ones logged so far
+    pub fn set_access_logging(&mut self, enabled: bool) {
+        self.accesses = enabled.then(|| RefCell::new(Vec::new()));
+    }
+
+    // Takes the accesses logged since the last call, in order
+    pub fn take_accesses(&self) -> Vec<(u16, Access)> {
+        self.accesses.as_ref().map(|accesses| accesses.take()).unwrap_or_default()
+    }
+
+    // Starts or stops counting the reads and writes of every address, dropping the counts so far.
+    // Like watchpoints, fetching instructions doesn't count.
+    pub fn set_heatmap(&mut self, enabled: bool) {
+        self.heatmap = enabled.then(|| RefCell::new(Heatmap::new()));
+    }
+
+    pub fn heatmap(&self) -> Option<Ref<'_, Heatmap>> {
+        self.heatmap.as_ref().map(RefCell::borrow)
+    }
+
+    // All of memory, reading it doesn't trigger watchpoints
+    pub fn bytes(&self) -> &[u8] {
+        &self.memory
+    }
+
+    // Writes back all of memory as returned by bytes, e.g. from a save state
+    #[cfg(feature = "std")]
+    pub(super) fn restore(&mut self, bytes: &[u8]) {
+        self.memory.copy_from_slice(bytes);
+        self.predecode();
+        self.code_write = None;
+        self.reserved_write = None;
+    }
+
+    // Address within memory
+    fn wrap(&self, addr: u16) -> u16 {
+        (addr as usize % self.size()) as u16
+    }
+
+    // Notes the access if it is watched and no earlier one is waiting, and logs it if enabled
+    fn watch(&self, addr: u16, access: Access) {
+        if let Some(accesses) = &self.accesses {
+            accesses.borrow_mut().push((addr, access));
+        }
+        if let Some(heatmap) = &self.heatmap {
+            heatmap.borrow_mut().record(addr, access);
+        }
+        if self.watch_hit.get().is_none() && self.watchpoints.iter().any(|w| w.matches(addr, access)) {
+            self.watch_hit.set(Some(WatchHit { addr, access }));
+        }
+    }
+
+    // Name of the loaded program, known when it was loaded from a file
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    // Loads program from file
+    #[cfg(feature = "std")]
+    pub fn load(&mut self, file: &File) -> Result<(), Chip8Error> {
+        self.load_from_reader(BufReader::new(file))
+    }
+
+    // Creates memory with the given program loaded
+    pub fn from_bytes(rom: &[u8]) -> Result<Memory, Chip8Error> {
+        let mut memory = Memory::new();
+        memory.load_from_bytes(rom)?;
+        Ok(memory)
+    }
+
+    // Creates memory with the program from the given file loaded
+    #[cfg(feature = "std")]
+    pub fn from_file(file_path: impl AsRef<Path>) -> Result<Memory, Chip8Error> {
+        let mut memory = Memory::new();
+        memory.load_from_file(file_path)?;
+        Ok(memory)
+    }
+
+    // Loads file from args - 2nd argument
+    #[cfg(feature = "std")]
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Memory, Chip8Error> {
+        match (args.next(), args.next()) {
+            (Some(_), Some(file_path)) => Memory::from_file(file_path),
+            _ => Err(LoadError::MissingFilePath.into())
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let memory = Memory::new();
+        assert_eq!(memory.read_byte(0), 0xF0);
+        assert_eq!(memory.read_byte(1), 0x90);
+        assert_eq!(memory.read_byte(2), 0x90);
+        assert_eq!(memory.read_byte(3), 0x90);
+        assert_eq!(memory.read_byte(4), 0xF0);
+    }
+
+    #[test]
+    fn test_load_font() {
+        let mut memory = Memory::new();
+        let mut font = FONT;
+        font[..5].copy_from_slice(&[0xf0, 0xf0, 0xf0, 0xf0, 0xf0]);
+        memory.load_font(&font).unwrap();
+        assert_eq!(memory.read_byte(memory.font_address(0x0) + 1), 0xf0);
+        assert_eq!(memory.font_address(0x1a), memory.font_address(0xa));
+
+        font[5..10].fill(0);
+        assert!(matches!(memory.load_font(&font), Err(Chip8Error::Load(LoadError::InvalidFont(_)))));
+        font[5..10].fill(0xff);
+        assert!(matches!(memory.load_font(&font), Err(Chip8Error::Load(LoadError::InvalidFont(_)))));
+        assert_eq!(memory.read_byte(memory.font_address(0x1)), 0x20); // Unchanged after a rejected font
+    }
+
+    #[test]
+    fn test_reload_and_clear() {
+        let mut memory = Memory::from_bytes(&[0x12, 0x00]).unwrap();
+        memory.write_byte(PROGRAM_START, 0xff);
+        memory.write_byte(0x300, 0xff);
+        memory.reload();
+        assert_eq!(memory.get_instruction(PROGRAM_START), 0x1200);
+        assert_eq!(memory.read_byte(0x300), 0xff);
+
+        let mut font = FONT;
+        font[0] = 0xe0;
+        memory.load_font(&font).unwrap();
+        memory.clear();
+        assert_eq!(memory.get_instruction(PROGRAM_START), 0x1200);
+        assert_eq!(memory.read_byte(0x300), 0x00);
+        assert_eq!(memory.read_byte(0x00), 0xe0);
+        assert_eq!(memory.read_byte(memory.big_font_address(0x1)), 0x18);
+    }
+
+    #[test]
+    fn test_splash() {
+        let memory = Memory::splash();
+        assert_eq!(memory.get_instruction(PROGRAM_START), 0x00E0);
+        assert_eq!(memory.read_byte(PROGRAM_START + SPLASH.len() as u16 - 1), 0xF0);
+    }
+
+    #[test]
+    fn test_read_write_byte() {
+        let mut memory = Memory::new();
+        memory.write_byte(0x200, 0xAB);
+        assert_eq!(memory.read_byte(0x200), 0xAB);
+    }
+
+    #[test]
+    fn test_code_write() {
+        let mut memory = Memory::new();
+        memory.write_byte(0x200, 0x12); // Nothing executed yet
+        assert_eq!(memory.take_code_write(), None);
+        memory.mark_executed(0x200);
+        memory.mark_executed(0x210);
+        memory.write_byte(0x212, 0x00);
+        assert_eq!(memory.take_code_write(), None);
+        memory.write_byte(0x211, 0x00);
+        memory.write_byte(0x204, 0x00);
+        assert_eq!(memory.take_code_write(), Some(0x211));
+        assert_eq!(memory.take_code_write(), None);
+
+        memory.reload();
+        memory.write_byte(0x204, 0x00);
+        assert_eq!(memory.take_code_write(), None);
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let mut memory = Memory::new();
+        memory.read_byte(0x300);
+        assert!(memory.heatmap().is_none());
+        memory.set_heatmap(true);
+        memory.read_byte(0x300);
+        memory.write_byte(0x300, 0x01);
+        memory.get_instruction(0x300); // Fetches don't count
+        let heatmap = memory.heatmap().unwrap();
+        assert_eq!((heatmap.reads(0x300), heatmap.writes(0x300)), (1, 1));
+    }
+
+    #[test]
+    fn test_out_of_bounds_wraps() {
+        let mut memory = Memory::new();
+        memory.write_byte(MEMORY_SIZE as u16 + 0x300, 0xAB);
+        assert_eq!(memory.read_byte(0x300), 0xAB);
+        assert_eq!(memory.read_byte(0xF300), 0xAB);
+        memory.write_byte(0x000, 0xCD);
+        memory.write_byte(0xFFF, 0x12);
+        assert_eq!(memory.get_instruction(0xFFF), 0x12CD);
+    }
+
+    #[test]
+    fn test_protection() {
+        let mut memory = Memory::new();
+        memory.write_byte(0x100, 0xAB);
+        assert_eq!(memory.take_reserved_write(), None); // Off by default
+
+        memory.set_protection(Protection::parse("strict").unwrap());
+        memory.write_byte(0x200, 0xAB);
+        assert_eq!(memory.take_reserved_write(), None);
+        memory.write_byte(0x1FF, 0xAB);
+        memory.write_byte(0x000, 0xAB);
+        assert_eq!(memory.take_reserved_write(), Some(0x1FF));
+        assert_eq!(memory.take_reserved_write(), None);
+        assert_eq!(memory.read_byte(0x1FF), 0xAB); // Written anyway, the emulator decides what to do
+        assert!(Protection::names().all(|name| Protection::parse(name).is_some()));
+    }
+
+    #[test]
+    fn test_with_layout() {
+        let mut memory = Memory::with_layout(MAX_MEMORY_SIZE, 0x600).unwrap();
+        memory.load_from_bytes(&[0x12, 0x34]).unwrap();
+        assert_eq!((memory.size(), memory.program_start()), (MAX_MEMORY_SIZE, 0x600));
+        assert_eq!(memory.get_instruction(0x600), 0x1234);
+        memory.write_byte(0xF300, 0xAB);
+        assert_eq!(memory.read_byte(0x300), 0x00); // No longer wraps around
+        assert_eq!(memory.read_byte(0xF300), 0xAB);
+        assert_eq!(memory.read_byte(0), FONT[0]);
+
+        assert!(memory.load_from_bytes(&vec![0; MAX_MEMORY_SIZE - 0x600 + 1]).is_err());
+        assert!(Memory::with_layout(0x3000, PROGRAM_START).is_err());
+        assert!(Memory::with_layout(MEMORY_SIZE / 2, PROGRAM_START).is_err());
+        assert!(Memory::with_layout(MEMORY_SIZE, 0x050).is_err());
+        assert!(Memory::with_layout(MEMORY_SIZE, MEMORY_SIZE as u16).is_err());
+    }
+
+    #[test]
+    fn test_get_instruction() {
+        let mut memory = Memory::new();
+        memory.write_byte(0x200, 0xAB);
+        memory.write_byte(0x201, 0xCD);
+        assert_eq!(memory.get_instruction(0x200), 0xABCD);
+    }
+
+    #[test]
+    fn test_predecoding() {
+        let mut memory = Memory::from_bytes(&[0x12, 0x34, 0x56, 0x78]).unwrap();
+        memory.set_predecoding(true);
+        assert_eq!(memory.fetch(0x200).code, 0x1234);
+        assert_eq!(memory.fetch(0x201).code, 0x3456);
+
+        // Rewriting a byte invalidates both instructions containing it
+        memory.write_byte(0x201, 0xFF);
+        assert_eq!(memory.fetch(0x200).code, 0x12FF);
+        assert_eq!(memory.fetch(0x201).code, 0xFF56);
+        memory.reload();
+        assert_eq!(memory.fetch(0x200).code, 0x1234);
+
+        memory.set_predecoding(false);
+        memory.write_byte(0x200, 0xAB);
+        assert_eq!(memory.fetch(0x200).code, 0xAB34);
+    }
+
+    #[test]
+    fn test_load_from_bytes() {
+        let mut memory = Memory::new();
+        memory.load_from_bytes(&[0x12, 0x34]).unwrap();
+        assert_eq!(memory.get_instruction(PROGRAM_START), 0x1234);
+
+        // A shorter program leaves nothing of the last one behind it
+        memory.load_from_bytes(&[0x60, 0x01, 0x61, 0x02]).unwrap();
+        memory.load_from_bytes(&[0x00, 0xE0]).unwrap();
+        assert_eq!(memory.get_instruction(PROGRAM_START), 0x00E0);
+        assert_eq!(memory.get_instruction(PROGRAM_START + 2), 0x0000);
+
+        let rom = vec![0; MEMORY_SIZE - PROGRAM_START as usize + 1];
+        assert!(matches!(memory.load_from_bytes(&rom), Err(Chip8Error::Load(LoadError::ProgramTooLarge(_, _)))));
+    }
+
+    #[test]
+    fn test_load_from_reader() {
+        let mut memory = Memory::new();
+        memory.load_from_reader(&[0xAB, 0xCD][..]).unwrap();
+        assert_eq!(memory.get_instruction(PROGRAM_START), 0xABCD);
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let mut memory = Memory::new();
+        memory.add_watchpoint(Watchpoint::parse("0x300-0x301:w").unwrap());
+        memory.read_byte(0x300);
+        memory.get_instruction(0x300);
+        assert_eq!(memory.take_watch_hit(), None);
+
+        memory.write_byte(0x301, 0xAA);
+        memory.write_byte(0x300, 0xBB); // Only the first access is kept
+        assert_eq!(memory.take_watch_hit(), Some(WatchHit { addr: 0x301, access: Access::Write }));
+        assert_eq!(memory.take_watch_hit(), None);
+
+        memory.remove_watchpoint(Watchpoint::parse("0x300-0x301:w").unwrap());
+        memory.write_byte(0x300, 0xCC);
+        assert_eq!(memory.take_watch_hit(), None);
+
+        memory.add_watchpoint(Watchpoint::parse("0x300").unwrap());
+        memory.clear_watchpoints();
+        memory.write_byte(0x300, 0xCC);
+        assert_eq!(memory.take_watch_hit(), None);
+    }
+
+    #[test]
+    fn test_access_logging() {
+        let mut memory = Memory::new();
+        memory.write_byte(0x300, 0xAA);
+        assert!(memory.take_accesses().is_empty());
+
+        memory.set_access_logging(true);
+        memory.write_byte(0x300, 0xBB);
+        memory.read_byte(0x301);
+        memory.get_instruction(0x300);
+        assert_eq!(memory.take_accesses(), vec![(0x300, Access::Write), (0x301, Access::Read)]);
+        assert!(memory.take_accesses().is_empty());
+        assert_eq!(memory.bytes()[0x300], 0xBB);
+    }
+
+    #[test]
+    fn test_name() {
+        let path = std::env::temp_dir().join("chip8_test_name.ch8");
+        std::fs::write(&path, [0x00, 0xE0]).unwrap();
+        let mut memory = Memory::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(memory.name(), Some("chip8_test_name"));
+
+        memory.load_from_bytes(&[0x00, 0xE0]).unwrap();
+        assert_eq!(memory.name(), None);
+    }
+
+    #[test]
+    fn test_from_file_missing() {
+        assert!(matches!(Memory::from_file("missing.ch8"), Err(Chip8Error::Load(LoadError::FileRead { .. }))));
+    }
+}
@@ -0,0 +1,109 @@
+use super::DISPLAY_WIDTH;
+use super::text::{draw_text, LINE_HEIGHT};
+
+// Entries of the pause menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    Resume,
+    Reset,
+    LoadState,
+    Quit,
+}
+
+impl MenuItem {
+    const ALL: [MenuItem; 4] = [MenuItem::Resume, MenuItem::Reset, MenuItem::LoadState, MenuItem::Quit];
+
+    fn label(self) -> &'static str {
+        match self {
+            MenuItem::Resume => "RESUME",
+            MenuItem::Reset => "RESET",
+            MenuItem::LoadState => "LOAD STATE",
+            MenuItem::Quit => "QUIT",
+        }
+    }
+
+    // Loading needs a save state to go back to
+    fn is_enabled(self, saved: bool) -> bool {
+        self != MenuItem::LoadState || saved
+    }
+}
+
+// Menu shown over the screen while paused, drawn with the display's own pixels
+pub struct PauseMenu {
+    selected: usize,
+    saved: bool, // Whether there is a save state to load
+}
+
+impl PauseMenu {
+    pub fn new(saved: bool) -> Self {
+        PauseMenu { selected: 0, saved }
+    }
+
+    pub fn selected(&self) -> MenuItem {
+        MenuItem::ALL[self.selected]
+    }
+
+    // Moves the selection up or down, skipping disabled entries and wrapping around
+    pub fn navigate(&mut self, down: bool) {
+        let len = MenuItem::ALL.len();
+        loop {
+            self.selected = if down { (self.selected + 1) % len } else { (self.selected + len - 1) % len };
+            if self.selected().is_enabled(self.saved) {
+                break;
+            }
+        }
+    }
+
+    // Dims the screen and draws the menu over it
+    pub fn render(&self, buffer: &mut [u32], filled: u32, empty: u32) {
+        for pixel in buffer.iter_mut() {
+            *pixel = (*pixel & 0xfcfcfc) >> 2;
+        }
+
+        draw_text(buffer, DISPLAY_WIDTH, 1, 1, "PAUSED", filled);
+        for (i, item) in MenuItem::ALL.iter().enumerate() {
+            let y = 1 + (i + 1) * LINE_HEIGHT;
+            let color = if item.is_enabled(self.saved) { filled } else { ((filled & 0xfefefe) >> 1) + ((empty & 0xfefefe) >> 1) };
+            if i == self.selected {
+                draw_text(buffer, DISPLAY_WIDTH, 1, y, ">", filled);
+            }
+            draw_text(buffer, DISPLAY_WIDTH, 5, y, item.label(), color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::DISPLAY_HEIGHT;
+
+    #[test]
+    fn test_navigate_skips_disabled() {
+        let mut menu = PauseMenu::new(false);
+        assert_eq!(menu.selected(), MenuItem::Resume);
+        menu.navigate(true);
+        assert_eq!(menu.selected(), MenuItem::Reset);
+        menu.navigate(true);
+        assert_eq!(menu.selected(), MenuItem::Quit);
+        menu.navigate(true);
+        assert_eq!(menu.selected(), MenuItem::Resume);
+        menu.navigate(false);
+        assert_eq!(menu.selected(), MenuItem::Quit);
+
+        let mut menu = PauseMenu::new(true);
+        menu.navigate(true);
+        menu.navigate(true);
+        assert_eq!(menu.selected(), MenuItem::LoadState);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut buffer = vec![0xffffff; DISPLAY_WIDTH * DISPLAY_HEIGHT];
+        PauseMenu::new(false).render(&mut buffer, 0xffffff, 0x000000);
+        assert_eq!(buffer[0], 0x3f3f3f); // Dimmed screen
+        assert_eq!(buffer[2 + DISPLAY_WIDTH], 0xffffff); // Top of the "P" in PAUSED
+        assert_eq!(buffer[1 + 7 * DISPLAY_WIDTH], 0xffffff); // Cursor next to RESUME
+        assert_eq!(buffer[5 + 19 * DISPLAY_WIDTH], 0x7f7f7f); // Disabled LOAD STATE
+        assert_eq!(buffer[1 + 19 * DISPLAY_WIDTH], 0x3f3f3f);
+    }
+}
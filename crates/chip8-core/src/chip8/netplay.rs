@@ -0,0 +1,154 @@
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use super::errors::{Chip8Error, ExecError, IoError};
+
+// Port the host listens on and a joining address without one connects to
+pub const NETPLAY_PORT: u16 = 6464;
+
+// First word of the handshake, changes whenever the messages do
+const VERSION: &str = "chip8-netplay/1";
+
+// Bytes sent every frame: the frame, the keys held and a hash of the machine state
+const MESSAGE_SIZE: usize = 8 + 2 + 8;
+
+// Which side of the link to be
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetplayMode {
+    Host, // Wait for the other player on NETPLAY_PORT
+    Join(String), // Connect to the host at the address, NETPLAY_PORT unless given
+}
+
+// Link to the other player's emulator, both exchange the keys held every frame and wait for
+// each other, so both machines run in lockstep with the same input
+pub struct Netplay {
+    stream: TcpStream,
+    seed: u64, // Random seed both machines run with, picked by the host
+}
+
+impl Netplay {
+    // Waits for the other player to connect, the fingerprint of both machines has to match
+    pub(super) fn host(port: u16, fingerprint: u64, seed: u64) -> Result<Netplay, Chip8Error> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).map_err(IoError::Netplay)?;
+        Netplay::accept(&listener, fingerprint, seed)
+    }
+
+    fn accept(listener: &TcpListener, fingerprint: u64, seed: u64) -> Result<Netplay, Chip8Error> {
+        let (stream, _) = listener.accept().map_err(IoError::Netplay)?;
+        Netplay::handshake(stream, fingerprint, Some(seed))
+    }
+
+    pub(super) fn join(addr: &str, fingerprint: u64) -> Result<Netplay, Chip8Error> {
+        let stream = match addr.contains(':') {
+            true => TcpStream::connect(addr),
+            false => TcpStream::connect((addr, NETPLAY_PORT)),
+        };
+        Netplay::handshake(stream.map_err(IoError::Netplay)?, fingerprint, None)
+    }
+
+    // Both sides send a line with the version, their fingerprint and the host's seed
+    fn handshake(mut stream: TcpStream, fingerprint: u64, seed: Option<u64>) -> Result<Netplay, Chip8Error> {
+        stream.set_nodelay(true).map_err(IoError::Netplay)?;
+        writeln!(stream, "{VERSION} {fingerprint:016x} {:016x}", seed.unwrap_or(0)).map_err(IoError::Netplay)?;
+
+        let line = read_line(&mut stream).map_err(IoError::Netplay)?;
+        let mut fields = line.split_whitespace();
+        let version = fields.next();
+        let theirs = fields.next().and_then(|field| u64::from_str_radix(field, 16).ok());
+        let their_seed = fields.next().and_then(|field| u64::from_str_radix(field, 16).ok());
+        let (Some(VERSION), Some(theirs), Some(their_seed)) = (version, theirs, their_seed) else {
+            return Err(mismatch("the other side doesn't speak the same netplay protocol"));
+        };
+        if theirs != fingerprint {
+            return Err(mismatch("the other player runs a different program or speed"));
+        }
+        Ok(Netplay { stream, seed: seed.unwrap_or(their_seed) })
+    }
+
+    pub(super) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Sends the keys held in the frame and the state the machine reached before it, then waits
+    // for the other player's, returning their keys. Machines in different states desynced.
+    pub(super) fn exchange(&mut self, frame: u64, keys: u16, state: u64) -> Result<u16, Chip8Error> {
+        let mut message = [0; MESSAGE_SIZE];
+        message[..8].copy_from_slice(&frame.to_le_bytes());
+        message[8..10].copy_from_slice(&keys.to_le_bytes());
+        message[10..].copy_from_slice(&state.to_le_bytes());
+        self.stream.write_all(&message).map_err(IoError::Netplay)?;
+
+        let mut reply = [0; MESSAGE_SIZE];
+        self.stream.read_exact(&mut reply).map_err(IoError::Netplay)?;
+        let (their_frame, rest) = reply.split_at(8);
+        let (their_keys, their_state) = rest.split_at(2);
+        if their_frame != frame.to_le_bytes() || their_state != state.to_le_bytes() {
+            return Err(ExecError::Desync(frame).into());
+        }
+        Ok(u16::from_le_bytes([their_keys[0], their_keys[1]]))
+    }
+}
+
+// Stable FNV-1a hash, for fingerprints and states both sides compare
+pub(super) fn hash(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+// Reads up to a newline byte by byte, leaving the frames sent right after it unread
+fn read_line(stream: &mut TcpStream) -> io::Result<String> {
+    let mut line = Vec::new();
+    let mut byte = [0];
+    while line.len() < 256 {
+        stream.read_exact(&mut byte)?;
+        if byte[0] == b'\n' {
+            break;
+        }
+        line.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+fn mismatch(message: &str) -> Chip8Error {
+    IoError::Netplay(io::Error::new(ErrorKind::InvalidData, message)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    // Host and joined side connected over the loopback interface
+    fn link(host_fingerprint: u64, join_fingerprint: u64) -> (Result<Netplay, Chip8Error>, Result<Netplay, Chip8Error>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let joining = thread::spawn(move || Netplay::join(&addr, join_fingerprint));
+        let host = Netplay::accept(&listener, host_fingerprint, 42);
+        (host, joining.join().unwrap())
+    }
+
+    #[test]
+    fn test_exchange() {
+        let (host, joined) = link(7, 7);
+        let (mut host, mut joined) = (host.unwrap(), joined.unwrap());
+        assert_eq!((host.seed(), joined.seed()), (42, 42));
+
+        let joining = thread::spawn(move || {
+            let keys = joined.exchange(0, 1 << 0xC, 99).unwrap();
+            (joined, keys)
+        });
+        assert_eq!(host.exchange(0, 1 << 0x1, 99).unwrap(), 1 << 0xC);
+        let (mut joined, keys) = joining.join().unwrap();
+        assert_eq!(keys, 1 << 0x1);
+
+        // Different states end the session on both sides
+        let joining = thread::spawn(move || joined.exchange(1, 0, 5));
+        assert!(matches!(host.exchange(1, 0, 6), Err(Chip8Error::Exec(ExecError::Desync(1)))));
+        assert!(joining.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_different_programs() {
+        let (host, joined) = link(7, 8);
+        assert!(matches!(host, Err(Chip8Error::Io(IoError::Netplay(_)))));
+        assert!(joined.is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use super::frontend::Key;
+use super::DISPLAY_WIDTH;
+use super::text::{draw_text, fill_rect, CHAR_WIDTH, LINE_HEIGHT};
+
+// What dismisses a notification
+pub enum Trigger {
+    ChipKey, // Any CHIP-8 key is held
+    Hotkey(Key), // The emulator hotkey is pressed
+    Paused, // Execution is paused
+    Resumed, // Execution runs again
+    Frames(u64), // The notification has been shown for this many frames
+}
+
+// Notification and what dismisses it
+pub type Rule = (&'static str, Trigger);
+
+// State of the emulator the rules are checked against, once a frame
+pub struct Status<F: Fn(Key) -> bool> {
+    pub frame: u64,
+    pub keys: u16, // Held CHIP-8 keys, bit n set if key n is down
+    pub paused: bool,
+    pub hotkey_pressed: F,
+}
+
+// Shows notifications on screen one at a time, each until its rule is followed
+pub struct Notifier {
+    rules: &'static [Rule],
+    current: usize,
+    shown_at: Option<u64>, // Frame at which the current notification appeared
+}
+
+impl Notifier {
+    pub fn new(rules: &'static [Rule]) -> Self {
+        Notifier { rules, current: 0, shown_at: None }
+    }
+
+    // Current notification, None once all are dismissed
+    pub fn notice(&self) -> Option<&'static str> {
+        self.rules.get(self.current).map(|(notice, _)| *notice)
+    }
+
+    // Moves on to the next notification once the current one is dismissed, returns true if it changed
+    pub fn update<F: Fn(Key) -> bool>(&mut self, status: &Status<F>) -> bool {
+        let Some((_, trigger)) = self.rules.get(self.current) else { return false };
+        let Some(shown_at) = self.shown_at else {
+            self.shown_at = Some(status.frame);
+            return true;
+        };
+
+        let done = match trigger {
+            Trigger::ChipKey => status.keys != 0,
+            Trigger::Hotkey(key) => (status.hotkey_pressed)(*key),
+            Trigger::Paused => status.paused,
+            Trigger::Resumed => !status.paused,
+            Trigger::Frames(frames) => status.frame - shown_at >= *frames,
+        };
+        if done {
+            self.current += 1;
+            self.shown_at = Some(status.frame);
+        }
+        done
+    }
+}
+
+// Draws a notification over the top of the screen, wrapped at spaces to the screen width
+pub fn render(buffer: &mut [u32], notice: &str, filled: u32, empty: u32) {
+    let mut lines: Vec<String> = Vec::new();
+    for word in notice.split(' ') {
+        match lines.last_mut() {
+            Some(line) if (line.len() + 1 + word.len()) * CHAR_WIDTH <= DISPLAY_WIDTH => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+
+    fill_rect(buffer, DISPLAY_WIDTH, 0, 0, DISPLAY_WIDTH, lines.len() * LINE_HEIGHT + 1, empty);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(buffer, DISPLAY_WIDTH, 1, 1 + i * LINE_HEIGHT, line, filled);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::DISPLAY_HEIGHT;
+
+    const RULES: &[Rule] = &[
+        ("HOLD A KEY", Trigger::ChipKey),
+        ("WAIT", Trigger::Frames(10)),
+        ("PAUSE", Trigger::Paused),
+        ("RESUME", Trigger::Resumed),
+        ("F1", Trigger::Hotkey(Key::F1)),
+    ];
+
+    fn status(frame: u64, keys: u16, paused: bool, hotkey: Option<Key>) -> Status<impl Fn(Key) -> bool> {
+        Status { frame, keys, paused, hotkey_pressed: move |key| Some(key) == hotkey }
+    }
+
+    #[test]
+    fn test_update() {
+        let mut notifier = Notifier::new(RULES);
+        assert!(notifier.update(&status(0, 0, false, None))); // First notification is shown right away
+        assert!(!notifier.update(&status(1, 0, false, None)));
+        assert!(notifier.update(&status(2, 0x0010, false, None)));
+        assert!(!notifier.update(&status(11, 0, false, None)));
+        assert!(notifier.update(&status(12, 0, false, None)));
+        assert!(notifier.update(&status(13, 0, true, None)));
+        assert!(!notifier.update(&status(14, 0, true, None)));
+        assert!(notifier.update(&status(15, 0, false, None)));
+        assert!(!notifier.update(&status(16, 0, false, Some(Key::F2))));
+        assert_eq!(notifier.notice(), Some("F1"));
+        assert!(notifier.update(&status(17, 0, false, Some(Key::F1))));
+        assert_eq!(notifier.notice(), None);
+        assert!(!notifier.update(&status(18, 0xffff, true, Some(Key::F1))));
+    }
+
+    #[test]
+    fn test_render() {
+        let mut buffer = vec![0xffffff; DISPLAY_WIDTH * DISPLAY_HEIGHT];
+        render(&mut buffer, "PRESS F1 TO SAVE THE STATE", 0xffffff, 0x000000);
+        assert_eq!(buffer[0], 0x000000); // Background of the notification
+        assert_eq!(buffer[1 + DISPLAY_WIDTH], 0xffffff); // Top of the "P"
+        assert_eq!(buffer[1 + 7 * DISPLAY_WIDTH], 0xffffff); // "T" of the wrapped second line
+        assert_eq!(buffer[13 * DISPLAY_WIDTH - 1], 0x000000); // Below the two lines
+        assert_eq!(buffer[14 * DISPLAY_WIDTH], 0xffffff); // Screen below is left as is
+    }
+}
@@ -0,0 +1,52 @@
+// Hex number, 0x prefix optional, as cheats, label files, watchpoints and save RAM ranges write
+// addresses and values
+pub(super) fn hex(text: &str) -> Option<u16> {
+    let text = text.trim();
+    let digits = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(text);
+    u16::from_str_radix(digits, 16).ok()
+}
+
+// Decimal, 0x hex or 0b binary, optionally negative, as breakpoint conditions and Octo source
+// write numbers
+#[cfg(feature = "std")]
+pub(super) fn number(token: &str) -> Option<i64> {
+    let (negative, digits) = match token.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, token),
+    };
+    let value = if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if let Some(binary) = digits.strip_prefix("0b").or_else(|| digits.strip_prefix("0B")) {
+        i64::from_str_radix(binary, 2).ok()?
+    } else if digits.starts_with(|c: char| c.is_ascii_digit()) {
+        digits.parse().ok()?
+    } else {
+        return None;
+    };
+    Some(if negative { -value } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(hex("2a0"), Some(0x2a0));
+        assert_eq!(hex(" 0x2A0 "), Some(0x2a0));
+        assert_eq!(hex("0X2a0"), Some(0x2a0));
+        assert_eq!(hex("0x"), None);
+        assert_eq!(hex("10000"), None);
+        assert_eq!(hex("zz"), None);
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number("42"), Some(42));
+        assert_eq!(number("0x2A"), Some(42));
+        assert_eq!(number("0b101010"), Some(42));
+        assert_eq!(number("-0x2a"), Some(-42));
+        assert_eq!(number("main"), None);
+        assert_eq!(number("0xzz"), None);
+    }
+}
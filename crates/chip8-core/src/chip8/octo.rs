@@ -0,0 +1,758 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use super::{errors::{Chip8Error, LoadError}, memory::Layout};
+use super::labels::Labels;
+use super::number::number;
+
+// Extension of Octo source files, assembled when loaded instead of read as a ROM
+pub const SOURCE_EXTENSION: &str = "8o";
+
+// Program assembled from Octo source, along with its labels for the debugger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assembled {
+    pub rom: Vec<u8>,
+    pub labels: Labels,
+}
+
+pub fn is_source(path: impl AsRef<Path>) -> bool {
+    path.as_ref().extension().is_some_and(|ext| ext.eq_ignore_ascii_case(SOURCE_EXTENSION))
+}
+
+// Assembles Octo source: labels, :const, :alias, :org, :next, :unpack, :byte and :call, every
+// statement of CHIP-8, SUPER-CHIP and XO-CHIP, and the if/then, if/begin/else/end and
+// loop/while/again control flow. Macros, :calc and :stringmode aren't supported.
+// Execution starts at the main label, reached through a jump at the program start unless the
+// source starts with it, as in Octo. The program is laid out from the program start and must
+// fit in the memory of the layout, e.g. 64KB for XO-CHIP.
+pub fn assemble(source: &str, layout: Layout) -> Result<Assembled, Chip8Error> {
+    let tokens = source.lines()
+        .enumerate()
+        .flat_map(|(n, line)| {
+            let code = line.split('#').next().unwrap_or_default();
+            code.split_whitespace().map(move |text| Token { text, line: n + 1 })
+        })
+        .collect();
+    Assembler::new(tokens, layout).run().map_err(|message| LoadError::Assembly(message).into())
+}
+
+pub fn assemble_file(path: impl AsRef<Path>, layout: Layout) -> Result<Assembled, Chip8Error> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path).map_err(|source| LoadError::FileRead { path: path.to_path_buf(), source })?;
+    assemble(&source, layout)
+}
+
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+}
+
+// Number, constant or label, the latter possibly defined further down
+enum Value<'a> {
+    Known(u16),
+    Label(&'a str),
+}
+
+// Part of the code a label defined further down is filled into
+#[derive(Clone, Copy)]
+enum Slot {
+    Addr, // The nnn of an instruction
+    Long, // The word following i := long
+    UnpackHigh(u8), // The byte of v0 := in :unpack, after the given nibble
+    UnpackLow, // The byte of v1 := in :unpack
+}
+
+struct Fixup<'a> {
+    addr: usize, // Of the instruction
+    slot: Slot,
+    name: &'a str,
+    line: usize,
+}
+
+// Right side of a comparison
+#[derive(Clone, Copy)]
+enum Operand {
+    Register(u8),
+    Byte(u8),
+}
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Eq,
+    Ne,
+    Key,
+    NotKey,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+}
+
+impl Comparison {
+    fn inverted(self) -> Comparison {
+        match self {
+            Comparison::Eq => Comparison::Ne,
+            Comparison::Ne => Comparison::Eq,
+            Comparison::Key => Comparison::NotKey,
+            Comparison::NotKey => Comparison::Key,
+            Comparison::Lt => Comparison::Ge,
+            Comparison::Ge => Comparison::Lt,
+            Comparison::Gt => Comparison::Le,
+            Comparison::Le => Comparison::Gt,
+        }
+    }
+}
+
+// Control flow waiting to be closed
+enum Block {
+    Branch(usize), // Jump over an if ... begin or else body, to the else or end
+    Loop(usize, Vec<usize>), // Start of the loop and the jumps of its whiles to past the again
+}
+
+struct Assembler<'a> {
+    tokens: Vec<Token<'a>>,
+    next: usize, // Index of the next token
+    line: usize, // Of the last token taken, for errors
+    rom: Vec<u8>, // Assembled from the program start on
+    here: usize, // Address of the next byte
+    layout: Layout, // Memory the program is assembled for
+    labels: HashMap<&'a str, u16>,
+    consts: HashMap<&'a str, u16>,
+    aliases: HashMap<&'a str, u8>,
+    fixups: Vec<Fixup<'a>>,
+    blocks: Vec<Block>,
+}
+
+impl<'a> Assembler<'a> {
+    fn new(tokens: Vec<Token<'a>>, layout: Layout) -> Self {
+        Assembler {
+            tokens,
+            next: 0,
+            line: 0,
+            rom: Vec::new(),
+            here: layout.program_start as usize,
+            layout,
+            labels: HashMap::new(),
+            consts: HashMap::new(),
+            aliases: HashMap::new(),
+            fixups: Vec::new(),
+            blocks: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Result<Assembled, String> {
+        let main_first = matches!(self.tokens.get(..2), Some([colon, name]) if colon.text == ":" && name.text == "main");
+        if !main_first {
+            self.emit(0x1000)?; // Jump to main, filled in at the end
+        }
+        while let Some(token) = self.take() {
+            self.statement(token)?;
+        }
+        if !self.blocks.is_empty() {
+            return Err("unexpected end of file, a begin or loop is never closed".to_string());
+        }
+
+        let main = *self.labels.get("main").ok_or("the program has no main label to start at")?;
+        if !main_first {
+            self.fill(self.layout.program_start as usize, Slot::Addr, main).map_err(|e| format!("main {e}"))?;
+        }
+        for fixup in std::mem::take(&mut self.fixups) {
+            let value = *self.labels.get(fixup.name).ok_or_else(|| format!("line {}: {} is not defined", fixup.line, fixup.name))?;
+            self.fill(fixup.addr, fixup.slot, value).map_err(|e| format!("line {}: {} {e}", fixup.line, fixup.name))?;
+        }
+
+        let mut labels = Labels::new();
+        for (name, addr) in self.labels {
+            labels.add(addr, name);
+        }
+        Ok(Assembled { rom: self.rom, labels })
+    }
+
+    fn statement(&mut self, token: &'a str) -> Result<(), String> {
+        match token {
+            ":" => {
+                let name = self.name()?;
+                self.define(name, self.here)?;
+            }
+            ":const" => {
+                let name = self.name()?;
+                let value = self.known()?;
+                self.consts.insert(name, value);
+            }
+            ":alias" => {
+                let name = self.name()?;
+                let register = self.register()?;
+                self.aliases.insert(name, register);
+            }
+            ":org" => {
+                let addr = self.known()? as usize;
+                if addr < self.layout.program_start as usize {
+                    return Err(self.error(format!("{addr:#05X} is before the program start")));
+                }
+                self.here = addr;
+            }
+            ":next" => {
+                let name = self.name()?;
+                self.define(name, self.here + 1)?; // The second byte of the next instruction
+            }
+            ":unpack" => {
+                let nibble = self.known()?;
+                if nibble > 0xF {
+                    return Err(self.error(format!("{nibble:#X} doesn't fit in a nibble")));
+                }
+                match self.value()? {
+                    Value::Known(addr) => {
+                        self.emit(0x6000 | (nibble << 4 | addr >> 8 & 0xF))?;
+                        self.emit(0x6100 | (addr & 0xFF))?;
+                    }
+                    Value::Label(name) => {
+                        self.later(name, Slot::UnpackHigh(nibble as u8));
+                        self.emit(0x6000)?;
+                        self.later(name, Slot::UnpackLow);
+                        self.emit(0x6100)?;
+                    }
+                }
+            }
+            ":byte" => {
+                let byte = self.byte()?;
+                self.write(byte)?;
+            }
+            ":call" => {
+                let target = self.value()?;
+                self.emit_addr(0x2000, target)?;
+            }
+            ":breakpoint" => {
+                self.expect()?; // Octo's own debugger, use --break here
+            }
+            ":monitor" => {
+                self.expect()?;
+                self.expect()?;
+            }
+            "clear" => self.emit(0x00E0)?,
+            "return" | ";" => self.emit(0x00EE)?,
+            "scroll-right" => self.emit(0x00FB)?,
+            "scroll-left" => self.emit(0x00FC)?,
+            "exit" => self.emit(0x00FD)?,
+            "lores" => self.emit(0x00FE)?,
+            "hires" => self.emit(0x00FF)?,
+            "audio" => self.emit(0xF002)?,
+            "scroll-down" => {
+                let n = self.nibble()?;
+                self.emit(0x00C0 | n)?;
+            }
+            "scroll-up" => {
+                let n = self.nibble()?;
+                self.emit(0x00D0 | n)?;
+            }
+            "jump" | "jump0" | "native" => {
+                let target = self.value()?;
+                let base = match token {
+                    "jump" => 0x1000,
+                    "jump0" => 0xB000,
+                    _ => 0x0000,
+                };
+                self.emit_addr(base, target)?;
+            }
+            "sprite" => {
+                let (x, y) = (self.register()?, self.register()?);
+                let n = self.nibble()?;
+                self.emit(0xD000 | x_y(x, y) | n)?;
+            }
+            "plane" => {
+                let n = self.nibble()?;
+                if n > 3 {
+                    return Err(self.error(format!("there is no plane {n}")));
+                }
+                self.emit(0xF001 | n << 8)?;
+            }
+            "bcd" | "saveflags" | "loadflags" => {
+                let x = self.register()?;
+                let base = match token {
+                    "bcd" => 0xF033,
+                    "saveflags" => 0xF075,
+                    _ => 0xF085,
+                };
+                self.emit(base | x_y(x, 0))?;
+            }
+            "save" | "load" => {
+                let x = self.register()?;
+                if self.peek() == Some("-") {
+                    self.take();
+                    let y = self.register()?;
+                    self.emit(if token == "save" { 0x5002 } else { 0x5003 } | x_y(x, y))?;
+                } else {
+                    self.emit(if token == "save" { 0xF055 } else { 0xF065 } | x_y(x, 0))?;
+                }
+            }
+            "delay" | "buzzer" | "pitch" => {
+                self.keyword(":=")?;
+                let x = self.register()?;
+                let base = match token {
+                    "delay" => 0xF015,
+                    "buzzer" => 0xF018,
+                    _ => 0xF03A,
+                };
+                self.emit(base | x_y(x, 0))?;
+            }
+            "i" => self.index()?,
+            "if" => {
+                let (x, comparison, operand) = self.condition()?;
+                match self.expect()? {
+                    "then" => self.skip_unless(x, comparison, operand)?,
+                    "begin" => {
+                        self.skip_unless(x, comparison.inverted(), operand)?;
+                        self.blocks.push(Block::Branch(self.here));
+                        self.emit(0x1000)?;
+                    }
+                    other => return Err(self.error(format!("expected then or begin, got {other}"))),
+                }
+            }
+            "else" => {
+                let Some(Block::Branch(jump)) = self.blocks.pop() else {
+                    return Err(self.error("else without a begin"));
+                };
+                let skip = self.here;
+                self.emit(0x1000)?;
+                self.fill(jump, Slot::Addr, self.here as u16)?;
+                self.blocks.push(Block::Branch(skip));
+            }
+            "end" => {
+                let Some(Block::Branch(jump)) = self.blocks.pop() else {
+                    return Err(self.error("end without a begin"));
+                };
+                self.fill(jump, Slot::Addr, self.here as u16)?;
+            }
+            "loop" => self.blocks.push(Block::Loop(self.here, Vec::new())),
+            "while" => {
+                let (x, comparison, operand) = self.condition()?;
+                self.skip_unless(x, comparison.inverted(), operand)?;
+                let jump = self.here;
+                let breaks = self.blocks.iter_mut().rev().find_map(|block| match block {
+                    Block::Loop(_, breaks) => Some(breaks),
+                    Block::Branch(_) => None,
+                });
+                match breaks {
+                    Some(breaks) => breaks.push(jump),
+                    None => return Err(self.error("while outside of a loop")),
+                }
+                self.emit(0x1000)?;
+            }
+            "again" => {
+                let Some(Block::Loop(start, breaks)) = self.blocks.pop() else {
+                    return Err(self.error("again without a loop"));
+                };
+                self.emit(0x1000 | start as u16)?;
+                for jump in breaks {
+                    self.fill(jump, Slot::Addr, self.here as u16)?;
+                }
+            }
+            token => {
+                if let Some(x) = self.register_named(token) {
+                    self.assign(x)?;
+                } else if let Some(number) = number(token) {
+                    let byte = self.to_byte(number)?;
+                    self.write(byte)?;
+                } else {
+                    let target = self.value_named(token)?;
+                    self.emit_addr(0x2000, target)?; // Any other name calls the subroutine
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // vx := ..., vx += ... and the other register operations
+    fn assign(&mut self, x: u8) -> Result<(), String> {
+        let op = self.expect()?;
+        let y = self.peek().and_then(|token| self.register_named(token));
+        if y.is_some() {
+            self.take();
+        }
+        let code = match (op, y) {
+            (":=", Some(y)) => 0x8000 | x_y(x, y),
+            ("|=", Some(y)) => 0x8001 | x_y(x, y),
+            ("&=", Some(y)) => 0x8002 | x_y(x, y),
+            ("^=", Some(y)) => 0x8003 | x_y(x, y),
+            ("+=", Some(y)) => 0x8004 | x_y(x, y),
+            ("-=", Some(y)) => 0x8005 | x_y(x, y),
+            (">>=", Some(y)) => 0x8006 | x_y(x, y),
+            ("=-", Some(y)) => 0x8007 | x_y(x, y),
+            ("<<=", Some(y)) => 0x800E | x_y(x, y),
+            (":=", None) => match self.peek() {
+                Some("random") => {
+                    self.take();
+                    0xC000 | x_y(x, 0) | self.byte()? as u16
+                }
+                Some("delay") => {
+                    self.take();
+                    0xF007 | x_y(x, 0)
+                }
+                Some("key") => {
+                    self.take();
+                    0xF00A | x_y(x, 0)
+                }
+                _ => 0x6000 | x_y(x, 0) | self.byte()? as u16,
+            },
+            ("+=", None) => 0x7000 | x_y(x, 0) | self.byte()? as u16,
+            ("-=", None) => 0x7000 | x_y(x, 0) | self.byte()?.wrapping_neg() as u16,
+            (op, _) => return Err(self.error(format!("can't use {op} on a register here"))),
+        };
+        self.emit(code)
+    }
+
+    // i := addr, i := long addr, i := hex vx, i := bighex vx and i += vx
+    fn index(&mut self) -> Result<(), String> {
+        match self.expect()? {
+            ":=" => match self.peek() {
+                Some("hex") | Some("bighex") => {
+                    let base = if self.take() == Some("hex") { 0xF029 } else { 0xF030 };
+                    let x = self.register()?;
+                    self.emit(base | x_y(x, 0))
+                }
+                Some("long") => {
+                    self.take();
+                    let target = self.value()?;
+                    self.emit(0xF000)?;
+                    match target {
+                        Value::Known(addr) => self.emit(addr),
+                        Value::Label(name) => {
+                            self.later(name, Slot::Long);
+                            self.emit(0x0000)
+                        }
+                    }
+                }
+                _ => {
+                    let target = self.value()?;
+                    self.emit_addr(0xA000, target)
+                }
+            },
+            "+=" => {
+                let x = self.register()?;
+                self.emit(0xF01E | x_y(x, 0))
+            }
+            op => Err(self.error(format!("can't use {op} on i"))),
+        }
+    }
+
+    // Register, comparison and what it's compared with, e.g. v3 != 0x1F or v0 key
+    fn condition(&mut self) -> Result<(u8, Comparison, Operand), String> {
+        let x = self.register()?;
+        let comparison = match self.expect()? {
+            "==" => Comparison::Eq,
+            "!=" => Comparison::Ne,
+            "key" => return Ok((x, Comparison::Key, Operand::Byte(0))),
+            "-key" => return Ok((x, Comparison::NotKey, Operand::Byte(0))),
+            "<" => Comparison::Lt,
+            ">" => Comparison::Gt,
+            "<=" => Comparison::Le,
+            ">=" => Comparison::Ge,
+            other => return Err(self.error(format!("expected a comparison, got {other}"))),
+        };
+        let operand = match self.peek().and_then(|token| self.register_named(token)) {
+            Some(y) => {
+                self.take();
+                Operand::Register(y)
+            }
+            None => Operand::Byte(self.byte()?),
+        };
+        Ok((x, comparison, operand))
+    }
+
+    // Emits code skipping the next instruction unless the condition holds. Ordering comparisons
+    // subtract into VF like Octo, so they clobber it.
+    fn skip_unless(&mut self, x: u8, comparison: Comparison, operand: Operand) -> Result<(), String> {
+        match (comparison, operand) {
+            (Comparison::Eq, Operand::Byte(kk)) => self.emit(0x4000 | x_y(x, 0) | kk as u16),
+            (Comparison::Eq, Operand::Register(y)) => self.emit(0x9000 | x_y(x, y)),
+            (Comparison::Ne, Operand::Byte(kk)) => self.emit(0x3000 | x_y(x, 0) | kk as u16),
+            (Comparison::Ne, Operand::Register(y)) => self.emit(0x5000 | x_y(x, y)),
+            (Comparison::Key, _) => self.emit(0xE0A1 | x_y(x, 0)),
+            (Comparison::NotKey, _) => self.emit(0xE09E | x_y(x, 0)),
+            // VF ends up 0 when the left side is less than the right one
+            (Comparison::Lt, operand) | (Comparison::Ge, operand) => {
+                self.subtract(Operand::Register(x), operand)?;
+                self.emit(if matches!(comparison, Comparison::Lt) { 0x4F00 } else { 0x3F00 })
+            }
+            (Comparison::Gt, operand) | (Comparison::Le, operand) => {
+                self.subtract(operand, Operand::Register(x))?;
+                self.emit(if matches!(comparison, Comparison::Gt) { 0x4F00 } else { 0x3F00 })
+            }
+        }
+    }
+
+    // VF := left - right, leaving VF 1 if there was no borrow and 0 if there was
+    fn subtract(&mut self, left: Operand, right: Operand) -> Result<(), String> {
+        match (left, right) {
+            (left, Operand::Register(y)) => {
+                match left {
+                    Operand::Register(x) => self.emit(0x8F00 | x_y(0, x))?,
+                    Operand::Byte(kk) => self.emit(0x6F00 | kk as u16)?,
+                }
+                self.emit(0x8F05 | x_y(0, y))
+            }
+            (Operand::Register(x), Operand::Byte(kk)) => {
+                self.emit(0x6F00 | kk as u16)?;
+                self.emit(0x8F07 | x_y(0, x))
+            }
+            (Operand::Byte(_), Operand::Byte(_)) => Err(self.error("can't compare two numbers")),
+        }
+    }
+
+    fn take(&mut self) -> Option<&'a str> {
+        let token = self.tokens.get(self.next)?;
+        self.next += 1;
+        self.line = token.line;
+        Some(token.text)
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.next).map(|token| token.text)
+    }
+
+    fn expect(&mut self) -> Result<&'a str, String> {
+        self.take().ok_or_else(|| format!("line {}: unexpected end of file", self.line))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), String> {
+        match self.expect()? {
+            token if token == keyword => Ok(()),
+            token => Err(self.error(format!("expected {keyword}, got {token}"))),
+        }
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> String {
+        format!("line {}: {message}", self.line)
+    }
+
+    // Name for a new label, constant or alias
+    fn name(&mut self) -> Result<&'a str, String> {
+        let name = self.expect()?;
+        if number(name).is_some() || self.register_named(name).is_some() || name.starts_with(':') {
+            return Err(self.error(format!("{name} can't be used as a name")));
+        }
+        Ok(name)
+    }
+
+    fn define(&mut self, name: &'a str, addr: usize) -> Result<(), String> {
+        if addr >= self.layout.size {
+            return Err(self.error(format!("{name} is past the end of memory")));
+        }
+        if self.labels.insert(name, addr as u16).is_some() {
+            return Err(self.error(format!("{name} is defined twice")));
+        }
+        Ok(())
+    }
+
+    fn register(&mut self) -> Result<u8, String> {
+        let token = self.expect()?;
+        self.register_named(token).ok_or_else(|| self.error(format!("expected a register, got {token}")))
+    }
+
+    // v0 to vf in any case, or an alias
+    fn register_named(&self, token: &str) -> Option<u8> {
+        if let Some(&register) = self.aliases.get(token) {
+            return Some(register);
+        }
+        let digit = token.strip_prefix(['v', 'V'])?;
+        if digit.len() != 1 {
+            return None;
+        }
+        u8::from_str_radix(digit, 16).ok()
+    }
+
+    fn value(&mut self) -> Result<Value<'a>, String> {
+        let token = self.expect()?;
+        self.value_named(token)
+    }
+
+    // A name that isn't known yet is taken as a label defined further down
+    fn value_named(&self, token: &'a str) -> Result<Value<'a>, String> {
+        if let Some(number) = number(token) {
+            return u16::try_from(number).map(Value::Known).map_err(|_| self.error(format!("{token} is out of range")));
+        }
+        match self.consts.get(token).or_else(|| self.labels.get(token)) {
+            Some(&value) => Ok(Value::Known(value)),
+            None if self.register_named(token).is_some() || token.starts_with(':') => Err(self.error(format!("unexpected {token}"))),
+            None => Ok(Value::Label(token)),
+        }
+    }
+
+    // Number, constant or label defined above
+    fn known(&mut self) -> Result<u16, String> {
+        match self.value()? {
+            Value::Known(value) => Ok(value),
+            Value::Label(name) => Err(self.error(format!("{name} is not defined"))),
+        }
+    }
+
+    // Bytes can be given as negative numbers, e.g. v0 += -1
+    fn byte(&mut self) -> Result<u8, String> {
+        let token = self.expect()?;
+        match number(token) {
+            Some(number) => self.to_byte(number),
+            None => match self.consts.get(token) {
+                Some(&value) => self.to_byte(value as i64),
+                None => Err(self.error(format!("expected a number, got {token}"))),
+            },
+        }
+    }
+
+    fn to_byte(&self, number: i64) -> Result<u8, String> {
+        match number {
+            -128..=-1 => Ok(number as i8 as u8),
+            0..=255 => Ok(number as u8),
+            _ => Err(self.error(format!("{number} doesn't fit in a byte"))),
+        }
+    }
+
+    fn nibble(&mut self) -> Result<u16, String> {
+        match self.byte()? {
+            n @ 0..=0xF => Ok(n as u16),
+            n => Err(self.error(format!("{n} doesn't fit in a nibble"))),
+        }
+    }
+
+    fn write(&mut self, byte: u8) -> Result<(), String> {
+        if self.here >= self.layout.size {
+            return Err(self.error("the program is past the end of memory"));
+        }
+        let index = self.here - self.layout.program_start as usize;
+        if self.rom.len() <= index {
+            self.rom.resize(index + 1, 0);
+        }
+        self.rom[index] = byte;
+        self.here += 1;
+        Ok(())
+    }
+
+    fn emit(&mut self, code: u16) -> Result<(), String> {
+        self.write((code >> 8) as u8)?;
+        self.write(code as u8)
+    }
+
+    // Instruction with an address, filled in later if it's a label defined further down
+    fn emit_addr(&mut self, base: u16, target: Value<'a>) -> Result<(), String> {
+        match target {
+            Value::Known(addr) if addr > 0xFFF => Err(self.error(format!("{addr:#X} doesn't fit in 12 bits, use i := long"))),
+            Value::Known(addr) => self.emit(base | addr),
+            Value::Label(name) => {
+                self.later(name, Slot::Addr);
+                self.emit(base)
+            }
+        }
+    }
+
+    // Fills the label into the instruction about to be emitted once it's defined
+    fn later(&mut self, name: &'a str, slot: Slot) {
+        self.fixups.push(Fixup { addr: self.here, slot, name, line: self.line });
+    }
+
+    fn fill(&mut self, addr: usize, slot: Slot, value: u16) -> Result<(), String> {
+        let index = addr - self.layout.program_start as usize;
+        let (high, low) = match slot {
+            Slot::Addr if value > 0xFFF => return Err(format!("at {value:#X} doesn't fit in 12 bits")),
+            Slot::Addr => (self.rom[index] & 0xF0 | (value >> 8) as u8, value as u8),
+            Slot::Long => ((value >> 8) as u8, value as u8),
+            Slot::UnpackHigh(nibble) => (self.rom[index], nibble << 4 | (value >> 8) as u8 & 0xF),
+            Slot::UnpackLow => (self.rom[index], value as u8),
+        };
+        self.rom[index] = high;
+        self.rom[index + 1] = low;
+        Ok(())
+    }
+}
+
+// The x and y fields of an instruction
+fn x_y(x: u8, y: u8) -> u16 {
+    (x as u16) << 8 | (y as u16) << 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Words of the program, starting at main
+    fn words(source: &str) -> Vec<u16> {
+        let rom = assemble(&format!(": main\n{source}"), Layout::default()).unwrap().rom;
+        rom.chunks(2).map(|word| u16::from_be_bytes([word[0], word.get(1).copied().unwrap_or(0)])).collect()
+    }
+
+    fn error(source: &str) -> String {
+        match assemble(source, Layout::default()) {
+            Err(Chip8Error::Load(LoadError::Assembly(message))) => message,
+            other => panic!("expected an assembly error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_statements() {
+        assert_eq!(words("clear v3 := 0x1F v3 += -1 v2 += v3 v2 =- v3 v1 >>= v1"), [0x00E0, 0x631F, 0x73FF, 0x8234, 0x8237, 0x8116]);
+        assert_eq!(words("i := 0x300 i += v2 i := hex v4 sprite v0 v1 5 bcd v5"), [0xA300, 0xF21E, 0xF429, 0xD015, 0xF533]);
+        assert_eq!(words("save v3 load v2 - v5 v0 := key v1 := random 0xF0 delay := v1"), [0xF355, 0x5253, 0xF00A, 0xC1F0, 0xF115]);
+        assert_eq!(words("hires scroll-down 4 plane 3 i := long 0x1234 return"), [0x00FF, 0x00C4, 0xF301, 0xF000, 0x1234, 0x00EE]);
+    }
+
+    #[test]
+    fn test_labels() {
+        let assembled = assemble("# Pong\n: main\n  draw_paddle\n  jump main\n: draw_paddle # Forward reference\n  ;\n", Layout::default()).unwrap();
+        assert_eq!(assembled.rom, [0x22, 0x04, 0x12, 0x00, 0x00, 0xEE]); // No jump when the source starts with main
+        assert_eq!(assembled.labels.name(0x204), Some("draw_paddle"));
+        assert_eq!(assembled.labels.name(0x200), Some("main"));
+
+        let assembled = assemble(": init\n  clear\n: main\n  init\n", Layout::default()).unwrap();
+        assert_eq!(assembled.rom, [0x12, 0x04, 0x00, 0xE0, 0x22, 0x02]);
+    }
+
+    #[test]
+    fn test_directives() {
+        // main comes after the constants, so the program starts with a jump to it
+        let source = ":const SPEED 3 :alias x v4\n: main x += SPEED :unpack 0xA data :next target v0 := 0 :org 0x300 : data 0xFF 1 :byte 2";
+        let assembled = assemble(source, Layout::default()).unwrap();
+        assert_eq!(&assembled.rom[2..10], [0x74, 0x03, 0x60, 0xA3, 0x61, 0x00, 0x60, 0x00]);
+        assert_eq!(assembled.labels.name(0x209), Some("target"));
+        assert_eq!(&assembled.rom[0x100..], [0xFF, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn test_control_flow() {
+        assert_eq!(words("if v0 == 3 then v1 := 1"), [0x4003, 0x6101]);
+        assert_eq!(words("if v0 key then clear"), [0xE0A1, 0x00E0]);
+        // The body is jumped over unless the condition holds
+        assert_eq!(words("if v0 != v1 begin clear else hires end"), [0x9010, 0x1208, 0x00E0, 0x120A, 0x00FF]);
+        assert_eq!(words("loop v0 += 1 while v0 != 10 again"), [0x7001, 0x400A, 0x1208, 0x1200]);
+        // Ordering comparisons subtract into VF
+        assert_eq!(words("if v2 < v3 then clear"), [0x8F20, 0x8F35, 0x4F00, 0x00E0]);
+        assert_eq!(words("if v2 >= 5 then clear"), [0x6F05, 0x8F27, 0x3F00, 0x00E0]);
+        assert_eq!(words("if v2 > 5 then clear"), [0x6F05, 0x8F25, 0x4F00, 0x00E0]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(error("clear"), "the program has no main label to start at");
+        assert_eq!(error(": main\n\n  jump nowhere"), "line 3: nowhere is not defined");
+        assert_eq!(error(": main v0 := 256"), "line 1: 256 doesn't fit in a byte");
+        assert_eq!(error(": main\nloop\n"), "unexpected end of file, a begin or loop is never closed");
+        assert_eq!(error(": main\n: main"), "line 2: main is defined twice");
+        assert_eq!(error(": main end"), "line 1: end without a begin");
+        assert_eq!(error(": main v0 *= v1"), "line 1: can't use *= on a register here");
+        assert_eq!(error(": main :org 0x1000 clear"), "line 1: the program is past the end of memory");
+    }
+
+    #[test]
+    fn test_layout() {
+        // XO-CHIP programs reach past 4KB, ETI-660 ones start at 0x600
+        let xochip = Layout { size: 0x10000, ..Layout::default() };
+        let assembled = assemble(": main :org 0x1000 : far clear", xochip).unwrap();
+        assert_eq!((assembled.rom.len(), assembled.labels.name(0x1000)), (0xE02, Some("far")));
+        let eti660 = Layout { program_start: 0x600, ..Layout::default() };
+        let assembled = assemble(": init clear : main init", eti660).unwrap();
+        assert_eq!(assembled.rom, [0x16, 0x04, 0x00, 0xE0, 0x26, 0x02]);
+    }
+
+    #[test]
+    fn test_is_source() {
+        assert!(is_source("pong.8o"));
+        assert!(is_source("PONG.8O"));
+        assert!(!is_source("pong.ch8"));
+    }
+}
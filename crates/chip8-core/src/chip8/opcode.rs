@@ -0,0 +1,189 @@
+use alloc::format;
+use core::fmt;
+use super::errors::{Chip8Error, LoadError};
+use super::rom::classify;
+
+// Instruction split into its fields once, so predecoded ones can be executed repeatedly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpCode {
+    pub(super) code: u16,
+    x: u8,
+    y: u8,
+    n: u8,
+    kk: u8,
+    nnn: u16,
+}
+
+impl OpCode {
+    // Splits any word into the fields, whether or not it's a known opcode
+    pub fn decode(code: u16) -> Self {
+        OpCode { code, x: ((code >> 8) & 0x000f) as u8, y: ((code >> 4) & 0x000f) as u8, n: (code & 0x000f) as u8, kk: (code & 0x00ff) as u8, nnn: code & 0x0fff }
+    }
+    pub fn vx (&self) -> usize { self.x as usize }
+    pub fn vy (&self) -> usize { self.y as usize }
+    pub fn nibble (&self) -> u8 { self.n }
+    pub fn byte (&self) -> u8 { self.kk }
+    pub fn addr (&self) -> u16 { self.nnn }
+
+    pub fn is_draw(&self) -> bool {
+        self.code >> 12 == 0xD
+    }
+
+    // Sets the sound timer, or the XO-CHIP audio pattern or pitch
+    pub fn is_sound(&self) -> bool {
+        self.code & 0xF0FF == 0xF018 || self.code & 0xF0FF == 0xF002 || self.code & 0xF0FF == 0xF03A
+    }
+
+    // Skips on a key or waits for one
+    pub fn is_key_read(&self) -> bool {
+        self.code & 0xF0FF == 0xE09E || self.code & 0xF0FF == 0xE0A1 || self.code & 0xF0FF == 0xF00A
+    }
+
+    // Whether any platform has the opcode, see rom::classify
+    pub fn is_known(&self) -> bool {
+        classify(self.code).is_some()
+    }
+}
+
+// Only opcodes of a known platform, see rom::classify
+impl TryFrom<u16> for OpCode {
+    type Error = Chip8Error;
+
+    fn try_from(code: u16) -> Result<Self, Chip8Error> {
+        match classify(code) {
+            Some(_) => Ok(OpCode::decode(code)),
+            None => Err(LoadError::InvalidArgument(format!("{code:#06X} is not an opcode of any known platform")).into()),
+        }
+    }
+}
+
+impl From<OpCode> for u16 {
+    fn from(op_code: OpCode) -> u16 {
+        op_code.code
+    }
+}
+
+// Disassembles the instruction in the usual CHIP-8 assembly syntax, e.g. DRW V0, V1, 5.
+// Words no platform knows are shown as data, the 0nnn machine code calls as SYS.
+impl fmt::Display for OpCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (x, y, n, kk, nnn) = (self.x, self.y, self.n, self.kk, self.nnn);
+        match (self.code >> 12, kk, n) {
+            (0x0, _, _) => match self.code {
+                0x0000 => write!(f, "NOP"),
+                0x00e0 => write!(f, "CLS"),
+                0x00ee => write!(f, "RET"),
+                0x00fb => write!(f, "SCR"),
+                0x00fc => write!(f, "SCL"),
+                0x00fd => write!(f, "EXIT"),
+                0x00fe => write!(f, "LOW"),
+                0x00ff => write!(f, "HIGH"),
+                0x0010 => write!(f, "MEGAOFF"),
+                0x0011 => write!(f, "MEGAON"),
+                0x0700 => write!(f, "STOPSND"),
+                code if code & 0xfff0 == 0x00c0 => write!(f, "SCD {n}"),
+                code if code & 0xfff0 == 0x00d0 => write!(f, "SCU {n}"),
+                code if code & 0xfff0 == 0x0600 => write!(f, "DIGISND {n}"),
+                code if code & 0xfff0 == 0x0800 => write!(f, "BMODE {n}"),
+                code => match code >> 8 {
+                    0x1 => write!(f, "LDHI I, {kk:#04X}"), // The low 16 bits of the address follow
+                    0x2 => write!(f, "LDPAL {kk}"),
+                    0x3 => write!(f, "SPRW {kk}"),
+                    0x4 => write!(f, "SPRH {kk}"),
+                    0x5 => write!(f, "ALPHA {kk}"),
+                    0x9 => write!(f, "CCOL {kk}"),
+                    _ => write!(f, "SYS {nnn:#05X}"),
+                },
+            },
+            (0x1, _, _) => write!(f, "JP {nnn:#05X}"),
+            (0x2, _, _) => write!(f, "CALL {nnn:#05X}"),
+            (0x3, _, _) => write!(f, "SE V{x:X}, {kk:#04X}"),
+            (0x4, _, _) => write!(f, "SNE V{x:X}, {kk:#04X}"),
+            (0x5, _, 0x0) => write!(f, "SE V{x:X}, V{y:X}"),
+            (0x5, _, 0x2) => write!(f, "SAVE V{x:X}-V{y:X}"),
+            (0x5, _, 0x3) => write!(f, "LOAD V{x:X}-V{y:X}"),
+            (0x6, _, _) => write!(f, "LD V{x:X}, {kk:#04X}"),
+            (0x7, _, _) => write!(f, "ADD V{x:X}, {kk:#04X}"),
+            (0x8, _, 0x0) => write!(f, "LD V{x:X}, V{y:X}"),
+            (0x8, _, 0x1) => write!(f, "OR V{x:X}, V{y:X}"),
+            (0x8, _, 0x2) => write!(f, "AND V{x:X}, V{y:X}"),
+            (0x8, _, 0x3) => write!(f, "XOR V{x:X}, V{y:X}"),
+            (0x8, _, 0x4) => write!(f, "ADD V{x:X}, V{y:X}"),
+            (0x8, _, 0x5) => write!(f, "SUB V{x:X}, V{y:X}"),
+            (0x8, _, 0x6) => write!(f, "SHR V{x:X}, V{y:X}"),
+            (0x8, _, 0x7) => write!(f, "SUBN V{x:X}, V{y:X}"),
+            (0x8, _, 0xe) => write!(f, "SHL V{x:X}, V{y:X}"),
+            (0x9, _, 0x0) => write!(f, "SNE V{x:X}, V{y:X}"),
+            (0xa, _, _) => write!(f, "LD I, {nnn:#05X}"),
+            (0xb, _, _) => write!(f, "JP V0, {nnn:#05X}"),
+            (0xc, _, _) => write!(f, "RND V{x:X}, {kk:#04X}"),
+            (0xd, _, _) => write!(f, "DRW V{x:X}, V{y:X}, {n}"),
+            (0xe, 0x9e, _) => write!(f, "SKP V{x:X}"),
+            (0xe, 0xa1, _) => write!(f, "SKNP V{x:X}"),
+            (0xf, 0x00, _) if x == 0 => write!(f, "LD I, LONG"), // The address follows
+            (0xf, 0x01, _) => write!(f, "PLANE {x}"),
+            (0xf, 0x02, _) => write!(f, "AUDIO"),
+            (0xf, 0x07, _) => write!(f, "LD V{x:X}, DT"),
+            (0xf, 0x0a, _) => write!(f, "LD V{x:X}, K"),
+            (0xf, 0x15, _) => write!(f, "LD DT, V{x:X}"),
+            (0xf, 0x18, _) => write!(f, "LD ST, V{x:X}"),
+            (0xf, 0x1e, _) => write!(f, "ADD I, V{x:X}"),
+            (0xf, 0x29, _) => write!(f, "LD F, V{x:X}"),
+            (0xf, 0x30, _) => write!(f, "LD HF, V{x:X}"),
+            (0xf, 0x33, _) => write!(f, "LD B, V{x:X}"),
+            (0xf, 0x3a, _) => write!(f, "PITCH V{x:X}"),
+            (0xf, 0x55, _) => write!(f, "LD [I], V{x:X}"),
+            (0xf, 0x65, _) => write!(f, "LD V{x:X}, [I]"),
+            (0xf, 0x75, _) => write!(f, "LD R, V{x:X}"),
+            (0xf, 0x85, _) => write!(f, "LD V{x:X}, R"),
+            _ => write!(f, "DW {:#06X}", self.code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let disassemble = |code| OpCode::decode(code).to_string();
+        assert_eq!(disassemble(0x00e0), "CLS");
+        assert_eq!(disassemble(0x1234), "JP 0x234");
+        assert_eq!(disassemble(0x6a2f), "LD VA, 0x2F");
+        assert_eq!(disassemble(0x8ab6), "SHR VA, VB");
+        assert_eq!(disassemble(0xd015), "DRW V0, V1, 5");
+        assert_eq!(disassemble(0xf365), "LD V3, [I]");
+        assert_eq!(disassemble(0x0123), "LDHI I, 0x23");
+        assert_eq!(disassemble(0x0a23), "SYS 0xA23");
+        assert_eq!(disassemble(0x8ab8), "DW 0x8AB8");
+    }
+
+    #[test]
+    fn test_display_matches_classify() {
+        for code in 0..=u16::MAX {
+            let text = OpCode::decode(code).to_string();
+            let known = !text.starts_with("DW") && !text.starts_with("SYS");
+            assert_eq!(known, classify(code).is_some(), "{code:#06X} disassembles as {text}");
+        }
+    }
+
+    #[test]
+    fn test_conversions() {
+        let op_code = OpCode::try_from(0xd015).unwrap();
+        assert_eq!(op_code, OpCode::decode(0xd015));
+        assert_eq!(u16::from(op_code), 0xd015);
+        assert!(OpCode::try_from(0x8ab8).is_err());
+        assert!(OpCode::try_from(0x0a23).is_err());
+    }
+
+    #[test]
+    fn test_classes() {
+        assert!(OpCode::decode(0xd015).is_draw());
+        assert!(OpCode::decode(0xf318).is_sound() && OpCode::decode(0xf002).is_sound());
+        assert!(!OpCode::decode(0xf315).is_sound());
+        assert!(OpCode::decode(0xe19e).is_key_read() && OpCode::decode(0xe1a1).is_key_read() && OpCode::decode(0xf20a).is_key_read());
+        assert!(!OpCode::decode(0xe19f).is_key_read());
+        assert!(OpCode::decode(0x00e0).is_known() && !OpCode::decode(0x8ab8).is_known());
+    }
+}
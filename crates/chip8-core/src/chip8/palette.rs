@@ -0,0 +1,138 @@
+use alloc::format;
+use core::fmt;
+use super::errors::{Chip8Error, LoadError};
+
+// Least contrast between filled and empty pixels that stays legible, the WCAG minimum for graphics
+pub const MIN_CONTRAST: f64 = 3.0;
+
+// 24-bit RGB color as used by the window buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color(pub u32);
+
+impl Color {
+    // Parses RRGGBB, optionally prefixed with #
+    pub fn parse(color: &str) -> Option<Color> {
+        let hex = color.strip_prefix('#').unwrap_or(color);
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        u32::from_str_radix(hex, 16).ok().map(Color)
+    }
+
+    // Relative luminance from 0 for black to 1 for white, as defined by WCAG.
+    // Gamma needs powf, which only std provides.
+    #[cfg(feature = "std")]
+    pub fn luminance(&self) -> f64 {
+        let linear = |shift: u32| {
+            let channel = (self.0 >> shift & 0xff) as f64 / 255.0;
+            if channel <= 0.04045 { channel / 12.92 } else { ((channel + 0.055) / 1.055).powf(2.4) }
+        };
+        0.2126 * linear(16) + 0.7152 * linear(8) + 0.0722 * linear(0)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:06x}", self.0)
+    }
+}
+
+// Colors of filled and empty pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub filled: Color,
+    pub empty: Color,
+}
+
+// Named palettes, selectable with --palette
+const PRESETS: &[(&str, Palette)] = &[
+    ("purple", Palette { filled: Color(0x800080), empty: Color(0xffc0cb) }),
+    ("green", Palette { filled: Color(0x33ff66), empty: Color(0x0a1f0f) }), // Classic phosphor monitor
+    ("amber", Palette { filled: Color(0xffb000), empty: Color(0x1f1200) }),
+    ("gameboy", Palette { filled: Color(0x0f380f), empty: Color(0x9bbc0f) }),
+    ("high-contrast", Palette { filled: Color(0xffffff), empty: Color(0x000000) }),
+    // Colors of the Okabe-Ito set, told apart from black with any kind of color blindness
+    ("colorblind-blue", Palette { filled: Color(0x56b4e9), empty: Color(0x000000) }),
+    ("colorblind-orange", Palette { filled: Color(0xe69f00), empty: Color(0x000000) }),
+];
+
+impl Palette {
+    pub fn preset(name: &str) -> Option<Palette> {
+        PRESETS.iter().find(|(preset, _)| *preset == name).map(|(_, palette)| *palette)
+    }
+
+    pub fn preset_names() -> impl Iterator<Item = &'static str> {
+        PRESETS.iter().map(|(name, _)| *name)
+    }
+
+    // Parses a custom palette given as FILLED,EMPTY hex colors
+    pub fn parse(colors: &str) -> Result<Palette, Chip8Error> {
+        let invalid = || LoadError::InvalidArgument(format!("expected two colors like FF00FF,000000, got {colors}"));
+        let (filled, empty) = colors.split_once(',').ok_or_else(invalid)?;
+        match (Color::parse(filled.trim()), Color::parse(empty.trim())) {
+            (Some(filled), Some(empty)) => Ok(Palette { filled, empty }),
+            _ => Err(invalid().into()),
+        }
+    }
+
+    // Ratio of the luminance of the lighter color to the darker, from 1 for the same color to 21
+    #[cfg(feature = "std")]
+    pub fn contrast(&self) -> f64 {
+        let (filled, empty) = (self.filled.luminance() + 0.05, self.empty.luminance() + 0.05);
+        filled.max(empty) / filled.min(empty)
+    }
+
+    // Why the pixels would be hard to make out, if the colors are too alike
+    #[cfg(feature = "std")]
+    pub fn contrast_warning(&self) -> Option<String> {
+        let contrast = self.contrast();
+        (contrast < MIN_CONTRAST).then(|| format!(
+            "colors {} and {} have a contrast of {contrast:.1}:1, below {MIN_CONTRAST}:1, so pixels may be hard to tell apart",
+            self.filled, self.empty,
+        ))
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        PRESETS[0].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(Color::parse("#ff8000"), Some(Color(0xff8000)));
+        assert_eq!(Color::parse("FF8000"), Some(Color(0xff8000)));
+        assert_eq!(Color::parse("#fff"), None);
+        assert_eq!(Color::parse("#+ff800"), None);
+        assert_eq!(Color(0x0f380f).to_string(), "#0f380f");
+    }
+
+    #[test]
+    fn test_parse_palette() {
+        let palette = Palette::parse("FF00FF, #000000").unwrap();
+        assert_eq!(palette, Palette { filled: Color(0xff00ff), empty: Color(0x000000) });
+        assert!(Palette::parse("FF00FF").is_err());
+        assert!(Palette::parse("FF00FF,black").is_err());
+    }
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(Palette::preset("gameboy").unwrap().empty, Color(0x9bbc0f));
+        assert!(Palette::preset("sepia").is_none());
+        assert!(Palette::preset_names().all(|name| Palette::preset(name).is_some()));
+    }
+
+    #[test]
+    fn test_contrast() {
+        assert_eq!(Palette::preset("high-contrast").unwrap().contrast(), 21.0);
+        let same = Palette { filled: Color(0x336699), empty: Color(0x336699) };
+        assert_eq!(same.contrast(), 1.0);
+        assert!(same.contrast_warning().unwrap().contains("#336699 and #336699 have a contrast of 1.0:1"));
+        assert!(PRESETS.iter().all(|(_, palette)| palette.contrast_warning().is_none()));
+    }
+}
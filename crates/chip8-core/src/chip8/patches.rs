@@ -0,0 +1,147 @@
+use alloc::{format, vec::Vec};
+#[cfg(feature = "std")]
+use std::{fs, io::ErrorKind, path::Path};
+use super::{Memory, errors::{Chip8Error, LoadError}};
+use super::number::hex;
+
+// Byte written to memory when the program starts, or on every frame if frozen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Patch {
+    pub addr: u16,
+    pub value: u8,
+    pub freeze: bool, // Rewritten every frame, e.g. to keep a lives counter from going down
+}
+
+// Cheats for a program, kept in a .cht file next to the ROM with one patch per line:
+//
+//   # Infinite lives
+//   freeze 2F3:05
+//   0x24A:00
+//
+// Addresses and values are in hex, anything after a # is a comment. Addresses must be
+// within the memory of the program's platform, 4KB unless it has more.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Patches {
+    patches: Vec<Patch>,
+}
+
+impl Patches {
+    pub fn new() -> Self {
+        Patches::default()
+    }
+
+    pub fn parse(text: &str, memory_size: usize) -> Result<Patches, Chip8Error> {
+        let mut patches = Patches::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || LoadError::InvalidPatch(format!("line {}: expected [freeze] ADDR:VALUE, got {line}", n + 1));
+            let (freeze, patch) = match line.strip_prefix("freeze") {
+                Some(rest) if rest.starts_with(char::is_whitespace) => (true, rest.trim()),
+                _ => (false, line),
+            };
+            let (addr, value) = patch.split_once(':').ok_or_else(invalid)?;
+            let addr = hex(addr).filter(|&addr| (addr as usize) < memory_size).ok_or_else(invalid)?;
+            let value = hex(value).and_then(|value| u8::try_from(value).ok()).ok_or_else(invalid)?;
+            patches.add(Patch { addr, value, freeze });
+        }
+        Ok(patches)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_file(path: impl AsRef<Path>, memory_size: usize) -> Result<Patches, Chip8Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| LoadError::FileRead { path: path.to_path_buf(), source })?;
+        Patches::parse(&text, memory_size)
+    }
+
+    // Loads the cheats next to the ROM, e.g. brix.cht for brix.ch8, None if there are none
+    #[cfg(feature = "std")]
+    pub fn for_rom(rom: impl AsRef<Path>, memory_size: usize) -> Result<Option<Patches>, Chip8Error> {
+        let path = rom.as_ref().with_extension("cht");
+        match fs::read_to_string(&path) {
+            Ok(text) => Patches::parse(&text, memory_size).map(Some),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(LoadError::FileRead { path, source }.into()),
+        }
+    }
+
+    pub fn add(&mut self, patch: Patch) {
+        self.patches.push(patch);
+    }
+
+    pub fn patches(&self) -> &[Patch] {
+        &self.patches
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    // Writes every patch, done whenever the program (re)starts
+    pub fn apply(&self, mem: &mut Memory) {
+        for patch in &self.patches {
+            mem.write_byte(patch.addr, patch.value);
+        }
+    }
+
+    // Writes the frozen values again, done after every frame
+    pub fn apply_frozen(&self, mem: &mut Memory) {
+        for patch in self.patches.iter().filter(|patch| patch.freeze) {
+            mem.write_byte(patch.addr, patch.value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{MAX_MEMORY_SIZE, MEMORY_SIZE};
+
+    #[test]
+    fn test_parse() {
+        let patches = Patches::parse("# Infinite lives\nfreeze 2F3:05\n\n0x24A:0x00 # Skip the intro\n", MEMORY_SIZE).unwrap();
+        assert_eq!(patches.patches(), &[
+            Patch { addr: 0x2F3, value: 0x05, freeze: true },
+            Patch { addr: 0x24A, value: 0x00, freeze: false },
+        ]);
+        assert!(Patches::parse("", MEMORY_SIZE).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(matches!(Patches::parse("2F3", MEMORY_SIZE), Err(Chip8Error::Load(LoadError::InvalidPatch(_)))));
+        assert!(Patches::parse("2F3:100", MEMORY_SIZE).is_err());
+        assert!(Patches::parse("1000:00", MEMORY_SIZE).is_err()); // Past the end of memory
+        assert!(Patches::parse("1000:00", MAX_MEMORY_SIZE).is_ok());
+        assert!(Patches::parse("freeze2F3:05", MEMORY_SIZE).is_err());
+        assert!(Patches::parse("thaw 2F3:05", MEMORY_SIZE).is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let patches = Patches::parse("freeze 300:05\n301:AA", MEMORY_SIZE).unwrap();
+        let mut mem = Memory::new();
+        patches.apply(&mut mem);
+        assert_eq!((mem.read_byte(0x300), mem.read_byte(0x301)), (0x05, 0xAA));
+
+        mem.write_byte(0x300, 0x04);
+        mem.write_byte(0x301, 0x00);
+        patches.apply_frozen(&mut mem);
+        assert_eq!((mem.read_byte(0x300), mem.read_byte(0x301)), (0x05, 0x00));
+    }
+
+    #[test]
+    fn test_for_rom() {
+        let rom = std::env::temp_dir().join("chip8_test_for_rom.ch8");
+        let cheats = rom.with_extension("cht");
+        assert_eq!(Patches::for_rom(&rom, MEMORY_SIZE).unwrap(), None);
+
+        fs::write(&cheats, "freeze 2F3:05").unwrap();
+        let patches = Patches::for_rom(&rom, MEMORY_SIZE);
+        fs::remove_file(&cheats).unwrap();
+        assert_eq!(patches.unwrap().unwrap().patches().len(), 1);
+    }
+}
@@ -0,0 +1,121 @@
+use alloc::{format, string::String, vec::Vec};
+use super::{DISPLAY_HEIGHT, DISPLAY_WIDTH, MAX_MEMORY_SIZE, MEMORY_SIZE, PROGRAM_START};
+use super::quirks::{MemoryIncrement, Quirks};
+use super::memory::Layout;
+use super::rom::Platform;
+
+// Interpreter a program was written for, with the quirks, speed and screen it expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Profile {
+    pub name: &'static str, // As given to --platform
+    pub platform: Platform, // Opcodes the programs may use
+    pub quirks: Quirks,
+    pub tickrate: u32, // Instructions per 60hz frame
+    pub resolution: (usize, usize), // Largest screen the programs draw to
+    pub memory_size: usize, // Bytes of memory
+    pub program_start: u16, // Where programs are loaded
+}
+
+const PROFILES: &[Profile] = &[
+    Profile {
+        name: "chip8",
+        platform: Platform::Chip8,
+        quirks: Quirks { wrap: false, memory: MemoryIncrement::XPlusOne, shift: false, jump: false },
+        tickrate: 15,
+        resolution: (64, 32),
+        memory_size: MEMORY_SIZE,
+        program_start: PROGRAM_START,
+    },
+    Profile {
+        name: "chip48",
+        platform: Platform::Chip8,
+        quirks: Quirks { wrap: false, memory: MemoryIncrement::X, shift: true, jump: true },
+        tickrate: 30,
+        resolution: (64, 32),
+        memory_size: MEMORY_SIZE,
+        program_start: PROGRAM_START,
+    },
+    Profile {
+        name: "eti660",
+        platform: Platform::Chip8,
+        quirks: Quirks { wrap: false, memory: MemoryIncrement::XPlusOne, shift: false, jump: false },
+        tickrate: 15,
+        resolution: (64, 32),
+        memory_size: MEMORY_SIZE,
+        program_start: 0x600, // Past the interpreter and its variables
+    },
+    Profile {
+        name: "schip",
+        platform: Platform::SuperChip,
+        quirks: Quirks { wrap: false, memory: MemoryIncrement::Unchanged, shift: true, jump: true },
+        tickrate: 30,
+        resolution: (128, 64),
+        memory_size: MEMORY_SIZE,
+        program_start: PROGRAM_START,
+    },
+    Profile {
+        name: "xochip",
+        platform: Platform::XoChip,
+        quirks: Quirks { wrap: true, memory: MemoryIncrement::XPlusOne, shift: false, jump: false },
+        tickrate: 200,
+        resolution: (128, 64),
+        memory_size: MAX_MEMORY_SIZE,
+        program_start: PROGRAM_START,
+    },
+];
+
+impl Profile {
+    pub fn parse(name: &str) -> Option<Profile> {
+        PROFILES.iter().find(|profile| profile.name == name).copied()
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        PROFILES.iter().map(|profile| profile.name)
+    }
+
+    // Memory the platform's programs are loaded into
+    pub fn layout(&self) -> Layout {
+        Layout { size: self.memory_size, program_start: self.program_start }
+    }
+
+    // Parts of the platform the emulator lacks, it runs CHIP-8 opcodes on a 64x32 screen only
+    pub fn unsupported(&self) -> Vec<String> {
+        let mut unsupported = Vec::new();
+        if self.platform > Platform::Chip8 {
+            unsupported.push(format!("{} opcodes are not supported", self.platform));
+        }
+        let (width, height) = self.resolution;
+        if width > DISPLAY_WIDTH || height > DISPLAY_HEIGHT {
+            unsupported.push(format!("the {width}x{height} screen is not supported"));
+        }
+        unsupported
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        for name in Profile::names() {
+            assert_eq!(Profile::parse(name).unwrap().name, name);
+        }
+        assert_eq!(Profile::parse("megachip"), None);
+
+        let chip48 = Profile::parse("chip48").unwrap();
+        assert_eq!(chip48.quirks.memory, MemoryIncrement::X);
+        assert!(chip48.quirks.jump);
+        assert_eq!(Profile::parse("eti660").unwrap().program_start, 0x600);
+        assert_eq!(Profile::parse("xochip").unwrap().memory_size, MAX_MEMORY_SIZE);
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert!(Profile::parse("chip8").unwrap().unsupported().is_empty());
+        assert_eq!(Profile::parse("schip").unwrap().unsupported(), vec![
+            "SUPER-CHIP opcodes are not supported".to_string(),
+            "the 128x64 screen is not supported".to_string(),
+        ]);
+    }
+}
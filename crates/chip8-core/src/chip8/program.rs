@@ -0,0 +1,172 @@
+use super::PROGRAM_START;
+
+// Writes a program instruction by instruction, named as in the opcode comments, so built-in
+// programs read as code rather than bytes. Every method is a const fn and the size is a
+// parameter, so programs are built into constants and a wrong size fails to compile:
+//
+// const IDLE: [u8; 2] = ProgramBuilder::new().jp(PROGRAM_START).build();
+pub struct ProgramBuilder<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ProgramBuilder<N> {
+    pub const fn new() -> Self {
+        ProgramBuilder { bytes: [0; N], len: 0 }
+    }
+
+    // Address the next instruction goes to, to jump back to or patch in later
+    pub const fn here(&self) -> u16 {
+        PROGRAM_START + self.len as u16
+    }
+
+    // Any instruction, for those without a method of their own
+    pub const fn op(mut self, op_code: u16) -> Self {
+        assert!(self.len + 2 <= N, "the program is larger than its size");
+        self.bytes[self.len] = (op_code >> 8) as u8;
+        self.bytes[self.len + 1] = op_code as u8;
+        self.len += 2;
+        self
+    }
+
+    // Bytes that aren't instructions, e.g. sprites
+    pub const fn data(mut self, data: &[u8]) -> Self {
+        assert!(self.len + data.len() <= N, "the program is larger than its size");
+        let mut i = 0;
+        while i < data.len() {
+            self.bytes[self.len + i] = data[i];
+            i += 1;
+        }
+        self.len += data.len();
+        self
+    }
+
+    // Fills in the address of the instruction at addr, e.g. an LD I to data further on
+    pub const fn patch(mut self, addr: u16, target: u16) -> Self {
+        let i = (addr - PROGRAM_START) as usize;
+        self.bytes[i] = (self.bytes[i] & 0xf0) | (target >> 8 & 0xf) as u8;
+        self.bytes[i + 1] = target as u8;
+        self
+    }
+
+    // 00E0 - CLS
+    pub const fn cls(self) -> Self {
+        self.op(0x00e0)
+    }
+
+    // 1nnn - JP addr
+    pub const fn jp(self, addr: u16) -> Self {
+        self.op(0x1000 | addr & 0xfff)
+    }
+
+    // 3xkk - SE Vx, byte
+    pub const fn se(self, vx: u8, byte: u8) -> Self {
+        self.op(0x3000 | x(vx) | byte as u16)
+    }
+
+    // 6xkk - LD Vx, byte
+    pub const fn ld(self, vx: u8, byte: u8) -> Self {
+        self.op(0x6000 | x(vx) | byte as u16)
+    }
+
+    // 7xkk - ADD Vx, byte
+    pub const fn add(self, vx: u8, byte: u8) -> Self {
+        self.op(0x7000 | x(vx) | byte as u16)
+    }
+
+    // 8xy0 - LD Vx, Vy
+    pub const fn ld_v(self, vx: u8, vy: u8) -> Self {
+        self.op(0x8000 | x(vx) | y(vy))
+    }
+
+    // 9xy0 - SNE Vx, Vy
+    pub const fn sne_v(self, vx: u8, vy: u8) -> Self {
+        self.op(0x9000 | x(vx) | y(vy))
+    }
+
+    // Annn - LD I, addr
+    pub const fn ld_i(self, addr: u16) -> Self {
+        self.op(0xa000 | addr & 0xfff)
+    }
+
+    // Dxyn - DRW Vx, Vy, nibble
+    pub const fn drw(self, vx: u8, vy: u8, height: u8) -> Self {
+        self.op(0xd000 | x(vx) | y(vy) | (height & 0xf) as u16)
+    }
+
+    // Ex9E - SKP Vx
+    pub const fn skp(self, vx: u8) -> Self {
+        self.op(0xe09e | x(vx))
+    }
+
+    // Fx18 - LD ST, Vx
+    pub const fn ld_st(self, vx: u8) -> Self {
+        self.op(0xf018 | x(vx))
+    }
+
+    // Fx1E - ADD I, Vx
+    pub const fn add_i(self, vx: u8) -> Self {
+        self.op(0xf01e | x(vx))
+    }
+
+    // Fx29 - LD F, Vx
+    pub const fn ld_f(self, vx: u8) -> Self {
+        self.op(0xf029 | x(vx))
+    }
+
+    // The program, which has to fill its size
+    pub const fn build(self) -> [u8; N] {
+        assert!(self.len == N, "the program is smaller than its size");
+        self.bytes
+    }
+}
+
+impl<const N: usize> Default for ProgramBuilder<N> {
+    fn default() -> Self {
+        ProgramBuilder::new()
+    }
+}
+
+// Register fields of an opcode
+const fn x(vx: u8) -> u16 {
+    ((vx & 0xf) as u16) << 8
+}
+
+const fn y(vy: u8) -> u16 {
+    ((vy & 0xf) as u16) << 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let program = ProgramBuilder::<8>::new().cls();
+        let load = program.here();
+        let program = program.ld_i(0).drw(0, 1, 5);
+        let sprite = program.here();
+        let program = program.data(&[0xf0, 0x90]).patch(load, sprite).build();
+        assert_eq!(program, [0x00, 0xE0, 0xA2, 0x06, 0xD0, 0x15, 0xF0, 0x90]);
+        assert_eq!(ProgramBuilder::<2>::new().jp(PROGRAM_START).build(), [0x12, 0x00]);
+    }
+
+    #[test]
+    fn test_splash() {
+        let code = [
+            0x00, 0xe0, 0x60, 0x11, 0x61, 0x0d, 0x62, 0x06, 0x63, 0x05, 0xa2, 0x1a, 0xd0,
+            0x15, 0xf3, 0x1e, 0x70, 0x05, 0x72, 0xff, 0x32, 0x00, 0x12, 0x0c, 0x12, 0x18,
+        ];
+        assert_eq!(crate::splash::SPLASH[..code.len()], code);
+    }
+
+    #[test]
+    fn test_tutorial() {
+        let code = [
+            0x00, 0xe0, 0x61, 0x1e, 0x62, 0x0d, 0x63, 0x02, 0x64, 0xff, 0x60, 0x00, 0xe0,
+            0x9e, 0x12, 0x1e, 0x90, 0x40, 0x12, 0x1e, 0x00, 0xe0, 0xf0, 0x29, 0xd1, 0x25,
+            0xf3, 0x18, 0x84, 0x00, 0x70, 0x01, 0x30, 0x10, 0x12, 0x0c, 0x12, 0x0a,
+        ];
+        assert_eq!(crate::splash::TUTORIAL, code);
+    }
+}
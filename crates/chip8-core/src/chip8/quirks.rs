@@ -0,0 +1,101 @@
+// Behaviors that differ between CHIP-8 interpreters, ROMs written for one
+// interpreter may rely on its behavior
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    pub wrap: bool, // Sprites wrap around the screen edges instead of being clipped at them
+    pub memory: MemoryIncrement, // How far Fx55 and Fx65 move I
+    pub shift: bool, // 8xy6 and 8xyE shift Vx in place, as on SUPER-CHIP, instead of shifting Vy into Vx
+    pub jump: bool, // Bxnn jumps to xnn + Vx, as on CHIP-48 and SUPER-CHIP, instead of nnn + V0
+}
+
+// What Fx55 and Fx65 leave in I after storing or loading V0 through Vx
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryIncrement {
+    XPlusOne, // Past the last register, as on the COSMAC VIP
+    X, // On the last register, as on CHIP-48
+    Unchanged, // As on SUPER-CHIP, what most modern programs expect
+}
+
+impl MemoryIncrement {
+    // Amount added to I once registers V0 through Vx were stored or loaded
+    pub fn amount(self, x: usize) -> u16 {
+        match self {
+            MemoryIncrement::XPlusOne => x as u16 + 1,
+            MemoryIncrement::X => x as u16,
+            MemoryIncrement::Unchanged => 0,
+        }
+    }
+}
+
+impl Quirks {
+    // Names of the quirks, as used by the ROM database
+    pub const NAMES: &'static [&'static str] = &["wrap", "memoryIncrementByX", "memoryLeaveIUnchanged", "shift", "jump"];
+
+    // Sets a quirk by name, returns false if there is no such quirk.
+    // Disabling either memory quirk while it is in effect goes back to the COSMAC VIP behavior.
+    pub fn set(&mut self, name: &str, enabled: bool) -> bool {
+        match name {
+            "wrap" => self.wrap = enabled,
+            "memoryIncrementByX" => self.set_memory(MemoryIncrement::X, enabled),
+            "memoryLeaveIUnchanged" => self.set_memory(MemoryIncrement::Unchanged, enabled),
+            "shift" => self.shift = enabled,
+            "jump" => self.jump = enabled,
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_memory(&mut self, memory: MemoryIncrement, enabled: bool) {
+        if enabled {
+            self.memory = memory;
+        } else if self.memory == memory {
+            self.memory = MemoryIncrement::XPlusOne;
+        }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Quirks { wrap: false, memory: MemoryIncrement::Unchanged, shift: true, jump: false }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set() {
+        let mut quirks = Quirks::default();
+        assert!(quirks.set("wrap", false));
+        assert!(!quirks.wrap);
+        assert!(!quirks.set("vblank", true));
+    }
+
+    #[test]
+    fn test_set_memory() {
+        let mut quirks = Quirks::default();
+        assert!(quirks.set("memoryIncrementByX", true));
+        assert_eq!(quirks.memory, MemoryIncrement::X);
+        quirks.set("memoryLeaveIUnchanged", false); // Not in effect, so kept
+        assert_eq!(quirks.memory, MemoryIncrement::X);
+        quirks.set("memoryIncrementByX", false);
+        assert_eq!(quirks.memory, MemoryIncrement::XPlusOne);
+        quirks.set("memoryLeaveIUnchanged", true);
+        assert_eq!(quirks.memory, MemoryIncrement::Unchanged);
+    }
+
+    #[test]
+    fn test_memory_amount() {
+        assert_eq!(MemoryIncrement::XPlusOne.amount(3), 4);
+        assert_eq!(MemoryIncrement::X.amount(3), 3);
+        assert_eq!(MemoryIncrement::Unchanged.amount(3), 0);
+    }
+
+    #[test]
+    fn test_names_can_be_set() {
+        for name in Quirks::NAMES {
+            assert!(Quirks::default().set(name, true));
+        }
+    }
+}
@@ -0,0 +1,43 @@
+// Random numbers for Cxkk: a xorshift generator, small and free of the operating system so
+// the instruction set doesn't depend on std. Seeded from the system only when the emulator
+// is created, and the same seed gives the same numbers on every platform.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn seeded(seed: u64) -> Self {
+        // Spreads close seeds apart, and the state must never be 0
+        Random { state: seed.wrapping_mul(0x9e3779b97f4a7c15) | 1 }
+    }
+
+    pub fn next_byte(&mut self) -> u8 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 24) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded() {
+        let bytes = |seed| {
+            let mut random = Random::seeded(seed);
+            (0..64).map(|_| random.next_byte()).collect::<Vec<u8>>()
+        };
+        assert_eq!(bytes(0), bytes(0));
+        assert_ne!(bytes(0), bytes(1));
+        assert!(bytes(0).iter().any(|&byte| byte != bytes(0)[0]));
+
+        // Every value turns up, Cxkk masks them so the low bits matter as much as the high ones
+        let mut random = Random::seeded(7);
+        let mut seen = [false; 256];
+        (0..4096).for_each(|_| seen[random.next_byte() as usize] = true);
+        assert!(seen.iter().all(|&seen| seen));
+    }
+}
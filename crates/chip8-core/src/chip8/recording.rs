@@ -0,0 +1,305 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use gif::{Encoder, Frame, Repeat};
+use super::{DISPLAY_HEIGHT, DISPLAY_WIDTH, draw::{self, Grid}, errors::{Chip8Error, IoError, LoadError}};
+
+// Input recordings are plain text so that a file cut short by a crash is still readable:
+//
+// CHIP8-INPUT 2
+// status recording
+// speed 0 <tickrate>
+// <frame> <key state as a 16-bit mask>
+// speed <frame> <tickrate>
+// ...
+//
+// Key states are stamped with the emulated 60hz frame rather than wall-clock time,
+// and the speed schedule records the instructions per frame from each frame on, so a
+// replay executes the same instructions between input changes at any speed setting.
+// The header holds the starting speed, later changes are written as they happen.
+//
+// The status line is rewritten to "complete" once the session ends cleanly,
+// a file still marked "recording" is a recovered session.
+const MAGIC: &str = "CHIP8-INPUT 2";
+const STATUS_RECORDING: &str = "status recording";
+const STATUS_COMPLETE: &str = "status complete "; // Same length as STATUS_RECORDING
+const SPEED: &str = "speed";
+
+// How often written data is forced to disk, in recorded frames
+const SYNC_INTERVAL: u32 = 60;
+
+pub struct InputRecorder {
+    writer: BufWriter<File>,
+    last_keys: Option<u16>,
+    tickrate: u32,
+    unsynced: u32,
+}
+
+impl InputRecorder {
+    // Creates the recording file and writes the recovery header with the starting speed
+    pub fn create(path: &Path, tickrate: u32) -> Result<Self, Chip8Error> {
+        let file = File::create(path).map_err(IoError::Recording)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{MAGIC}\n{STATUS_RECORDING}\n{SPEED} 0 {tickrate}").map_err(IoError::Recording)?;
+        writer.flush().map_err(IoError::Recording)?;
+        Ok(InputRecorder { writer, last_keys: None, tickrate, unsynced: 0 })
+    }
+
+    // Records the key state and speed of a frame, only changes are written
+    pub fn record(&mut self, frame: u64, keys: u16, tickrate: u32) -> Result<(), Chip8Error> {
+        if self.tickrate != tickrate {
+            writeln!(self.writer, "{SPEED} {frame} {tickrate}").map_err(IoError::Recording)?;
+            self.tickrate = tickrate;
+        }
+        if self.last_keys != Some(keys) {
+            writeln!(self.writer, "{frame} {keys:04x}").map_err(IoError::Recording)?;
+            self.last_keys = Some(keys);
+        }
+        self.writer.flush().map_err(IoError::Recording)?;
+
+        // Periodically force the data to disk so a power loss doesn't lose the session
+        self.unsynced += 1;
+        if self.unsynced >= SYNC_INTERVAL {
+            self.writer.get_ref().sync_data().map_err(IoError::Recording)?;
+            self.unsynced = 0;
+        }
+        Ok(())
+    }
+
+    // Marks the recording as complete
+    pub fn finish(mut self) -> Result<(), Chip8Error> {
+        self.writer.flush().map_err(IoError::Recording)?;
+        let file = self.writer.get_mut();
+        file.seek(SeekFrom::Start(MAGIC.len() as u64 + 1)).map_err(IoError::Recording)?;
+        file.write_all(STATUS_COMPLETE.as_bytes()).map_err(IoError::Recording)?;
+        Ok(file.sync_all().map_err(IoError::Recording)?)
+    }
+}
+
+pub struct InputRecording {
+    pub complete: bool, // False if the session was cut short
+    pub speeds: Vec<(u64, u32)>, // Frame and the tickrate from then on
+    pub events: Vec<(u64, u16)>, // Frame and key state
+}
+
+impl InputRecording {
+    // Reads a recording, keeping everything up to the last intact line
+    pub fn open(path: &Path) -> Result<Self, Chip8Error> {
+        let invalid = || LoadError::InvalidRecording(path.to_path_buf());
+        let file = File::open(path).map_err(IoError::Recording)?;
+        let mut lines = BufReader::new(file).lines();
+
+        if lines.next().transpose().map_err(IoError::Recording)?.as_deref() != Some(MAGIC) {
+            return Err(invalid().into());
+        }
+        let complete = match lines.next().transpose().map_err(IoError::Recording)? {
+            Some(status) if status == STATUS_COMPLETE => true,
+            Some(status) if status == STATUS_RECORDING => false,
+            _ => return Err(invalid().into()),
+        };
+
+        let mut speeds = Vec::new();
+        let mut events = Vec::new();
+        for line in lines {
+            let Ok(line) = line else { break };
+            let fields = line.split(' ').collect::<Vec<&str>>();
+            match fields[..] {
+                [SPEED, frame, tickrate] => match (frame.parse(), tickrate.parse()) {
+                    (Ok(frame), Ok(tickrate)) => speeds.push((frame, tickrate)),
+                    _ => break,
+                },
+                [frame, keys] => match (frame.parse(), u16::from_str_radix(keys, 16)) {
+                    (Ok(frame), Ok(keys)) => events.push((frame, keys)),
+                    _ => break,
+                },
+                _ => break, // Truncated line written during a crash
+            }
+        }
+
+        // The starting speed is part of the header
+        if speeds.first().map(|&(frame, _)| frame) != Some(0) {
+            return Err(invalid().into());
+        }
+        Ok(InputRecording { complete, speeds, events })
+    }
+
+    // Key state during the given frame
+    pub fn keys_at(&self, frame: u64) -> u16 {
+        let i = self.events.partition_point(|&(start, _)| start <= frame);
+        if i == 0 { 0 } else { self.events[i - 1].1 }
+    }
+
+    // Instructions per frame during the given frame
+    pub fn tickrate_at(&self, frame: u64) -> u32 {
+        let i = self.speeds.partition_point(|&(start, _)| start <= frame);
+        self.speeds[i.max(1) - 1].1
+    }
+}
+
+// Frames captured per second and the shortest delay GIF viewers respect, in 1/100 s
+const GIF_FRAME_RATE: u32 = 60;
+const GIF_MIN_DELAY: u32 = 2;
+
+// Records the display into an animated GIF, one capture per 60hz frame
+pub struct GifRecorder {
+    encoder: Encoder<BufWriter<File>>,
+    factor: usize, // Pixel size of a grid cell
+    pending: Option<Vec<u8>>, // Last image, written once it changes
+    pending_start: u32, // Frame at which the pending image appeared
+    frames: u32, // Frames captured so far
+}
+
+impl GifRecorder {
+    // Creates the GIF file, colors are used for the palette
+    pub fn create(path: &Path, factor: usize, filled: u32, empty: u32) -> Result<Self, Chip8Error> {
+        let file = File::create(path).map_err(IoError::Recording)?;
+        let width = (DISPLAY_WIDTH * factor) as u16;
+        let height = (DISPLAY_HEIGHT * factor) as u16;
+        let palette = [empty, filled].iter()
+            .flat_map(|color| [(color >> 16) as u8, (color >> 8) as u8, *color as u8])
+            .collect::<Vec<u8>>();
+
+        let mut encoder = Encoder::new(BufWriter::new(file), width, height, &palette).map_err(IoError::Gif)?;
+        encoder.set_repeat(Repeat::Infinite).map_err(IoError::Gif)?;
+        Ok(GifRecorder { encoder, factor, pending: None, pending_start: 0, frames: 0 })
+    }
+
+    // Captures a frame, identical frames only extend the previous one
+    pub fn capture(&mut self, grid: &Grid) -> Result<(), Chip8Error> {
+        let pixels = self.pixels(grid);
+        let frame = self.frames;
+        self.frames += 1;
+
+        if self.pending.as_ref() == Some(&pixels) {
+            return Ok(());
+        }
+
+        // Images shown for less than the minimal delay are replaced by the next one
+        if let Some(pending) = self.pending.take() {
+            if self.delay(frame) >= GIF_MIN_DELAY {
+                self.write(pending, self.delay(frame))?;
+                self.pending_start = frame;
+            }
+        }
+        self.pending = Some(pixels);
+        Ok(())
+    }
+
+    // Writes out the last image and the trailer
+    pub fn finish(mut self) -> Result<(), Chip8Error> {
+        if let Some(pending) = self.pending.take() {
+            let delay = self.delay(self.frames).max(GIF_MIN_DELAY);
+            self.write(pending, delay)?;
+        }
+        let mut file = self.encoder.into_inner().map_err(IoError::Recording)?;
+        Ok(file.flush().map_err(IoError::Recording)?)
+    }
+
+    // Time between the pending image and the given frame, in 1/100 s
+    fn delay(&self, frame: u32) -> u32 {
+        let centis = |frame: u32| frame * 100 / GIF_FRAME_RATE;
+        centis(frame) - centis(self.pending_start)
+    }
+
+    fn write(&mut self, pixels: Vec<u8>, delay: u32) -> Result<(), Chip8Error> {
+        let width = (DISPLAY_WIDTH * self.factor) as u16;
+        let height = (DISPLAY_HEIGHT * self.factor) as u16;
+        let mut frame = Frame::from_indexed_pixels(width, height, pixels, None);
+        frame.delay = delay.min(u16::MAX as u32) as u16;
+        Ok(self.encoder.write_frame(&frame).map_err(IoError::Gif)?)
+    }
+
+    // Scales the grid up into palette indices
+    fn pixels(&self, grid: &Grid) -> Vec<u8> {
+        let width = DISPLAY_WIDTH * self.factor;
+        let height = DISPLAY_HEIGHT * self.factor;
+        (0..width * height)
+            .map(|i| draw::is_lit(grid, i % width / self.factor, i / width / self.factor) as u8)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_record_and_open() {
+        let path = std::env::temp_dir().join("chip8_test_record_and_open.txt");
+        let mut recorder = InputRecorder::create(&path, 16).unwrap();
+        recorder.record(0, 0x0000, 16).unwrap();
+        recorder.record(1, 0x0000, 16).unwrap();
+        recorder.record(2, 0x0012, 16).unwrap();
+        recorder.record(3, 0x0012, 30).unwrap();
+        recorder.finish().unwrap();
+
+        let recording = InputRecording::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(recording.complete);
+        assert_eq!(recording.speeds, vec![(0, 16), (3, 30)]);
+        assert_eq!(recording.events, vec![(0, 0x0000), (2, 0x0012)]);
+    }
+
+    #[test]
+    fn test_open_unfinished() {
+        let path = std::env::temp_dir().join("chip8_test_open_unfinished.txt");
+        let mut recorder = InputRecorder::create(&path, 16).unwrap();
+        recorder.record(0, 0x0001, 16).unwrap();
+        drop(recorder); // Simulates a crash
+
+        // Append a half-written line
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"3 00").unwrap();
+        file.write_all(b"\n9").unwrap();
+
+        let recording = InputRecording::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(!recording.complete);
+        assert_eq!(recording.events, vec![(0, 0x0001), (3, 0x0000)]);
+    }
+
+    #[test]
+    fn test_schedule_lookup() {
+        let recording = InputRecording {
+            complete: true,
+            speeds: vec![(0, 16), (10, 30)],
+            events: vec![(2, 0x0001), (5, 0x0000)],
+        };
+        assert_eq!(recording.keys_at(0), 0x0000);
+        assert_eq!(recording.keys_at(2), 0x0001);
+        assert_eq!(recording.keys_at(4), 0x0001);
+        assert_eq!(recording.keys_at(100), 0x0000);
+        assert_eq!(recording.tickrate_at(9), 16);
+        assert_eq!(recording.tickrate_at(10), 30);
+    }
+
+    #[test]
+    fn test_gif_recorder() {
+        let path = std::env::temp_dir().join("chip8_test_gif_recorder.gif");
+        let mut grid = [0; DISPLAY_HEIGHT];
+        let mut recorder = GifRecorder::create(&path, 2, 0xffffff, 0x000000).unwrap();
+        for _ in 0..30 {
+            recorder.capture(&grid).unwrap();
+        }
+        grid[0] = 1 << 62;
+        recorder.capture(&grid).unwrap(); // Too short to be kept
+        grid[0] = 1 << 63;
+        for _ in 0..60 {
+            recorder.capture(&grid).unwrap();
+        }
+        recorder.finish().unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push((frame.delay, frame.buffer[0], frame.buffer[2]));
+        }
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(frames, vec![(50, 0, 0), (101, 1, 0)]);
+        assert_eq!(bytes.last(), Some(&0x3b)); // Trailer
+    }
+}
@@ -0,0 +1,290 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use super::errors::{Chip8Error, IoError};
+use super::watchpoint::Watchpoint;
+
+// Sent before a packet's checksum
+const CHECKSUM_MARKER: u8 = b'#';
+
+// Sent by a debugger to stop the running program
+const INTERRUPT: u8 = 0x03;
+
+// Reply telling the debugger the program stopped, with SIGTRAP as the reason
+pub(super) const STOPPED: &str = "S05";
+
+// Request from a debugger, a subset of the GDB remote serial protocol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Request {
+    Interrupt, // Ctrl-C
+    HaltReason, // ?
+    ReadRegisters, // g
+    ReadMemory(u16, u16), // m addr,length
+    WriteMemory(u16, Vec<u8>), // M addr,length:bytes
+    Step, // s
+    Continue, // c
+    AddBreakpoint(u16), // Z0,addr,kind
+    RemoveBreakpoint(u16), // z0,addr,kind
+    AddWatchpoint(Watchpoint), // Z2 for writes, Z3 for reads and Z4 for both
+    RemoveWatchpoint(Watchpoint),
+    Supported, // qSupported
+    Detach, // D or k
+    Unsupported, // Answered with an empty reply
+}
+
+impl Request {
+    // Parses the data of a packet, everything between $ and #
+    pub(super) fn parse(packet: &str) -> Request {
+        let (command, args) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        let request = match command {
+            "?" => Some(Request::HaltReason),
+            "g" => Some(Request::ReadRegisters),
+            "m" => memory_range(args).map(|(addr, length)| Request::ReadMemory(addr, length)),
+            "M" => write_memory(args),
+            "s" => Some(Request::Step),
+            "c" => Some(Request::Continue),
+            "Z" | "z" => point(command == "Z", args),
+            "q" if args.starts_with("Supported") => Some(Request::Supported),
+            "D" | "k" => Some(Request::Detach),
+            _ => None,
+        };
+        request.unwrap_or(Request::Unsupported)
+    }
+}
+
+// Parses addr,length in hex
+fn memory_range(args: &str) -> Option<(u16, u16)> {
+    let (addr, length) = args.split_once(',')?;
+    Some((u16::from_str_radix(addr, 16).ok()?, u16::from_str_radix(length, 16).ok()?))
+}
+
+fn write_memory(args: &str) -> Option<Request> {
+    let (range, data) = args.split_once(':')?;
+    let (addr, length) = memory_range(range)?;
+    let bytes = decode_hex(data)?;
+    (bytes.len() == length as usize).then_some(Request::WriteMemory(addr, bytes))
+}
+
+// Parses type,addr,kind of a breakpoint or watchpoint, the kind is the watched length
+fn point(add: bool, args: &str) -> Option<Request> {
+    let mut fields = args.split(',');
+    let (kind, addr, length) = (fields.next()?, fields.next()?, fields.next()?);
+    let addr = u16::from_str_radix(addr, 16).ok()?;
+    let length = u16::from_str_radix(length, 16).ok()?.max(1);
+    let (read, write) = match kind {
+        "0" | "1" => return Some(if add { Request::AddBreakpoint(addr) } else { Request::RemoveBreakpoint(addr) }),
+        "2" => (false, true),
+        "3" => (true, false),
+        "4" => (true, true),
+        _ => return None,
+    };
+    let watchpoint = Watchpoint { start: addr, end: addr.checked_add(length - 1)?, read, write };
+    Some(if add { Request::AddWatchpoint(watchpoint) } else { Request::RemoveWatchpoint(watchpoint) })
+}
+
+pub(super) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte))
+}
+
+// Frames a reply as $data#checksum
+fn packet(data: &str) -> Vec<u8> {
+    format!("${data}#{:02x}", checksum(data.as_bytes())).into_bytes()
+}
+
+// Takes the complete packets and interrupts out of the received bytes, leaving a partial packet.
+// Valid packets are acknowledged with + and corrupted ones with -, so the debugger resends them.
+fn take_packets(input: &mut Vec<u8>, acks: &mut Vec<u8>) -> Vec<Request> {
+    let mut requests = Vec::new();
+    let mut consumed = 0;
+    while consumed < input.len() {
+        match input[consumed] {
+            INTERRUPT => {
+                requests.push(Request::Interrupt);
+                consumed += 1;
+            }
+            b'$' => {
+                let Some(end) = input[consumed..].iter().position(|&byte| byte == CHECKSUM_MARKER) else { break };
+                let end = consumed + end;
+                let Some(sum) = input.get(end + 1..end + 3) else { break };
+                let data = &input[consumed + 1..end];
+                let valid = std::str::from_utf8(sum).ok().and_then(|sum| u8::from_str_radix(sum, 16).ok()) == Some(checksum(data));
+                if valid {
+                    acks.push(b'+');
+                    requests.push(Request::parse(&String::from_utf8_lossy(data)));
+                } else {
+                    acks.push(b'-');
+                }
+                consumed = end + 3;
+            }
+            _ => consumed += 1, // Acknowledgements of our replies and noise
+        }
+    }
+    input.drain(..consumed);
+    requests
+}
+
+struct Client {
+    stream: TcpStream,
+    input: Vec<u8>, // Received bytes not yet making up a whole packet
+    running: bool, // Continued, the debugger waits for a stop reply
+}
+
+// Lets one debugger at a time attach over TCP, polled by the emulator without blocking
+pub(super) struct RemoteServer {
+    listener: TcpListener,
+    client: Option<Client>,
+}
+
+impl RemoteServer {
+    pub(super) fn bind(addr: impl ToSocketAddrs) -> Result<RemoteServer, Chip8Error> {
+        let listener = TcpListener::bind(addr).map_err(IoError::Remote)?;
+        listener.set_nonblocking(true).map_err(IoError::Remote)?;
+        Ok(RemoteServer { listener, client: None })
+    }
+
+    pub(super) fn local_addr(&self) -> Result<SocketAddr, Chip8Error> {
+        Ok(self.listener.local_addr().map_err(IoError::Remote)?)
+    }
+
+    // Accepts a waiting debugger and returns if one just attached, along with the requests received since the last poll.
+    // A debugger that disconnects or fails is dropped, another one can attach after it.
+    pub(super) fn poll(&mut self) -> (bool, Vec<Request>) {
+        let mut attached = false;
+        if self.client.is_none() {
+            if let Ok((stream, addr)) = self.listener.accept() {
+                if stream.set_nonblocking(true).is_ok() {
+                    println!("Debugger attached from {addr}");
+                    self.client = Some(Client { stream, input: Vec::new(), running: false });
+                    attached = true;
+                }
+            }
+        }
+        let Some(client) = self.client.as_mut() else { return (attached, Vec::new()) };
+
+        let mut buffer = [0; 1024];
+        loop {
+            match client.stream.read(&mut buffer) {
+                Ok(0) => return (attached, self.lost()),
+                Ok(read) => client.input.extend_from_slice(&buffer[..read]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return (attached, self.lost()),
+            }
+        }
+        let mut acks = Vec::new();
+        let requests = take_packets(&mut client.input, &mut acks);
+        if client.stream.write_all(&acks).is_err() {
+            return (attached, self.lost());
+        }
+        (attached, requests)
+    }
+
+    pub(super) fn reply(&mut self, data: &str) {
+        let Some(client) = self.client.as_mut() else { return };
+        if client.stream.write_all(&packet(data)).is_err() {
+            self.disconnect();
+        }
+    }
+
+    // Whether the attached debugger waits for the program to stop
+    pub(super) fn is_running(&self) -> bool {
+        self.client.as_ref().is_some_and(|client| client.running)
+    }
+
+    pub(super) fn set_running(&mut self, running: bool) {
+        if let Some(client) = self.client.as_mut() {
+            client.running = running;
+        }
+    }
+
+    pub(super) fn disconnect(&mut self) {
+        if self.client.take().is_some() {
+            println!("Debugger detached");
+        }
+    }
+
+    // Drops a debugger whose connection failed, handled as if it detached
+    fn lost(&mut self) -> Vec<Request> {
+        self.disconnect();
+        vec![Request::Detach]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Request::parse("?"), Request::HaltReason);
+        assert_eq!(Request::parse("g"), Request::ReadRegisters);
+        assert_eq!(Request::parse("m200,4"), Request::ReadMemory(0x200, 4));
+        assert_eq!(Request::parse("M300,2:abcd"), Request::WriteMemory(0x300, vec![0xab, 0xcd]));
+        assert_eq!(Request::parse("M300,3:abcd"), Request::Unsupported);
+        assert_eq!(Request::parse("Z0,2f0,2"), Request::AddBreakpoint(0x2f0));
+        assert_eq!(Request::parse("z0,2f0,2"), Request::RemoveBreakpoint(0x2f0));
+        assert_eq!(Request::parse("Z2,300,2"), Request::AddWatchpoint(Watchpoint { start: 0x300, end: 0x301, read: false, write: true }));
+        assert_eq!(Request::parse("qSupported:multiprocess+"), Request::Supported);
+        assert_eq!(Request::parse("vCont?"), Request::Unsupported);
+        assert_eq!(Request::parse(""), Request::Unsupported);
+    }
+
+    #[test]
+    fn test_take_packets() {
+        let mut input = b"+$g#67$m200,2#00\x03$s#7".to_vec();
+        let mut acks = Vec::new();
+        assert_eq!(take_packets(&mut input, &mut acks), vec![Request::ReadRegisters, Request::Interrupt]);
+        assert_eq!(acks, b"+-");
+        assert_eq!(input, b"$s#7"); // Waits for the rest of the checksum
+
+        input.extend_from_slice(b"3");
+        assert_eq!(take_packets(&mut input, &mut acks), vec![Request::Step]);
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn test_packet() {
+        assert_eq!(packet("OK"), b"$OK#9a");
+        assert_eq!(packet(""), b"$#00");
+        assert_eq!(encode_hex(&[0x0a, 0xff]), "0aff");
+        assert_eq!(decode_hex("0aFF"), Some(vec![0x0a, 0xff]));
+        assert_eq!(decode_hex("0a0"), None);
+    }
+
+    #[test]
+    fn test_server() {
+        let mut server = RemoteServer::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        client.write_all(b"$?#3f").unwrap();
+
+        let mut attached = false;
+        let mut requests = Vec::new();
+        for _ in 0..100 {
+            let (now_attached, received) = server.poll();
+            attached |= now_attached;
+            requests.extend(received);
+            if !requests.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(attached);
+        assert_eq!(requests, vec![Request::HaltReason]);
+
+        server.reply(STOPPED);
+        let mut reply = [0; 8];
+        client.read_exact(&mut reply).unwrap();
+        assert_eq!(&reply, b"+$S05#b8");
+    }
+}
@@ -0,0 +1,267 @@
+use alloc::{collections::BTreeSet, format, string::String, vec, vec::Vec};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+use sha1::{Digest, Sha1};
+use super::patches::Patches;
+#[cfg(feature = "std")]
+use super::{errors::{Chip8Error, LoadError}, memory::Layout, octo};
+
+// Program the emulator can switch to while running, along with its cheats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomBank {
+    pub name: String, // Shown in the window title
+    pub rom: Vec<u8>,
+    pub patches: Patches,
+}
+
+// Reads a ROM, or assembles it for the memory layout if it's Octo source, e.g. pong.8o
+#[cfg(feature = "std")]
+pub fn read(path: impl AsRef<Path>, layout: Layout) -> Result<Vec<u8>, Chip8Error> {
+    let path = path.as_ref();
+    if octo::is_source(path) {
+        return octo::assemble_file(path, layout).map(|assembled| assembled.rom);
+    }
+    fs::read(path).map_err(|source| LoadError::FileRead { path: path.to_path_buf(), source }.into())
+}
+
+#[cfg(feature = "std")]
+impl RomBank {
+    // Reads the ROM and the cheats next to it
+    pub fn from_file(path: impl AsRef<Path>, layout: Layout) -> Result<RomBank, Chip8Error> {
+        let path = path.as_ref();
+        let rom = read(path, layout)?;
+        let name = path.file_stem().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+        let patches = Patches::for_rom(path, layout.size)?.unwrap_or_default();
+        Ok(RomBank { name, rom, patches })
+    }
+}
+
+// Platforms a program may be written for, ordered by how much they extend CHIP-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Platform {
+    Chip8,
+    SuperChip,
+    MegaChip,
+    XoChip,
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Platform::Chip8 => write!(f, "CHIP-8"),
+            Platform::SuperChip => write!(f, "SUPER-CHIP"),
+            Platform::MegaChip => write!(f, "MEGA-CHIP"),
+            Platform::XoChip => write!(f, "XO-CHIP"),
+        }
+    }
+}
+
+// Platform that introduced the opcode, None for opcodes no platform knows
+pub fn classify(op: u16) -> Option<Platform> {
+    let x = (op >> 8) & 0xf;
+    match (op >> 12, op & 0xff, op & 0xf) {
+        (0x0, _, _) if op == 0x0000 || op == 0x00e0 || op == 0x00ee => Some(Platform::Chip8),
+        (0x0, _, _) if op & 0xfff0 == 0x00c0 || (0x00fb..=0x00ff).contains(&op) => Some(Platform::SuperChip),
+        (0x0, _, _) if op & 0xfff0 == 0x00d0 => Some(Platform::XoChip),
+        (0x0, _, _) if op == 0x0010 || op == 0x0011 || (0x0100..=0x05ff).contains(&op) || op & 0xfff0 == 0x0600 || op == 0x0700 || op & 0xfff0 == 0x0800 || op & 0xff00 == 0x0900 => Some(Platform::MegaChip),
+        (0x0, _, _) => None,
+        (0x1..=0x4 | 0x6 | 0x7 | 0xa..=0xc, _, _) => Some(Platform::Chip8),
+        (0x5, _, 0x0) | (0x9, _, 0x0) => Some(Platform::Chip8),
+        (0x5, _, 0x2 | 0x3) => Some(Platform::XoChip),
+        (0x8, _, 0x0..=0x7 | 0xe) => Some(Platform::Chip8),
+        (0xd, _, 0x0) => Some(Platform::SuperChip),
+        (0xd, _, _) => Some(Platform::Chip8),
+        (0xe, 0x9e | 0xa1, _) => Some(Platform::Chip8),
+        (0xf, 0x07 | 0x0a | 0x15 | 0x18 | 0x1e | 0x29 | 0x33 | 0x55 | 0x65, _) => Some(Platform::Chip8),
+        (0xf, 0x30 | 0x75 | 0x85, _) => Some(Platform::SuperChip),
+        (0xf, 0x00, _) if x == 0 => Some(Platform::XoChip),
+        (0xf, 0x01, _) => Some(Platform::XoChip),
+        (0xf, 0x02 | 0x3a, _) => Some(Platform::XoChip),
+        _ => None,
+    }
+}
+
+// Static facts about a ROM, gathered without running it
+pub struct RomInfo {
+    pub size: usize,
+    pub sha1: String,
+    pub platform: Platform, // Most advanced platform among the reachable opcodes
+    pub entry: u16,
+    pub entry_jump: Option<u16>, // Target of the first instruction if it's a jump
+    pub opcodes: Vec<(u16, u16)>, // Reachable instructions as address and opcode
+    pub unknown: Vec<(u16, u16)>, // Reachable opcodes no platform knows
+}
+
+impl RomInfo {
+    // Analyzes the ROM as loaded at the program start, which the platform decides
+    pub fn analyze(rom: &[u8], program_start: u16) -> Self {
+        let opcodes = reachable(rom, program_start);
+        let platform = opcodes.iter()
+            .filter_map(|&(_, op)| classify(op))
+            .max()
+            .unwrap_or(Platform::Chip8);
+        let unknown = opcodes.iter()
+            .filter(|&&(_, op)| classify(op).is_none())
+            .copied()
+            .collect();
+        let entry_jump = opcodes.first()
+            .filter(|&&(addr, op)| addr == program_start && op >> 12 == 0x1)
+            .map(|&(_, op)| op & 0x0fff);
+
+        RomInfo {
+            size: rom.len(),
+            sha1: format!("{:x}", Sha1::digest(rom)),
+            platform,
+            entry: program_start,
+            entry_jump,
+            opcodes,
+            unknown,
+        }
+    }
+}
+
+impl fmt::Display for RomInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Size: {} bytes", self.size)?;
+        writeln!(f, "SHA-1: {}", self.sha1)?;
+        writeln!(f, "Platform: {}", self.platform)?;
+        match self.entry_jump {
+            Some(target) => writeln!(f, "Entry point: {:#05X} (jumps to {:#05X})", self.entry, target)?,
+            None => writeln!(f, "Entry point: {:#05X}", self.entry)?,
+        }
+        writeln!(f, "Reachable instructions: {}", self.opcodes.len())?;
+        if self.unknown.is_empty() {
+            write!(f, "Unknown opcodes: none")
+        } else {
+            write!(f, "Unknown opcodes:")?;
+            for (addr, op) in &self.unknown {
+                write!(f, "\n  {:#06X} at {:#05X}", op, addr)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Follows jumps, calls and skips from the entry point to find the instructions
+// that can be executed, so data mixed into the program isn't mistaken for code
+pub(super) fn reachable(rom: &[u8], program_start: u16) -> Vec<(u16, u16)> {
+    let end = program_start as usize + rom.len();
+    let fetch = |addr: u16| {
+        let i = (addr - program_start) as usize;
+        ((rom[i] as u16) << 8) | rom[i + 1] as u16
+    };
+
+    let mut visited = BTreeSet::new();
+    let mut pending = vec![program_start];
+    while let Some(addr) = pending.pop() {
+        if addr < program_start || addr as usize + 1 >= end || !visited.insert(addr) {
+            continue;
+        }
+        let op = fetch(addr);
+        // Addresses past the end of the address space aren't followed
+        let next = addr.checked_add(2);
+        let skip = next.and_then(|next| next.checked_add(2));
+        match (op >> 12, op & 0xff) {
+            (0x0, 0xee) | (0x0, 0xfd) => (), // Return and exit
+            (0x1, _) => pending.push(op & 0x0fff),
+            (0x2, _) => pending.extend([Some(op & 0x0fff), next].into_iter().flatten()),
+            (0x3 | 0x4 | 0x5 | 0x9, _) | (0xe, 0x9e | 0xa1) => pending.extend([next, skip].into_iter().flatten()),
+            (0xb, _) => (), // Target depends on a register
+            (0xf, 0x00) if op == 0xf000 => pending.extend(skip), // Followed by a 16-bit address
+            (0x0, _) if op & 0xff00 == 0x0100 => pending.extend(skip), // Followed by the low 16 bits of a 24-bit address
+            _ => pending.extend(next),
+        }
+    }
+    visited.into_iter().map(|addr| (addr, fetch(addr))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{PROGRAM_START, splash::SPLASH};
+
+    #[test]
+    fn test_bank_from_file() {
+        let path = std::env::temp_dir().join("chip8_test_bank.ch8");
+        fs::write(&path, [0x00, 0xE0]).unwrap();
+        fs::write(path.with_extension("cht"), "freeze 300:01").unwrap();
+        let bank = RomBank::from_file(&path, Layout::default());
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(path.with_extension("cht")).unwrap();
+
+        let bank = bank.unwrap();
+        assert_eq!(bank.name, "chip8_test_bank");
+        assert_eq!(bank.rom, vec![0x00, 0xE0]);
+        assert_eq!(bank.patches.patches().len(), 1);
+        assert!(matches!(RomBank::from_file("missing.ch8", Layout::default()), Err(Chip8Error::Load(LoadError::FileRead { .. }))));
+    }
+
+    #[test]
+    fn test_read_source() {
+        let path = std::env::temp_dir().join("chip8_test_read.8o");
+        fs::write(&path, ": main\n  clear\n").unwrap();
+        let rom = read(&path, Layout::default());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(rom.unwrap(), [0x00, 0xE0]);
+    }
+
+    #[test]
+    fn test_analyze_splash() {
+        let info = RomInfo::analyze(&SPLASH, PROGRAM_START);
+        assert_eq!(info.size, 56);
+        assert_eq!(info.sha1, "0413001b44ba0feb030d6732a15edca82c3f9fe2");
+        assert_eq!(info.platform, Platform::Chip8);
+        assert_eq!(info.entry_jump, None);
+        assert_eq!(info.opcodes.len(), 13); // Glyph data is not code
+        assert!(info.unknown.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_platform() {
+        let info = RomInfo::analyze(&[0x12, 0x04, 0xff, 0xff, 0x00, 0xff, 0x12, 0x06], PROGRAM_START);
+        assert_eq!(info.platform, Platform::SuperChip);
+        assert_eq!(info.entry_jump, Some(0x204));
+        assert!(info.unknown.is_empty()); // 0xFFFF at 0x202 is never reached
+
+        let info = RomInfo::analyze(&[0xf0, 0x00, 0x12, 0x34, 0x12, 0x04], PROGRAM_START);
+        assert_eq!(info.platform, Platform::XoChip);
+        assert_eq!(info.opcodes, vec![(0x200, 0xf000), (0x204, 0x1204)]);
+    }
+
+    #[test]
+    fn test_analyze_unknown() {
+        let info = RomInfo::analyze(&[0x81, 0x28, 0xe1, 0x00, 0x12, 0x04], PROGRAM_START);
+        assert_eq!(info.unknown, vec![(0x200, 0x8128), (0x202, 0xe100)]);
+    }
+
+    #[test]
+    fn test_analyze_program_start() {
+        // ETI-660 programs start at 0x600, so their jumps only make sense from there
+        let info = RomInfo::analyze(&[0x16, 0x04, 0xff, 0xff, 0x00, 0xe0], 0x600);
+        assert_eq!((info.entry, info.entry_jump), (0x600, Some(0x604)));
+        assert_eq!(info.opcodes, vec![(0x600, 0x1604), (0x604, 0x00e0)]);
+    }
+
+    #[test]
+    fn test_reachable_end() {
+        // Code runs up to the end of the address space and stops there
+        let rom = [0x60, 0x00].repeat(0x7f00);
+        assert_eq!(reachable(&rom, PROGRAM_START).len(), 0x7f00);
+        assert_eq!(reachable(&[0x30, 0x00].repeat(0x7f00), PROGRAM_START).len(), 0x7f00);
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(0x00e0), Some(Platform::Chip8));
+        assert_eq!(classify(0x00c4), Some(Platform::SuperChip));
+        assert_eq!(classify(0x00d4), Some(Platform::XoChip));
+        assert_eq!(classify(0x0a23), None);
+        assert_eq!(classify(0x0123), Some(Platform::MegaChip));
+        assert_eq!(classify(0x0011), Some(Platform::MegaChip));
+        assert_eq!(classify(0xd120), Some(Platform::SuperChip));
+        assert_eq!(classify(0xf275), Some(Platform::SuperChip));
+        assert_eq!(classify(0x5122), Some(Platform::XoChip));
+        assert_eq!(classify(0x8128), None);
+    }
+}
@@ -0,0 +1,237 @@
+use std::hint;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Length of a 60hz frame, the period of the display and the timers
+pub const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+// Frames the emulation catches up on by default, more are dropped
+const MAX_LAG: u32 = 4;
+
+// Last stretch before a deadline that is spun through rather than slept,
+// sleeps overshoot by up to the timer resolution of the OS
+const SPIN: Duration = Duration::from_millis(1);
+
+// What to do about the missed frames when the emulation falls behind real time,
+// e.g. after a slow frame or the computer waking from sleep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LagPolicy {
+    CatchUp, // Run all of them as fast as possible
+    Skip, // Drop them, the emulation stays behind
+    Clamp(u32), // Catch up on this many at most and drop the rest
+}
+
+impl LagPolicy {
+    // catch-up, skip or the number of frames to catch up on at most
+    pub fn parse(name: &str) -> Option<LagPolicy> {
+        match name {
+            "catch-up" => Some(LagPolicy::CatchUp),
+            "skip" => Some(LagPolicy::Skip),
+            frames => frames.parse().ok().map(LagPolicy::Clamp),
+        }
+    }
+
+    // Frames the emulation may fall behind before the missed ones are dropped
+    fn max_lag(&self) -> Option<u32> {
+        match self {
+            LagPolicy::CatchUp => None,
+            LagPolicy::Skip => Some(0),
+            LagPolicy::Clamp(frames) => Some(*frames),
+        }
+    }
+}
+
+impl Default for LagPolicy {
+    fn default() -> Self {
+        LagPolicy::Clamp(MAX_LAG)
+    }
+}
+
+// Paces frames against deadlines counted from a fixed start, so a frame that
+// runs or sleeps too long is made up by the next ones instead of adding up as drift
+pub struct Scheduler {
+    start: Instant, // When frame 0 was due
+    frames: u32, // Frames finished since the start
+    policy: LagPolicy,
+}
+
+impl Scheduler {
+    pub fn new(now: Instant, policy: LagPolicy) -> Self {
+        Scheduler { start: now, frames: 0, policy }
+    }
+
+    // Waits until the current frame is due and moves on to the next one
+    pub fn wait(&mut self) {
+        let now = Instant::now();
+        sleep_until(now + self.finish_frame(now));
+    }
+
+    // Moves on to the next frame, returns how long to wait for the current one to be due.
+    // Falling further behind than the policy allows counts from now instead of
+    // rushing through the missed frames.
+    pub fn finish_frame(&mut self, now: Instant) -> Duration {
+        self.frames += 1;
+        let due = self.start + FRAME * self.frames;
+        if self.policy.max_lag().is_some_and(|frames| now > due + FRAME * frames) {
+            self.start = now;
+            self.frames = 0;
+        }
+        due.saturating_duration_since(now)
+    }
+}
+
+// Paces emulation by the clock while the monitor's refresh paces the loop, as presenting
+// a frame with vsync waits for it. Every refresh is told the timer ticks due since the last
+// one and how far into the next frame the clock is, so each 60hz frame still gets its tick
+// rate of instructions and ends with its timer tick on time, its instructions spread over
+// the refreshes it spans.
+pub struct FramePacer {
+    last: Instant, // When the time was last handed out
+    elapsed: u128, // Nanoseconds since the last timer tick handed out
+    policy: LagPolicy,
+}
+
+// What a refresh has to catch up on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Due {
+    pub ticks: u32, // Frames to end, each ticking the timers
+    pub instructions: u32, // Instructions into the frame after them, less than the tick rate
+}
+
+impl FramePacer {
+    pub fn new(now: Instant, policy: LagPolicy) -> Self {
+        FramePacer { last: now, elapsed: 0, policy }
+    }
+
+    // Frames due by now at 60hz, more than the policy allows are dropped
+    pub fn due(&mut self, now: Instant, tickrate: u32) -> Due {
+        let frame = FRAME.as_nanos();
+        self.elapsed = self.elapsed.saturating_add(now.saturating_duration_since(self.last).as_nanos());
+        self.last = now;
+        if let Some(frames) = self.policy.max_lag() {
+            self.elapsed = self.elapsed.min((frames as u128 + 1) * frame);
+        }
+        let ticks = saturate(self.elapsed / frame);
+        self.elapsed -= ticks as u128 * frame;
+        let instructions = saturate(self.elapsed.min(frame - 1) * tickrate as u128 / frame);
+        Due { ticks, instructions }
+    }
+}
+
+// Counts past u32::MAX, as a stall of years would be, are cut down to it
+fn saturate(count: u128) -> u32 {
+    u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+// Sleeps until shortly before the deadline and spins for the rest, so the
+// deadline is met however coarse the sleeps are
+fn sleep_until(deadline: Instant) {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining > SPIN {
+        thread::sleep(remaining - SPIN);
+    }
+    while Instant::now() < deadline {
+        hint::spin_loop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_are_evenly_spaced() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(start, LagPolicy::default());
+        assert_eq!(scheduler.finish_frame(start), FRAME);
+        assert_eq!(scheduler.finish_frame(start + FRAME), FRAME);
+    }
+
+    #[test]
+    fn test_late_frame_is_made_up() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(start, LagPolicy::default());
+        scheduler.finish_frame(start);
+
+        // The first frame ended 10ms late, so the second one only waits for the rest of its time
+        let late = start + FRAME + Duration::from_millis(10);
+        assert_eq!(scheduler.finish_frame(late), FRAME - Duration::from_millis(10));
+        assert_eq!(scheduler.finish_frame(late + Duration::from_millis(30)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_lag_policies() {
+        let start = Instant::now();
+        let late = start + FRAME * 10;
+
+        // Catching up runs the missed frames without waiting
+        let mut scheduler = Scheduler::new(start, LagPolicy::CatchUp);
+        for _ in 0..10 {
+            assert_eq!(scheduler.finish_frame(late), Duration::ZERO);
+        }
+        assert_eq!(scheduler.finish_frame(late), FRAME);
+
+        // Skipping waits a full frame from now
+        let mut scheduler = Scheduler::new(start, LagPolicy::Skip);
+        assert_eq!(scheduler.finish_frame(late), Duration::ZERO);
+        assert_eq!(scheduler.finish_frame(late), FRAME);
+
+        // Clamping to 2 frames drops the 9 missed ones and counts from now, clamping to 20 catches up on them
+        let mut scheduler = Scheduler::new(start, LagPolicy::Clamp(2));
+        assert_eq!(scheduler.finish_frame(late), Duration::ZERO);
+        assert_eq!(scheduler.finish_frame(late), FRAME);
+        let mut scheduler = Scheduler::new(start, LagPolicy::Clamp(20));
+        assert_eq!(scheduler.finish_frame(late), Duration::ZERO);
+        assert_eq!(scheduler.finish_frame(late), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_parse_lag_policy() {
+        assert_eq!(LagPolicy::parse("catch-up"), Some(LagPolicy::CatchUp));
+        assert_eq!(LagPolicy::parse("skip"), Some(LagPolicy::Skip));
+        assert_eq!(LagPolicy::parse("8"), Some(LagPolicy::Clamp(8)));
+        assert_eq!(LagPolicy::parse("fast"), None);
+    }
+
+    #[test]
+    fn test_pacer_spreads_frames_over_refreshes() {
+        // A 144hz monitor ends a frame every 2.4 refreshes, its instructions run as the clock reaches them
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(start, LagPolicy::default());
+        let due: Vec<Due> = (1..=12).map(|n| pacer.due(start + FRAME * 5 * n / 12, 10)).collect();
+        assert_eq!(due.iter().map(|due| due.ticks).sum::<u32>(), 5); // 5 frames, none lost to rounding
+        assert!(due.iter().all(|due| due.ticks <= 1 && due.instructions < 10));
+        assert_eq!(due[0], Due { ticks: 0, instructions: 4 });
+        assert_eq!(due[1], Due { ticks: 0, instructions: 8 });
+        assert_eq!(due[11], Due { ticks: 1, instructions: 0 });
+
+        // A long stall is dropped down to the lag policy's frames
+        assert_eq!(pacer.due(start + Duration::from_secs(2), 10), Due { ticks: MAX_LAG + 1, instructions: 0 });
+        assert_eq!(pacer.due(start + Duration::from_secs(2) + FRAME * 3 / 4, 10), Due { ticks: 0, instructions: 7 });
+    }
+
+    #[test]
+    fn test_pacer_saturates() {
+        // Catching up on a stall of years asks for as many frames as fit
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(start, LagPolicy::CatchUp);
+        let due = pacer.due(start + Duration::from_secs(100_000_000), 1000);
+        assert_eq!(due, Due { ticks: u32::MAX, instructions: 999 });
+    }
+
+    #[test]
+    fn test_sleep_until() {
+        let deadline = Instant::now() + Duration::from_millis(3);
+        sleep_until(deadline);
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[test]
+    fn test_lag_is_dropped() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(start, LagPolicy::default());
+        let late = start + Duration::from_secs(1);
+        assert_eq!(scheduler.finish_frame(late), Duration::ZERO);
+        assert_eq!(scheduler.finish_frame(late), FRAME);
+    }
+}
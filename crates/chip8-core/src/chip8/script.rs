@@ -0,0 +1,219 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Scope, AST, INT};
+use super::{Chip8, Memory, MEMORY_SIZE, NUM_REGISTERS, errors::{Chip8Error, ExecError, LoadError}, watchpoint::Access};
+
+// Points in emulation a script can hook into by defining a function with the given name
+pub(super) enum Hook {
+    Frame, // on_frame(), after every 60hz frame
+    Instruction(u16, u16), // on_instruction(pc, opcode), before the instruction is fetched
+    Access(u16, Access), // on_read(addr, value) or on_write(addr, value), after the instruction made the access
+}
+
+// Machine state scripts read and change, copied in before a hook runs and back out after it
+struct State {
+    v: [u8; NUM_REGISTERS],
+    idx: u16,
+    pc: u16,
+    dt: u8,
+    st: u8,
+    memory: Vec<u8>,
+    pokes: Vec<(u16, u8)>, // Memory writes made by the hook, in order
+    pause: bool, // The hook asked to pause the emulator
+}
+
+// Rhai script with hooks called by the emulator, e.g. to cheat, test or train.
+// Hooks read and change the machine through v(n), set_v(n, x), i(), set_i(x), pc(), set_pc(x),
+// dt(), set_dt(x), st(), set_st(x), peek(addr), poke(addr, x) and pause().
+pub(super) struct Script {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>, // Variables of the script's top level, shared by its hooks
+    state: Arc<Mutex<State>>,
+}
+
+impl Script {
+    pub(super) fn load(path: impl AsRef<Path>) -> Result<Script, Chip8Error> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|source| LoadError::FileRead { path: path.to_path_buf(), source })?;
+        Script::compile(&source)
+    }
+
+    // Compiles the script and runs its top level once
+    pub(super) fn compile(source: &str) -> Result<Script, Chip8Error> {
+        let state = Arc::new(Mutex::new(State {
+            v: [0; NUM_REGISTERS], idx: 0, pc: 0, dt: 0, st: 0, memory: vec![0; MEMORY_SIZE], pokes: Vec::new(), pause: false,
+        }));
+        let mut engine = Engine::new();
+        register_api(&mut engine, &state);
+
+        let ast = engine.compile(source).map_err(|e| LoadError::Script(e.to_string()))?;
+        let mut scope = Scope::new();
+        engine.run_ast_with_scope(&mut scope, &ast).map_err(|e| LoadError::Script(e.to_string()))?;
+        Ok(Script { engine, ast, scope, state })
+    }
+
+    // Whether the script wants to know about memory accesses
+    pub(super) fn watches_memory(&self) -> bool {
+        self.defines("on_read", 2) || self.defines("on_write", 2)
+    }
+
+    fn defines(&self, name: &str, params: usize) -> bool {
+        self.ast.iter_functions().any(|f| f.name == name && f.params.len() == params)
+    }
+
+    // Calls the hook if the script defines it, applying the changes it makes
+    pub(super) fn run(&mut self, hook: Hook, chip8: &mut Chip8, mem: &mut Memory) -> Result<(), Chip8Error> {
+        match hook {
+            Hook::Frame => self.call("on_frame", Vec::new(), chip8, mem),
+            Hook::Instruction(pc, opcode) => self.call("on_instruction", vec![(pc as INT).into(), (opcode as INT).into()], chip8, mem),
+            Hook::Access(addr, access) => {
+                let name = match access {
+                    Access::Read => "on_read",
+                    Access::Write => "on_write",
+                };
+                let value = mem.bytes()[addr as usize] as INT;
+                self.call(name, vec![(addr as INT).into(), value.into()], chip8, mem)
+            }
+        }
+    }
+
+    fn call(&mut self, name: &str, args: Vec<Dynamic>, chip8: &mut Chip8, mem: &mut Memory) -> Result<(), Chip8Error> {
+        if !self.defines(name, args.len()) {
+            return Ok(());
+        }
+
+        {
+            let mut state = lock(&self.state);
+            (state.v, state.idx, state.pc, state.dt, state.st) = (chip8.v, chip8.idx, chip8.pc, chip8.dt, chip8.st);
+            state.memory.clear();
+            state.memory.extend_from_slice(mem.bytes());
+        }
+        let options = CallFnOptions::new().eval_ast(false).rewind_scope(false);
+        let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut self.scope, &self.ast, name, args);
+
+        let mut state = lock(&self.state);
+        (chip8.v, chip8.idx, chip8.pc, chip8.dt, chip8.st) = (state.v, state.idx, state.pc, state.dt, state.st);
+        for (addr, byte) in state.pokes.drain(..) {
+            mem.write_byte(addr, byte);
+        }
+        if std::mem::take(&mut state.pause) {
+            chip8.paused = true;
+            chip8.update_title();
+        }
+        result.map(|_| ()).map_err(|e| ExecError::Script(format!("{name}: {e}")).into())
+    }
+}
+
+// Locks the state, which stays usable even if a call panicked while holding it
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Registers the functions hooks use to access the machine
+fn register_api(engine: &mut Engine, state: &Arc<Mutex<State>>) {
+    let s = state.clone();
+    engine.register_fn("v", move |n: INT| register(n).map(|n| lock(&s).v[n] as INT));
+    let s = state.clone();
+    engine.register_fn("set_v", move |n: INT, value: INT| register(n).map(|n| lock(&s).v[n] = value as u8));
+    let s = state.clone();
+    engine.register_fn("i", move || lock(&s).idx as INT);
+    let s = state.clone();
+    engine.register_fn("set_i", move |value: INT| lock(&s).idx = value as u16);
+    let s = state.clone();
+    engine.register_fn("pc", move || lock(&s).pc as INT);
+    let s = state.clone();
+    engine.register_fn("set_pc", move |value: INT| {
+        let mut state = lock(&s);
+        address(value, state.memory.len()).map(|addr| state.pc = addr)
+    });
+    let s = state.clone();
+    engine.register_fn("dt", move || lock(&s).dt as INT);
+    let s = state.clone();
+    engine.register_fn("set_dt", move |value: INT| lock(&s).dt = value as u8);
+    let s = state.clone();
+    engine.register_fn("st", move || lock(&s).st as INT);
+    let s = state.clone();
+    engine.register_fn("set_st", move |value: INT| lock(&s).st = value as u8);
+    let s = state.clone();
+    engine.register_fn("peek", move |addr: INT| {
+        let state = lock(&s);
+        address(addr, state.memory.len()).map(|addr| state.memory[addr as usize] as INT)
+    });
+    let s = state.clone();
+    engine.register_fn("poke", move |addr: INT, value: INT| -> Result<(), Box<EvalAltResult>> {
+        let mut state = lock(&s);
+        let addr = address(addr, state.memory.len())?;
+        state.memory[addr as usize] = value as u8; // Later peeks in the same hook see the write
+        state.pokes.push((addr, value as u8));
+        Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn("pause", move || lock(&s).pause = true);
+}
+
+fn register(n: INT) -> Result<usize, Box<EvalAltResult>> {
+    usize::try_from(n).ok().filter(|&n| n < NUM_REGISTERS).ok_or_else(|| format!("no register V{n}").into())
+}
+
+fn address(addr: INT, size: usize) -> Result<u16, Box<EvalAltResult>> {
+    u16::try_from(addr).ok().filter(|&addr| (addr as usize) < size).ok_or_else(|| format!("address {addr:#X} is out of memory").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_hook() {
+        let mut script = Script::compile("
+            let frames = 0;
+            fn on_frame() {
+                frames += 1;
+                set_v(3, v(3) + frames);
+                poke(0x300, peek(0x300) + 1);
+            }
+        ").unwrap();
+        let (mut chip8, mut mem) = (Chip8::new(), Memory::new());
+        script.run(Hook::Frame, &mut chip8, &mut mem).unwrap();
+        script.run(Hook::Frame, &mut chip8, &mut mem).unwrap();
+        assert_eq!(chip8.v[3], 3); // 1 + 2, the top level variable is kept between calls
+        assert_eq!(mem.read_byte(0x300), 2);
+    }
+
+    #[test]
+    fn test_instruction_hook() {
+        let mut script = Script::compile("
+            fn on_instruction(pc, opcode) {
+                if opcode == 0x00E0 { set_pc(pc + 2); pause(); }
+            }
+        ").unwrap();
+        let (mut chip8, mut mem) = (Chip8::new(), Memory::new());
+        script.run(Hook::Instruction(0x200, 0x1200), &mut chip8, &mut mem).unwrap();
+        assert_eq!(chip8.pc, 0x200);
+        script.run(Hook::Instruction(0x200, 0x00E0), &mut chip8, &mut mem).unwrap();
+        assert_eq!(chip8.pc, 0x202);
+        assert!(chip8.paused);
+        assert!(!script.watches_memory());
+    }
+
+    #[test]
+    fn test_access_hook() {
+        let mut script = Script::compile("fn on_write(addr, value) { if addr == 0x300 { set_v(0, value); } }").unwrap();
+        let (mut chip8, mut mem) = (Chip8::new(), Memory::new());
+        assert!(script.watches_memory());
+        mem.write_byte(0x300, 0x42);
+        script.run(Hook::Access(0x300, Access::Read), &mut chip8, &mut mem).unwrap(); // Not hooked
+        assert_eq!(chip8.v[0], 0);
+        script.run(Hook::Access(0x300, Access::Write), &mut chip8, &mut mem).unwrap();
+        assert_eq!(chip8.v[0], 0x42);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(Script::compile("fn on_frame( {"), Err(Chip8Error::Load(LoadError::Script(_)))));
+        let mut script = Script::compile("fn on_frame() { set_v(16, 0); }").unwrap();
+        let (mut chip8, mut mem) = (Chip8::new(), Memory::new());
+        assert!(matches!(script.run(Hook::Frame, &mut chip8, &mut mem), Err(Chip8Error::Exec(ExecError::Script(_)))));
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// How often the measured speed is refreshed
+const SAMPLE_PERIOD: Duration = Duration::from_secs(1);
+
+// Measures displayed frames and executed instructions per second
+pub struct SpeedCounter {
+    since: Instant, // Start of the current sample
+    frames: u32, // Frames displayed during the current sample
+    instructions: u64, // Instruction count at the start of the current sample
+    fps: f64,
+    ips: f64,
+}
+
+impl SpeedCounter {
+    pub fn new(now: Instant, instructions: u64) -> Self {
+        SpeedCounter { since: now, frames: 0, instructions, fps: 0.0, ips: 0.0 }
+    }
+
+    // Counts a displayed frame given the total instructions executed so far,
+    // returns true when the measured speed was refreshed
+    pub fn frame(&mut self, now: Instant, instructions: u64) -> bool {
+        self.frames += 1;
+        let elapsed = now.duration_since(self.since);
+        if elapsed < SAMPLE_PERIOD {
+            return false;
+        }
+
+        let seconds = elapsed.as_secs_f64();
+        self.fps = self.frames as f64 / seconds;
+        self.ips = instructions.saturating_sub(self.instructions) as f64 / seconds;
+        self.since = now;
+        self.frames = 0;
+        self.instructions = instructions;
+        true
+    }
+}
+
+impl fmt::Display for SpeedCounter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.0} FPS, {:.0} IPS", self.fps, self.ips)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speed_counter() {
+        let start = Instant::now();
+        let mut counter = SpeedCounter::new(start, 100);
+        for frame in 1..60 {
+            assert!(!counter.frame(start + Duration::from_millis(frame * 16), 100 + frame * 16));
+        }
+        assert!(counter.frame(start + Duration::from_secs(1), 100 + 960));
+        assert_eq!(counter.to_string(), "60 FPS, 960 IPS");
+
+        // Paused, nothing executes
+        assert!(!counter.frame(start + Duration::from_millis(1500), 1060));
+        assert!(counter.frame(start + Duration::from_secs(2), 1060));
+        assert_eq!(counter.to_string(), "2 FPS, 0 IPS");
+    }
+}
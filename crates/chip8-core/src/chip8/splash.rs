@@ -0,0 +1,68 @@
+use super::program::ProgramBuilder;
+
+// Built-in program shown when the emulator is started without a ROM.
+// Draws "CHIP-8" in the middle of the screen and then idles.
+pub const SPLASH: [u8; 56] = {
+    let program = ProgramBuilder::new()
+        .cls()
+        .ld(0, 17) // x of the first glyph
+        .ld(1, 13) // y of every glyph
+        .ld(2, 6) // glyphs left to draw
+        .ld(3, 5); // glyph size in bytes
+    let load_glyphs = program.here();
+    let program = program.ld_i(0);
+    let draw = program.here();
+    let program = program
+        .drw(0, 1, 5)
+        .add_i(3)
+        .add(0, 5)
+        .add(2, 0xff)
+        .se(2, 0)
+        .jp(draw);
+    let idle = program.here();
+    let program = program.jp(idle);
+    let glyphs = program.here();
+    program
+        .data(&[0xf0, 0x80, 0x80, 0x80, 0xf0]) // "C"
+        .data(&[0x90, 0x90, 0xf0, 0x90, 0x90]) // "H"
+        .data(&[0xe0, 0x40, 0x40, 0x40, 0xe0]) // "I"
+        .data(&[0xf0, 0x90, 0xf0, 0x80, 0x80]) // "P"
+        .data(&[0x00, 0x00, 0xf0, 0x00, 0x00]) // "-"
+        .data(&[0xf0, 0x90, 0xf0, 0x90, 0xf0]) // "8"
+        .patch(load_glyphs, glyphs)
+        .build()
+};
+
+// Built-in program for the guided tour. Shows the digit of the CHIP-8 key being
+// held in the middle of the screen, with a short beep whenever it changes.
+pub const TUTORIAL: [u8; 38] = {
+    let program = ProgramBuilder::new()
+        .cls()
+        .ld(1, 30) // x of the digit
+        .ld(2, 13) // y of the digit
+        .ld(3, 2) // beep length
+        .ld(4, 0xff); // digit shown, none yet
+    let scan = program.here();
+    let program = program.ld(0, 0); // key to check
+    let check = program.here();
+    let program = program.skp(0);
+    let not_held = program.here();
+    let program = program.jp(0).sne_v(0, 4);
+    let already_shown = program.here();
+    let program = program
+        .jp(0)
+        .cls()
+        .ld_f(0)
+        .drw(1, 2, 5)
+        .ld_st(3)
+        .ld_v(4, 0);
+    let next_key = program.here();
+    program
+        .add(0, 1)
+        .se(0, 16)
+        .jp(check)
+        .jp(scan) // scan the keys again
+        .patch(not_held, next_key)
+        .patch(already_shown, next_key)
+        .build()
+};
@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use image::{Rgb, RgbImage};
+use super::frontend::{Frontend, Key, Scale, WindowSettings};
+use super::errors::{Chip8Error, IoError, LoadError};
+use super::rom::reachable;
+use super::text::{draw_text, fill_rect, CHAR_WIDTH, LINE_HEIGHT};
+
+// Sprite heights of the built-in font and of the tallest Dxyn sprite
+pub const FONT_HEIGHT: usize = 5;
+pub const MAX_HEIGHT: usize = 15;
+
+// Sheet layout, every cell is a sprite with its address below it
+const COLUMNS: usize = 16;
+const PIXEL_SIZE: usize = 2; // Sheet pixels per sprite pixel
+const CELL_WIDTH: usize = 8 * PIXEL_SIZE + 4;
+const MARGIN: usize = 2;
+
+// Sheet colors
+const LIT_COLOR: u32 = 0xffffff;
+const DARK_COLOR: u32 = 0x303030; // Unlit pixels of a sprite, showing its extent
+const TEXT_COLOR: u32 = 0xc0c0c0;
+
+// Every height-byte aligned run of the ROM that may be a sprite, to spot the graphics in a
+// program. Runs whose lit rows are all reachable code are left out, and so are blank ones.
+pub struct SpriteSheet {
+    sprites: Vec<(u16, Vec<u8>)>, // Address and bytes, a row of 8 pixels each
+    height: usize, // Rows of every sprite
+}
+
+impl SpriteSheet {
+    // Scans the ROM as loaded at the program start, which the platform decides
+    pub fn scan(rom: &[u8], height: usize, program_start: u16) -> Result<SpriteSheet, Chip8Error> {
+        if !(1..=MAX_HEIGHT).contains(&height) {
+            return Err(LoadError::InvalidArgument(format!("sprites are 1 to {MAX_HEIGHT} rows high, got {height}")).into());
+        }
+        let code: BTreeSet<u16> = reachable(rom, program_start).into_iter().flat_map(|(addr, _)| [Some(addr), addr.checked_add(1)]).flatten().collect();
+        let sprites = rom.chunks_exact(height)
+            .enumerate()
+            // Sprites past the end of the address space can't be drawn
+            .map_while(|(n, bytes)| Some((program_start.checked_add(u16::try_from(n * height).ok()?)?, bytes)))
+            .filter(|(addr, bytes)| {
+                // Blank rows don't count, leaving out runs of only code and padding
+                bytes.iter().zip(*addr..=u16::MAX).any(|(&byte, addr)| byte != 0 && !code.contains(&addr))
+            })
+            .map(|(addr, bytes)| (addr, bytes.to_vec()))
+            .collect();
+        Ok(SpriteSheet { sprites, height })
+    }
+
+    pub fn sprites(&self) -> &[(u16, Vec<u8>)] {
+        &self.sprites
+    }
+
+    pub fn width(&self) -> usize {
+        COLUMNS * CELL_WIDTH + MARGIN
+    }
+
+    pub fn height(&self) -> usize {
+        self.sprites.len().div_ceil(COLUMNS).max(1) * self.cell_height() + MARGIN
+    }
+
+    fn cell_height(&self) -> usize {
+        self.height * PIXEL_SIZE + 2 + LINE_HEIGHT
+    }
+
+    // The sheet row by row, width() pixels wide
+    pub fn render(&self) -> Vec<u32> {
+        let width = self.width();
+        let mut buffer = vec![0x000000; width * self.height()];
+        for (n, (addr, bytes)) in self.sprites.iter().enumerate() {
+            let x = MARGIN + n % COLUMNS * CELL_WIDTH;
+            let y = MARGIN + n / COLUMNS * self.cell_height();
+            for (row, byte) in bytes.iter().enumerate() {
+                for column in 0..8 {
+                    let color = if byte & (0x80 >> column) != 0 { LIT_COLOR } else { DARK_COLOR };
+                    fill_rect(&mut buffer, width, x + column * PIXEL_SIZE, y + row * PIXEL_SIZE, PIXEL_SIZE, PIXEL_SIZE, color);
+                }
+            }
+            let label = format!("{addr:03X}");
+            let label_x = x + (8 * PIXEL_SIZE).saturating_sub(label.len() * CHAR_WIDTH) / 2;
+            draw_text(&mut buffer, width, label_x, y + self.height * PIXEL_SIZE + 2, &label, TEXT_COLOR);
+        }
+        buffer
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Chip8Error> {
+        let (width, buffer) = (self.width(), self.render());
+        let image = RgbImage::from_fn(width as u32, self.height() as u32, |x, y| {
+            let color = buffer[x as usize + y as usize * width];
+            Rgb([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+        });
+        Ok(image.save(path.as_ref()).map_err(IoError::Image)?)
+    }
+
+    // Shows the sheet in a window until it's closed or Esc is pressed
+    pub fn show(&self, frontend: &dyn Frontend, title: &str) -> Result<(), Chip8Error> {
+        let (width, height, buffer) = (self.width(), self.height(), self.render());
+        let mut window = frontend.open_window(&WindowSettings::new(title, width, height, Scale::X2))?;
+        while window.is_open() && !window.get_keys().contains(&Key::Escape) {
+            window.update(&buffer, width, height)?;
+            thread::sleep(Duration::from_millis(33)); // The sheet doesn't change, 30 updates a second keep it responsive
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::PROGRAM_START;
+
+    #[test]
+    fn test_scan() {
+        // Jumps over a blank run and two sprites, the jump itself is left out
+        let rom = [0x12, 0x00, 0x00, 0x00, 0x00, 0xF0, 0x90, 0x90, 0x90, 0xF0, 0x20, 0x60, 0x20, 0x20, 0x70];
+        let sheet = SpriteSheet::scan(&rom, FONT_HEIGHT, PROGRAM_START).unwrap();
+        let addrs: Vec<u16> = sheet.sprites().iter().map(|(addr, _)| *addr).collect();
+        assert_eq!(addrs, [0x205, 0x20A]);
+        assert_eq!(sheet.sprites()[1].1, [0x20, 0x60, 0x20, 0x20, 0x70]);
+
+        assert!(SpriteSheet::scan(&rom, 0, PROGRAM_START).is_err());
+        assert!(SpriteSheet::scan(&rom, MAX_HEIGHT + 1, PROGRAM_START).is_err());
+
+        // Bytes past the end of the address space are left out
+        let mut rom = vec![0xff; 0xfe02];
+        rom[..2].copy_from_slice(&[0x12, 0x00]);
+        let sheet = SpriteSheet::scan(&rom, 1, PROGRAM_START).unwrap();
+        assert_eq!(sheet.sprites().last().unwrap().0, 0xffff);
+
+        // ETI-660 programs are scanned from 0x600
+        let rom = [0x16, 0x00, 0xF0, 0x90];
+        let sheet = SpriteSheet::scan(&rom, 2, 0x600).unwrap();
+        assert_eq!(sheet.sprites()[0].0, 0x602);
+    }
+
+    #[test]
+    fn test_render() {
+        let sheet = SpriteSheet { sprites: vec![(0x300, vec![0x80, 0x00])], height: 2 };
+        let buffer = sheet.render();
+        let at = |x: usize, y: usize| buffer[x + y * sheet.width()];
+        assert_eq!(at(MARGIN, MARGIN), LIT_COLOR);
+        assert_eq!(at(MARGIN + PIXEL_SIZE - 1, MARGIN + PIXEL_SIZE - 1), LIT_COLOR);
+        assert_eq!(at(MARGIN + PIXEL_SIZE, MARGIN), DARK_COLOR);
+        assert_eq!(at(MARGIN, MARGIN + PIXEL_SIZE), DARK_COLOR);
+        assert_eq!(at(MARGIN + CELL_WIDTH, MARGIN), 0x000000); // No second sprite
+        assert_eq!(sheet.height(), sheet.cell_height() + MARGIN);
+    }
+}
@@ -0,0 +1,45 @@
+use alloc::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+use super::{DISPLAY_WIDTH, NUM_REGISTERS, STACK_DEPTH, draw};
+
+// Snapshot of the machine for tests and external tools, serializable e.g. to JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MachineState {
+    pub v: [u8; NUM_REGISTERS],
+    pub i: u16,
+    pub pc: u16,
+    pub sp: u8,
+    pub dt: u8,
+    pub st: u8,
+    pub stack: [u16; STACK_DEPTH],
+    pub display: Vec<u64>, // Rows from the top, the most significant bit is the leftmost pixel
+}
+
+impl MachineState {
+    // Whether the pixel is lit, false outside the screen
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        x < DISPLAY_WIDTH && self.display.get(y).is_some_and(|row| row >> (DISPLAY_WIDTH - 1 - x) & 1 == 1)
+    }
+
+    // The screen as rows of # for lit and . for dark pixels, see draw::to_ascii
+    pub fn to_ascii(&self) -> String {
+        draw::to_ascii(&self.display)
+    }
+}
+
+// Machine and memory to go back to, taken by Chip8::save_state and the save state hotkey and
+// kept only in memory. Only the machine can be looked into, the rest is for restore_state.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct SaveState {
+    pub(super) machine: MachineState,
+    pub(super) calls: [u16; STACK_DEPTH], // Subroutine entered by every call on the stack
+    pub(super) memory: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl SaveState {
+    pub fn machine(&self) -> &MachineState {
+        &self.machine
+    }
+}
@@ -0,0 +1,67 @@
+use core::fmt;
+
+// Emulated frames a sample covers, one second at 60hz
+const SAMPLE_FRAMES: u32 = 60;
+
+// What the program did during one emulated second
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub instructions: u64,
+    pub draws: u64,
+    pub collisions: u64, // Draws that erased a pixel
+    pub key_waits: u64, // Fx0A instructions executed
+    pub max_stack_depth: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} IPS, {} draws/s, {} collisions/s, {} key waits/s, stack depth {}",
+            self.instructions, self.draws, self.collisions, self.key_waits, self.max_stack_depth)
+    }
+}
+
+// Counts the current emulated second and keeps the last complete one
+#[derive(Default)]
+pub struct StatsCounter {
+    pub current: Stats,
+    frames: u32, // Frames counted into the current sample
+    last: Stats,
+}
+
+impl StatsCounter {
+    // Counts a frame given the call depth at its end, returns true when a sample completed
+    pub fn frame(&mut self, stack_depth: usize) -> bool {
+        self.frames += 1;
+        if self.frames < SAMPLE_FRAMES {
+            return false;
+        }
+        self.last = self.current;
+        self.current = Stats { max_stack_depth: stack_depth, ..Stats::default() };
+        self.frames = 0;
+        true
+    }
+
+    pub fn last(&self) -> Stats {
+        self.last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_counter() {
+        let mut counter = StatsCounter::default();
+        for _ in 1..SAMPLE_FRAMES {
+            counter.current.instructions += 10;
+            assert!(!counter.frame(2));
+        }
+        counter.current.draws += 1;
+        counter.current.max_stack_depth = 3;
+        assert!(counter.frame(2));
+        assert_eq!(counter.last(), Stats { instructions: 590, draws: 1, collisions: 0, key_waits: 0, max_stack_depth: 3 });
+        assert_eq!(counter.current.max_stack_depth, 2); // Still inside the subroutines
+        assert_eq!(counter.last().to_string(), "590 IPS, 1 draws/s, 0 collisions/s, 0 key waits/s, stack depth 3");
+    }
+}
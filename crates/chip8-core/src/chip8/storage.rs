@@ -0,0 +1,157 @@
+use alloc::{collections::BTreeMap, string::{String, ToString}, vec::Vec};
+#[cfg(feature = "std")]
+use std::{env, fs, io::ErrorKind, path::PathBuf};
+use super::errors::{Chip8Error, IoError};
+
+// Where persistent data such as save states and high scores is kept. Keys are
+// slash separated names like "states/<rom sha1>/1", so every backend can lay
+// them out its own way. Send, so an emulator can be moved to another thread.
+pub trait Storage: Send {
+    // Data stored under the key, None if there is none
+    fn load(&self, key: &str) -> Result<Option<Vec<u8>>, Chip8Error>;
+    fn save(&mut self, key: &str, data: &[u8]) -> Result<(), Chip8Error>;
+    // Removing a missing key is not an error
+    fn remove(&mut self, key: &str) -> Result<(), Chip8Error>;
+}
+
+// Keeps every key in its own file below a directory
+#[cfg(feature = "std")]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl FileStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileStorage { root: root.into() }
+    }
+
+    // Storage in the user's data directory, $XDG_DATA_HOME/chip8 or ~/.local/share/chip8
+    pub fn default_location() -> Self {
+        let data = env::var_os("XDG_DATA_HOME").map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+            .unwrap_or_else(|| PathBuf::from("."));
+        FileStorage::new(data.join("chip8"))
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, Chip8Error> {
+        validate(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Storage for FileStorage {
+    fn load(&self, key: &str) -> Result<Option<Vec<u8>>, Chip8Error> {
+        match fs::read(self.path(key)?) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(IoError::Storage { key: key.to_string(), source }.into()),
+        }
+    }
+
+    // Writes to a temporary file first, so a crash never leaves half-written data
+    fn save(&mut self, key: &str, data: &[u8]) -> Result<(), Chip8Error> {
+        let path = self.path(key)?;
+        // Appended rather than replacing the extension, so keys differing only in it don't share one
+        let file_name = key.rsplit('/').next().unwrap_or(key);
+        let temp = path.with_file_name(format!("{file_name}.tmp"));
+        let error = |source| IoError::Storage { key: key.to_string(), source };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(error)?;
+        }
+        fs::write(&temp, data).map_err(error)?;
+        Ok(fs::rename(&temp, &path).map_err(error)?)
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), Chip8Error> {
+        match fs::remove_file(self.path(key)?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(IoError::Storage { key: key.to_string(), source }.into()),
+        }
+    }
+}
+
+// Keeps everything in memory, for tests and frontends without persistence
+#[derive(Default)]
+pub struct MemoryStorage {
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        MemoryStorage::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self, key: &str) -> Result<Option<Vec<u8>>, Chip8Error> {
+        validate(key)?;
+        Ok(self.entries.get(key).cloned())
+    }
+
+    fn save(&mut self, key: &str, data: &[u8]) -> Result<(), Chip8Error> {
+        validate(key)?;
+        self.entries.insert(key.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), Chip8Error> {
+        validate(key)?;
+        self.entries.remove(key);
+        Ok(())
+    }
+}
+
+// Keys are made of letters, digits, '-', '_' and '.' separated by '/', so they are
+// valid file names everywhere and can't escape the storage directory
+fn validate(key: &str) -> Result<(), Chip8Error> {
+    let valid = key.split('/').all(|part| {
+        !part.is_empty() && part != "." && part != ".."
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(IoError::InvalidStorageKey(key.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Behavior every backend must share
+    fn check_backend(storage: &mut dyn Storage) {
+        assert_eq!(storage.load("scores/pong").unwrap(), None);
+        storage.save("scores/pong", &[1, 2, 3]).unwrap();
+        assert_eq!(storage.load("scores/pong").unwrap(), Some(vec![1, 2, 3]));
+        storage.save("scores/pong", &[4]).unwrap();
+        assert_eq!(storage.load("scores/pong").unwrap(), Some(vec![4]));
+        storage.remove("scores/pong").unwrap();
+        storage.remove("scores/pong").unwrap();
+        assert_eq!(storage.load("scores/pong").unwrap(), None);
+
+        for key in ["", "../escape", "states//1", "/absolute", "space key"] {
+            assert!(storage.save(key, &[]).is_err(), "{key:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        check_backend(&mut MemoryStorage::new());
+    }
+
+    #[test]
+    fn test_file_storage() {
+        let root = env::temp_dir().join("chip8_test_file_storage");
+        let mut storage = FileStorage::new(&root);
+        check_backend(&mut storage);
+
+        storage.save("flags.tmp", &[1]).unwrap();
+        storage.save("flags.json", &[2]).unwrap();
+        assert_eq!(storage.load("flags.tmp").unwrap(), Some(vec![1]));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use image::Rgb;
+use super::{Chip8, Memory, DISPLAY_HEIGHT, DISPLAY_WIDTH, draw, errors::{Chip8Error, IoError, LoadError}};
+
+// What the screen is expected to look like after a test run
+pub enum Expectation {
+    Hash(u64), // Hash of the framebuffer, see framebuffer_hash
+    Image(PathBuf), // Screenshot taken with the default colors
+    Text(PathBuf), // Screen as rows of # and ., see draw::to_ascii
+}
+
+// Runs the program without a window for the given number of instructions,
+// a frame ends once every tickrate instructions
+pub fn run_headless(chip8: &mut Chip8, mem: &mut Memory, cycles: u64) -> Result<(), Chip8Error> {
+    chip8.latch_input()?;
+    chip8.patches.apply(mem);
+    let mut executed = 0;
+    for _ in 0..cycles {
+        chip8.step(mem)?;
+        executed += 1;
+        if executed >= chip8.get_tickrate() {
+            chip8.end_frame(mem)?;
+            executed = 0;
+        }
+    }
+    Ok(())
+}
+
+// Stable FNV-1a hash of the framebuffer, pixels are read row by row
+pub fn framebuffer_hash(chip8: &Chip8) -> u64 {
+    let grid = chip8.display.get_grid();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for pixel in draw::pixels(grid) {
+        hash ^= pixel as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// Compares the framebuffer with the expectation
+pub fn matches(chip8: &Chip8, expectation: &Expectation) -> Result<bool, Chip8Error> {
+    match expectation {
+        Expectation::Hash(hash) => Ok(framebuffer_hash(chip8) == *hash),
+        Expectation::Text(path) => {
+            let text = fs::read_to_string(path).map_err(|source| LoadError::FileRead { path: path.clone(), source })?;
+            let grid = draw::from_ascii(&text).ok_or_else(|| LoadError::InvalidScreen(path.clone()))?;
+            Ok(grid == *chip8.display.get_grid())
+        }
+        Expectation::Image(path) => {
+            let image = image::open(path).map_err(IoError::Image)?.to_rgb8();
+            let (width, height) = image.dimensions();
+            if !(width as usize).is_multiple_of(DISPLAY_WIDTH) || width as usize / DISPLAY_WIDTH * DISPLAY_HEIGHT != height as usize {
+                return Ok(false);
+            }
+
+            // Sample the middle of every cell, light pixels are filled
+            let factor = width as usize / DISPLAY_WIDTH;
+            let grid = chip8.display.get_grid();
+            let matching = (0..DISPLAY_WIDTH).all(|x| (0..DISPLAY_HEIGHT).all(|y| {
+                let Rgb([r, g, b]) = *image.get_pixel((x * factor + factor / 2) as u32, (y * factor + factor / 2) as u32);
+                let filled = (r as u32 + g as u32 + b as u32) / 3 > 127;
+                filled == draw::is_lit(grid, x, y)
+            }));
+            Ok(matching)
+        }
+    }
+}
+
+// Saves the screen as text if the file ends in .txt, as a PNG image otherwise
+pub fn save_screen(chip8: &Chip8, path: &Path) -> Result<(), Chip8Error> {
+    if path.extension().is_some_and(|ext| ext == "txt") {
+        Ok(fs::write(path, chip8.display.to_ascii()).map_err(IoError::Screen)?)
+    } else {
+        chip8.screenshot(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{recording::InputRecorder, PROGRAM_START};
+
+    #[test]
+    fn test_run_headless() {
+        let mut chip8 = Chip8::new();
+        let mut mem = Memory::splash();
+        let empty = framebuffer_hash(&chip8);
+        run_headless(&mut chip8, &mut mem, 100).unwrap();
+        assert_ne!(framebuffer_hash(&chip8), empty);
+    }
+
+    #[test]
+    fn test_run_headless_waits_for_key() {
+        let mut chip8 = Chip8::new();
+        let mut mem = Memory::new();
+        mem.write_byte(0x200, 0xF0); // LD V0, K
+        mem.write_byte(0x201, 0x0A);
+        run_headless(&mut chip8, &mut mem, 10).unwrap();
+        assert_eq!(chip8.pc, 0x200);
+
+        // Frames keep ending while waiting
+        let frame = chip8.frame;
+        run_headless(&mut chip8, &mut mem, 64).unwrap();
+        assert_eq!((chip8.pc, chip8.frame), (0x200, frame + 4));
+    }
+
+    #[test]
+    fn test_replay_at_recorded_speed() {
+        let path = std::env::temp_dir().join("chip8_test_replay_at_recorded_speed.txt");
+        let mut recorder = InputRecorder::create(&path, 16).unwrap();
+        recorder.record(0, 0x0000, 16).unwrap();
+        recorder.record(3, 0x0020, 16).unwrap(); // Key 5 held from frame 3
+        recorder.record(5, 0x0000, 16).unwrap(); // and released in frame 5
+        recorder.finish().unwrap();
+
+        let mut chip8 = Chip8::new();
+        chip8.set_tickrate(4); // Replay ignores the current speed
+        chip8.replay_input(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut mem = Memory::new();
+        for (i, byte) in [0xF0, 0x0A, 0xF1, 0x07, 0x12, 0x04].into_iter().enumerate() {
+            mem.write_byte(PROGRAM_START + i as u16, byte); // LD V0, K; LD V1, DT; JP 0x204
+        }
+        chip8.dt = 0xff;
+        run_headless(&mut chip8, &mut mem, 100).unwrap();
+        assert_eq!(chip8.get_tickrate(), 16);
+        assert_eq!(chip8.v[0], 5);
+        assert_eq!(chip8.v[1], 0xff - 5); // Key was stored once released
+    }
+
+    #[test]
+    fn test_matches() {
+        let mut chip8 = Chip8::new();
+        let mut mem = Memory::splash();
+        run_headless(&mut chip8, &mut mem, 100).unwrap();
+        let hash = framebuffer_hash(&chip8);
+        assert!(matches(&chip8, &Expectation::Hash(hash)).unwrap());
+        assert!(!matches(&chip8, &Expectation::Hash(hash ^ 1)).unwrap());
+
+        let path = std::env::temp_dir().join("chip8_test_matches.png");
+        chip8.screenshot(&path).unwrap();
+        let matching = matches(&chip8, &Expectation::Image(path.clone())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matching);
+
+        let path = std::env::temp_dir().join("chip8_test_matches.txt");
+        save_screen(&chip8, &path).unwrap();
+        let matching = matches(&chip8, &Expectation::Text(path.clone())).unwrap();
+        chip8.reset(&mut mem);
+        let cleared = matches(&chip8, &Expectation::Text(path.clone())).unwrap();
+        std::fs::write(&path, "###").unwrap();
+        let invalid = matches(&chip8, &Expectation::Text(path.clone()));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matching && !cleared);
+        assert!(matches!(invalid, Err(Chip8Error::Load(LoadError::InvalidScreen(_)))));
+    }
+}
@@ -0,0 +1,1261 @@
+use super::*;
+    use std::collections::HashMap;
+    use super::quirks::MemoryIncrement;
+    use super::battery::SaveRam;
+    use super::storage::MemoryStorage;
+
+    fn setup_chip8_and_memory() -> (Chip8, Memory) {
+        let chip8 = Chip8::new();
+        let mem = Memory::new();
+        (chip8, mem)
+    }
+
+    // Collects the events other than instructions, as the debugger reports them
+    fn debugger_events(chip8: &mut Chip8) -> std::sync::Arc<std::sync::Mutex<Vec<Event>>> {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        chip8.on_event(move |event| if !matches!(event, Event::Instruction { .. }) { seen.lock().unwrap().push(event.clone()) });
+        events
+    }
+
+    #[test]
+    fn test_new_chip8() {
+        let chip8 = Chip8::new();
+        assert_eq!(chip8.v, [0x00; NUM_REGISTERS]);
+        assert_eq!(chip8.idx, 0x0000);
+        assert_eq!(chip8.dt, 0);
+        assert_eq!(chip8.st, 0);
+        assert_eq!(chip8.pc, PROGRAM_START);
+        assert_eq!(chip8.sp, 0x00);
+        assert_eq!(chip8.stack, [0x0000; STACK_DEPTH]);
+    }
+
+    #[test]
+    fn test_chip8_with_bindings() {
+        let mut chip8 = Chip8::new();
+        let mut bindings = HashMap::new();
+        bindings.insert(0x2, Key::W);
+        bindings.insert(0x4, Key::A);
+        bindings.insert(0x6, Key::D);
+        bindings.insert(0x8, Key::S);
+        chip8.with_bindings(bindings);
+        assert_eq!(chip8.keyboard.get_by_key(&Key::W), Some(&0x2));
+        assert_eq!(chip8.keyboard.get_by_key(&Key::S), Some(&0x8));
+        assert_eq!(chip8.keyboard.get_by_key(&Key::Key1), None);
+    }
+
+    #[test]
+    fn test_chip8_insert_binding() {
+        let mut chip8 = Chip8::new();
+        chip8.insert_binding(0x2, Key::W);
+        assert_eq!(chip8.keyboard.get_by_key(&Key::W), Some(&0x2));
+    }
+
+    #[test]
+    fn test_chip8_set_scale() {
+        let mut chip8 = Chip8::new();
+        chip8.set_scale(Scale::X2);
+        assert_eq!(chip8.display.get_scale() as u16, Scale::X2 as u16);
+    }
+
+    #[test]
+    fn test_chip8_set_fullscreen() {
+        let mut chip8 = Chip8::new();
+        chip8.set_fullscreen(true);
+        assert!(chip8.display.is_fullscreen());
+        chip8.set_fullscreen(false);
+        assert!(!chip8.display.is_fullscreen());
+    }
+
+    #[test]
+    fn test_chip8_run() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        mem.write_byte(PROGRAM_START, 0x60); 
+        mem.write_byte(PROGRAM_START + 1, 0x00); 
+        mem.write_byte(PROGRAM_START + 2, 0xFF); 
+        let result = chip8.run(&mut mem);
+        assert!(result.is_err()); 
+        assert_eq!(chip8.v[0], 0x00);
+    }
+
+    #[test]
+    fn test_chip8_update() {
+        let mut chip8 = Chip8::new();
+        chip8.dt = 5; 
+        chip8.update_timers();
+        assert_eq!(chip8.dt, 4);
+    }
+
+    #[test]
+    fn test_chip8_splash() {
+        let mut chip8 = Chip8::new();
+        let mut mem = Memory::splash();
+        for _ in 0..64 {
+            let instruction = mem.get_instruction(chip8.pc);
+            chip8.pc += 2;
+            chip8.execute(instruction, &mut mem).unwrap();
+        }
+        assert_eq!(chip8.pc, 0x218); // Idling after drawing
+        assert!(chip8.display.get_grid().iter().any(|&row| row != 0));
+    }
+
+    #[test]
+    fn test_chip8_tutorial() {
+        let mut chip8 = Chip8::new();
+        let mut mem = Memory::from_bytes(&splash::TUTORIAL).unwrap();
+        chip8.playback = Some(InputRecording { complete: true, speeds: vec![(0, 16)], events: vec![(0, 1 << 0x7)] });
+        testing::run_headless(&mut chip8, &mut mem, 200).unwrap();
+        assert_eq!(chip8.v[4], 0x7); // Digit of the held key is shown
+
+        let expected = Memory::new();
+        let mut digit = [0; DISPLAY_HEIGHT];
+        let glyph = (0..5).map(|i| expected.read_byte(expected.font_address(0x7) + i)).collect::<Vec<u8>>();
+        draw::Wrap.draw(&mut digit, 30, 13, &glyph);
+        assert_eq!(chip8.display.to_ascii(), draw::to_ascii(&digit));
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut chip8 = Chip8::new();
+        let mut mem = Memory::splash();
+        testing::run_headless(&mut chip8, &mut mem, 64).unwrap();
+        mem.write_byte(0x300, 0xff);
+        chip8.reset(&mut mem);
+        assert_eq!(chip8.pc, PROGRAM_START);
+        assert_eq!(chip8.v, [0x00; NUM_REGISTERS]);
+        assert!(chip8.display.get_grid().iter().all(|&row| row == 0));
+        assert_eq!(mem.read_byte(0x300), 0xff);
+
+        chip8.power_cycle(&mut mem);
+        assert_eq!(mem.read_byte(0x300), 0x00);
+        assert_eq!(mem.get_instruction(PROGRAM_START), 0x00E0);
+    }
+
+    #[test]
+    fn test_save_state() {
+        let mut chip8 = Chip8::new();
+        let mut mem = Memory::splash();
+        assert!(!chip8.quick_load(&mut mem)); // Nothing saved yet
+        testing::run_headless(&mut chip8, &mut mem, 64).unwrap();
+        chip8.quick_save(&mem);
+        let saved = chip8.save_state(&mem);
+        let state = chip8.state();
+        assert_eq!(saved.machine(), &state);
+
+        mem.write_byte(0x300, 0xff);
+        chip8.reset(&mut mem);
+        assert!(chip8.quick_load(&mut mem));
+        assert_eq!(chip8.state(), state);
+        assert_eq!(mem.read_byte(0x300), 0x00);
+
+        // A state taken by the embedder goes back the same way, but not into memory of another size
+        chip8.reset(&mut mem);
+        assert!(chip8.restore_state(&mut mem, &saved));
+        assert_eq!(chip8.state(), state);
+        assert!(!chip8.restore_state(&mut Memory::with_layout(MAX_MEMORY_SIZE, PROGRAM_START).unwrap(), &saved));
+    }
+
+    #[test]
+    fn test_save_ram_survives_power_cycle() {
+        let mut chip8 = Chip8::new();
+        chip8.set_storage(MemoryStorage::new());
+        chip8.set_save_ram(Some(SaveRam::parse("0x300-0x301").unwrap()));
+        let mut mem = Memory::splash();
+        mem.write_byte(0x300, 0x42);
+        mem.write_byte(0x302, 0x42); // Outside the region
+        chip8.power_cycle(&mut mem);
+        assert_eq!(mem.read_byte(0x300), 0x42);
+        assert_eq!(mem.read_byte(0x302), 0x00);
+
+        mem.write_byte(0x301, 0x43);
+        chip8.reset(&mut mem);
+        assert_eq!((mem.read_byte(0x300), mem.read_byte(0x301)), (0x42, 0x43));
+    }
+
+    #[test]
+    fn test_save_ram_restored_on_load() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        chip8.set_storage(MemoryStorage::new());
+        chip8.set_save_ram(Some(SaveRam::parse("0x300-0x301").unwrap()));
+        chip8.load_rom_bytes(&mut mem, &[0x12, 0x00]).unwrap();
+        mem.write_byte(0x300, 0x42);
+
+        // Switching away saves the memory of the first program, switching back restores it
+        chip8.load_rom_bytes(&mut mem, &[0x12, 0x02]).unwrap();
+        mem.write_byte(0x300, 0x00);
+        chip8.load_rom_bytes(&mut mem, &[0x12, 0x00]).unwrap();
+        assert_eq!(mem.read_byte(0x300), 0x42);
+    }
+
+    #[test]
+    fn test_next_draw() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        mem.load_from_bytes(&[0x60, 0x48, 0x61, 0x05, 0xD0, 0x13]).unwrap(); // LD V0, 72; LD V1, 5; DRW V0, V1, 3
+        assert_eq!(chip8.next_draw(&mem), None);
+        testing::run_headless(&mut chip8, &mut mem, 2).unwrap();
+        assert_eq!(chip8.next_draw(&mem), Some((8, 5, 3)));
+    }
+
+    #[test]
+    fn test_load_rom_bytes() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        chip8.load_rom_bytes(&mut mem, &[0x60, 0x05, 0xA3, 0x00, 0xF0, 0x15, 0x22, 0x08, 0x00, 0xEE]).unwrap();
+        testing::run_headless(&mut chip8, &mut mem, 4).unwrap();
+        assert_eq!((chip8.v[0], chip8.idx, chip8.dt, chip8.sp), (0x05, 0x300, 0x05, 1));
+
+        // A shorter program starts afresh, with none of the last one's code or state left
+        chip8.load_rom_bytes(&mut mem, &[0x00, 0xE0]).unwrap();
+        assert_eq!(chip8.pc, PROGRAM_START);
+        assert_eq!(mem.get_instruction(PROGRAM_START), 0x00E0);
+        assert!((PROGRAM_START + 2..PROGRAM_START + 10).all(|addr| mem.read_byte(addr) == 0));
+        assert_eq!((chip8.v[0], chip8.idx, chip8.dt, chip8.sp), (0, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_title() {
+        let (mut chip8, _) = setup_chip8_and_memory();
+        assert_eq!(chip8.title(), WINDOW_NAME);
+
+        chip8.rom_name = Some("pong".to_string());
+        chip8.paused = true;
+        chip8.set_tickrate(DEFAULT_TICKRATE * 2);
+        assert_eq!(chip8.title(), format!("pong - {WINDOW_NAME} - Paused - 2x"));
+
+        chip8.set_title("Player 1");
+        assert_eq!(chip8.title(), "Player 1");
+    }
+
+    #[test]
+    fn test_audio_disabled() {
+        let (mut chip8, _) = setup_chip8_and_memory();
+        assert!(!chip8.capabilities().supports(Feature::Audio));
+        chip8.set_audio_enabled(false).unwrap();
+        chip8.st = 10;
+        chip8.update_timers(); // Runs silently without a device
+        assert_eq!(chip8.st, 9);
+
+        chip8.set_muted(true);
+        assert_eq!(chip8.title(), format!("{WINDOW_NAME} - Muted"));
+    }
+
+    #[test]
+    fn test_watchpoint_pauses() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        mem.load_from_bytes(&[0xA3, 0x00, 0x60, 0x01, 0xF0, 0x55]).unwrap(); // LD I, 0x300; LD V0, 1; LD [I], V0
+        mem.add_watchpoint(watchpoint::Watchpoint::parse("0x300:w").unwrap());
+        let events = debugger_events(&mut chip8);
+        chip8.step(&mut mem).unwrap();
+        chip8.step(&mut mem).unwrap();
+        assert!(!chip8.paused);
+        chip8.step(&mut mem).unwrap();
+        assert!(chip8.paused);
+        assert_eq!(mem.take_watch_hit(), None); // Taken by the step
+        assert_eq!(*events.lock().unwrap(), vec![Event::Watchpoint { addr: 0x300, access: watchpoint::Access::Write, pc: 0x204, opcode: 0xF055 }]);
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn test_serve_api() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        let load = ApiRequest::Load(vec![0x60, 0x2A, 0x12, 0x02]); // LD V0, 0x2A
+        assert!(matches!(chip8.serve_api(&load, &mut mem).unwrap(), ApiResponse::Done));
+        assert!(matches!(chip8.serve_api(&ApiRequest::Step, &mut mem).unwrap(), ApiResponse::Error(409, _)));
+
+        chip8.serve_api(&ApiRequest::Pause, &mut mem).unwrap();
+        chip8.serve_api(&ApiRequest::Step, &mut mem).unwrap();
+        let ApiResponse::Json(state) = chip8.serve_api(&ApiRequest::State, &mut mem).unwrap() else {
+            panic!("expected the state as JSON");
+        };
+        let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+        assert_eq!(state["paused"], true);
+        assert_eq!(state["machine"]["v"][0], 0x2A);
+        assert_eq!(state["machine"]["pc"], 0x202);
+
+        let too_large = ApiRequest::Load(vec![0; MEMORY_SIZE]);
+        assert!(matches!(chip8.serve_api(&too_large, &mut mem).unwrap(), ApiResponse::Error(400, _)));
+        assert!(matches!(chip8.serve_api(&ApiRequest::Screen, &mut mem).unwrap(), ApiResponse::Png(png) if png.starts_with(b"\x89PNG")));
+    }
+
+    #[test]
+    fn test_self_modify_breakpoint() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        // LD I, 0x300; LD [I], V0; LD I, 0x200; LD [I], V0 overwriting the first instruction
+        mem.load_from_bytes(&[0xA3, 0x00, 0xF0, 0x55, 0xA2, 0x00, 0xF0, 0x55]).unwrap();
+        chip8.add_breakpoint(Breakpoint::parse("smc").unwrap());
+        chip8.set_self_modify_warnings(true);
+        let events = debugger_events(&mut chip8);
+        for _ in 0..3 {
+            chip8.step(&mut mem).unwrap();
+        }
+        assert!(!chip8.paused);
+        chip8.step(&mut mem).unwrap();
+        assert!(chip8.paused);
+        assert_eq!(mem.take_code_write(), None); // Taken by the step
+        assert_eq!(*events.lock().unwrap(), vec![
+            Event::SelfModify { addr: 0x200, pc: 0x206, opcode: 0xF055 },
+            Event::Breakpoint { breakpoint: "smc".to_string(), pc: 0x206, instruction: "LD [I], V0".to_string(), write: Some(0x200) },
+        ]);
+    }
+
+    #[test]
+    fn test_reserved_write() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        // LD I, 0x1FE; LD [I], V1 writing 0x1FE and 0x1FF
+        mem.load_from_bytes(&[0xA1, 0xFE, 0xF1, 0x55, 0xA1, 0xFE, 0xF1, 0x55]).unwrap();
+        mem.set_protection(Protection::Warn);
+        let events = debugger_events(&mut chip8);
+        for _ in 0..2 {
+            chip8.step(&mut mem).unwrap();
+        }
+        assert_eq!(*events.lock().unwrap(), vec![Event::ReservedWrite { addr: 0x1FE, pc: 0x202, opcode: 0xF155 }]);
+        mem.set_protection(Protection::Strict);
+        chip8.step(&mut mem).unwrap();
+        let err = chip8.step(&mut mem).unwrap_err();
+        assert!(matches!(err, Chip8Error::Exec(ExecError::ReservedWrite(0x1FE, 0x206))));
+    }
+
+    #[test]
+    fn test_stats() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        // CALL 0x204; draws the font's 0 twice at 0, 0 and loops
+        mem.load_from_bytes(&[0x22, 0x04, 0x00, 0x00, 0xD0, 0x05, 0xD0, 0x05, 0x12, 0x04]).unwrap();
+        chip8.set_tickrate(3);
+        testing::run_headless(&mut chip8, &mut mem, 3 * 60).unwrap();
+        let stats = chip8.stats();
+        assert_eq!(stats.instructions, 180);
+        assert_eq!((stats.draws, stats.collisions), (120, 60)); // Every second draw erases the first
+        assert_eq!((stats.key_waits, stats.max_stack_depth), (0, 1));
+
+        chip8.set_stats_shown(true);
+        assert!(chip8.title().ends_with(" - 180 IPS, 120 draws/s, 60 collisions/s, 0 key waits/s, stack depth 1"));
+    }
+
+    #[test]
+    fn test_breakpoint_pauses() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        mem.load_from_bytes(&[0x60, 0x01, 0x60, 0x02, 0x12, 0x00]).unwrap(); // LD V0, 1; LD V0, 2; JP 0x200
+        chip8.add_breakpoint(Breakpoint::parse("0x202 if V0 == 1").unwrap());
+        let events = debugger_events(&mut chip8);
+        assert!(!chip8.check_breakpoints(&mem));
+        chip8.step(&mut mem).unwrap();
+        assert!(chip8.check_breakpoints(&mem));
+        assert!(chip8.paused);
+        assert!(matches!(&events.lock().unwrap()[..], [Event::Breakpoint { pc: 0x202, instruction, write: None, .. }] if instruction == "LD V0, 0x02"));
+
+        chip8.paused = false;
+        assert!(!chip8.check_breakpoints(&mem)); // Resuming runs the instruction
+        chip8.step(&mut mem).unwrap();
+        chip8.step(&mut mem).unwrap();
+        chip8.step(&mut mem).unwrap();
+        assert!(chip8.check_breakpoints(&mem)); // Hit again on the next pass
+
+        chip8.v[0] = 2;
+        chip8.resumed_at = None;
+        assert!(!chip8.check_breakpoints(&mem));
+    }
+
+    #[test]
+    fn test_first_breakpoint() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        mem.load_from_bytes(&[0x60, 0x01, 0xF0, 0x18, 0x12, 0x00]).unwrap(); // LD V0, 1; LD ST, V0; JP 0x200
+        chip8.add_breakpoint(Breakpoint::parse("first sound").unwrap());
+        chip8.step(&mut mem).unwrap();
+        assert!(chip8.check_breakpoints(&mem));
+        assert!(chip8.breakpoints.is_empty());
+
+        chip8.paused = false;
+        for _ in 0..3 {
+            chip8.step(&mut mem).unwrap();
+        }
+        assert_eq!(chip8.pc, 0x202);
+        assert!(!chip8.check_breakpoints(&mem)); // Not again on the next pass
+    }
+
+    #[test]
+    fn test_step_frame() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        mem.load_from_bytes(&[0x60, 0x00, 0x70, 0x01, 0x12, 0x02]).unwrap(); // LD V0, 0; ADD V0, 1; JP 0x202
+        chip8.set_tickrate(10);
+        chip8.dt = 5;
+        assert_eq!(chip8.step_frame(&mut mem, 0).unwrap(), 0);
+        assert_eq!((chip8.instructions, chip8.v[0], chip8.dt, chip8.frame), (10, 5, 4, 1));
+
+        // A breakpoint stops the frame early, the next step finishes it
+        chip8.add_breakpoint(Breakpoint::parse("0x202 if V0 == 7").unwrap());
+        assert_eq!(chip8.step_frame(&mut mem, 0).unwrap(), 5);
+        assert_eq!((chip8.pc, chip8.v[0], chip8.dt), (0x202, 7, 4));
+        assert_eq!(chip8.step_frame(&mut mem, 5).unwrap(), 0);
+        assert_eq!((chip8.instructions, chip8.v[0], chip8.dt), (20, 10, 3));
+    }
+
+    #[test]
+    fn test_run_frame() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        chip8.load_rom_bytes(&mut mem, &[0x60, 0x00, 0x70, 0x01, 0x12, 0x02]).unwrap(); // LD V0, 0; ADD V0, 1; JP 0x202
+        chip8.set_tickrate(10);
+        chip8.dt = 5;
+        chip8.press_key(0x3);
+        chip8.run_frame(&mut mem).unwrap();
+        assert_eq!((chip8.instructions, chip8.v[0], chip8.dt, chip8.frame), (10, 5, 4, 1));
+        assert!(chip8.is_key_down(0x3)); // Latched for the next frame
+    }
+
+    #[test]
+    fn test_state() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        mem.load_from_bytes(&[0x63, 0x2A, 0xA0, 0x00, 0xD0, 0x05, 0x22, 0x00]).unwrap(); // LD V3; LD I, 0 (font "0"); DRW; CALL 0x200
+        for _ in 0..4 {
+            chip8.step(&mut mem).unwrap();
+        }
+        let state = chip8.state();
+        assert_eq!((state.v[3], state.i, state.pc, state.sp), (0x2A, 0x000, 0x200, 1));
+        assert_eq!(state.stack[0], 0x208);
+        assert!(state.is_lit(0, 0));
+        assert!(!state.is_lit(1, 1)); // Inside the "0"
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<MachineState>(&json).unwrap(), state);
+    }
+
+    fn assert_send<T: Send>() {}
+
+    #[test]
+    fn test_send() {
+        // Emulators can be built on one thread and run on another
+        assert_send::<Chip8>();
+    }
+
+    #[test]
+    fn test_observers() {
+        use std::sync::{Arc, Mutex};
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        let sounds = Arc::new(Mutex::new(Vec::new()));
+        let seen = frames.clone();
+        chip8.on_frame(move |frame| seen.lock().unwrap().push(frame.is_lit(0, 0)));
+        let heard = sounds.clone();
+        chip8.on_sound(move |sounding| heard.lock().unwrap().push(sounding));
+
+        chip8.st = 2;
+        chip8.end_frame(&mut mem).unwrap();
+        chip8.display.draw(0, 0, [0x80].into_iter());
+        chip8.end_frame(&mut mem).unwrap();
+        chip8.end_frame(&mut mem).unwrap();
+        assert_eq!(*frames.lock().unwrap(), vec![false, true, true]);
+        assert_eq!(*sounds.lock().unwrap(), vec![true, false]); // Only changes are reported
+    }
+
+    #[test]
+    fn test_switch_bank() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        let mut patches = Patches::new();
+        patches.add(patches::Patch { addr: 0x300, value: 0x07, freeze: false });
+        chip8.add_bank(RomBank { name: "first".to_string(), rom: vec![0x60, 0x01, 0x61, 0x02], patches: Patches::new() });
+        chip8.add_bank(RomBank { name: "second".to_string(), rom: vec![0x62, 0x03], patches });
+
+        chip8.switch_bank(&mut mem, 0).unwrap();
+        chip8.v[0] = 0x55;
+        mem.write_byte(0x400, 0xFF);
+        chip8.switch_bank(&mut mem, 1).unwrap();
+        assert_eq!(chip8.v[0], 0); // Reset along with the memory
+        assert_eq!(mem.read_byte(0x400), 0);
+        assert_eq!(mem.get_instruction(PROGRAM_START), 0x6203);
+        assert_eq!(mem.get_instruction(PROGRAM_START + 2), 0); // Nothing left of the longer first program
+        assert_eq!(mem.read_byte(0x300), 0x07);
+        assert_eq!(chip8.rom_name.as_deref(), Some("second"));
+        assert!(chip8.switch_bank(&mut mem, 2).is_err());
+    }
+
+    #[test]
+    fn test_patches() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        mem.load_from_bytes(&[0x12, 0x00]).unwrap();
+        chip8.set_patches(Patches::parse("freeze 300:05\n200:13", MEMORY_SIZE).unwrap());
+        chip8.reset(&mut mem);
+        assert_eq!(mem.get_instruction(PROGRAM_START), 0x1300);
+
+        mem.write_byte(0x300, 0x04);
+        chip8.end_frame(&mut mem).unwrap();
+        assert_eq!(mem.read_byte(0x300), 0x05);
+        chip8.power_cycle(&mut mem);
+        assert_eq!(mem.read_byte(0x300), 0x05);
+    }
+
+    #[test]
+    fn test_serve_remote() {
+        let (mut chip8, mut mem) = setup_chip8_and_memory();
+        mem.load_from_bytes(&[0x60, 0x01, 0xA3, 0x00]).unwrap(); // LD V0, 1; LD I, 0x300
+        let mut serve = |chip8: &mut Chip8, request| chip8.serve_remote(request, &mut mem).unwrap();
+
+        assert_eq!(serve(&mut chip8, Request::Step), Some("S05".to_string()));
+        let registers = serve(&mut chip8, Request::ReadRegisters).unwrap();
+        assert_eq!(&registers[..2], "01");
+        assert_eq!(&registers[32..], "00000202000000"); // I, PC, SP, DT and ST
+
+        assert_eq!(serve(&mut chip8, Request::ReadMemory(0x200, 2)), Some("6001".to_string()));
+        assert_eq!(serve(&mut chip8, Request::WriteMemory(0x300, vec![0xAB])), Some("OK".to_string()));
+        assert_eq!(serve(&mut chip8, Request::WriteMemory(0xFFF, vec![0xAB, 0xCD])), Some("E01".to_string()));
+        assert_eq!(serve(&mut chip8, Request::ReadMemory(0x300, 1)), Some("ab".to_string()));
+
+        assert_eq!(serve(&mut chip8, Request::AddBreakpoint(0x202)), Some("OK".to_string()));
+        assert_eq!(chip8.breakpoints, vec![Breakpoint::at(0x202)]);
+        assert_eq!(serve(&mut chip8, Request::RemoveBreakpoint(0x202)), Some("OK".to_string()));
+        assert!(chip8.breakpoints.is_empty());
+
+        chip8.paused = true;
+        assert_eq!(serve(&mut chip8, Request::Continue), None); // Replied to when the program stops
+        assert!(!chip8.paused);
+        assert_eq!(serve(&mut chip8, Request::Interrupt), None);
+        assert!(chip8.paused);
+        assert_eq!(serve(&mut chip8, Request::Unsupported), Some(String::new()));
+    }
+
+    // 8xy4-8xyE checked against a model of the arithmetic for any registers and values,
+    // including Vx or Vy being VF
+    mod arithmetic_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        // Result and flag of the operation on the values of Vx and Vy
+        fn model(op: u8, x: u8, y: u8, shift_quirk: bool) -> (u8, u8) {
+            let shifted = if shift_quirk { x } else { y };
+            match op {
+                0x4 => ((x as u16 + y as u16) as u8, (x as u16 + y as u16 > 0xff) as u8),
+                0x5 => ((x as i16 - y as i16) as u8, (x >= y) as u8),
+                0x6 => (shifted / 2, shifted % 2),
+                0x7 => ((y as i16 - x as i16) as u8, (y >= x) as u8),
+                0xe => ((shifted as u16 * 2) as u8, (shifted >= 0x80) as u8),
+                _ => unreachable!(),
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn test_8xyn_matches_model(
+                op in prop::sample::select(vec![0x4u8, 0x5, 0x6, 0x7, 0xe]),
+                vx in 0..NUM_REGISTERS,
+                vy in 0..NUM_REGISTERS,
+                v in any::<[u8; NUM_REGISTERS]>(),
+                shift in any::<bool>(),
+            ) {
+                let (mut chip8, mut mem) = setup_chip8_and_memory();
+                chip8.set_quirks(Quirks { shift, ..Quirks::default() });
+                chip8.v = v;
+                chip8.execute(0x8000 | (vx as u16) << 8 | (vy as u16) << 4 | op as u16, &mut mem).unwrap();
+
+                let (result, flag) = model(op, v[vx], v[vy], shift);
+                let mut expected = v;
+                expected[vx] = result;
+                expected[FLAG_REGISTER] = flag; // Set last, so it wins when Vx is VF
+                prop_assert_eq!(chip8.v, expected);
+            }
+        }
+    }
+
+    mod opcode_tests {
+        use super::*;
+
+        #[test]
+        fn test_opcode() {
+            let opcode = OpCode::decode(0x1234);
+            assert_eq!(opcode.code, 0x1234);
+            assert_eq!(opcode.vx(), 0x2);
+            assert_eq!(opcode.vy(), 0x3);
+            assert_eq!(opcode.nibble(), 0x4);
+            assert_eq!(opcode.byte(), 0x34);
+            assert_eq!(opcode.addr(), 0x234);
+        }
+
+        #[test]
+        fn test_chip8_execute_00e0() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            let result = chip8.execute(0x00e0, &mut mem);
+            assert!(result.is_ok());
+            let cleared_display = [0; DISPLAY_HEIGHT];
+            assert_eq!(*chip8.display.get_grid(), cleared_display);
+        }
+
+        #[test]
+        fn test_chip8_execute_megachip() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            assert!(chip8.execute(0x0302, &mut mem).is_err()); // Unknown outside the Megachip mode
+            chip8.execute(0x0011, &mut mem).unwrap();
+            assert!(chip8.display.is_megachip());
+
+            // One color at 0x300, a 2x1 sprite using it at 0x304
+            for (i, byte) in [0xff, 0x12, 0x34, 0x56, 0x01, 0x00].into_iter().enumerate() {
+                mem.write_byte(0x300 + i as u16, byte);
+            }
+            chip8.idx = 0x300;
+            for op_code in [0x0201, 0x0302, 0x0401, 0x0901] {
+                chip8.execute(op_code, &mut mem).unwrap();
+            }
+            chip8.idx = 0x304;
+            chip8.v[0] = 10;
+            chip8.execute(0xD000, &mut mem).unwrap();
+            assert_eq!(chip8.v[FLAG_REGISTER], 0);
+            chip8.execute(0xD000, &mut mem).unwrap();
+            assert_eq!(chip8.v[FLAG_REGISTER], 1); // Drawn over the collision color
+            let screen = chip8.display.get_megachip().unwrap();
+            assert_eq!((screen.pixel(10, 10), screen.pixel(11, 10)), (1, 0));
+
+            chip8.execute(0x0010, &mut mem).unwrap();
+            assert!(!chip8.display.is_megachip());
+        }
+
+        #[test]
+        fn test_chip8_execute_megachip_ldhi() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.execute(0x0011, &mut mem).unwrap();
+            mem.write_byte(0x202, 0x0A);
+            mem.write_byte(0x203, 0xBC);
+            chip8.pc = 0x202; // As if fetched from 0x200
+            chip8.execute(0x0100, &mut mem).unwrap();
+            assert_eq!((chip8.idx, chip8.pc), (0x0ABC, 0x204));
+
+            // Beyond the 4K of memory
+            chip8.pc = 0x202;
+            assert!(matches!(chip8.execute(0x0101, &mut mem), Err(Chip8Error::Exec(ExecError::AddressOutOfRange(0x10ABC, 0x200)))));
+        }
+
+        #[test]
+        fn test_chip8_execute_00ee() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.sp = 1;
+            chip8.stack[0] = 0x0200;
+            let result = chip8.execute(0x00ee, &mut mem);
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, 0x0200);
+            assert_eq!(chip8.sp, 0);
+        }
+
+        #[test]
+        fn test_chip8_execute_00ee_underflow() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            assert!(matches!(chip8.execute(0x00ee, &mut mem), Err(Chip8Error::Exec(ExecError::StackUnderflow(_)))));
+            assert_eq!(chip8.sp, 0);
+        }
+
+        #[test]
+        fn test_chip8_execute_1nnn() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            let result = chip8.execute(0x1234, &mut mem);
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, 0x0234);
+        }
+
+        #[test]
+        fn test_chip8_execute_2nnn() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            let result = chip8.execute(0x2345, &mut mem);
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, 0x0345);
+            assert_eq!(chip8.sp, 1);
+            assert_eq!(chip8.stack[0], 0x0200);
+        }
+
+        #[test]
+        fn test_chip8_execute_2nnn_overflow() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            for _ in 0..STACK_DEPTH {
+                chip8.execute(0x2345, &mut mem).unwrap();
+            }
+            assert!(matches!(chip8.execute(0x2345, &mut mem), Err(Chip8Error::Exec(ExecError::StackOverflow(_)))));
+            assert_eq!(chip8.call_depth(), STACK_DEPTH);
+        }
+
+        #[test]
+        fn test_chip8_nested_subroutines() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            // Every level calls from its own address, all 16 slots are used
+            for depth in 0..STACK_DEPTH as u16 {
+                chip8.pc = 0x202 + depth * 4;
+                chip8.execute(0x2400 + depth, &mut mem).unwrap();
+                assert_eq!(chip8.pc, 0x400 + depth);
+                assert_eq!(chip8.call_depth(), depth as usize + 1);
+            }
+            for depth in (0..STACK_DEPTH as u16).rev() {
+                chip8.execute(0x00ee, &mut mem).unwrap();
+                assert_eq!(chip8.pc, 0x202 + depth * 4);
+                assert_eq!(chip8.call_depth(), depth as usize);
+            }
+            assert!(chip8.execute(0x00ee, &mut mem).is_err());
+        }
+
+        #[test]
+        fn test_chip8_call_stack() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.execute(0x2300, &mut mem).unwrap();
+            chip8.execute(0x2400, &mut mem).unwrap();
+            assert_eq!(chip8.call_stack(), [0x300, 0x400]);
+            chip8.execute(0x00ee, &mut mem).unwrap();
+            chip8.execute(0x2500, &mut mem).unwrap();
+            assert_eq!(chip8.call_stack(), [0x300, 0x500]);
+            chip8.reset(&mut mem);
+            assert!(chip8.call_stack().is_empty());
+        }
+
+        #[test]
+        fn test_chip8_execute_3xkk() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x01;
+            let result = chip8.execute(0x3001, &mut mem);
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, PROGRAM_START + 2);
+        }
+
+        #[test]
+        fn test_chip8_execute_4xkk() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x01;
+            let result = chip8.execute(0x4002, &mut mem);
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, PROGRAM_START + 2);
+        }
+
+        #[test]
+        fn test_chip8_execute_5xy0() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x01;
+            chip8.v[1] = 0x01;
+            let result = chip8.execute(0x5010, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, PROGRAM_START + 2); 
+        }
+
+        #[test]
+        fn test_chip8_execute_6xkk() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            let result = chip8.execute(0x6001, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_7xkk() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            let result = chip8.execute(0x7001, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy0() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x0F;
+            chip8.v[1] = 0x01;
+            let result = chip8.execute(0x8010, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], chip8.v[1]); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy1() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x0F;
+            chip8.v[1] = 0x01;
+            let result = chip8.execute(0x8011, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x0F | 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy2() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x0F;
+            chip8.v[1] = 0x01;
+            let result = chip8.execute(0x8012, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x0F & 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy3() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x0F;
+            chip8.v[1] = 0x01;
+            let result = chip8.execute(0x8013, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x0F ^ 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy4_no_carry() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x0F;
+            chip8.v[1] = 0x01;
+            let result = chip8.execute(0x8014, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x0F + 0x01); 
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x00); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy4_carry() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0xFF;
+            chip8.v[1] = 0x01;
+            let result = chip8.execute(0x8014, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0xFFu8.wrapping_add(0x01)); 
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy5_no_borrow() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x0F;
+            chip8.v[1] = 0x01;
+            let result = chip8.execute(0x8015, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x0F - 0x01); 
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy5_borrow() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x00;
+            chip8.v[1] = 0x01;
+            let result = chip8.execute(0x8015, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x00u8.wrapping_sub(0x01)); 
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x00); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy6_even() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x20;
+            let result = chip8.execute(0x8006, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x10); 
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x00); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy6_odd() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x21;
+            let result = chip8.execute(0x8006, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x10); 
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy6_without_shift_quirk() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.set_quirks(Quirks { shift: false, ..Quirks::default() });
+            chip8.v[0] = 0x20;
+            chip8.v[1] = 0x05;
+            let result = chip8.execute(0x8016, &mut mem);
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x02); // Vy shifted into Vx
+            assert_eq!(chip8.v[1], 0x05);
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x01);
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy7_no_borrow() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x01;
+            chip8.v[1] = 0x0F;
+            let result = chip8.execute(0x8017, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x0F - 0x01); 
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xy7_borrow() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x0F;
+            chip8.v[1] = 0x01;
+            let result = chip8.execute(0x8017, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x01u8.wrapping_sub(0x0F)); 
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x00); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xye_no_carry() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x20;
+            let result = chip8.execute(0x800e, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x40); 
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x00); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xye_carry() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x81;
+            let result = chip8.execute(0x800e, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x02); 
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_8xye_without_shift_quirk() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.set_quirks(Quirks { shift: false, ..Quirks::default() });
+            chip8.v[0] = 0x01;
+            chip8.v[1] = 0x81;
+            let result = chip8.execute(0x801e, &mut mem);
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x02); // Vy shifted into Vx
+            assert_eq!(chip8.v[1], 0x81);
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x01);
+        }
+
+        #[test]
+        fn test_chip8_execute_9xy0() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x01;
+            chip8.v[1] = 0x02;
+            let result = chip8.execute(0x9010, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, PROGRAM_START + 2); 
+        }
+
+        #[test]
+        fn test_chip8_execute_annn() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            let result = chip8.execute(0xA123, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.idx, 0x0123); 
+        }
+
+        #[test]
+        fn test_chip8_execute_bnnn() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x01;
+            let result = chip8.execute(0xB123, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, 0x0123 + 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_bxnn_with_jump_quirk() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.set_quirks(Quirks { jump: true, ..Quirks::default() });
+            chip8.v[0] = 0x01;
+            chip8.v[1] = 0x02;
+            let result = chip8.execute(0xB123, &mut mem);
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, 0x0123 + 0x02); // Offset by V1 instead of V0
+        }
+
+        #[test]
+        fn test_chip8_execute_cxkk() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            let result = chip8.execute(0xC0FF, &mut mem); 
+            assert!(result.is_ok());
+            assert_ne!(chip8.v[0], 0x00); 
+        }
+
+        #[test]
+        fn test_chip8_execute_dxyn_no_collision() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            let result = chip8.execute(0xD005, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x00); 
+        }
+
+        #[test]
+        fn test_chip8_execute_dxyn_collision() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.display.draw(0, 0, (0..5).map(|_| 0xFF));
+            let result = chip8.execute(0xD005, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[FLAG_REGISTER], 0x01); 
+        }
+
+        #[test]
+        fn test_chip8_execute_ex9e() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x01;
+            let _ = chip8.display.init();
+            let result = chip8.execute(0xE09E, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, PROGRAM_START); 
+        }
+
+        #[test]
+        fn test_chip8_execute_exa1() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x01;
+            let _ = chip8.display.init();
+            let result = chip8.execute(0xE0A1, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.pc, PROGRAM_START + 2); 
+        }
+
+        #[test]
+        fn test_chip8_execute_fx07() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.dt = 0x05;
+            let result = chip8.execute(0xF007, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x05); 
+        }
+
+        #[test]
+        fn test_chip8_execute_fx0a() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.pc += 2;
+            let result = chip8.execute(0xF30A, &mut mem);
+            assert!(result.is_ok());
+            assert_eq!(chip8.cpu_state, CpuState::WaitingForKey(3));
+            assert_eq!(chip8.pc, PROGRAM_START); // Stays on the instruction
+
+            // Steps only check the keys until one is pressed and released
+            chip8.step(&mut mem).unwrap();
+            assert_eq!((chip8.pc, chip8.instructions), (PROGRAM_START, 0));
+        }
+
+        #[test]
+        fn test_press_key() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x0B;
+            chip8.press_key(0x0B);
+            chip8.press_key(0x10); // Not a key
+            chip8.execute(0xE09E, &mut mem).unwrap();
+            assert_eq!(chip8.pc, PROGRAM_START); // Not until the next frame
+            chip8.latch_input().unwrap();
+            chip8.execute(0xE09E, &mut mem).unwrap();
+            assert_eq!(chip8.pc, PROGRAM_START + 2);
+            assert_eq!(chip8.keys, 1 << 0x0B);
+
+            chip8.execute(0xF30A, &mut mem).unwrap();
+            chip8.step(&mut mem).unwrap();
+            assert_eq!(chip8.cpu_state, CpuState::WaitingForRelease(3, 0x0B));
+            chip8.release_key(0x0B);
+            chip8.latch_input().unwrap();
+            chip8.step(&mut mem).unwrap();
+            assert_eq!(chip8.cpu_state, CpuState::WaitingForRelease(3, 0x0B)); // Down until the frame ends
+            chip8.latch_input().unwrap();
+            chip8.step(&mut mem).unwrap();
+            assert_eq!((chip8.v[3], chip8.cpu_state), (0x0B, CpuState::Running));
+        }
+
+        #[test]
+        fn test_set_seed() {
+            let random = |seed| {
+                let (mut chip8, mut mem) = setup_chip8_and_memory();
+                chip8.set_seed(seed);
+                (0..NUM_REGISTERS).map(|n| {
+                    chip8.execute(0xC0FF | (n as u16) << 8, &mut mem).unwrap();
+                    chip8.v[n]
+                }).collect::<Vec<u8>>()
+            };
+            assert_eq!(random(1), random(1));
+            assert_ne!(random(1), random(2));
+        }
+
+        #[test]
+        fn test_record_events() {
+            let path = std::env::temp_dir().join("chip8_test_record_events.jsonl");
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.record_events(&path).unwrap();
+            // V0 = 5, delay timer = V0, draw 5 rows at V0, V0, return outside a subroutine
+            for (n, byte) in [0x60, 0x05, 0xF0, 0x15, 0xD0, 0x05, 0x00, 0xEE].into_iter().enumerate() {
+                mem.write_byte(PROGRAM_START + n as u16, byte);
+            }
+            chip8.press_key(0x0A);
+            chip8.latch_input().unwrap();
+            for _ in 0..3 {
+                chip8.step(&mut mem).unwrap();
+            }
+            assert!(chip8.step(&mut mem).is_err());
+            drop(chip8); // Writes out the log
+
+            let contents = std::fs::read_to_string(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            let events: Vec<&str> = contents.lines().collect();
+            assert_eq!(events, [
+                r#"{"frame":0,"event":"key","key":10,"pressed":true}"#,
+                r#"{"frame":0,"event":"instruction","pc":512,"opcode":24581}"#,
+                r#"{"frame":0,"event":"instruction","pc":514,"opcode":61461}"#,
+                r#"{"frame":0,"event":"timer","timer":"delay","value":5}"#,
+                r#"{"frame":0,"event":"instruction","pc":516,"opcode":53253}"#,
+                r#"{"frame":0,"event":"draw","x":5,"y":5,"height":5,"collision":false}"#,
+                r#"{"frame":0,"event":"instruction","pc":518,"opcode":238}"#,
+                r#"{"frame":0,"event":"error","message":"Stack underflow: return at 0x206 outside of a subroutine"}"#,
+            ]);
+        }
+
+        #[test]
+        fn test_autofire() {
+            let (mut chip8, _) = setup_chip8_and_memory();
+            chip8.set_autofire(0x05, Some(30)); // Down every other frame
+            chip8.press_key(0x05);
+            let frames: Vec<u16> = (0..4).map(|_| {
+                chip8.latch_input().unwrap();
+                chip8.keys
+            }).collect();
+            assert_eq!(frames, [1 << 0x05, 0, 1 << 0x05, 0]);
+        }
+
+        #[test]
+        fn test_key_tap_within_frame() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x04;
+            chip8.press_key(0x04);
+            chip8.release_key(0x04);
+            chip8.latch_input().unwrap();
+            chip8.execute(0xE09E, &mut mem).unwrap();
+            chip8.execute(0xE09E, &mut mem).unwrap();
+            assert_eq!(chip8.pc, PROGRAM_START + 4); // Seen by every instruction of the frame
+            chip8.latch_input().unwrap();
+            chip8.execute(0xE0A1, &mut mem).unwrap();
+            assert_eq!(chip8.pc, PROGRAM_START + 6);
+        }
+
+        #[test]
+        fn test_chip8_execute_fx15() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x05;
+            let result = chip8.execute(0xF015, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.dt, 0x05); 
+        }
+
+        #[test]
+        fn test_chip8_execute_fx18() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x05;
+            let result = chip8.execute(0xF018, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.st, 0x05); 
+        }
+
+        #[test]
+        fn test_chip8_execute_fx1e() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.idx = 0x05;
+            chip8.v[0] = 0x05;
+            let result = chip8.execute(0xF01E, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.idx, 0x0A); 
+        }
+
+        #[test]
+        fn test_chip8_execute_fx29() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x05;
+            let result = chip8.execute(0xF029, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.idx, 0x05 * SPRITE_SIZE as u16); 
+        }
+
+        #[test]
+        fn test_chip8_execute_fx30() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.v[0] = 0x12;
+            let result = chip8.execute(0xF030, &mut mem);
+            assert!(result.is_ok());
+            assert_eq!(chip8.idx, mem.big_font_address(0x2));
+            assert_eq!(mem.read_byte(chip8.idx + 2), 0x03);
+        }
+
+        #[test]
+        fn test_chip8_execute_fx75_fx85() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.set_storage(MemoryStorage::new());
+            mem.load_from_bytes(&[0x12, 0x00]).unwrap();
+            chip8.v[..3].copy_from_slice(&[1, 2, 3]);
+            assert!(chip8.execute(0xF175, &mut mem).is_ok());
+            chip8.v[..3].fill(0);
+            assert!(chip8.execute(0xF285, &mut mem).is_ok());
+            assert_eq!(chip8.v[..3], [1, 2, 0]);
+
+            // Saved for the next session of the ROM
+            let saved = chip8.storage().load("flags/92a5652d382a18e89c4881ec57041fc7d885ca80").unwrap();
+            assert_eq!(saved.map(|flags| flags[..3].to_vec()), Some(vec![1, 2, 0]));
+        }
+
+        #[test]
+        fn test_chip8_execute_fx33() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.idx = 0x05;
+            chip8.v[0] = 123;
+            let result = chip8.execute(0xF033, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(mem.read_byte(0x05), 1); 
+            assert_eq!(mem.read_byte(0x06), 2); 
+            assert_eq!(mem.read_byte(0x07), 3); 
+        }
+
+        #[test]
+        fn test_chip8_execute_fx55() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.idx = 0x05;
+            chip8.v[0] = 0x01;
+            chip8.v[1] = 0x02;
+            let result = chip8.execute(0xF155, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(mem.read_byte(0x05), 0x01); 
+            assert_eq!(mem.read_byte(0x06), 0x02); 
+            assert_eq!(chip8.idx, 0x05); // Left unchanged by default
+        }
+
+        #[test]
+        fn test_chip8_execute_fx55_memory_quirk() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            for (memory, idx) in [(MemoryIncrement::XPlusOne, 0x07), (MemoryIncrement::X, 0x06), (MemoryIncrement::Unchanged, 0x05)] {
+                chip8.set_quirks(Quirks { memory, ..Quirks::default() });
+                chip8.idx = 0x05;
+                chip8.execute(0xF155, &mut mem).unwrap();
+                assert_eq!(chip8.idx, idx);
+            }
+        }
+
+        #[test]
+        fn test_chip8_execute_fx65() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.idx = 0x05;
+            mem.write_byte(0x05, 0x01);
+            mem.write_byte(0x06, 0x02);
+            let result = chip8.execute(0xF165, &mut mem); 
+            assert!(result.is_ok());
+            assert_eq!(chip8.v[0], 0x01); 
+            assert_eq!(chip8.v[1], 0x02); 
+            assert_eq!(chip8.idx, 0x05);
+        }
+
+        #[test]
+        fn test_chip8_execute_fx65_memory_quirk() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.set_quirks(Quirks { memory: MemoryIncrement::XPlusOne, ..Quirks::default() });
+            chip8.idx = 0x05;
+            chip8.execute(0xF265, &mut mem).unwrap();
+            assert_eq!(chip8.idx, 0x08);
+        }
+        #[test]
+        fn test_chip8_execute_raw_edges() {
+            // No opcode may panic, whatever the state at the edges of memory and the stack
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            for megachip in [false, true] {
+                chip8.display.set_megachip(megachip);
+                for (pc, idx, sp) in [(0xFFFE, 0xFFFF, STACK_DEPTH as u8), (0x0FFE, 0x0FFF, 0), (0x0000, 0xFFF0, 1)] {
+                    // Every instruction kind with the first and last register as Vx
+                    for op_code in (0..=u16::MAX).filter(|op_code| matches!(op_code >> 8 & 0xf, 0x0 | 0xf)) {
+                        (chip8.pc, chip8.idx, chip8.sp, chip8.v) = (pc, idx, sp, [0xFF; NUM_REGISTERS]);
+                        let _ = chip8.execute_raw(op_code, &mut mem);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn test_chip8_execute_raw() {
+            let (mut chip8, mut mem) = setup_chip8_and_memory();
+            chip8.execute_raw(0x6A02, &mut mem).unwrap();
+            assert_eq!((chip8.v[0xA], chip8.pc), (0x02, PROGRAM_START + 2));
+            assert!(matches!(chip8.execute_raw(0x00EE, &mut mem), Err(Chip8Error::Exec(ExecError::StackUnderflow(0x202)))));
+        }
+    }
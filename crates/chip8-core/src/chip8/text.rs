@@ -0,0 +1,121 @@
+// Height of a line of text, including the gap below it
+pub const LINE_HEIGHT: usize = 6;
+
+// Width of a character, including the gap after it
+pub const CHAR_WIDTH: usize = 4;
+
+// Draws text with the 3x5 font into a buffer of the given width, characters past the edges are cut off
+pub fn draw_text(buffer: &mut [u32], width: usize, x: usize, y: usize, text: &str, color: u32) {
+    let height = buffer.len() / width;
+    for (n, c) in text.chars().enumerate() {
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for column in 0..3 {
+                let (px, py) = (x + n * CHAR_WIDTH + column, y + row);
+                if bits & (0b100 >> column) != 0 && px < width && py < height {
+                    buffer[px + py * width] = color;
+                }
+            }
+        }
+    }
+}
+
+// Fills a rectangle in a buffer of the given width, cut off at the edges
+pub fn fill_rect(buffer: &mut [u32], width: usize, x: usize, y: usize, w: usize, h: usize, color: u32) {
+    let height = buffer.len() / width;
+    for py in y..(y + h).min(height) {
+        for px in x..(x + w).min(width) {
+            buffer[px + py * width] = color;
+        }
+    }
+}
+
+// Rows of a 3x5 glyph, lowercase letters are drawn as uppercase and unknown characters as blanks
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b011, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b010, 0b100, 0b100, 0b100, 0b010],
+        ')' => [0b010, 0b001, 0b001, 0b001, 0b010],
+        '[' => [0b110, 0b100, 0b100, 0b100, 0b110],
+        ']' => [0b011, 0b001, 0b001, 0b001, 0b011],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b110, 0b001, 0b010, 0b000, 0b010],
+        _ => [0; 5],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_text() {
+        let mut buffer = vec![0; 8 * 6];
+        draw_text(&mut buffer, 8, 0, 0, "1a", 0xffffff);
+        assert_eq!(buffer[1], 0xffffff); // Top of the "1"
+        assert_eq!(buffer[0], 0);
+        assert_eq!(buffer[5], 0xffffff); // Top of the "A", same as "a"
+        assert_eq!(buffer[4 + 8], 0xffffff);
+    }
+
+    #[test]
+    fn test_draw_text_cut_off() {
+        let mut buffer = vec![0; 4 * 3];
+        draw_text(&mut buffer, 4, 2, 0, "88", 0xffffff); // Would be out of bounds
+        assert_eq!(buffer[2], 0xffffff);
+        assert_eq!(buffer[3], 0xffffff);
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut buffer = vec![0; 4 * 4];
+        fill_rect(&mut buffer, 4, 2, 2, 5, 5, 0xff0000);
+        assert_eq!(buffer.iter().filter(|&&color| color == 0xff0000).count(), 4);
+        assert_eq!(buffer[2 + 2 * 4], 0xff0000);
+    }
+}
@@ -0,0 +1,34 @@
+use super::notify::{Rule, Trigger};
+use super::{GIF_RECORDING_KEY, LOAD_STATE_KEY, MEMORY_VIEWER_KEY, SAVE_STATE_KEY, SCREENSHOT_KEY, STEP_KEY};
+
+// Hints shown to first-time users, in order, short enough to fit over the tutorial's digit
+pub const RULES: &[Rule] = &[
+    ("HOLD 1234 QWER ASDF ZXCV", Trigger::ChipKey),
+    ("KEYS FOLLOW THE HEX KEYPAD", Trigger::Frames(240)),
+    ("ESC PAUSES", Trigger::Paused),
+    ("F7 OPENS THE DEBUGGER", Trigger::Hotkey(MEMORY_VIEWER_KEY)),
+    ("F8 STEPS ONE INSTRUCTION", Trigger::Hotkey(STEP_KEY)),
+    ("ESC RESUMES", Trigger::Resumed),
+    ("F1 SAVES A STATE", Trigger::Hotkey(SAVE_STATE_KEY)),
+    ("F2 LOADS IT BACK", Trigger::Hotkey(LOAD_STATE_KEY)),
+    ("F12 SAVES A SCREENSHOT", Trigger::Hotkey(SCREENSHOT_KEY)),
+    ("F10 RECORDS A GIF", Trigger::Hotkey(GIF_RECORDING_KEY)),
+    ("F10 AGAIN STOPS IT", Trigger::Hotkey(GIF_RECORDING_KEY)),
+    ("RUN CHIP8 ROM.CH8 TO PLAY", Trigger::Frames(480)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{DISPLAY_HEIGHT, DISPLAY_WIDTH, notify::render};
+
+    #[test]
+    fn test_hints_fit() {
+        // The digit is drawn from row 13 down and has to stay visible
+        for (hint, _) in RULES {
+            let mut buffer = vec![0x123456; DISPLAY_WIDTH * DISPLAY_HEIGHT];
+            render(&mut buffer, hint, 0xffffff, 0x000000);
+            assert!(buffer[13 * DISPLAY_WIDTH..].iter().all(|&color| color == 0x123456), "{hint}");
+        }
+    }
+}
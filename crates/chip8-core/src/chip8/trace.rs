@@ -0,0 +1,265 @@
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use super::{Chip8, Memory, NUM_REGISTERS, errors::{Chip8Error, IoError, LoadError}, opcode::OpCode};
+
+// State after executing one instruction, one line of a trace: the address and opcode of the
+// instruction, then the registers and timers it left behind, all in hex, e.g.
+// 0200 6A02 V:00000000000000000000020000000000 I:0000 SP:0 DT:00 ST:00
+// Anything after a ; is a comment, e.g. the disassembled instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub pc: u16,
+    pub opcode: u16,
+    pub v: [u8; NUM_REGISTERS],
+    pub i: u16,
+    pub sp: u8,
+    pub dt: u8,
+    pub st: u8,
+}
+
+impl TraceEntry {
+    pub fn parse(line: &str) -> Option<TraceEntry> {
+        let mut fields = line.split(';').next()?.split_whitespace();
+        let mut field = |prefix: &str| fields.next()?.strip_prefix(prefix);
+        let pc = u16::from_str_radix(field("")?, 16).ok()?;
+        let opcode = u16::from_str_radix(field("")?, 16).ok()?;
+        let registers = field("V:")?;
+        let i = u16::from_str_radix(field("I:")?, 16).ok()?;
+        let sp = u8::from_str_radix(field("SP:")?, 16).ok()?;
+        let dt = u8::from_str_radix(field("DT:")?, 16).ok()?;
+        let st = u8::from_str_radix(field("ST:")?, 16).ok()?;
+        if fields.next().is_some() || registers.len() != 2 * NUM_REGISTERS || !registers.is_ascii() {
+            return None;
+        }
+
+        let mut v = [0; NUM_REGISTERS];
+        for (n, register) in v.iter_mut().enumerate() {
+            *register = u8::from_str_radix(&registers[2 * n..2 * n + 2], 16).ok()?;
+        }
+        Some(TraceEntry { pc, opcode, v, i, sp, dt, st })
+    }
+
+    // Names and values of the fields that differ from the other entry, e.g. "V3 02 != 03"
+    fn differences(&self, other: &TraceEntry) -> Vec<String> {
+        let mut differences = Vec::new();
+        let mut compare = |name: String, ours: String, theirs: String| {
+            if ours != theirs {
+                differences.push(format!("{name} {ours} != {theirs}"));
+            }
+        };
+        compare("PC".to_string(), format!("{:04X}", self.pc), format!("{:04X}", other.pc));
+        compare("opcode".to_string(), format!("{:04X}", self.opcode), format!("{:04X}", other.opcode));
+        for n in 0..NUM_REGISTERS {
+            compare(format!("V{n:X}"), format!("{:02X}", self.v[n]), format!("{:02X}", other.v[n]));
+        }
+        compare("I".to_string(), format!("{:04X}", self.i), format!("{:04X}", other.i));
+        compare("SP".to_string(), format!("{:X}", self.sp), format!("{:X}", other.sp));
+        compare("DT".to_string(), format!("{:02X}", self.dt), format!("{:02X}", other.dt));
+        compare("ST".to_string(), format!("{:02X}", self.st), format!("{:02X}", other.st));
+        differences
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04X} {:04X} V:", self.pc, self.opcode)?;
+        for register in self.v {
+            write!(f, "{register:02X}")?;
+        }
+        write!(f, " I:{:04X} SP:{:X} DT:{:02X} ST:{:02X}", self.i, self.sp, self.dt, self.st)
+    }
+}
+
+// First instruction after which the emulator and the reference trace disagree
+pub struct Divergence {
+    pub step: usize, // Instructions executed before it, counted from 0
+    pub expected: TraceEntry,
+    pub actual: TraceEntry,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Diverged at instruction {}: {}", self.step, self.actual.differences(&self.expected).join(", "))?;
+        writeln!(f, "expected {}", self.expected)?;
+        write!(f, "actual   {}", self.actual)
+    }
+}
+
+// Reads a trace, skipping empty lines
+pub fn read(path: impl AsRef<Path>) -> Result<Vec<TraceEntry>, Chip8Error> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path).map_err(|source| LoadError::FileRead { path: path.to_path_buf(), source })?;
+    contents.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| TraceEntry::parse(line).ok_or_else(|| LoadError::InvalidTrace(n + 1).into()))
+        .collect()
+}
+
+// Runs the program without a window for the given number of instructions like
+// testing::run_headless, writing a trace line after every one. With labels set on the
+// emulator, every line ends in the instruction disassembled with them, e.g. ; CALL draw_paddle
+// The output is flushed after every frame, so a crash loses at most the lines of the last one.
+pub fn record(chip8: &mut Chip8, mem: &mut Memory, cycles: u64, mut out: impl Write) -> Result<(), Chip8Error> {
+    if cycles == 0 {
+        return Ok(());
+    }
+    let mut steps = 0;
+    let labels = chip8.labels().clone();
+    run(chip8, mem, |entry, frame_ended| {
+        match labels.is_empty() {
+            true => writeln!(out, "{entry}"),
+            false => writeln!(out, "{entry} ; {}", labels.disassemble(OpCode::decode(entry.opcode))),
+        }.map_err(IoError::Trace)?;
+        steps += 1;
+        if frame_ended || steps == cycles {
+            out.flush().map_err(IoError::Trace)?;
+        }
+        Ok(steps < cycles)
+    })
+}
+
+// Runs the program without a window for as many instructions as the trace has,
+// stopping at the first one leaving a state different from the trace
+pub fn verify(chip8: &mut Chip8, mem: &mut Memory, reference: &[TraceEntry]) -> Result<Option<Divergence>, Chip8Error> {
+    let mut expected = reference.iter().enumerate();
+    let mut divergence = None;
+    if !reference.is_empty() {
+        run(chip8, mem, |actual, _| {
+            let Some((step, &expected)) = expected.next() else {
+                return Ok(false);
+            };
+            if actual != expected {
+                divergence = Some(Divergence { step, expected, actual });
+                return Ok(false);
+            }
+            Ok(step + 1 < reference.len())
+        })?;
+    }
+    Ok(divergence)
+}
+
+// Steps the program, ending a frame every tickrate instructions, for as long as the callback
+// given the state after every instruction and whether it ended a frame returns true
+fn run(chip8: &mut Chip8, mem: &mut Memory, mut each: impl FnMut(TraceEntry, bool) -> Result<bool, Chip8Error>) -> Result<(), Chip8Error> {
+    chip8.latch_input()?;
+    chip8.patches.apply(mem);
+    let mut executed = 0;
+    loop {
+        let (pc, opcode) = (chip8.pc, mem.get_instruction(chip8.pc));
+        chip8.step(mem)?;
+        executed += 1;
+        let frame_ended = executed >= chip8.get_tickrate();
+        if frame_ended {
+            chip8.end_frame(mem)?;
+            executed = 0;
+        }
+        let entry = TraceEntry { pc, opcode, v: chip8.v, i: chip8.idx, sp: chip8.sp, dt: chip8.dt, st: chip8.st };
+        if !each(entry, frame_ended)? {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{MEMORY_SIZE, PROGRAM_START, labels::Labels};
+
+    // Counts up V0 in a loop
+    fn counter() -> Memory {
+        let mut mem = Memory::new();
+        for (i, byte) in [0x70, 0x01, 0x12, 0x00].into_iter().enumerate() {
+            mem.write_byte(PROGRAM_START + i as u16, byte);
+        }
+        mem
+    }
+
+    fn trace(cycles: u64) -> Vec<TraceEntry> {
+        let mut out = Vec::new();
+        record(&mut Chip8::new(), &mut counter(), cycles, &mut out).unwrap();
+        assert!(!String::from_utf8_lossy(&out).contains(';')); // No comments without labels
+        String::from_utf8(out).unwrap().lines().map(|line| TraceEntry::parse(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let line = "0200 6A02 V:00000000000000000000020000000000 I:0000 SP:0 DT:00 ST:00";
+        let entry = TraceEntry::parse(line).unwrap();
+        assert_eq!((entry.pc, entry.opcode, entry.v[0xA]), (0x200, 0x6a02, 0x02));
+        assert_eq!(entry.to_string(), line);
+        assert!(TraceEntry::parse("0200 6A02 V:00 I:0000 SP:0 DT:00 ST:00").is_none());
+        assert!(TraceEntry::parse(&format!("{line} extra")).is_none());
+        assert_eq!(TraceEntry::parse(&format!("{line} ; LD VA, 0x02")), Some(entry));
+    }
+
+    #[test]
+    fn test_record() {
+        let entries = trace(4);
+        assert_eq!(entries.len(), 4);
+        assert_eq!((entries[0].pc, entries[0].opcode, entries[0].v[0]), (0x200, 0x7001, 1));
+        assert_eq!((entries[1].pc, entries[1].opcode), (0x202, 0x1200));
+        assert_eq!(entries[2].v[0], 2);
+        assert!(trace(0).is_empty());
+    }
+
+    #[test]
+    fn test_record_labels() {
+        let mut chip8 = Chip8::new();
+        chip8.set_labels(Labels::parse("count = 0x200", MEMORY_SIZE).unwrap());
+        let mut out = Vec::new();
+        record(&mut chip8, &mut counter(), 2, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[0].ends_with(" ; ADD V0, 0x01"));
+        assert!(lines[1].ends_with(" ; JP count"));
+        assert!(lines.iter().all(|line| TraceEntry::parse(line).is_some()));
+    }
+
+    // Output keeping count of its flushes
+    #[derive(Default)]
+    struct Flushes(usize);
+
+    impl Write for Flushes {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.0 += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_record_flushes_every_frame() {
+        let mut chip8 = Chip8::new();
+        chip8.set_tickrate(10);
+        let mut out = Flushes::default();
+        record(&mut chip8, &mut counter(), 25, &mut out).unwrap();
+        assert_eq!(out.0, 3); // After both frames and the last line
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut reference = trace(10);
+        assert!(verify(&mut Chip8::new(), &mut counter(), &reference).unwrap().is_none());
+
+        reference[6].v[0] = 0x09;
+        let divergence = verify(&mut Chip8::new(), &mut counter(), &reference).unwrap().unwrap();
+        assert_eq!(divergence.step, 6);
+        assert_eq!(divergence.actual.v[0], 4);
+        assert!(divergence.to_string().starts_with("Diverged at instruction 6: V0 04 != 09"));
+    }
+
+    #[test]
+    fn test_read() {
+        let path = std::env::temp_dir().join("chip8_test_trace.log");
+        fs::write(&path, "0200 7001 V:01000000000000000000000000000000 I:0000 SP:0 DT:00 ST:00\n\nnot a trace\n").unwrap();
+        let result = read(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(Chip8Error::Load(LoadError::InvalidTrace(3)))));
+    }
+}
@@ -0,0 +1,90 @@
+use alloc::format;
+use core::fmt;
+use serde::Serialize;
+use super::errors::{Chip8Error, LoadError};
+use super::number::hex;
+
+// Kind of memory access a program makes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Access::Read => write!(f, "read"),
+            Access::Write => write!(f, "write"),
+        }
+    }
+}
+
+// Range of addresses whose reads, writes or both pause the emulator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub start: u16,
+    pub end: u16, // Last watched address
+    pub read: bool,
+    pub write: bool,
+}
+
+impl Watchpoint {
+    pub fn matches(&self, addr: u16, access: Access) -> bool {
+        let watched = match access {
+            Access::Read => self.read,
+            Access::Write => self.write,
+        };
+        watched && (self.start..=self.end).contains(&addr)
+    }
+
+    // Parses ADDR or START-END, optionally followed by :r, :w or :rw, addresses in hex.
+    // Both reads and writes are watched by default.
+    pub fn parse(spec: &str) -> Result<Watchpoint, Chip8Error> {
+        let invalid = || LoadError::InvalidArgument(format!("expected a watchpoint like 0x300-0x30F:w, got {spec}"));
+        let (range, access) = spec.split_once(':').unwrap_or((spec, "rw"));
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        let (start, end) = (hex(start).ok_or_else(invalid)?, hex(end).ok_or_else(invalid)?);
+        let (read, write) = match access {
+            "r" => (true, false),
+            "w" => (false, true),
+            "rw" => (true, true),
+            _ => return Err(invalid().into()),
+        };
+        if start > end {
+            return Err(invalid().into());
+        }
+        Ok(Watchpoint { start, end, read, write })
+    }
+}
+
+// Access that triggered a watchpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchHit {
+    pub addr: u16,
+    pub access: Access,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Watchpoint::parse("0x300").unwrap(), Watchpoint { start: 0x300, end: 0x300, read: true, write: true });
+        assert_eq!(Watchpoint::parse("300-30f:w").unwrap(), Watchpoint { start: 0x300, end: 0x30F, read: false, write: true });
+        assert_eq!(Watchpoint::parse("0x200:r").unwrap(), Watchpoint { start: 0x200, end: 0x200, read: true, write: false });
+        assert!(Watchpoint::parse("0x30f-0x300").is_err());
+        assert!(Watchpoint::parse("0x300:x").is_err());
+        assert!(Watchpoint::parse("here").is_err());
+    }
+
+    #[test]
+    fn test_matches() {
+        let watchpoint = Watchpoint::parse("0x300-0x30F:w").unwrap();
+        assert!(watchpoint.matches(0x305, Access::Write));
+        assert!(!watchpoint.matches(0x305, Access::Read));
+        assert!(!watchpoint.matches(0x310, Access::Write));
+    }
+}
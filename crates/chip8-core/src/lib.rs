@@ -0,0 +1,49 @@
+//! CHIP-8 emulator with SUPER-CHIP, XO-CHIP and experimental Megachip support.
+//!
+//! [`Chip8Builder`] creates the emulator together with the [`Memory`] holding the program:
+//!
+//! ```no_run
+//! use chip8_core::{Chip8Builder, Quirks};
+//!
+//! let (mut chip8, mut mem) = Chip8Builder::new()
+//!     .rom_file("pong.ch8")
+//!     .quirks(Quirks::default())
+//!     .build()?;
+//! chip8.run(&mut mem)?;
+//! # Ok::<(), chip8_core::Error>(())
+//! ```
+//!
+//! Without the default `std` feature the core builds as `no_std` with `alloc`, e.g. for a
+//! microcontroller driving an LED matrix: [`Chip8::run_frame`] runs a 60hz frame of a program
+//! loaded with [`Memory::load_from_bytes`], and [`Chip8::on_frame`] hands over the screen.
+//! Windows, audio, files and the run loop need `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// The emulator lives in chip8/, its modules are re-exported here so they are
+// reached as chip8_core::palette rather than chip8_core::chip8::palette
+mod chip8;
+
+pub use chip8::{battery, draw, errors, events, font, frame, frontend, heatmap, megachip, memory, opcode, palette, patches, profile, program, quirks, rom, splash, state, stats, storage, watchpoint};
+#[cfg(feature = "std")]
+pub use chip8::{audio, backend, bench, breakpoint, builder, capabilities, compat, conformance, database, decompile, filter, icon, indicator, labels, netplay, octo, recording, scheduler, sprites, testing, trace};
+#[cfg(feature = "bench")]
+pub use chip8::internals;
+#[cfg(feature = "embedded-graphics")]
+pub use chip8::embedded;
+
+// What most programs need
+pub use chip8::{Chip8, Memory};
+#[cfg(feature = "std")]
+pub use builder::Chip8Builder;
+pub use errors::Chip8Error as Error;
+pub use palette::{Color, Palette};
+pub use quirks::Quirks;
+pub use state::MachineState;
+#[cfg(feature = "std")]
+pub use state::SaveState;
+pub use frontend::Key; // Host keys the keypad is bound to
+#[cfg(feature = "std")]
+pub use frontend::Frontend; // Opens the windows
@@ -0,0 +1,51 @@
+// Runs the bundled programs and the test ROMs in tests/roms headlessly and compares
+// the screen with the snapshot in tests/snapshots. Set CHIP8_UPDATE_SNAPSHOTS=1 to
+// write the snapshots from the current screens instead, then review the diff.
+use std::{env, fs, path::Path};
+use chip8_core::Chip8;
+use chip8_core::{splash::{SPLASH, TUTORIAL}, testing};
+
+// Enough for the test ROMs to reach their idle loop
+const CYCLES: u64 = 10_000;
+
+// Runs the program and compares the screen with its snapshot, returning a message on mismatch
+fn check(name: &str, rom: &[u8]) -> Option<String> {
+    let (mut chip8, mut mem) = Chip8::builder().rom(rom).headless().build().unwrap();
+    testing::run_headless(&mut chip8, &mut mem, CYCLES).unwrap();
+    let screen = chip8.state().to_ascii();
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots").join(format!("{name}.txt"));
+    if env::var_os("CHIP8_UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, &screen).unwrap();
+        return None;
+    }
+    match fs::read_to_string(&path) {
+        Ok(snapshot) if snapshot == screen => None,
+        Ok(snapshot) => Some(format!("{name} differs from its snapshot\nexpected:\n{snapshot}actual:\n{screen}")),
+        Err(_) => Some(format!("{name} has no snapshot at {}\nactual:\n{screen}", path.display())),
+    }
+}
+
+#[test]
+fn test_bundled_programs() {
+    let failures: Vec<String> = [("splash", &SPLASH[..]), ("tutorial", &TUTORIAL[..])].into_iter()
+        .filter_map(|(name, rom)| check(name, rom))
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn test_roms() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/roms");
+    let mut roms: Vec<_> = fs::read_dir(dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ch8"))
+        .collect();
+    roms.sort();
+    assert!(!roms.is_empty());
+
+    let failures: Vec<String> = roms.iter()
+        .filter_map(|path| check(&path.file_stem().unwrap().to_string_lossy(), &fs::read(path).unwrap()))
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
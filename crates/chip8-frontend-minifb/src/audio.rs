@@ -0,0 +1,37 @@
+use chip8_core::audio::Beep;
+use chip8_core::backend::Backend;
+use chip8_core::frontend::AudioOutput;
+
+#[cfg(feature = "sdl2")]
+mod sdl2_backend;
+#[cfg(feature = "cpal")]
+mod cpal_backend;
+#[cfg(all(feature = "rodio", not(feature = "cpal")))]
+mod rodio_backend;
+
+// Opens the beep through SDL2 for its windows, or when it's the only library built in, and
+// otherwise through cpal or rodio, in that order of preference
+pub(super) fn open(beep: Beep, backend: Backend) -> Result<Box<dyn AudioOutput>, String> {
+    #[cfg(feature = "sdl2")]
+    if backend == Backend::Sdl2 || cfg!(not(any(feature = "cpal", feature = "rodio"))) {
+        return Ok(Box::new(sdl2_backend::Sdl2Backend::open(beep)?));
+    }
+    #[cfg(not(feature = "sdl2"))]
+    let _ = backend;
+    open_without_sdl2(beep)
+}
+
+#[cfg(feature = "cpal")]
+fn open_without_sdl2(beep: Beep) -> Result<Box<dyn AudioOutput>, String> {
+    Ok(Box::new(cpal_backend::CpalBackend::open(beep)?))
+}
+
+#[cfg(all(feature = "rodio", not(feature = "cpal")))]
+fn open_without_sdl2(beep: Beep) -> Result<Box<dyn AudioOutput>, String> {
+    Ok(Box::new(rodio_backend::RodioBackend::open(beep)?))
+}
+
+#[cfg(not(any(feature = "rodio", feature = "cpal")))]
+fn open_without_sdl2(_beep: Beep) -> Result<Box<dyn AudioOutput>, String> {
+    Err("built without an audio backend".to_string())
+}
@@ -0,0 +1,52 @@
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{BufferSize, Device, FromSample, SampleFormat, SampleRate, SizedSample, Stream, StreamConfig, SupportedBufferSize};
+use chip8_core::audio::{Beep, AUDIO_BUFFER_SIZE, SAMPLE_RATE};
+use chip8_core::frontend::AudioOutput;
+
+// Plays the beep straight from a cpal stream callback, without rodio's mixer in between
+pub(super) struct CpalBackend {
+    _stream: Stream, // Must be kept alive to play
+}
+
+impl AudioOutput for CpalBackend {}
+
+impl CpalBackend {
+    // Opens the default output device, fails with the reason if there is none
+    pub(super) fn open(beep: Beep) -> Result<Self, String> {
+        let device = cpal::default_host().default_output_device().ok_or("no output device available")?;
+        let supported = device.supported_output_configs()
+            .map_err(|e| e.to_string())?
+            .find(|config| config.min_sample_rate().0 <= SAMPLE_RATE && config.max_sample_rate().0 >= SAMPLE_RATE)
+            .ok_or_else(|| format!("output device doesn't support {SAMPLE_RATE} Hz"))?
+            .with_sample_rate(SampleRate(SAMPLE_RATE));
+
+        // Callbacks of exactly one beep buffer come at a steady pace, so a beep scheduled
+        // ahead by the emulator is never cut short by a late, oversized callback
+        let buffer_size = match supported.buffer_size() {
+            SupportedBufferSize::Range { min, max } => BufferSize::Fixed((AUDIO_BUFFER_SIZE as u32).clamp(*min, *max)),
+            SupportedBufferSize::Unknown => BufferSize::Default,
+        };
+        let config = StreamConfig { channels: supported.channels(), sample_rate: supported.sample_rate(), buffer_size };
+
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => build::<f32>(&device, &config, beep),
+            SampleFormat::I16 => build::<i16>(&device, &config, beep),
+            SampleFormat::U16 => build::<u16>(&device, &config, beep),
+            format => Err(format!("unsupported sample format {format}")),
+        }?;
+        stream.play().map_err(|e| e.to_string())?;
+        Ok(CpalBackend { _stream: stream })
+    }
+}
+
+// Builds a stream playing the beep on every channel
+fn build<T: SizedSample + FromSample<f32>>(device: &Device, config: &StreamConfig, mut beep: Beep) -> Result<Stream, String> {
+    let channels = config.channels as usize;
+    let write = move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+        for frame in data.chunks_mut(channels) {
+            frame.fill(T::from_sample(beep.next().unwrap_or(0.0)));
+        }
+    };
+    device.build_output_stream(config, write, |e| eprintln!("Audio stream error: {e}"), None)
+        .map_err(|e| e.to_string())
+}
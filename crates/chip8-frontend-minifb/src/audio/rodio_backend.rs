@@ -0,0 +1,40 @@
+use std::time::Duration;
+use rodio::{OutputStream, Sink, Source};
+use chip8_core::audio::{Beep, SAMPLE_RATE};
+use chip8_core::frontend::AudioOutput;
+
+// Plays the beep through a rodio sink
+pub(super) struct RodioBackend {
+    _stream: OutputStream, // Must be kept alive for the sink to play
+    _sink: Sink,
+}
+
+impl AudioOutput for RodioBackend {}
+
+impl RodioBackend {
+    // Opens the default output device, fails with the reason if there is none
+    pub(super) fn open(beep: Beep) -> Result<Self, String> {
+        let (stream, stream_handle) = OutputStream::try_default().map_err(|e| e.to_string())?;
+        let sink = Sink::try_new(&stream_handle).map_err(|e| e.to_string())?;
+        sink.append(BeepSource(beep));
+        Ok(RodioBackend { _stream: stream, _sink: sink })
+    }
+}
+
+// The beep as a rodio source, one channel at SAMPLE_RATE
+struct BeepSource(Beep);
+
+impl Iterator for BeepSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        self.0.next()
+    }
+}
+
+impl Source for BeepSource {
+    fn current_frame_len(&self) -> Option<usize> { None }
+    fn channels(&self) -> u16 { 1 }
+    fn sample_rate(&self) -> u32 { SAMPLE_RATE }
+    fn total_duration(&self) -> Option<Duration> { None }
+}
@@ -0,0 +1,32 @@
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use chip8_core::audio::{Beep, AUDIO_BUFFER_SIZE, SAMPLE_RATE};
+use chip8_core::frontend::AudioOutput;
+
+// Plays the beep from SDL2's audio callback, which asks for one beep buffer at a time
+pub(super) struct Sdl2Backend {
+    _device: AudioDevice<Callback>, // Must be kept alive to play
+}
+
+struct Callback(Beep);
+
+impl AudioCallback for Callback {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        out.fill_with(|| self.0.next().unwrap_or(0.0));
+    }
+}
+
+impl AudioOutput for Sdl2Backend {}
+
+impl Sdl2Backend {
+    // Opens the default output device, fails with the reason if there is none
+    pub(super) fn open(beep: Beep) -> Result<Self, String> {
+        let audio = sdl2::init()?.audio()?;
+        let desired = AudioSpecDesired { freq: Some(SAMPLE_RATE as i32), channels: Some(1), samples: Some(AUDIO_BUFFER_SIZE as u16) };
+        // SDL converts the samples to whatever the device takes
+        let device = audio.open_playback(None, &desired, |_| Callback(beep))?;
+        device.resume();
+        Ok(Sdl2Backend { _device: device })
+    }
+}
@@ -0,0 +1,48 @@
+use chip8_core::frontend::{Key, KeyRepeat};
+
+// Keyboard state built from the key events of libraries that report no state of their own,
+// as SDL2 and winit do. A press of a key already held is a repeat.
+#[derive(Default)]
+pub(super) struct KeyTracker {
+    held: Vec<Key>,
+    pressed: Vec<Key>, // Since the last update, repeats left out
+    repeated: Vec<Key>, // Since the last update, repeats included
+    events: Vec<(Key, bool)>, // Presses and releases since they were last taken
+}
+
+impl KeyTracker {
+    // Forgets the presses of the last update, before the events of the next are handled
+    pub(super) fn start_update(&mut self) {
+        self.pressed.clear();
+        self.repeated.clear();
+    }
+
+    pub(super) fn press(&mut self, key: Key) {
+        self.repeated.push(key);
+        if !self.held.contains(&key) {
+            self.pressed.push(key);
+            self.held.push(key);
+        }
+        self.events.push((key, true));
+    }
+
+    pub(super) fn release(&mut self, key: Key) {
+        self.held.retain(|&held| held != key);
+        self.events.push((key, false));
+    }
+
+    pub(super) fn held(&self) -> Vec<Key> {
+        self.held.clone()
+    }
+
+    pub(super) fn pressed(&self, repeat: KeyRepeat) -> Vec<Key> {
+        match repeat {
+            KeyRepeat::Yes => self.repeated.clone(),
+            KeyRepeat::No => self.pressed.clone(),
+        }
+    }
+
+    pub(super) fn take_events(&mut self) -> Vec<(Key, bool)> {
+        std::mem::take(&mut self.events)
+    }
+}
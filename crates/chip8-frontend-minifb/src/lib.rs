@@ -0,0 +1,56 @@
+//! Windows and sound for [`chip8_core`]: windows through minifb, or SDL2 and wgpu with their
+//! features, and the beep through rodio, cpal or SDL2.
+//!
+//! ```no_run
+//! use chip8_core::Chip8Builder;
+//! use chip8_frontend_minifb::MinifbFrontend;
+//!
+//! let (mut chip8, mut mem) = Chip8Builder::new()
+//!     .rom_file("pong.ch8")
+//!     .frontend(MinifbFrontend)
+//!     .build()?;
+//! chip8.run(&mut mem)?;
+//! # Ok::<(), chip8_core::Error>(())
+//! ```
+
+use chip8_core::audio::Beep;
+use chip8_core::backend::Backend;
+use chip8_core::errors::{Chip8Error, IoError};
+use chip8_core::frontend::{AudioOutput, Frontend, Window, WindowSettings};
+
+mod audio;
+#[cfg(any(feature = "sdl2", feature = "wgpu"))]
+mod key_tracker;
+#[cfg(feature = "sdl2")]
+mod sdl2_window;
+#[cfg(feature = "wgpu")]
+mod wgpu_window;
+mod window;
+
+// Backend windows are drawn through unless set otherwise, SDL2 when built with it
+pub const DEFAULT_BACKEND: Backend = if cfg!(feature = "sdl2") { Backend::Sdl2 } else { Backend::Minifb };
+
+// Opens the windows through the backend they ask for, failing for those not built in
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinifbFrontend;
+
+impl Frontend for MinifbFrontend {
+    fn open_window(&self, settings: &WindowSettings) -> Result<Box<dyn Window>, Chip8Error> {
+        Ok(match settings.backend {
+            Backend::Minifb => Box::new(window::MinifbWindow::new(settings)?),
+            #[cfg(feature = "sdl2")]
+            Backend::Sdl2 => Box::new(sdl2_window::SdlWindow::new(settings)?),
+            #[cfg(feature = "wgpu")]
+            Backend::Wgpu => Box::new(wgpu_window::WgpuWindow::new(settings)?),
+            // Those not built in, and any added to chip8-core later
+            backend => {
+                let feature = format!("{backend:?}").to_lowercase();
+                return Err(IoError::WindowCreation(format!("built without the {feature} feature")).into());
+            }
+        })
+    }
+
+    fn open_audio(&self, beep: Beep, backend: Backend) -> Result<Box<dyn AudioOutput>, String> {
+        audio::open(beep, backend)
+    }
+}
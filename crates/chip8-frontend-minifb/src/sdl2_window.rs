@@ -0,0 +1,248 @@
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::surface::Surface;
+use sdl2::video::{Window, WindowContext, WindowPos};
+use sdl2::{EventPump, Sdl};
+use chip8_core::errors::{Chip8Error, IoError};
+use chip8_core::frontend::{self, Key, KeyRepeat, WindowSettings};
+use chip8_core::icon::WindowIcon;
+use super::key_tracker::KeyTracker;
+
+// Window drawn through SDL2's renderer, presenting in step with the monitor's refresh with vsync.
+// Unlike minifb it takes an icon on every platform and has a real fullscreen mode.
+pub(super) struct SdlWindow {
+    canvas: Canvas<Window>,
+    creator: TextureCreator<WindowContext>,
+    texture: Option<(Texture, (usize, usize))>, // Streamed to every frame, made anew for a new size
+    pixels: Vec<u8>, // The buffer as the texture takes it
+    events: EventPump,
+    keys: KeyTracker,
+    open: bool,
+    _sdl: Sdl, // SDL shuts down once the last context is dropped
+}
+
+impl SdlWindow {
+    pub(super) fn new(settings: &WindowSettings) -> Result<Self, Chip8Error> {
+        // Read as the window is created: WM_CLASS on X11, and the app id on Wayland, named
+        // differently before SDL 2.26. A name SDL can't take as a C string is left out.
+        if !settings.app_name.contains('\0') {
+            for hint in ["SDL_VIDEO_X11_WMCLASS", "SDL_VIDEO_WAYLAND_WMCLASS", "SDL_APP_ID"] {
+                sdl2::hint::set(hint, settings.app_name);
+            }
+        }
+        let sdl = sdl2::init().map_err(IoError::Sdl)?;
+        let video = sdl.video().map_err(IoError::Sdl)?;
+        let factor = settings.scale.factor() as u32;
+        let mut builder = video.window(settings.title, settings.width as u32 * factor, settings.height as u32 * factor);
+        builder.position_centered();
+        if settings.resize {
+            builder.resizable();
+        }
+        // The borderless window minifb stands in for fullscreen with is the real thing here
+        if settings.borderless {
+            builder.fullscreen_desktop();
+        }
+        let window = builder.build().map_err(|e| IoError::Sdl(e.to_string()))?;
+        let mut canvas = window.into_canvas();
+        if settings.vsync {
+            canvas = canvas.present_vsync();
+        }
+        let canvas = canvas.build().map_err(|e| IoError::Sdl(e.to_string()))?;
+        let events = sdl.event_pump().map_err(IoError::Sdl)?;
+        let creator = canvas.texture_creator();
+        Ok(SdlWindow { canvas, creator, texture: None, pixels: Vec::new(), events, keys: KeyTracker::default(), open: true, _sdl: sdl })
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Quit { .. } | Event::Window { win_event: WindowEvent::Close, .. } => self.open = false,
+            Event::KeyDown { keycode: Some(keycode), .. } => {
+                if let Some(key) = key(keycode) {
+                    self.keys.press(key);
+                }
+            }
+            Event::KeyUp { keycode: Some(keycode), .. } => {
+                if let Some(key) = key(keycode) {
+                    self.keys.release(key);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl frontend::Window for SdlWindow {
+    fn update(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Chip8Error> {
+        self.keys.start_update();
+        while let Some(event) = self.events.poll_event() {
+            self.handle(event);
+        }
+
+        // Scaled to fit the window with bars around it, like minifb's AspectRatioStretch
+        if self.texture.as_ref().is_none_or(|(_, size)| *size != (width, height)) {
+            self.canvas.set_logical_size(width as u32, height as u32).map_err(|e| IoError::Sdl(e.to_string()))?;
+            let texture = self.creator.create_texture_streaming(PixelFormatEnum::RGB888, width as u32, height as u32)
+                .map_err(|e| IoError::Sdl(e.to_string()))?;
+            if let Some((old, _)) = self.texture.replace((texture, (width, height))) {
+                // Made by the canvas, which is still alive
+                unsafe { old.destroy() };
+            }
+        }
+        let (texture, _) = self.texture.as_mut().expect("the texture was made for the size");
+        self.pixels.clear();
+        self.pixels.extend(buffer.iter().flat_map(|pixel| pixel.to_ne_bytes()));
+        texture.update(None, &self.pixels, width * 4).map_err(|e| IoError::Sdl(e.to_string()))?;
+        self.canvas.clear();
+        self.canvas.copy(texture, None, None).map_err(IoError::Sdl)?;
+        self.canvas.present(); // Waits for the vertical blank with vsync
+        Ok(())
+    }
+
+    fn get_keys(&self) -> Vec<Key> {
+        self.keys.held()
+    }
+
+    fn get_keys_pressed(&self, repeat: KeyRepeat) -> Vec<Key> {
+        self.keys.pressed(repeat)
+    }
+
+    fn take_key_events(&mut self) -> Vec<(Key, bool)> {
+        self.keys.take_events()
+    }
+
+    fn set_title(&mut self, title: &str) {
+        let _ = self.canvas.window_mut().set_title(title); // Only fails on a NUL in the title
+    }
+
+    fn set_position(&mut self, x: isize, y: isize) {
+        self.canvas.window_mut().set_position(WindowPos::Positioned(x as i32), WindowPos::Positioned(y as i32));
+    }
+
+    fn set_icon(&mut self, icon: &WindowIcon) {
+        let mut pixels: Vec<u8> = icon.pixels().iter().flat_map(|pixel| pixel.to_ne_bytes()).collect();
+        let (width, height) = (icon.width() as u32, icon.height() as u32);
+        let surface = Surface::from_data(&mut pixels, width, height, width * 4, PixelFormatEnum::ARGB8888);
+        if let Ok(surface) = surface {
+            self.canvas.window_mut().set_icon(surface);
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+// The key for an SDL key, by the symbol on it rather than its position
+fn key(keycode: Keycode) -> Option<Key> {
+    Some(match keycode {
+        Keycode::NUM_0 => Key::Key0,
+        Keycode::NUM_1 => Key::Key1,
+        Keycode::NUM_2 => Key::Key2,
+        Keycode::NUM_3 => Key::Key3,
+        Keycode::NUM_4 => Key::Key4,
+        Keycode::NUM_5 => Key::Key5,
+        Keycode::NUM_6 => Key::Key6,
+        Keycode::NUM_7 => Key::Key7,
+        Keycode::NUM_8 => Key::Key8,
+        Keycode::NUM_9 => Key::Key9,
+        Keycode::A => Key::A,
+        Keycode::B => Key::B,
+        Keycode::C => Key::C,
+        Keycode::D => Key::D,
+        Keycode::E => Key::E,
+        Keycode::F => Key::F,
+        Keycode::G => Key::G,
+        Keycode::H => Key::H,
+        Keycode::I => Key::I,
+        Keycode::J => Key::J,
+        Keycode::K => Key::K,
+        Keycode::L => Key::L,
+        Keycode::M => Key::M,
+        Keycode::N => Key::N,
+        Keycode::O => Key::O,
+        Keycode::P => Key::P,
+        Keycode::Q => Key::Q,
+        Keycode::R => Key::R,
+        Keycode::S => Key::S,
+        Keycode::T => Key::T,
+        Keycode::U => Key::U,
+        Keycode::V => Key::V,
+        Keycode::W => Key::W,
+        Keycode::X => Key::X,
+        Keycode::Y => Key::Y,
+        Keycode::Z => Key::Z,
+        Keycode::F1 => Key::F1,
+        Keycode::F2 => Key::F2,
+        Keycode::F3 => Key::F3,
+        Keycode::F4 => Key::F4,
+        Keycode::F5 => Key::F5,
+        Keycode::F6 => Key::F6,
+        Keycode::F7 => Key::F7,
+        Keycode::F8 => Key::F8,
+        Keycode::F9 => Key::F9,
+        Keycode::F10 => Key::F10,
+        Keycode::F11 => Key::F11,
+        Keycode::F12 => Key::F12,
+        Keycode::F13 => Key::F13,
+        Keycode::F14 => Key::F14,
+        Keycode::F15 => Key::F15,
+        Keycode::DOWN => Key::Down,
+        Keycode::LEFT => Key::Left,
+        Keycode::RIGHT => Key::Right,
+        Keycode::UP => Key::Up,
+        Keycode::QUOTE => Key::Apostrophe,
+        Keycode::BACKQUOTE => Key::Backquote,
+        Keycode::BACKSLASH => Key::Backslash,
+        Keycode::COMMA => Key::Comma,
+        Keycode::EQUALS => Key::Equal,
+        Keycode::LEFTBRACKET => Key::LeftBracket,
+        Keycode::MINUS => Key::Minus,
+        Keycode::PERIOD => Key::Period,
+        Keycode::RIGHTBRACKET => Key::RightBracket,
+        Keycode::SEMICOLON => Key::Semicolon,
+        Keycode::SLASH => Key::Slash,
+        Keycode::BACKSPACE => Key::Backspace,
+        Keycode::DELETE => Key::Delete,
+        Keycode::END => Key::End,
+        Keycode::RETURN => Key::Enter,
+        Keycode::ESCAPE => Key::Escape,
+        Keycode::HOME => Key::Home,
+        Keycode::INSERT => Key::Insert,
+        Keycode::APPLICATION => Key::Menu,
+        Keycode::PAGEDOWN => Key::PageDown,
+        Keycode::PAGEUP => Key::PageUp,
+        Keycode::PAUSE => Key::Pause,
+        Keycode::SPACE => Key::Space,
+        Keycode::TAB => Key::Tab,
+        Keycode::NUMLOCKCLEAR => Key::NumLock,
+        Keycode::CAPSLOCK => Key::CapsLock,
+        Keycode::SCROLLLOCK => Key::ScrollLock,
+        Keycode::LSHIFT => Key::LeftShift,
+        Keycode::RSHIFT => Key::RightShift,
+        Keycode::LCTRL => Key::LeftCtrl,
+        Keycode::RCTRL => Key::RightCtrl,
+        Keycode::KP_0 => Key::NumPad0,
+        Keycode::KP_1 => Key::NumPad1,
+        Keycode::KP_2 => Key::NumPad2,
+        Keycode::KP_3 => Key::NumPad3,
+        Keycode::KP_4 => Key::NumPad4,
+        Keycode::KP_5 => Key::NumPad5,
+        Keycode::KP_6 => Key::NumPad6,
+        Keycode::KP_7 => Key::NumPad7,
+        Keycode::KP_8 => Key::NumPad8,
+        Keycode::KP_9 => Key::NumPad9,
+        Keycode::KP_PERIOD => Key::NumPadDot,
+        Keycode::KP_DIVIDE => Key::NumPadSlash,
+        Keycode::KP_MULTIPLY => Key::NumPadAsterisk,
+        Keycode::KP_MINUS => Key::NumPadMinus,
+        Keycode::KP_PLUS => Key::NumPadPlus,
+        Keycode::KP_ENTER => Key::NumPadEnter,
+        Keycode::LALT => Key::LeftAlt,
+        Keycode::RALT => Key::RightAlt,
+        Keycode::LGUI => Key::LeftSuper,
+        Keycode::RGUI => Key::RightSuper,
+        _ => return None,
+    })
+}
@@ -0,0 +1,278 @@
+use std::cell::RefCell;
+use pixels::{Pixels, PixelsBuilder, SurfaceTexture};
+use winit::dpi::{LogicalSize, PhysicalPosition};
+use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::platform::run_return::EventLoopExtRunReturn;
+use winit::window::{Fullscreen, Icon, Window, WindowBuilder};
+use chip8_core::errors::{Chip8Error, IoError};
+use chip8_core::frontend::{self, Key, KeyRepeat, WindowSettings};
+use chip8_core::icon::WindowIcon;
+use super::key_tracker::KeyTracker;
+
+thread_local! {
+    // winit allows a single event loop per program, kept here while no window uses it
+    static EVENT_LOOP: RefCell<Option<EventLoop<()>>> = const { RefCell::new(None) };
+}
+
+// Window whose buffer is scaled by the GPU through pixels, keeping its aspect ratio
+pub(super) struct WgpuWindow {
+    pixels: Pixels, // Dropped before the window it draws to
+    window: Window,
+    event_loop: Option<EventLoop<()>>, // Given back when the window closes
+    size: (usize, usize), // Of the buffer shown
+    keys: KeyTracker,
+    open: bool,
+}
+
+impl WgpuWindow {
+    pub(super) fn new(settings: &WindowSettings) -> Result<Self, Chip8Error> {
+        let event_loop = EVENT_LOOP.with(|event_loop| event_loop.borrow_mut().take()).unwrap_or_default();
+        match open(&event_loop, settings) {
+            Ok((window, pixels)) => Ok(WgpuWindow {
+                pixels,
+                window,
+                event_loop: Some(event_loop),
+                size: (settings.width, settings.height),
+                keys: KeyTracker::default(),
+                open: true,
+            }),
+            Err(e) => {
+                EVENT_LOOP.with(|parked| *parked.borrow_mut() = Some(event_loop));
+                Err(e)
+            }
+        }
+    }
+
+    fn handle(&mut self, event: WindowEvent) -> Result<(), Chip8Error> {
+        match event {
+            WindowEvent::CloseRequested => self.open = false,
+            WindowEvent::Resized(size) => {
+                self.pixels.resize_surface(size.width, size.height).map_err(|e| IoError::Gpu(e.to_string()))?;
+            }
+            WindowEvent::KeyboardInput { input: KeyboardInput { virtual_keycode: Some(keycode), state, .. }, .. } => {
+                // winit repeats a held key as more presses
+                match (key(keycode), state) {
+                    (Some(key), ElementState::Pressed) => self.keys.press(key),
+                    (Some(key), ElementState::Released) => self.keys.release(key),
+                    (None, _) => (),
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+fn open(event_loop: &EventLoop<()>, settings: &WindowSettings) -> Result<(Window, Pixels), Chip8Error> {
+    let (width, height) = (settings.width, settings.height);
+    let factor = settings.scale.factor() as f64;
+    let mut builder = WindowBuilder::new()
+        .with_title(settings.title)
+        .with_inner_size(LogicalSize::new(width as f64 * factor, height as f64 * factor))
+        .with_resizable(settings.resize);
+    // WM_CLASS on X11, and the app id on Wayland, which winit sets from the same name
+    #[cfg(target_os = "linux")]
+    {
+        use winit::platform::x11::WindowBuilderExtX11;
+        builder = builder.with_name(settings.app_name, settings.app_name);
+    }
+    if settings.borderless {
+        builder = builder.with_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
+    let window = builder.build(event_loop).map_err(|e| IoError::Gpu(e.to_string()))?;
+    let size = window.inner_size();
+    let pixels = PixelsBuilder::new(width as u32, height as u32, SurfaceTexture::new(size.width, size.height, &window))
+        .enable_vsync(settings.vsync)
+        .build()
+        .map_err(|e| IoError::Gpu(e.to_string()))?;
+    Ok((window, pixels))
+}
+
+impl frontend::Window for WgpuWindow {
+    fn update(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Chip8Error> {
+        // Takes the events that came in since the last frame, without waiting for more
+        self.keys.start_update();
+        let mut events = Vec::new();
+        let mut rescaled = Ok(());
+        if let Some(event_loop) = self.event_loop.as_mut() {
+            let pixels = &mut self.pixels;
+            event_loop.run_return(|event, _, control_flow| match event {
+                // Lends the size the window takes at the new scale, so it can't be kept for later.
+                // Moving to a monitor of another DPI changes it without a Resized event.
+                Event::WindowEvent { event: WindowEvent::ScaleFactorChanged { new_inner_size, .. }, .. } => {
+                    rescaled = pixels.resize_surface(new_inner_size.width, new_inner_size.height);
+                }
+                Event::WindowEvent { event, .. } => events.extend(event.to_static()),
+                Event::MainEventsCleared => *control_flow = ControlFlow::Exit,
+                _ => (),
+            });
+        }
+        rescaled.map_err(|e| IoError::Gpu(e.to_string()))?;
+        for event in events {
+            self.handle(event)?;
+        }
+
+        if self.size != (width, height) {
+            self.pixels.resize_buffer(width as u32, height as u32).map_err(|e| IoError::Gpu(e.to_string()))?;
+            self.size = (width, height);
+        }
+        for (pixel, color) in self.pixels.frame_mut().chunks_exact_mut(4).zip(buffer) {
+            let [_, r, g, b] = color.to_be_bytes();
+            pixel.copy_from_slice(&[r, g, b, 0xff]);
+        }
+        Ok(self.pixels.render().map_err(|e| IoError::Gpu(e.to_string()))?)
+    }
+
+    fn get_keys(&self) -> Vec<Key> {
+        self.keys.held()
+    }
+
+    fn get_keys_pressed(&self, repeat: KeyRepeat) -> Vec<Key> {
+        self.keys.pressed(repeat)
+    }
+
+    fn take_key_events(&mut self) -> Vec<(Key, bool)> {
+        self.keys.take_events()
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+
+    fn set_position(&mut self, x: isize, y: isize) {
+        self.window.set_outer_position(PhysicalPosition::new(x as i32, y as i32));
+    }
+
+    fn set_icon(&mut self, icon: &WindowIcon) {
+        let rgba = icon.pixels().iter().flat_map(|pixel| {
+            let [a, r, g, b] = pixel.to_be_bytes();
+            [r, g, b, a]
+        }).collect();
+        self.window.set_window_icon(Icon::from_rgba(rgba, icon.width() as u32, icon.height() as u32).ok());
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+impl Drop for WgpuWindow {
+    fn drop(&mut self) {
+        let event_loop = self.event_loop.take();
+        EVENT_LOOP.with(|parked| *parked.borrow_mut() = event_loop);
+    }
+}
+
+// The key for a winit key, by the symbol on it rather than its position
+fn key(keycode: VirtualKeyCode) -> Option<Key> {
+    Some(match keycode {
+        VirtualKeyCode::Key0 => Key::Key0,
+        VirtualKeyCode::Key1 => Key::Key1,
+        VirtualKeyCode::Key2 => Key::Key2,
+        VirtualKeyCode::Key3 => Key::Key3,
+        VirtualKeyCode::Key4 => Key::Key4,
+        VirtualKeyCode::Key5 => Key::Key5,
+        VirtualKeyCode::Key6 => Key::Key6,
+        VirtualKeyCode::Key7 => Key::Key7,
+        VirtualKeyCode::Key8 => Key::Key8,
+        VirtualKeyCode::Key9 => Key::Key9,
+        VirtualKeyCode::A => Key::A,
+        VirtualKeyCode::B => Key::B,
+        VirtualKeyCode::C => Key::C,
+        VirtualKeyCode::D => Key::D,
+        VirtualKeyCode::E => Key::E,
+        VirtualKeyCode::F => Key::F,
+        VirtualKeyCode::G => Key::G,
+        VirtualKeyCode::H => Key::H,
+        VirtualKeyCode::I => Key::I,
+        VirtualKeyCode::J => Key::J,
+        VirtualKeyCode::K => Key::K,
+        VirtualKeyCode::L => Key::L,
+        VirtualKeyCode::M => Key::M,
+        VirtualKeyCode::N => Key::N,
+        VirtualKeyCode::O => Key::O,
+        VirtualKeyCode::P => Key::P,
+        VirtualKeyCode::Q => Key::Q,
+        VirtualKeyCode::R => Key::R,
+        VirtualKeyCode::S => Key::S,
+        VirtualKeyCode::T => Key::T,
+        VirtualKeyCode::U => Key::U,
+        VirtualKeyCode::V => Key::V,
+        VirtualKeyCode::W => Key::W,
+        VirtualKeyCode::X => Key::X,
+        VirtualKeyCode::Y => Key::Y,
+        VirtualKeyCode::Z => Key::Z,
+        VirtualKeyCode::F1 => Key::F1,
+        VirtualKeyCode::F2 => Key::F2,
+        VirtualKeyCode::F3 => Key::F3,
+        VirtualKeyCode::F4 => Key::F4,
+        VirtualKeyCode::F5 => Key::F5,
+        VirtualKeyCode::F6 => Key::F6,
+        VirtualKeyCode::F7 => Key::F7,
+        VirtualKeyCode::F8 => Key::F8,
+        VirtualKeyCode::F9 => Key::F9,
+        VirtualKeyCode::F10 => Key::F10,
+        VirtualKeyCode::F11 => Key::F11,
+        VirtualKeyCode::F12 => Key::F12,
+        VirtualKeyCode::F13 => Key::F13,
+        VirtualKeyCode::F14 => Key::F14,
+        VirtualKeyCode::F15 => Key::F15,
+        VirtualKeyCode::Down => Key::Down,
+        VirtualKeyCode::Left => Key::Left,
+        VirtualKeyCode::Right => Key::Right,
+        VirtualKeyCode::Up => Key::Up,
+        VirtualKeyCode::Apostrophe => Key::Apostrophe,
+        VirtualKeyCode::Grave => Key::Backquote,
+        VirtualKeyCode::Backslash => Key::Backslash,
+        VirtualKeyCode::Comma => Key::Comma,
+        VirtualKeyCode::Equals => Key::Equal,
+        VirtualKeyCode::LBracket => Key::LeftBracket,
+        VirtualKeyCode::Minus => Key::Minus,
+        VirtualKeyCode::Period => Key::Period,
+        VirtualKeyCode::RBracket => Key::RightBracket,
+        VirtualKeyCode::Semicolon => Key::Semicolon,
+        VirtualKeyCode::Slash => Key::Slash,
+        VirtualKeyCode::Back => Key::Backspace,
+        VirtualKeyCode::Delete => Key::Delete,
+        VirtualKeyCode::End => Key::End,
+        VirtualKeyCode::Return => Key::Enter,
+        VirtualKeyCode::Escape => Key::Escape,
+        VirtualKeyCode::Home => Key::Home,
+        VirtualKeyCode::Insert => Key::Insert,
+        VirtualKeyCode::Apps => Key::Menu,
+        VirtualKeyCode::PageDown => Key::PageDown,
+        VirtualKeyCode::PageUp => Key::PageUp,
+        VirtualKeyCode::Pause => Key::Pause,
+        VirtualKeyCode::Space => Key::Space,
+        VirtualKeyCode::Tab => Key::Tab,
+        VirtualKeyCode::Numlock => Key::NumLock,
+        VirtualKeyCode::Capital => Key::CapsLock,
+        VirtualKeyCode::Scroll => Key::ScrollLock,
+        VirtualKeyCode::LShift => Key::LeftShift,
+        VirtualKeyCode::RShift => Key::RightShift,
+        VirtualKeyCode::LControl => Key::LeftCtrl,
+        VirtualKeyCode::RControl => Key::RightCtrl,
+        VirtualKeyCode::Numpad0 => Key::NumPad0,
+        VirtualKeyCode::Numpad1 => Key::NumPad1,
+        VirtualKeyCode::Numpad2 => Key::NumPad2,
+        VirtualKeyCode::Numpad3 => Key::NumPad3,
+        VirtualKeyCode::Numpad4 => Key::NumPad4,
+        VirtualKeyCode::Numpad5 => Key::NumPad5,
+        VirtualKeyCode::Numpad6 => Key::NumPad6,
+        VirtualKeyCode::Numpad7 => Key::NumPad7,
+        VirtualKeyCode::Numpad8 => Key::NumPad8,
+        VirtualKeyCode::Numpad9 => Key::NumPad9,
+        VirtualKeyCode::NumpadDecimal => Key::NumPadDot,
+        VirtualKeyCode::NumpadDivide => Key::NumPadSlash,
+        VirtualKeyCode::NumpadMultiply => Key::NumPadAsterisk,
+        VirtualKeyCode::NumpadSubtract => Key::NumPadMinus,
+        VirtualKeyCode::NumpadAdd => Key::NumPadPlus,
+        VirtualKeyCode::NumpadEnter => Key::NumPadEnter,
+        VirtualKeyCode::LAlt => Key::LeftAlt,
+        VirtualKeyCode::RAlt => Key::RightAlt,
+        VirtualKeyCode::LWin => Key::LeftSuper,
+        VirtualKeyCode::RWin => Key::RightSuper,
+        _ => return None,
+    })
+}
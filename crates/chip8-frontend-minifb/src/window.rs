@@ -0,0 +1,155 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use minifb::{InputCallback, ScaleMode, WindowOptions};
+use chip8_core::errors::{Chip8Error, IoError};
+use chip8_core::frontend::{Key, KeyRepeat, Scale, Window, WindowSettings};
+use chip8_core::icon::WindowIcon;
+
+// Window drawn through minifb, which has no vsync or fullscreen mode of its own
+pub(super) struct MinifbWindow {
+    window: minifb::Window,
+    key_events: KeyEvents, // Presses and releases since they were last taken
+}
+
+impl MinifbWindow {
+    pub(super) fn new(settings: &WindowSettings) -> Result<Self, Chip8Error> {
+        let options = WindowOptions {
+            resize: settings.resize,
+            scale: scale(settings.scale),
+            borderless: settings.borderless,
+            scale_mode: ScaleMode::AspectRatioStretch,
+            ..WindowOptions::default()
+        };
+        let mut window = minifb::Window::new(settings.title, settings.width, settings.height, options)
+            .map_err(|e| IoError::WindowCreation(e.to_string()))?;
+        let key_events = KeyEvents::default();
+        window.set_input_callback(Box::new(key_events.clone()));
+        #[cfg(target_os = "linux")]
+        set_class(&window, settings.app_name);
+        Ok(MinifbWindow { window, key_events })
+    }
+}
+
+// Names the application an X11 window belongs to through its WM_CLASS, which minifb leaves
+// unset. minifb has no app id on Wayland, where desktops match the window by its executable.
+#[cfg(target_os = "linux")]
+fn set_class(window: &minifb::Window, name: &str) {
+    use std::ffi::CString;
+    use raw_window_handle::{HasDisplayHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle};
+    use x11_dl::xlib::{XClassHint, Xlib};
+    let (Ok(handle), Ok(display)) = (window.window_handle(), window.display_handle()) else { return };
+    let (RawWindowHandle::Xlib(handle), RawDisplayHandle::Xlib(display)) = (handle.as_raw(), display.as_raw()) else { return };
+    let (Some(display), Ok(xlib), Ok(name)) = (display.display, Xlib::open(), CString::new(name)) else { return };
+    // The name serves as both the instance and the class
+    let mut hint = XClassHint { res_name: name.as_ptr().cast_mut(), res_class: name.as_ptr().cast_mut() };
+    // The display and window are minifb's and outlive the call, Xlib copies the strings
+    unsafe {
+        (xlib.XSetClassHint)(display.as_ptr().cast(), handle.window, &mut hint);
+        (xlib.XFlush)(display.as_ptr().cast());
+    }
+}
+
+impl Window for MinifbWindow {
+    fn update(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Chip8Error> {
+        Ok(self.window.update_with_buffer(buffer, width, height).map_err(|e| IoError::WindowUpdate(e.to_string()))?)
+    }
+
+    fn get_keys(&self) -> Vec<Key> {
+        self.window.get_keys().into_iter().filter_map(key).collect()
+    }
+
+    fn get_keys_pressed(&self, repeat: KeyRepeat) -> Vec<Key> {
+        let repeat = match repeat {
+            KeyRepeat::Yes => minifb::KeyRepeat::Yes,
+            KeyRepeat::No => minifb::KeyRepeat::No,
+        };
+        self.window.get_keys_pressed(repeat).into_iter().filter_map(key).collect()
+    }
+
+    fn take_key_events(&mut self) -> Vec<(Key, bool)> {
+        self.key_events.0.take()
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+
+    fn set_position(&mut self, x: isize, y: isize) {
+        self.window.set_position(x, y);
+    }
+
+    // Only X11 lets a program set its icon while running, Wayland desktops take it from the
+    // application's .desktop entry, Windows from its resources and macOS from its bundle.
+    // minifb panics when asked on Wayland, so the window is checked first.
+    fn set_icon(&mut self, icon: &WindowIcon) {
+        #[cfg(target_os = "linux")]
+        {
+            use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+            let x11 = self.window.window_handle().is_ok_and(|handle| matches!(handle.as_raw(), RawWindowHandle::Xlib(_)));
+            if x11 {
+                // _NET_WM_ICON layout: width, height and the pixels, each a long
+                let data: Vec<u64> = [icon.width(), icon.height()].into_iter().map(|n| n as u64)
+                    .chain(icon.pixels().iter().map(|&pixel| pixel as u64))
+                    .collect();
+                self.window.set_icon(minifb::Icon::Buffer(data.as_ptr(), data.len() as u32));
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = icon;
+    }
+
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+}
+
+fn scale(scale: Scale) -> minifb::Scale {
+    match scale {
+        Scale::X1 => minifb::Scale::X1,
+        Scale::X2 => minifb::Scale::X2,
+        Scale::X4 => minifb::Scale::X4,
+        Scale::X8 => minifb::Scale::X8,
+        Scale::X16 => minifb::Scale::X16,
+        Scale::X32 => minifb::Scale::X32,
+        Scale::FitScreen => minifb::Scale::FitScreen,
+    }
+}
+
+// Collects the key presses and releases minifb reports while the window updates
+#[derive(Clone, Default)]
+struct KeyEvents(Rc<RefCell<Vec<(Key, bool)>>>);
+
+impl InputCallback for KeyEvents {
+    fn add_char(&mut self, _: u32) {}
+
+    fn set_key_state(&mut self, key_code: minifb::Key, pressed: bool) {
+        if let Some(key) = key(key_code) {
+            self.0.borrow_mut().push((key, pressed));
+        }
+    }
+}
+
+// The keys are named alike in both, minifb's Unknown and Count have no counterpart
+macro_rules! keys {
+    ($($name:ident),* $(,)?) => {
+        fn key(key: minifb::Key) -> Option<Key> {
+            match key {
+                $(minifb::Key::$name => Some(Key::$name),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+keys! {
+    Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15,
+    Down, Left, Right, Up,
+    Apostrophe, Backquote, Backslash, Comma, Equal, LeftBracket, Minus, Period, RightBracket, Semicolon, Slash,
+    Backspace, Delete, End, Enter, Escape, Home, Insert, Menu, PageDown, PageUp, Pause, Space, Tab,
+    NumLock, CapsLock, ScrollLock,
+    LeftShift, RightShift, LeftCtrl, RightCtrl, LeftAlt, RightAlt, LeftSuper, RightSuper,
+    NumPad0, NumPad1, NumPad2, NumPad3, NumPad4, NumPad5, NumPad6, NumPad7, NumPad8, NumPad9,
+    NumPadDot, NumPadSlash, NumPadAsterisk, NumPadMinus, NumPadPlus, NumPadEnter,
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use chip8_core::Chip8;
+use libfuzzer_sys::fuzz_target;
+
+// Loads the input as the program and executes every word of it as an instruction,
+// so the handlers see arbitrary opcodes along with memory they can read back.
+// Errors are fine, only panics are failures.
+fuzz_target!(|data: &[u8]| {
+    let Ok((mut chip8, mut mem)) = Chip8::builder().rom(data).headless().build() else {
+        return;
+    };
+    for word in data.chunks_exact(2) {
+        let _ = chip8.execute_raw(u16::from_be_bytes([word[0], word[1]]), &mut mem);
+    }
+});
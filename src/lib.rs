@@ -1,2 +0,0 @@
-pub mod chip8;
-pub use chip8::{Chip8, Memory};
\ No newline at end of file